@@ -0,0 +1,68 @@
+//! Compares `ECPoint::generator_mul` with `generator().scalar_mul`, and times proving knowledge
+//! of a discrete log, which multiplies the generator twice. Then compares
+//! `ECPoint::generator_mul_batch` with one `generator_mul` call per scalar, the batch being run
+//! in parallel with `--features rayon`.
+//!
+//! Run with `cargo bench --bench generator_mul`
+
+use std::time::{Duration, Instant};
+
+use curv::cryptographic_primitives::proofs::sigma_dlog::DLogProof;
+use curv::elliptic::curves::traits::{ECPoint, ECScalar};
+use zeroize::Zeroize;
+
+const ITERATIONS: u32 = 1000;
+const BATCH_SIZE: usize = 256;
+
+fn measure<F: FnMut()>(mut f: F) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn bench<P>(curve_name: &str)
+where
+    P: ECPoint + Clone + Send,
+    P::Scalar: Zeroize + Clone + Sync,
+{
+    let k: P::Scalar = ECScalar::new_random();
+
+    let table = measure(|| {
+        let _ = P::generator_mul(&k);
+    });
+    let scalar_mul = measure(|| {
+        let _ = P::generator().scalar_mul(&k.get_element());
+    });
+    let prove = measure(|| {
+        let _ = DLogProof::<P>::prove(&k);
+    });
+    println!(
+        "{:<12} generator_mul: {:>10?}   scalar_mul: {:>10?}   DLogProof::prove: {:>10?}",
+        curve_name, table, scalar_mul, prove
+    );
+
+    let scalars: Vec<P::Scalar> = (0..BATCH_SIZE).map(|_| ECScalar::new_random()).collect();
+    let start = Instant::now();
+    let _ = P::generator_mul_batch(&scalars);
+    let batch = start.elapsed();
+    let start = Instant::now();
+    for k in &scalars {
+        let _ = P::generator_mul(k);
+    }
+    let individual = start.elapsed();
+    println!(
+        "{:<12} {} scalars, generator_mul_batch: {:>10?}   generator_mul calls: {:>10?}",
+        curve_name, BATCH_SIZE, batch, individual
+    );
+}
+
+fn main() {
+    bench::<curv::elliptic::curves::secp256_k1::GE>("secp256k1");
+    bench::<curv::elliptic::curves::p256::GE>("p256");
+    bench::<curv::elliptic::curves::ed25519::GE>("ed25519");
+    bench::<curv::elliptic::curves::curve_ristretto::GE>("ristretto");
+    bench::<curv::elliptic::curves::bls12_381::g1::GE>("bls12_381_1");
+    bench::<curv::elliptic::curves::bls12_381::g2::GE>("bls12_381_2");
+}
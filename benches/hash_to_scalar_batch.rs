@@ -0,0 +1,47 @@
+//! Compares `ECScalar::hash_to_scalar_batch` with hashing every message on its own. The batch is
+//! only faster with the `rayon` feature.
+//!
+//! Run with `cargo bench --bench hash_to_scalar_batch --features rayon`
+
+use std::time::{Duration, Instant};
+
+use curv::elliptic::curves::traits::ECScalar;
+
+const ITERATIONS: u32 = 10;
+const DST: &[u8] = b"curv hash_to_scalar_batch bench";
+
+fn measure<F: FnMut()>(mut f: F) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn bench<S: ECScalar + Send>(curve_name: &str, n: usize) {
+    let messages: Vec<Vec<u8>> = (0..n)
+        .map(|i| (i as u64).to_be_bytes().repeat(16))
+        .collect();
+    let messages: Vec<&[u8]> = messages.iter().map(|m| m.as_slice()).collect();
+
+    let batch = measure(|| {
+        let _ = S::hash_to_scalar_batch(&messages, DST);
+    });
+    let one_by_one = measure(|| {
+        let _: Vec<S> = messages.iter().map(|m| S::hash_to_scalar(m, DST)).collect();
+    });
+    println!(
+        "{:<12} n = {:<6} batch: {:>10?}   one by one: {:>10?}",
+        curve_name, n, batch, one_by_one
+    );
+}
+
+fn main() {
+    for &n in &[100, 10_000] {
+        bench::<curv::elliptic::curves::secp256_k1::FE>("secp256k1", n);
+        bench::<curv::elliptic::curves::p256::FE>("p256", n);
+        bench::<curv::elliptic::curves::ed25519::FE>("ed25519", n);
+        bench::<curv::elliptic::curves::curve_ristretto::FE>("ristretto", n);
+        bench::<curv::elliptic::curves::bls12_381::g1::FE>("bls12_381", n);
+    }
+}
@@ -0,0 +1,45 @@
+//! Compares `ECPoint::multi_scalar_mul` with multiplying every point and adding up the products.
+//!
+//! Run with `cargo bench --bench multi_scalar_mul`
+
+use std::time::{Duration, Instant};
+
+use curv::elliptic::curves::traits::{ECPoint, ECScalar};
+
+const ITERATIONS: u32 = 10;
+
+fn measure<F: FnMut()>(mut f: F) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn bench<P: ECPoint + Clone>(curve_name: &str, n: usize) {
+    let points: Vec<P> = (0..n)
+        .map(|_| P::generator().scalar_mul(&P::Scalar::new_random().get_element()))
+        .collect();
+    let scalars: Vec<P::Scalar> = (0..n).map(|_| ECScalar::new_random()).collect();
+
+    let msm = measure(|| {
+        let _ = P::multi_scalar_mul(&scalars, &points);
+    });
+    let naive = measure(|| {
+        let _ = points
+            .iter()
+            .zip(&scalars)
+            .map(|(p, s)| p.scalar_mul(&s.get_element()))
+            .reduce(|acc, p| acc + p);
+    });
+    println!(
+        "{:<12} n = {:<5} multi_scalar_mul: {:>12?}   naive loop: {:>12?}",
+        curve_name, n, msm, naive
+    );
+}
+
+fn main() {
+    for n in &[16, 64, 256, 1024, 4096] {
+        bench::<curv::elliptic::curves::secp256_k1::GE>("secp256k1", *n);
+    }
+}
@@ -0,0 +1,49 @@
+//! Compares constructing scalars from the backend encoding (`ECScalar::from_bytes_exact`) with
+//! the BigInt path (`ECScalar::from(&BigInt::from_bytes(..))`).
+//!
+//! Run with `cargo bench --bench scalar_from_bytes`
+
+use std::time::{Duration, Instant};
+
+use curv::arithmetic::traits::*;
+use curv::elliptic::curves::traits::ECScalar;
+use curv::BigInt;
+
+const ITERATIONS: u32 = 10_000;
+
+fn measure<F: FnMut()>(mut f: F) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn bench<S: ECScalar>(curve_name: &str, little_endian: bool) {
+    let scalar = S::new_random();
+    let bn_bytes = BigInt::to_bytes(&scalar.to_big_int());
+    let mut bytes = vec![0u8; 32 - bn_bytes.len()];
+    bytes.extend_from_slice(&bn_bytes);
+    if little_endian {
+        bytes.reverse();
+    }
+
+    let exact = measure(|| {
+        let _ = S::from_bytes_exact(&bytes).unwrap();
+    });
+    let through_bigint = measure(|| {
+        let _: S = ECScalar::from(&BigInt::from_bytes(&bn_bytes));
+    });
+    println!(
+        "{:<12} from_bytes_exact: {:>10?}   BigInt path: {:>10?}",
+        curve_name, exact, through_bigint
+    );
+}
+
+fn main() {
+    bench::<curv::elliptic::curves::secp256_k1::FE>("secp256k1", false);
+    bench::<curv::elliptic::curves::p256::FE>("p256", false);
+    bench::<curv::elliptic::curves::ed25519::FE>("ed25519", true);
+    bench::<curv::elliptic::curves::curve_ristretto::FE>("ristretto", true);
+    bench::<curv::elliptic::curves::bls12_381::g1::FE>("bls12_381", false);
+}
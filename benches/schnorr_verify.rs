@@ -0,0 +1,57 @@
+//! Compares Schnorr verification through `ECPoint::double_scalar_mul_base` with checking
+//! `sG = R + eX` with two separate multiplications.
+//!
+//! Run with `cargo bench --bench schnorr_verify`
+
+use std::time::{Duration, Instant};
+
+use curv::cryptographic_primitives::signatures::schnorr::{self, Generic, Signature};
+use curv::elliptic::curves::traits::{ConstantTimeBaseMul, ECPoint, ECScalar};
+use zeroize::Zeroize;
+
+const ITERATIONS: u32 = 1000;
+
+fn measure<F: FnMut()>(mut f: F) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn naive_verify<P>(sig: &Signature<P>, pk: &P, message: &[u8]) -> bool
+where
+    P: ECPoint + Clone,
+    P::Scalar: Clone,
+{
+    let e = schnorr::challenge(&sig.r, pk, message);
+    let lhs = P::generator() * sig.s.clone();
+    let rhs = sig.r.clone() + pk.clone() * e;
+    lhs.pk_to_key_slice() == rhs.pk_to_key_slice()
+}
+
+fn bench<P>(curve_name: &str)
+where
+    P: ConstantTimeBaseMul + Clone,
+    P::Scalar: Zeroize + Clone,
+{
+    let sk: P::Scalar = ECScalar::new_random();
+    let pk = P::generator() * sk.clone();
+    let sig = schnorr::sign::<Generic<P>>(&sk, b"message");
+
+    let optimized =
+        measure(|| assert!(schnorr::verify::<Generic<P>>(&pk, b"message", &sig).is_ok()));
+    let naive = measure(|| assert!(naive_verify(&sig, &pk, b"message")));
+    println!(
+        "{:<12} verify: {:>12?}   naive verify: {:>12?}",
+        curve_name, optimized, naive
+    );
+}
+
+fn main() {
+    use curv::elliptic::curves::*;
+    bench::<secp256_k1::GE>("secp256k1");
+    bench::<p256::GE>("p256");
+    bench::<ed25519::GE>("ed25519");
+    bench::<curve_ristretto::GE>("ristretto");
+}
@@ -217,6 +217,14 @@ impl BitManipulation for BigInt {
     fn bit_length(&self) -> usize {
         self.gmp.bit_length()
     }
+
+    fn trailing_zeros(&self) -> Option<usize> {
+        if self.is_zero() {
+            return None;
+        }
+        // bits are tested in two's complement, which keeps the trailing zeros of negative numbers
+        (0..).find(|bit| self.gmp.tstbit(*bit))
+    }
 }
 
 impl Integer for BigInt {
@@ -196,6 +196,10 @@ impl BitManipulation for BigInt {
     fn bit_length(&self) -> usize {
         self.num.bits() as usize
     }
+
+    fn trailing_zeros(&self) -> Option<usize> {
+        self.num.trailing_zeros().map(|n| n as usize)
+    }
 }
 
 impl NumberTests for BigInt {
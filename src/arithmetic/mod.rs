@@ -95,6 +95,30 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_bit_length_of_known_values() {
+        let expectations = [(1, 1), (2, 2), (255, 8), (256, 9), (257, 9)];
+        for (n, bits) in expectations.iter() {
+            assert_eq!(BigInt::from(*n).bit_length(), *bits, "bit length of {}", n);
+        }
+        assert_eq!((BigInt::one() << 255).bit_length(), 256);
+    }
+
+    #[test]
+    fn test_trailing_zeros() {
+        assert_eq!(BigInt::zero().trailing_zeros(), None);
+        assert_eq!(BigInt::one().trailing_zeros(), Some(0));
+        assert_eq!(BigInt::from(256).trailing_zeros(), Some(8));
+        assert_eq!(BigInt::from(0b10100).trailing_zeros(), Some(2));
+        assert_eq!(BigInt::from(-12).trailing_zeros(), Some(2));
+        assert_eq!((BigInt::from(3) << 300).trailing_zeros(), Some(300));
+
+        assert!(BigInt::from(256).is_even());
+        assert!(!BigInt::from(255).is_even());
+        assert!(BigInt::zero().is_even());
+        assert!(BigInt::from(-2).is_even());
+    }
+
     #[test]
     fn test_setting_bit() {
         let mut n = BigInt::zero();
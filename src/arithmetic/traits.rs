@@ -220,6 +220,16 @@ pub trait BitManipulation {
     /// assert_eq!(BigInt::from(0b1011).bit_length(), 4);
     /// ```
     fn bit_length(&self) -> usize;
+    /// Number of zero bits below the lowest set bit, or `None` if the number is zero
+    ///
+    /// Negative numbers have the same trailing zeros as their absolute value.
+    ///
+    /// ```
+    /// # use curv::arithmetic::*;
+    /// assert_eq!(BigInt::from(0b101000).trailing_zeros(), Some(3));
+    /// assert_eq!(BigInt::from(0).trailing_zeros(), None);
+    /// ```
+    fn trailing_zeros(&self) -> Option<usize>;
 }
 
 #[deprecated(
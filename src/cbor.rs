@@ -0,0 +1,854 @@
+/*
+    This file is part of Curv library
+    Copyright 2018 by Kzen Networks
+    (https://github.com/KZen-networks/curv)
+    License MIT: <https://github.com/KZen-networks/curv/blob/master/LICENSE>
+*/
+
+//! Minimal CBOR (RFC 8949) encoding of serde types.
+//!
+//! Structs are encoded as maps keyed by the field names, so the output can be inspected with
+//! any CBOR tool without knowing the Rust types. The encoding is meant for debugging and
+//! interoperability, not as a compact serialization format.
+
+use std::error;
+use std::fmt;
+
+use serde::de::{self, DeserializeOwned, IntoDeserializer, Visitor};
+use serde::ser::{self, Serialize};
+
+const MAJOR_UNSIGNED: u8 = 0;
+const MAJOR_NEGATIVE: u8 = 1;
+const MAJOR_BYTES: u8 = 2;
+const MAJOR_TEXT: u8 = 3;
+const MAJOR_ARRAY: u8 = 4;
+const MAJOR_MAP: u8 = 5;
+const MAJOR_TAG: u8 = 6;
+const MAJOR_SIMPLE: u8 = 7;
+
+const SIMPLE_FALSE: u8 = 20;
+const SIMPLE_TRUE: u8 = 21;
+const SIMPLE_NULL: u8 = 22;
+const SIMPLE_F32: u8 = 26;
+const SIMPLE_F64: u8 = 27;
+
+/// Nesting depth above which decoding fails, so that malicious input can't overflow the stack
+const MAX_DEPTH: usize = 128;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error(String);
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CBOR error: {}", self.0)
+    }
+}
+
+impl error::Error for Error {}
+
+impl ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error(msg.to_string())
+    }
+}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error(msg.to_string())
+    }
+}
+
+/// Encodes `value` to CBOR
+pub fn to_vec<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, Error> {
+    let mut encoder = Encoder { out: Vec::new() };
+    value.serialize(&mut encoder)?;
+    Ok(encoder.out)
+}
+
+/// Returns the length of the encoding of `value`, without writing the encoding
+pub fn serialized_size<T: Serialize + ?Sized>(value: &T) -> Result<usize, Error> {
+    let mut encoder = Encoder { out: Counter(0) };
+    value.serialize(&mut encoder)?;
+    Ok(encoder.out.0)
+}
+
+/// Decodes a value from CBOR. Fails if `bytes` doesn't hold exactly one encoded value.
+pub fn from_slice<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, Error> {
+    from_slice_with_max_len(bytes, usize::MAX)
+}
+
+/// Like [from_slice], but fails as soon as an array or a map declares more than `max_len`
+/// items, before decoding any of them
+pub fn from_slice_with_max_len<T: DeserializeOwned>(
+    bytes: &[u8],
+    max_len: usize,
+) -> Result<T, Error> {
+    let mut decoder = Decoder {
+        input: bytes,
+        depth: 0,
+        max_len: max_len as u64,
+    };
+    let value = T::deserialize(&mut decoder)?;
+    if !decoder.input.is_empty() {
+        return Err(Error("trailing bytes".to_string()));
+    }
+    Ok(value)
+}
+
+/// Checks that `bytes` hold exactly one well formed CBOR item of the subset decoded by
+/// [from_slice], and that no array or map declares more than `max_len` items, without decoding
+/// it into any type nor allocating
+pub fn check_well_formed(bytes: &[u8], max_len: usize) -> Result<(), Error> {
+    from_slice_with_max_len::<de::IgnoredAny>(bytes, max_len).map(|_| ())
+}
+
+/// Where an [Encoder] writes to: a buffer, or a counter when only the size is wanted
+trait Output: Default {
+    fn write(&mut self, bytes: &[u8]);
+    fn append(&mut self, other: Self);
+}
+
+impl Output for Vec<u8> {
+    fn write(&mut self, bytes: &[u8]) {
+        self.extend_from_slice(bytes);
+    }
+
+    fn append(&mut self, mut other: Self) {
+        Vec::append(self, &mut other);
+    }
+}
+
+#[derive(Default)]
+struct Counter(usize);
+
+impl Output for Counter {
+    fn write(&mut self, bytes: &[u8]) {
+        self.0 += bytes.len();
+    }
+
+    fn append(&mut self, other: Self) {
+        self.0 += other.0;
+    }
+}
+
+struct Encoder<W> {
+    out: W,
+}
+
+impl<W: Output> Encoder<W> {
+    fn write_head(&mut self, major: u8, n: u64) {
+        let major = major << 5;
+        if n < 24 {
+            self.out.write(&[major | n as u8]);
+        } else if n <= u64::from(u8::MAX) {
+            self.out.write(&[major | 24]);
+            self.out.write(&[n as u8]);
+        } else if n <= u64::from(u16::MAX) {
+            self.out.write(&[major | 25]);
+            self.out.write(&(n as u16).to_be_bytes());
+        } else if n <= u64::from(u32::MAX) {
+            self.out.write(&[major | 26]);
+            self.out.write(&(n as u32).to_be_bytes());
+        } else {
+            self.out.write(&[major | 27]);
+            self.out.write(&n.to_be_bytes());
+        }
+    }
+
+    fn write_text(&mut self, s: &str) {
+        self.write_head(MAJOR_TEXT, s.len() as u64);
+        self.out.write(s.as_bytes());
+    }
+}
+
+/// Array or map whose items are buffered until their count is known
+struct Compound<'a, W> {
+    parent: &'a mut Encoder<W>,
+    items: Encoder<W>,
+    major: u8,
+    count: u64,
+    // enum variants are encoded as a map of one entry from the variant name to its content
+    variant: Option<&'static str>,
+}
+
+impl<'a, W: Output> Compound<'a, W> {
+    fn new(parent: &'a mut Encoder<W>, major: u8, variant: Option<&'static str>) -> Self {
+        Compound {
+            parent,
+            items: Encoder { out: W::default() },
+            major,
+            count: 0,
+            variant,
+        }
+    }
+
+    fn item<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.count += 1;
+        value.serialize(&mut self.items)
+    }
+
+    fn field<T: Serialize + ?Sized>(&mut self, key: &str, value: &T) -> Result<(), Error> {
+        self.count += 1;
+        self.items.write_text(key);
+        value.serialize(&mut self.items)
+    }
+
+    fn finish(self) -> Result<(), Error> {
+        if let Some(variant) = self.variant {
+            self.parent.write_head(MAJOR_MAP, 1);
+            self.parent.write_text(variant);
+        }
+        self.parent.write_head(self.major, self.count);
+        self.parent.out.append(self.items.out);
+        Ok(())
+    }
+}
+
+impl<'a, W: Output> ser::Serializer for &'a mut Encoder<W> {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = Compound<'a, W>;
+    type SerializeTuple = Compound<'a, W>;
+    type SerializeTupleStruct = Compound<'a, W>;
+    type SerializeTupleVariant = Compound<'a, W>;
+    type SerializeMap = Compound<'a, W>;
+    type SerializeStruct = Compound<'a, W>;
+    type SerializeStructVariant = Compound<'a, W>;
+
+    fn serialize_bool(self, v: bool) -> Result<(), Error> {
+        self.out
+            .write(&[MAJOR_SIMPLE << 5 | if v { SIMPLE_TRUE } else { SIMPLE_FALSE }]);
+        Ok(())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), Error> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), Error> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), Error> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), Error> {
+        if v < 0 {
+            // -1 - v doesn't overflow for any negative v
+            self.write_head(MAJOR_NEGATIVE, (-1 - v) as u64);
+        } else {
+            self.write_head(MAJOR_UNSIGNED, v as u64);
+        }
+        Ok(())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), Error> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), Error> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), Error> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), Error> {
+        self.write_head(MAJOR_UNSIGNED, v);
+        Ok(())
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), Error> {
+        self.out.write(&[MAJOR_SIMPLE << 5 | SIMPLE_F32]);
+        self.out.write(&v.to_be_bytes());
+        Ok(())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), Error> {
+        self.out.write(&[MAJOR_SIMPLE << 5 | SIMPLE_F64]);
+        self.out.write(&v.to_be_bytes());
+        Ok(())
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), Error> {
+        self.write_text(v.encode_utf8(&mut [0u8; 4]));
+        Ok(())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), Error> {
+        self.write_text(v);
+        Ok(())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), Error> {
+        self.write_head(MAJOR_BYTES, v.len() as u64);
+        self.out.write(v);
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<(), Error> {
+        self.serialize_unit()
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> {
+        self.out.write(&[MAJOR_SIMPLE << 5 | SIMPLE_NULL]);
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), Error> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<(), Error> {
+        self.write_text(variant);
+        Ok(())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.write_head(MAJOR_MAP, 1);
+        self.write_text(variant);
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Compound<'a, W>, Error> {
+        Ok(Compound::new(self, MAJOR_ARRAY, None))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Compound<'a, W>, Error> {
+        Ok(Compound::new(self, MAJOR_ARRAY, None))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a, W>, Error> {
+        Ok(Compound::new(self, MAJOR_ARRAY, None))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a, W>, Error> {
+        Ok(Compound::new(self, MAJOR_ARRAY, Some(variant)))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Compound<'a, W>, Error> {
+        Ok(Compound::new(self, MAJOR_MAP, None))
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Compound<'a, W>, Error> {
+        Ok(Compound::new(self, MAJOR_MAP, None))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a, W>, Error> {
+        Ok(Compound::new(self, MAJOR_MAP, Some(variant)))
+    }
+}
+
+impl<'a, W: Output> ser::SerializeSeq for Compound<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.item(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.finish()
+    }
+}
+
+impl<'a, W: Output> ser::SerializeTuple for Compound<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.item(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.finish()
+    }
+}
+
+impl<'a, W: Output> ser::SerializeTupleStruct for Compound<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.item(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.finish()
+    }
+}
+
+impl<'a, W: Output> ser::SerializeTupleVariant for Compound<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.item(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.finish()
+    }
+}
+
+impl<'a, W: Output> ser::SerializeMap for Compound<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        // a map entry counts once, for its key
+        self.item(key)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut self.items)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.finish()
+    }
+}
+
+impl<'a, W: Output> ser::SerializeStruct for Compound<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.finish()
+    }
+}
+
+impl<'a, W: Output> ser::SerializeStructVariant for Compound<'a, W> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.finish()
+    }
+}
+
+struct Decoder<'de> {
+    input: &'de [u8],
+    depth: usize,
+    max_len: u64,
+}
+
+impl<'de> Decoder<'de> {
+    fn eof() -> Error {
+        Error("unexpected end of input".to_string())
+    }
+
+    fn peek(&self) -> Result<u8, Error> {
+        self.input.first().copied().ok_or_else(Self::eof)
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'de [u8], Error> {
+        if self.input.len() < n {
+            return Err(Self::eof());
+        }
+        let (head, tail) = self.input.split_at(n);
+        self.input = tail;
+        Ok(head)
+    }
+
+    /// Reads the initial byte of an item and its argument. Returns (major type, additional
+    /// information, argument).
+    fn read_head(&mut self) -> Result<(u8, u8, u64), Error> {
+        let initial = self.take(1)?[0];
+        let major = initial >> 5;
+        let info = initial & 0x1f;
+        let n = match info {
+            0..=23 => u64::from(info),
+            24 => u64::from(self.take(1)?[0]),
+            25 => {
+                let mut buf = [0u8; 2];
+                buf.copy_from_slice(self.take(2)?);
+                u64::from(u16::from_be_bytes(buf))
+            }
+            26 => {
+                let mut buf = [0u8; 4];
+                buf.copy_from_slice(self.take(4)?);
+                u64::from(u32::from_be_bytes(buf))
+            }
+            27 => {
+                let mut buf = [0u8; 8];
+                buf.copy_from_slice(self.take(8)?);
+                u64::from_be_bytes(buf)
+            }
+            _ => return Err(Error("indefinite lengths are not supported".to_string())),
+        };
+        Ok((major, info, n))
+    }
+
+    fn read_len(&mut self, n: u64) -> Result<&'de [u8], Error> {
+        if n > self.input.len() as u64 {
+            return Err(Self::eof());
+        }
+        self.take(n as usize)
+    }
+
+    fn enter(&mut self) -> Result<(), Error> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(Error("nesting too deep".to_string()));
+        }
+        Ok(())
+    }
+}
+
+impl<'de> de::Deserializer<'de> for &mut Decoder<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let (major, info, n) = self.read_head()?;
+        match major {
+            MAJOR_UNSIGNED => visitor.visit_u64(n),
+            MAJOR_NEGATIVE => {
+                if n > i64::MAX as u64 {
+                    return Err(Error("negative integer out of range".to_string()));
+                }
+                visitor.visit_i64(-1 - n as i64)
+            }
+            MAJOR_BYTES => visitor.visit_borrowed_bytes(self.read_len(n)?),
+            MAJOR_TEXT => {
+                let bytes = self.read_len(n)?;
+                let s = std::str::from_utf8(bytes)
+                    .map_err(|_| Error("text is not valid UTF-8".to_string()))?;
+                visitor.visit_borrowed_str(s)
+            }
+            MAJOR_ARRAY | MAJOR_MAP => {
+                if n > self.max_len {
+                    return Err(Error(format!(
+                        "{} items, more than the limit of {}",
+                        n, self.max_len
+                    )));
+                }
+                // every item takes at least a byte
+                if n > self.input.len() as u64 {
+                    return Err(Decoder::eof());
+                }
+                self.enter()?;
+                let mut access = Items {
+                    decoder: &mut *self,
+                    remaining: n,
+                };
+                let value = if major == MAJOR_ARRAY {
+                    visitor.visit_seq(&mut access)?
+                } else {
+                    visitor.visit_map(&mut access)?
+                };
+                if access.remaining != 0 {
+                    return Err(Error("unread items".to_string()));
+                }
+                self.depth -= 1;
+                Ok(value)
+            }
+            // tags are only hints about the tagged item, which is decoded as is
+            MAJOR_TAG => {
+                self.enter()?;
+                let value = self.deserialize_any(visitor)?;
+                self.depth -= 1;
+                Ok(value)
+            }
+            _ => match info {
+                SIMPLE_FALSE => visitor.visit_bool(false),
+                SIMPLE_TRUE => visitor.visit_bool(true),
+                SIMPLE_NULL => visitor.visit_unit(),
+                SIMPLE_F32 => visitor.visit_f32(f32::from_bits(n as u32)),
+                SIMPLE_F64 => visitor.visit_f64(f64::from_bits(n)),
+                _ => Err(Error(format!("unsupported simple value {}", info))),
+            },
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.peek()? == MAJOR_SIMPLE << 5 | SIMPLE_NULL {
+            self.take(1)?;
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        if self.peek()? >> 5 == MAJOR_TEXT {
+            let variant: &str = de::Deserialize::deserialize(&mut *self)?;
+            return visitor.visit_enum(variant.into_deserializer());
+        }
+        match self.read_head()? {
+            (MAJOR_MAP, _, 1) => {
+                self.enter()?;
+                let value = visitor.visit_enum(&mut *self)?;
+                self.depth -= 1;
+                Ok(value)
+            }
+            _ => Err(Error("expected an enum variant".to_string())),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+struct Items<'a, 'de> {
+    decoder: &'a mut Decoder<'de>,
+    remaining: u64,
+}
+
+impl<'a, 'de> de::SeqAccess<'de> for Items<'a, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.decoder).map(Some)
+    }
+}
+
+impl<'a, 'de> de::MapAccess<'de> for Items<'a, 'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.decoder).map(Some)
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        seed.deserialize(&mut *self.decoder)
+    }
+}
+
+impl<'de> de::EnumAccess<'de> for &mut Decoder<'de> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), Error> {
+        let variant = seed.deserialize(&mut *self)?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for &mut Decoder<'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        de::Deserialize::deserialize(self)
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_any(self, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_any(self, visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Kind {
+        Unit,
+        Newtype(u8),
+        Tuple(u8, u8),
+        Struct { a: i32 },
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Sample {
+        number: i64,
+        text: String,
+        list: Vec<u32>,
+        flag: bool,
+        missing: Option<u8>,
+        kinds: Vec<Kind>,
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let sample = Sample {
+            number: -300,
+            text: "hello".to_string(),
+            list: vec![0, 23, 24, 65_536],
+            flag: true,
+            missing: None,
+            kinds: vec![
+                Kind::Unit,
+                Kind::Newtype(7),
+                Kind::Tuple(1, 2),
+                Kind::Struct { a: -1 },
+            ],
+        };
+        let bytes = to_vec(&sample).unwrap();
+        assert_eq!(from_slice::<Sample>(&bytes).unwrap(), sample);
+        assert_eq!(serialized_size(&sample).unwrap(), bytes.len());
+    }
+
+    #[test]
+    fn test_serialized_size() {
+        assert_eq!(serialized_size(&23u8).unwrap(), 1);
+        assert_eq!(serialized_size(&1_000_000u64).unwrap(), 5);
+        assert_eq!(serialized_size("IETF").unwrap(), 5);
+        let long_list: Vec<u32> = (0..300).collect();
+        assert_eq!(
+            serialized_size(&long_list).unwrap(),
+            to_vec(&long_list).unwrap().len()
+        );
+    }
+
+    #[test]
+    fn test_rfc_8949_examples() {
+        assert_eq!(
+            to_vec(&1_000_000u64).unwrap(),
+            hex::decode("1a000f4240").unwrap()
+        );
+        assert_eq!(to_vec(&-1000i64).unwrap(), hex::decode("3903e7").unwrap());
+        assert_eq!(to_vec("IETF").unwrap(), hex::decode("6449455446").unwrap());
+        assert_eq!(
+            to_vec(&[1u8, 2, 3]).unwrap(),
+            hex::decode("83010203").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_malformed_input_is_rejected() {
+        let bytes = to_vec(&vec![1u32, 2, 3]).unwrap();
+        assert!(from_slice::<Vec<u32>>(&bytes[..bytes.len() - 1]).is_err());
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(from_slice::<Vec<u32>>(&trailing).is_err());
+        // an array claiming 2^64 - 1 items
+        assert!(from_slice::<Vec<u32>>(&hex::decode("9bffffffffffffffff").unwrap()).is_err());
+        assert!(from_slice::<Vec<u32>>(&vec![0x81; 1000]).is_err());
+    }
+
+    #[test]
+    fn test_max_len() {
+        let bytes = to_vec(&vec![1u32, 2, 3]).unwrap();
+        assert_eq!(
+            from_slice_with_max_len::<Vec<u32>>(&bytes, 3).unwrap(),
+            vec![1, 2, 3]
+        );
+        let err = from_slice_with_max_len::<Vec<u32>>(&bytes, 2).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "CBOR error: 3 items, more than the limit of 2"
+        );
+        // nested arrays are bounded too
+        let bytes = to_vec(&vec![vec![1u32, 2, 3]]).unwrap();
+        assert!(from_slice_with_max_len::<Vec<Vec<u32>>>(&bytes, 2).is_err());
+        // more items than bytes left
+        let err = from_slice::<Vec<u32>>(&[0x9a, 0, 1, 0, 0, 0]).unwrap_err();
+        assert_eq!(err.to_string(), "CBOR error: unexpected end of input");
+    }
+
+    #[test]
+    fn test_check_well_formed() {
+        let bytes = to_vec(&(vec![1u32, 2, 3], "text")).unwrap();
+        assert!(check_well_formed(&bytes, 3).is_ok());
+        assert!(check_well_formed(&bytes, 2).is_err());
+        assert!(check_well_formed(&bytes[..bytes.len() - 1], 3).is_err());
+        let mut extended = bytes;
+        extended.push(0);
+        assert!(check_well_formed(&extended, 3).is_err());
+        // invalid UTF-8, and an indefinite length
+        assert!(check_well_formed(&[0x61, 0xff], 3).is_err());
+        assert!(check_well_formed(&[0x9f, 0xff], 3).is_err());
+    }
+}
@@ -40,10 +40,44 @@ impl Commitment<BigInt> for HashCommitment {
     }
 }
 
+/// Smallest blinding factor accepted by
+/// [create_commitment_with_defined_nonce_size](HashCommitment::create_commitment_with_defined_nonce_size),
+/// shorter ones could be found by brute force, revealing the committed value of a guessable
+/// message
+pub const MIN_NONCE_BITS: usize = 128;
+
+impl HashCommitment {
+    /// Same as [create_commitment](Commitment::create_commitment), with a blinding factor of
+    /// `nonce_bits` bits instead of 256. Returns the commitment, the blinding factor and
+    /// `nonce_bits`, so the agreed size can be sent along with the opening. The commitment is
+    /// opened as any other, with
+    /// [create_commitment_with_user_defined_randomness](Commitment::create_commitment_with_user_defined_randomness).
+    ///
+    /// ## Panics
+    /// Panics if `nonce_bits` is smaller than [MIN_NONCE_BITS].
+    pub fn create_commitment_with_defined_nonce_size(
+        message: &BigInt,
+        nonce_bits: usize,
+    ) -> (BigInt, BigInt, usize) {
+        assert!(
+            nonce_bits >= MIN_NONCE_BITS,
+            "a nonce of {} bits is too short to hide the message",
+            nonce_bits
+        );
+        let blinding_factor = BigInt::sample(nonce_bits);
+        let com = HashCommitment::create_commitment_with_user_defined_randomness(
+            message,
+            &blinding_factor,
+        );
+        (com, blinding_factor, nonce_bits)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::Commitment;
     use super::HashCommitment;
+    use super::MIN_NONCE_BITS;
     use super::SECURITY_BITS;
     use crate::arithmetic::traits::*;
     use crate::BigInt;
@@ -107,4 +141,28 @@ mod tests {
         let hash_result = BigInt::from_bytes(digest.result().as_ref());
         assert_eq!(&commitment, &hash_result);
     }
+
+    #[test]
+    fn test_create_commitment_with_defined_nonce_size() {
+        let message = BigInt::sample(SECURITY_BITS);
+        for nonce_bits in [MIN_NONCE_BITS, 200, 512].iter() {
+            let (commitment, blind_factor, size) =
+                HashCommitment::create_commitment_with_defined_nonce_size(&message, *nonce_bits);
+            assert_eq!(size, *nonce_bits);
+            assert!(blind_factor.bit_length() <= *nonce_bits);
+            assert_eq!(
+                HashCommitment::create_commitment_with_user_defined_randomness(
+                    &message,
+                    &blind_factor
+                ),
+                commitment
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_create_commitment_with_a_short_nonce() {
+        HashCommitment::create_commitment_with_defined_nonce_size(&BigInt::one(), 127);
+    }
 }
@@ -7,13 +7,26 @@
 
 use std::marker::PhantomData;
 
+use zeroize::Zeroize;
+
 use super::traits::Commitment;
 use super::SECURITY_BITS;
 use crate::arithmetic::traits::*;
+use crate::cryptographic_primitives::proofs::sigma_dlog::DLogProof;
+use crate::cryptographic_primitives::proofs::ProofError;
 
+use crate::elliptic::curves::multiscalar::multiscalar_mul;
 use crate::elliptic::curves::traits::*;
 use crate::BigInt;
 
+const MULTI_GENERATORS_LABEL: &[u8] = b"curv pedersen commit_multi";
+const EQUALITY_PROOF_DOMAIN: &[u8] = b"curv pedersen equality";
+
+/// Proof that two Pedersen commitments hide the same value, see
+/// [PedersenCommitment::prove_equal]. It is a proof of knowledge of the discrete log of the
+/// difference of the commitments in respect to H.
+pub type EqualityProof<P> = DLogProof<P>;
+
 /// compute c = mG + rH
 /// where m is the commited value, G is the group generator,
 /// H is a random point and r is a blinding value.
@@ -46,3 +59,381 @@ where
         (com, blinding_factor)
     }
 }
+
+impl<P> PedersenCommitment<P>
+where
+    P: ECPoint + Clone,
+    P::Scalar: Zeroize,
+{
+    /// compute c = 0G + rH = rH
+    pub fn commit_zero(blinding: &P::Scalar) -> P {
+        P::base_point2().scalar_mul(&blinding.get_element())
+    }
+
+    /// Proves that c is a commitment to zero, that is c = rH, by proving knowledge of the
+    /// discrete log of c in respect to H.
+    ///
+    /// ## Panics
+    /// Panics if c is not equal to rH.
+    pub fn prove_commits_to_zero(c: &P, r: &P::Scalar) -> DLogProof<P> {
+        // points are compared by their encoding, which doesn't depend on how they were computed
+        assert!(
+            c.pk_to_key_slice() == Self::commit_zero(r).pk_to_key_slice(),
+            "c is not a commitment to zero with blinding r"
+        );
+        let h = P::base_point2();
+        DLogProof::builder().witness(r).generator(&h).prove()
+    }
+
+    /// Verifies a proof produced by [prove_commits_to_zero](Self::prove_commits_to_zero)
+    pub fn verify_commits_to_zero(c: &P, proof: &DLogProof<P>) -> Result<(), ProofError> {
+        if proof.pk.pk_to_key_slice() != c.pk_to_key_slice() {
+            return Err(ProofError);
+        }
+        let h = P::base_point2();
+        DLogProof::builder().generator(&h).verify(proof)
+    }
+
+    /// Proves that c1 = vG + r1H and c2 = vG + r2H commit to the same value v, by proving
+    /// knowledge of the discrete log r1 - r2 of c1 - c2 in respect to H.
+    ///
+    /// ## Panics
+    /// Panics if c1 or c2 doesn't open to v with the given blinding.
+    pub fn prove_equal(
+        c1: &P,
+        v: &P::Scalar,
+        r1: &P::Scalar,
+        c2: &P,
+        r2: &P::Scalar,
+    ) -> EqualityProof<P> {
+        for (c, r) in [(c1, r1), (c2, r2)].iter() {
+            let expected: P = Self::create_commitment_with_user_defined_randomness(
+                &v.to_big_int(),
+                &r.to_big_int(),
+            );
+            assert!(
+                c.pk_to_key_slice() == expected.pk_to_key_slice(),
+                "commitment doesn't open to v"
+            );
+        }
+        let mut r = r1.sub(&r2.get_element());
+        let h = P::base_point2();
+        let proof = DLogProof::builder()
+            .witness(&r)
+            .generator(&h)
+            .domain(EQUALITY_PROOF_DOMAIN)
+            .prove();
+        r.zeroize();
+        proof
+    }
+
+    /// Verifies a proof produced by [prove_equal](Self::prove_equal)
+    pub fn verify_equal(c1: &P, c2: &P, proof: &EqualityProof<P>) -> Result<(), ProofError> {
+        let difference = c1.sub_point(&c2.get_element());
+        if proof.pk.pk_to_key_slice() != difference.pk_to_key_slice() {
+            return Err(ProofError);
+        }
+        let h = P::base_point2();
+        DLogProof::builder()
+            .generator(&h)
+            .domain(EQUALITY_PROOF_DOMAIN)
+            .verify(proof)
+    }
+
+    /// Checks that every `commitments[i]` opens to `values[i]` with blinding `blindings[i]`.
+    ///
+    /// The openings are checked all at once over a random linear combination, with a single
+    /// multi-scalar multiplication. Only if this check fails are the openings checked one by one,
+    /// and the indices of the ones that fail are returned.
+    ///
+    /// ## Panics
+    /// Panics if the slices differ in length.
+    pub fn batch_verify_openings(
+        commitments: &[P],
+        values: &[BigInt],
+        blindings: &[BigInt],
+    ) -> Result<(), Vec<usize>>
+    where
+        P::Scalar: Clone,
+    {
+        assert_eq!(commitments.len(), values.len());
+        assert_eq!(commitments.len(), blindings.len());
+        if commitments.is_empty() {
+            return Ok(());
+        }
+
+        // sum rho_i*c_i - (sum rho_i*v_i)*G = (sum rho_i*r_i)*H
+        let rhos: Vec<P::Scalar> = commitments.iter().map(|_| ECScalar::new_random()).collect();
+        let q = P::Scalar::q();
+        let mut rho_v = BigInt::zero();
+        let mut rho_r = BigInt::zero();
+        for ((rho, v), r) in rhos.iter().zip(values).zip(blindings) {
+            let rho = rho.to_big_int();
+            rho_v = BigInt::mod_add(&rho_v, &BigInt::mod_mul(&rho, v, &q), &q);
+            rho_r = BigInt::mod_add(&rho_r, &BigInt::mod_mul(&rho, r, &q), &q);
+        }
+        let neg_rho_v: P::Scalar = ECScalar::from(&BigInt::mod_sub(&q, &rho_v, &q));
+        let rho_r: P::Scalar = ECScalar::from(&rho_r);
+
+        let mut scalars = rhos;
+        scalars.push(neg_rho_v);
+        let mut points = commitments.to_vec();
+        points.push(P::generator());
+        let lhs = multiscalar_mul(&scalars, &points);
+        let rhs = P::base_point2() * rho_r;
+        if lhs.map(|lhs| lhs.pk_to_key_slice()) == Some(rhs.pk_to_key_slice()) {
+            return Ok(());
+        }
+
+        let invalid: Vec<usize> = (0..commitments.len())
+            .filter(|i| {
+                let expected: P =
+                    PedersenCommitment::create_commitment_with_user_defined_randomness(
+                        &values[*i],
+                        &blindings[*i],
+                    );
+                expected.pk_to_key_slice() != commitments[*i].pk_to_key_slice()
+            })
+            .collect();
+        if invalid.is_empty() {
+            Ok(())
+        } else {
+            Err(invalid)
+        }
+    }
+}
+
+impl<P> PedersenCommitment<P>
+where
+    P: ECPoint + Clone + Send + Sync + 'static,
+    P::Scalar: Clone,
+{
+    /// Returns the generators `G1, ..., Gn` [commit_multi](Self::commit_multi) commits to `n`
+    /// values with
+    pub fn multi_generators(n: usize) -> Vec<P> {
+        P::generator_set(MULTI_GENERATORS_LABEL, n)
+    }
+
+    /// compute c = v1*G1 + v2*G2 + ... + vn*Gn + rH
+    /// where G1, ..., Gn are [multi_generators](Self::multi_generators), independent of each
+    /// other and of G and H. The commitment hides all the values with the single blinding r and
+    /// binds to each of them in its position.
+    pub fn commit_multi(values: &[P::Scalar], blinding: &P::Scalar) -> P {
+        let mut points = Self::multi_generators(values.len());
+        points.push(P::base_point2());
+        let mut scalars = values.to_vec();
+        scalars.push(blinding.clone());
+        multiscalar_mul(&scalars, &points).expect("there is at least the blinding term")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_for_all_curves;
+
+    test_for_all_curves!(test_balanced_transaction_commits_to_zero);
+    fn test_balanced_transaction_commits_to_zero<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize + Clone,
+    {
+        // inputs 5 + 7, outputs 8 + 4
+        let balanced = [5, 7, 8, 4];
+        let blindings: Vec<P::Scalar> = (0..4).map(|_| ECScalar::new_random()).collect();
+        let (net, r) = net_commitment::<P>(&balanced, &blindings);
+        let proof = PedersenCommitment::prove_commits_to_zero(&net, &r);
+        assert!(PedersenCommitment::verify_commits_to_zero(&net, &proof).is_ok());
+
+        // inputs 5 + 7, outputs 8 + 5
+        let unbalanced = [5, 7, 8, 5];
+        let (net, r) = net_commitment::<P>(&unbalanced, &blindings);
+        let proof =
+            PedersenCommitment::prove_commits_to_zero(&PedersenCommitment::commit_zero(&r), &r);
+        assert!(PedersenCommitment::verify_commits_to_zero(&net, &proof).is_err());
+    }
+
+    test_for_all_curves!(test_batch_verify_openings);
+    fn test_batch_verify_openings<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize + Clone,
+    {
+        let q = P::Scalar::q();
+        let values: Vec<BigInt> = (0..8).map(|_| BigInt::sample_below(&q)).collect();
+        let blindings: Vec<BigInt> = (0..8).map(|_| BigInt::sample_below(&q)).collect();
+        let mut commitments: Vec<P> = values
+            .iter()
+            .zip(&blindings)
+            .map(|(v, r)| PedersenCommitment::create_commitment_with_user_defined_randomness(v, r))
+            .collect();
+        assert_eq!(
+            PedersenCommitment::batch_verify_openings(&commitments, &values, &blindings),
+            Ok(())
+        );
+
+        commitments[5] = PedersenCommitment::create_commitment_with_user_defined_randomness(
+            &(&values[5] + BigInt::one()),
+            &blindings[5],
+        );
+        assert_eq!(
+            PedersenCommitment::batch_verify_openings(&commitments, &values, &blindings),
+            Err(vec![5])
+        );
+        assert_eq!(
+            PedersenCommitment::<P>::batch_verify_openings(&[], &[], &[]),
+            Ok(())
+        );
+    }
+
+    test_for_all_curves!(test_commit_multi_is_homomorphic);
+    fn test_commit_multi_is_homomorphic<P>()
+    where
+        P: ECPoint + Clone + Send + Sync + 'static,
+        P::Scalar: Zeroize + Clone,
+    {
+        let a: Vec<P::Scalar> = (0..4).map(|_| ECScalar::new_random()).collect();
+        let b: Vec<P::Scalar> = (0..4).map(|_| ECScalar::new_random()).collect();
+        let r1: P::Scalar = ECScalar::new_random();
+        let r2: P::Scalar = ECScalar::new_random();
+        let sum: Vec<P::Scalar> = a
+            .iter()
+            .zip(&b)
+            .map(|(a, b)| a.clone() + b.clone())
+            .collect();
+        let c = PedersenCommitment::<P>::commit_multi(&a, &r1)
+            + PedersenCommitment::<P>::commit_multi(&b, &r2);
+        let expected = PedersenCommitment::<P>::commit_multi(&sum, &(r1.clone() + r2));
+        assert_eq!(c.pk_to_key_slice(), expected.pk_to_key_slice());
+
+        // without values this is a commitment to zero
+        assert_eq!(
+            PedersenCommitment::<P>::commit_multi(&[], &r1).pk_to_key_slice(),
+            PedersenCommitment::<P>::commit_zero(&r1).pk_to_key_slice()
+        );
+    }
+
+    test_for_all_curves!(test_commit_multi_generators_are_distinct);
+    fn test_commit_multi_generators_are_distinct<P>()
+    where
+        P: ECPoint + Clone + Send + Sync + 'static,
+        P::Scalar: Clone,
+    {
+        let mut points: Vec<Vec<u8>> = PedersenCommitment::<P>::multi_generators(8)
+            .iter()
+            .map(|g| g.pk_to_key_slice())
+            .collect();
+        points.push(P::generator().pk_to_key_slice());
+        points.push(P::base_point2().pk_to_key_slice());
+        let distinct: std::collections::HashSet<_> = points.iter().collect();
+        assert_eq!(distinct.len(), points.len());
+
+        // every value is bound to its position
+        let a: P::Scalar = ECScalar::new_random();
+        let b: P::Scalar = ECScalar::new_random();
+        let r: P::Scalar = ECScalar::new_random();
+        let ab = PedersenCommitment::<P>::commit_multi(&[a.clone(), b.clone()], &r);
+        let ba = PedersenCommitment::<P>::commit_multi(&[b, a], &r);
+        assert_ne!(ab.pk_to_key_slice(), ba.pk_to_key_slice());
+    }
+
+    test_for_all_curves!(test_prove_equal);
+    fn test_prove_equal<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize + Clone,
+    {
+        let v: P::Scalar = ECScalar::new_random();
+        let r1: P::Scalar = ECScalar::new_random();
+        let r2: P::Scalar = ECScalar::new_random();
+        let commit = |v: &P::Scalar, r: &P::Scalar| -> P {
+            PedersenCommitment::create_commitment_with_user_defined_randomness(
+                &v.to_big_int(),
+                &r.to_big_int(),
+            )
+        };
+        let c1 = commit(&v, &r1);
+        let c2 = commit(&v, &r2);
+        let proof = PedersenCommitment::prove_equal(&c1, &v, &r1, &c2, &r2);
+        assert!(PedersenCommitment::verify_equal(&c1, &c2, &proof).is_ok());
+        assert!(PedersenCommitment::verify_equal(&c2, &c1, &proof).is_err());
+
+        // a commitment to another value
+        let c3 = commit(&P::Scalar::new_random(), &r2);
+        assert!(PedersenCommitment::verify_equal(&c1, &c3, &proof).is_err());
+        // an equality proof is not a proof of commitment to zero
+        let zero = PedersenCommitment::<P>::commit_zero(&r1);
+        let proof = PedersenCommitment::prove_commits_to_zero(&zero, &r1);
+        let c4 = zero.clone() + commit(&v, &r2);
+        assert!(PedersenCommitment::verify_equal(&c4, &commit(&v, &r2), &proof).is_err());
+    }
+
+    test_for_all_curves!(
+        #[should_panic]
+        test_prove_equal_different_values
+    );
+    fn test_prove_equal_different_values<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize + Clone,
+    {
+        let v: P::Scalar = ECScalar::new_random();
+        let r: P::Scalar = ECScalar::new_random();
+        let c1: P = PedersenCommitment::create_commitment_with_user_defined_randomness(
+            &v.to_big_int(),
+            &r.to_big_int(),
+        );
+        let c2: P = PedersenCommitment::create_commitment_with_user_defined_randomness(
+            &(v.to_big_int() + BigInt::one()),
+            &r.to_big_int(),
+        );
+        PedersenCommitment::prove_equal(&c1, &v, &r, &c2, &r);
+    }
+
+    #[test]
+    fn test_equality_proof_serde_round_trip() {
+        use crate::elliptic::curves::secp256_k1::{FE, GE};
+
+        let v: FE = ECScalar::new_random();
+        let r1: FE = ECScalar::new_random();
+        let r2: FE = ECScalar::new_random();
+        let c1: GE = PedersenCommitment::create_commitment_with_user_defined_randomness(
+            &v.to_big_int(),
+            &r1.to_big_int(),
+        );
+        let c2: GE = PedersenCommitment::create_commitment_with_user_defined_randomness(
+            &v.to_big_int(),
+            &r2.to_big_int(),
+        );
+        let proof = PedersenCommitment::prove_equal(&c1, &v, &r1, &c2, &r2);
+        let json = serde_json::to_string(&proof).unwrap();
+        let decoded: EqualityProof<GE> = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, proof);
+        assert!(PedersenCommitment::verify_equal(&c1, &c2, &decoded).is_ok());
+    }
+
+    /// Returns C1 + C2 - C3 - C4 and its blinding r1 + r2 - r3 - r4
+    fn net_commitment<P>(values: &[u64; 4], blindings: &[P::Scalar]) -> (P, P::Scalar)
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Clone,
+    {
+        let coms: Vec<P> = values
+            .iter()
+            .zip(blindings)
+            .map(|(v, r)| {
+                PedersenCommitment::create_commitment_with_user_defined_randomness(
+                    &BigInt::from(*v),
+                    &r.to_big_int(),
+                )
+            })
+            .collect();
+        let net = (coms[0].clone() + coms[1].clone())
+            .sub_point(&coms[2].get_element())
+            .sub_point(&coms[3].get_element());
+        let r = (blindings[0].clone() + blindings[1].clone())
+            .sub(&blindings[2].get_element())
+            .sub(&blindings[3].get_element());
+        (net, r)
+    }
+}
@@ -4,6 +4,7 @@
     (https://github.com/KZen-networks/curv)
     License MIT: https://github.com/KZen-networks/curv/blob/master/LICENSE
 */
+use super::traits::Hash;
 use crate::arithmetic::traits::*;
 use crate::elliptic::curves::traits::{ECPoint, ECScalar};
 use crate::BigInt;
@@ -11,6 +12,12 @@ use blake2b_simd::Params;
 
 pub struct Blake;
 
+/// Unkeyed and unpersonalized BLAKE2b-512, hashing its inputs as [HSha256] does, so the two can
+/// be swapped.
+///
+/// [HSha256]: super::hash_sha256::HSha256
+pub struct HBlake2b;
+
 impl Blake {
     pub fn create_hash(big_ints: &[&BigInt], persona: &[u8]) -> BigInt {
         let mut digest = Params::new().hash_length(64).personal(persona).to_state();
@@ -35,10 +42,35 @@ impl Blake {
     }
 }
 
+impl Hash for HBlake2b {
+    fn create_hash(big_ints: &[&BigInt]) -> BigInt {
+        let mut digest = Params::new().hash_length(64).to_state();
+        for value in big_ints {
+            digest.update(&BigInt::to_bytes(value));
+        }
+        BigInt::from_bytes(digest.finalize().as_ref())
+    }
+
+    fn create_hash_from_slice(byte_slice: &[u8]) -> BigInt {
+        let digest = Params::new().hash_length(64).hash(byte_slice);
+        BigInt::from_bytes(digest.as_ref())
+    }
+
+    fn create_hash_from_ge<P: ECPoint>(ge_vec: &[&P]) -> P::Scalar {
+        let mut digest = Params::new().hash_length(64).to_state();
+        for value in ge_vec {
+            digest.update(&value.pk_to_key_slice());
+        }
+        let result = BigInt::from_bytes(digest.finalize().as_ref());
+        ECScalar::from(&result)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::Blake;
+    use super::{Blake, HBlake2b};
     use crate::arithmetic::traits::*;
+    use crate::cryptographic_primitives::hashing::traits::Hash;
     use crate::elliptic::curves::traits::{ECPoint, ECScalar};
     use crate::BigInt;
 
@@ -49,6 +81,33 @@ mod tests {
         assert!(result > BigInt::zero());
     }
 
+    // RFC 7693, appendix A
+    #[test]
+    fn test_hblake2b_vector() {
+        let expected = "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1\
+                        7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923";
+        assert_eq!(HBlake2b::create_hash_from_slice(b"abc").to_hex(), expected);
+        // inputs are concatenated big-endian, as by HSha256
+        let abc = BigInt::from_bytes(b"abc");
+        assert_eq!(HBlake2b::create_hash(&[&abc]).to_hex(), expected);
+        let (a, bc) = (BigInt::from_bytes(b"a"), BigInt::from_bytes(b"bc"));
+        assert_eq!(HBlake2b::create_hash(&[&a, &bc]).to_hex(), expected);
+    }
+
+    crate::test_for_all_curves!(test_hblake2b_from_ge);
+    fn test_hblake2b_from_ge<P>()
+    where
+        P: ECPoint,
+        P::Scalar: PartialEq + std::fmt::Debug,
+    {
+        let point = P::base_point2();
+        let mut bytes = point.pk_to_key_slice();
+        bytes.extend_from_slice(&P::generator().pk_to_key_slice());
+        let result: P::Scalar = HBlake2b::create_hash_from_ge(&[&point, &P::generator()]);
+        let expected: P::Scalar = ECScalar::from(&HBlake2b::create_hash_from_slice(&bytes));
+        assert_eq!(result, expected);
+    }
+
     crate::test_for_all_curves!(create_hash_from_ge_test);
 
     fn create_hash_from_ge_test<P>()
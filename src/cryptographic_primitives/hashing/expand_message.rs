@@ -0,0 +1,113 @@
+/*
+    This file is part of Curv library
+    Copyright 2018 by Kzen Networks
+    (https://github.com/KZen-networks/curv)
+    License MIT: https://github.com/KZen-networks/curv/blob/master/LICENSE
+*/
+
+//! `expand_message_xmd` with SHA-256, as specified by RFC 9380, section 5.3.1
+//! (https://www.rfc-editor.org/rfc/rfc9380#section-5.3.1). Hash-to-curve suites use it to turn a
+//! message into uniform bytes, bound to a domain separation tag.
+
+use sha2::{Digest, Sha256};
+
+const B_IN_BYTES: usize = 32;
+const S_IN_BYTES: usize = 64;
+
+/// Expands `msg` into `len_in_bytes` uniformly random bytes, bound to the domain separation tag
+/// `dst`. Tags longer than 255 bytes are hashed down as specified in section 5.3.3.
+///
+/// ## Panics
+/// Panics if `len_in_bytes` is zero or larger than 255 * 32 bytes.
+pub fn expand_message_xmd_sha256(msg: &[u8], dst: &[u8], len_in_bytes: usize) -> Vec<u8> {
+    let ell = len_in_bytes.div_ceil(B_IN_BYTES);
+    assert!(
+        len_in_bytes > 0 && ell <= 255,
+        "expand_message_xmd can't output {} bytes",
+        len_in_bytes
+    );
+    let dst = if dst.len() > 255 {
+        Sha256::new()
+            .chain(b"H2C-OVERSIZE-DST-")
+            .chain(dst)
+            .result()
+            .to_vec()
+    } else {
+        dst.to_vec()
+    };
+    let dst_prime = |hasher: Sha256| hasher.chain(&dst).chain([dst.len() as u8]);
+
+    let b_0 = dst_prime(
+        Sha256::new()
+            .chain([0u8; S_IN_BYTES])
+            .chain(msg)
+            .chain((len_in_bytes as u16).to_be_bytes())
+            .chain([0u8]),
+    )
+    .result();
+    let mut b_i = dst_prime(Sha256::new().chain(b_0).chain([1u8])).result();
+    let mut uniform_bytes = b_i.to_vec();
+    for i in 2..=ell {
+        let mut xored = [0u8; B_IN_BYTES];
+        for (x, (b0, bi)) in xored.iter_mut().zip(b_0.iter().zip(b_i.iter())) {
+            *x = b0 ^ bi;
+        }
+        b_i = dst_prime(Sha256::new().chain(xored).chain([i as u8])).result();
+        uniform_bytes.extend_from_slice(&b_i);
+    }
+    uniform_bytes.truncate(len_in_bytes);
+    uniform_bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 9380, appendix K.1
+    const DST: &[u8] = b"QUUX-V01-CS02-with-expander-SHA256-128";
+
+    #[test]
+    fn test_rfc9380_vectors() {
+        let vectors: [(&[u8], usize, &str); 4] = [
+            (
+                b"",
+                0x20,
+                "68a985b87eb6b46952128911f2a4412bbc302a9d759667f87f7a21d803f07235",
+            ),
+            (
+                b"abc",
+                0x20,
+                "d8ccab23b5985ccea865c6c97b6e5b8350e794e603b4b97902f53a8a0d605615",
+            ),
+            (
+                b"abcdef0123456789",
+                0x20,
+                "eff31487c770a893cfb36f912fbfcbff40d5661771ca4b2cb4eafe524333f5c1",
+            ),
+            (
+                b"",
+                0x80,
+                "af84c27ccfd45d41914fdff5df25293e221afc53d8ad2ac06d5e3e29485dadbee0d121587713a3e0dd4d5e69e93eb7cd4f5df4cd103e188cf60cb02edc3edf18eda8576c412b18ffb658e3dd6ec849469b979d444cf7b26911a08e63cf31f9dcc541708d3491184472c2c29bb749d4286b004ceb5ee6b9a7fa5b646c993f0ced",
+            ),
+        ];
+        for (msg, len, expected) in vectors.iter() {
+            assert_eq!(
+                hex::encode(expand_message_xmd_sha256(msg, DST, *len)),
+                *expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_long_dst_is_hashed() {
+        let long_dst = [7u8; 300];
+        let hashed = Sha256::new()
+            .chain(b"H2C-OVERSIZE-DST-")
+            .chain(&long_dst[..])
+            .result();
+        assert_eq!(
+            expand_message_xmd_sha256(b"msg", &long_dst, 48),
+            expand_message_xmd_sha256(b"msg", &hashed, 48)
+        );
+    }
+}
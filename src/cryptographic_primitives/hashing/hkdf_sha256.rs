@@ -0,0 +1,90 @@
+/*
+    This file is part of Curv library
+    Copyright 2018 by Kzen Networks
+    (https://github.com/KZen-networks/curv)
+    License MIT: https://github.com/KZen-networks/curv/blob/master/LICENSE
+*/
+
+//! HKDF over HMAC-SHA256, as specified by RFC 5869 (https://tools.ietf.org/html/rfc5869).
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use zeroize::Zeroize;
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub const HASH_LEN: usize = 32;
+
+/// Extracts a pseudorandom key from the input keying material `ikm`
+pub fn extract(salt: &[u8], ikm: &[u8]) -> [u8; HASH_LEN] {
+    let mut hmac = HmacSha256::new_varkey(salt).expect("HMAC accepts keys of any size");
+    hmac.input(ikm);
+    let mut prk = [0u8; HASH_LEN];
+    prk.copy_from_slice(hmac.result().code().as_slice());
+    prk
+}
+
+/// Expands the pseudorandom key `prk` into `len` bytes bound to `info`.
+///
+/// ## Panics
+/// Panics if `len` is larger than 255 * 32 bytes, the most HKDF can output.
+pub fn expand(prk: &[u8], info: &[u8], len: usize) -> Vec<u8> {
+    assert!(len <= 255 * HASH_LEN, "HKDF output is too long");
+    let mut okm = Vec::with_capacity(len);
+    let mut block: Vec<u8> = Vec::new();
+    let mut counter = 1u8;
+    while okm.len() < len {
+        let mut hmac = HmacSha256::new_varkey(prk).expect("HMAC accepts keys of any size");
+        hmac.input(&block);
+        hmac.input(info);
+        hmac.input(&[counter]);
+        block = hmac.result().code().to_vec();
+        let n = std::cmp::min(HASH_LEN, len - okm.len());
+        okm.extend_from_slice(&block[..n]);
+        counter = counter.wrapping_add(1);
+    }
+    block.zeroize();
+    okm
+}
+
+/// Extracts then expands, see [extract] and [expand]
+pub fn hkdf(salt: &[u8], ikm: &[u8], info: &[u8], len: usize) -> Vec<u8> {
+    let mut prk = extract(salt, ikm);
+    let okm = expand(&prk, info, len);
+    prk.zeroize();
+    okm
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arithmetic::traits::*;
+    use crate::BigInt;
+
+    fn hex(s: &str) -> Vec<u8> {
+        let bytes = BigInt::to_bytes(&BigInt::from_hex(s).unwrap());
+        let mut padded = vec![0u8; s.len() / 2 - bytes.len()];
+        padded.extend_from_slice(&bytes);
+        padded
+    }
+
+    // RFC 5869, appendix A.1
+    #[test]
+    fn test_rfc5869_vector() {
+        let ikm = [0x0bu8; 22];
+        let salt = hex("000102030405060708090a0b0c");
+        let info = hex("f0f1f2f3f4f5f6f7f8f9");
+
+        let prk = extract(&salt, &ikm);
+        assert_eq!(
+            prk.to_vec(),
+            hex("077709362c2e32df0ddc3f0dc47bba6390b6c73bb50f9c3122ec844ad7c2b3e5")
+        );
+        let okm = expand(&prk, &info, 42);
+        assert_eq!(
+            okm,
+            hex("3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865")
+        );
+        assert_eq!(hkdf(&salt, &ikm, &info, 42), okm);
+    }
+}
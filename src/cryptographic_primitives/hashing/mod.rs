@@ -5,8 +5,12 @@
     License MIT: https://github.com/KZen-networks/curv/blob/master/LICENSE
 */
 pub mod blake2b512;
+pub mod expand_message;
 pub mod hash_sha256;
 pub mod hash_sha512;
+pub mod hkdf_sha256;
 pub mod hmac_sha512;
 pub mod merkle_tree;
+pub mod rfc6979;
 pub mod traits;
+pub mod transcript;
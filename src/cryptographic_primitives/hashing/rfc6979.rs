@@ -0,0 +1,184 @@
+/*
+    This file is part of Curv library
+    Copyright 2018 by Kzen Networks
+    (https://github.com/KZen-networks/curv)
+    License MIT: https://github.com/KZen-networks/curv/blob/master/LICENSE
+*/
+
+//! Deterministic nonces of RFC 6979 (https://tools.ietf.org/html/rfc6979#section-3.2) with
+//! HMAC-SHA256, for reproducible ECDSA signatures.
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use zeroize::Zeroize;
+
+use crate::arithmetic::traits::*;
+use crate::elliptic::curves::traits::ECScalar;
+use crate::BigInt;
+
+type HmacSha256 = Hmac<Sha256>;
+
+const HASH_LEN: usize = 32;
+
+/// Derives the nonce k for signing the digest `msg_hash` with `secret`, as specified by RFC 6979
+/// with HMAC-SHA256 over the order of the scalar's group. Candidates that aren't in `[1, q)` are
+/// rejected and the next one is drawn, as required by step 3.2.h.
+///
+/// `msg_hash` is the SHA-256 digest of the message read as a big-endian integer.
+///
+/// ## Panics
+/// Panics if `msg_hash` is longer than 256 bits.
+pub fn nonce<S: ECScalar>(secret: &S, msg_hash: &BigInt) -> S {
+    assert!(
+        msg_hash.bit_length() <= 8 * HASH_LEN,
+        "msg_hash isn't a SHA-256 digest"
+    );
+    let q = S::q();
+    let qlen = q.bit_length();
+    let rlen = qlen.div_ceil(8);
+
+    let mut x = int2octets(&secret.to_big_int(), rlen);
+    // bits2octets(h1)
+    let h1 = bits2int(&int2octets(msg_hash, HASH_LEN), qlen).modulus(&q);
+    let h1 = int2octets(&h1, rlen);
+
+    let mut v = [0x01u8; HASH_LEN];
+    let mut k = [0x00u8; HASH_LEN];
+    for separator in [0x00u8, 0x01] {
+        k = hmac(&k, &[&v, &[separator], &x, &h1]);
+        v = hmac(&k, &[&v]);
+    }
+    x.zeroize();
+
+    loop {
+        let mut t = Vec::with_capacity(rlen + HASH_LEN);
+        while t.len() < rlen {
+            v = hmac(&k, &[&v]);
+            t.extend_from_slice(&v);
+        }
+        let mut candidate = bits2int(&t, qlen);
+        t.zeroize();
+        if candidate > BigInt::zero() && candidate < q {
+            let nonce = S::from(&candidate);
+            candidate.zeroize();
+            k.zeroize();
+            v.zeroize();
+            return nonce;
+        }
+        k = hmac(&k, &[&v, &[0x00]]);
+        v = hmac(&k, &[&v]);
+    }
+}
+
+fn hmac(key: &[u8], data: &[&[u8]]) -> [u8; HASH_LEN] {
+    let mut hmac = HmacSha256::new_varkey(key).expect("HMAC accepts keys of any size");
+    for d in data {
+        hmac.input(d);
+    }
+    let mut out = [0u8; HASH_LEN];
+    out.copy_from_slice(hmac.result().code().as_slice());
+    out
+}
+
+// the leftmost qlen bits of `bytes`
+fn bits2int(bytes: &[u8], qlen: usize) -> BigInt {
+    let n = BigInt::from_bytes(bytes);
+    if bytes.len() * 8 > qlen {
+        n >> (bytes.len() * 8 - qlen)
+    } else {
+        n
+    }
+}
+
+// big-endian encoding of `n` on exactly `len` bytes
+fn int2octets(n: &BigInt, len: usize) -> Vec<u8> {
+    let bytes = BigInt::to_bytes(n);
+    let mut padded = vec![0u8; len - bytes.len()];
+    padded.extend_from_slice(&bytes);
+    padded
+}
+
+#[cfg(test)]
+mod tests {
+    use sha2::Digest;
+
+    use super::*;
+    use crate::elliptic::curves::{ed25519, p256, secp256_k1};
+
+    fn sha256(message: &[u8]) -> BigInt {
+        BigInt::from_bytes(&Sha256::digest(message))
+    }
+
+    fn assert_nonce<S: ECScalar>(secret: &BigInt, message: &[u8], expected: &str) {
+        let secret: S = ECScalar::from(secret);
+        let k = nonce(&secret, &sha256(message));
+        assert_eq!(k.to_big_int(), BigInt::from_hex(expected).unwrap());
+    }
+
+    // RFC 6979, appendix A.2.5
+    #[test]
+    fn test_rfc6979_p256_vectors() {
+        let x =
+            BigInt::from_hex("c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721")
+                .unwrap();
+        assert_nonce::<p256::FE>(
+            &x,
+            b"sample",
+            "a6e3c57dd01abe90086538398355dd4c3b17aa873382b0f24d6129493d8aad60",
+        );
+        assert_nonce::<p256::FE>(
+            &x,
+            b"test",
+            "d16b6ae827f17175e040871a1c7ec3500192c4c92677336ec2537acaee0008e0",
+        );
+    }
+
+    // the secp256k1 vectors used by Bitcoin implementations, e.g. python-ecdsa
+    #[test]
+    fn test_rfc6979_secp256k1_vectors() {
+        let one = BigInt::one();
+        assert_nonce::<secp256_k1::FE>(
+            &one,
+            b"Satoshi Nakamoto",
+            "8f8a276c19f4149656b280621e358cce24f5f52542772691ee69063b74f15d15",
+        );
+        assert_nonce::<secp256_k1::FE>(
+            &one,
+            b"All those moments will be lost in time, like tears in rain. Time to die...",
+            "38aa22d72376b4dbc472e06c3ba403ee0a394da63fc58d88686c611aba98d6b3",
+        );
+        assert_nonce::<secp256_k1::FE>(
+            &(secp256_k1::FE::q() - BigInt::one()),
+            b"Satoshi Nakamoto",
+            "33a19b60e25fb6f4435af53a3d42d493644827367e6453928554f43e49aa6f90",
+        );
+        assert_nonce::<secp256_k1::FE>(
+            &BigInt::from_hex("f8b8af8ce3c7cca5e300d33939540c10d45ce001b8f252bfbc57ba0342904181")
+                .unwrap(),
+            b"Alan Turing",
+            "525a82b70e67874398067543fd84c83d30c175fdc45fdeee082fe13b1d7cfdf1",
+        );
+    }
+
+    // Candidates above the order are rejected twice before this nonce. The order of ed25519 is
+    // just above 2^252 and candidates have 253 bits, so about half of them are rejected. The
+    // expected value was computed with an independent implementation of the RFC.
+    #[test]
+    fn test_rfc6979_retries_candidates_above_the_order() {
+        assert_nonce::<ed25519::FE>(
+            &BigInt::one(),
+            b"sample",
+            "e873c0b154d01985fbdd819fe0517acd242e3d5d9ac8509ccd29f9523b33b54",
+        );
+    }
+
+    #[test]
+    fn test_nonce_depends_on_secret_and_message() {
+        let secret: secp256_k1::FE = ECScalar::new_random();
+        let k = nonce(&secret, &sha256(b"message"));
+        assert_eq!(nonce(&secret, &sha256(b"message")), k);
+        assert_ne!(nonce(&secret, &sha256(b"another message")), k);
+        let other_secret: secp256_k1::FE = ECScalar::new_random();
+        assert_ne!(nonce(&other_secret, &sha256(b"message")), k);
+    }
+}
@@ -0,0 +1,259 @@
+/*
+    This file is part of Curv library
+    Copyright 2018 by Kzen Networks
+    (https://github.com/KZen-networks/curv)
+    License MIT: https://github.com/KZen-networks/curv/blob/master/LICENSE
+*/
+
+use std::collections::HashSet;
+
+use digest::Digest;
+use sha2::Sha256;
+
+use crate::arithmetic::traits::*;
+use crate::elliptic::curves::traits::{ECPoint, ECScalar};
+use crate::BigInt;
+
+pub const DIGEST_SIZE: usize = 32;
+
+const OP_INIT: u8 = 0;
+const OP_MESSAGE: u8 = 1;
+const OP_PREHASHED: u8 = 2;
+const OP_CHALLENGE: u8 = 3;
+const OP_CHALLENGE_POINTS: u8 = 4;
+
+/// Transcript of a protocol that Fiat-Shamir challenges are derived from.
+///
+/// Every appended value is absorbed into a running SHA-256 state together with its label and
+/// length, so two different sequences of appended values never lead to the same state. Deriving
+/// a challenge also updates the state, so successive challenges are independent.
+///
+/// ## Large messages
+/// A message appended with [append_message](Self::append_message) is hashed once more every time
+/// the transcript is replayed. Protocols over large messages should instead hash the message
+/// once with [Transcript::prehash] and append the digest with
+/// [append_prehashed](Self::append_prehashed). A prehashed message is framed differently from a
+/// plain one, so appending a digest never collides with appending a 32 bytes long message.
+///
+/// ```
+/// # use curv::cryptographic_primitives::hashing::transcript::Transcript;
+/// # use curv::elliptic::curves::secp256_k1::FE;
+/// let message = vec![0u8; 1 << 20];
+/// let digest = Transcript::prehash(&message);
+///
+/// let mut transcript = Transcript::new(b"my protocol");
+/// transcript.append_prehashed(b"message", &digest);
+/// let e: FE = transcript.challenge_scalar(b"challenge");
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Transcript {
+    state: [u8; DIGEST_SIZE],
+}
+
+impl Transcript {
+    /// Starts a transcript for the protocol identified by `label`
+    pub fn new(label: &'static [u8]) -> Transcript {
+        let mut transcript = Transcript {
+            state: [0u8; DIGEST_SIZE],
+        };
+        transcript.absorb(OP_INIT, label, &[]);
+        transcript
+    }
+
+    /// Hashes a message to the digest expected by [append_prehashed](Self::append_prehashed)
+    pub fn prehash(message: &[u8]) -> [u8; DIGEST_SIZE] {
+        let mut digest = [0u8; DIGEST_SIZE];
+        digest.copy_from_slice(&Sha256::digest(message));
+        digest
+    }
+
+    pub fn append_message(&mut self, label: &'static [u8], message: &[u8]) {
+        self.absorb(OP_MESSAGE, label, message);
+    }
+
+    /// Appends the encoding of a point returned by [pk_to_key_slice](ECPoint::pk_to_key_slice)
+    pub fn append_point<P: ECPoint>(&mut self, label: &'static [u8], point: &P) {
+        self.append_message(label, &point.pk_to_key_slice());
+    }
+
+    /// Appends the big-endian encoding of a scalar
+    pub fn append_scalar<S: ECScalar>(&mut self, label: &'static [u8], scalar: &S) {
+        self.append_message(label, &BigInt::to_bytes(&scalar.to_big_int()));
+    }
+
+    /// Appends the SHA-256 digest of a message, as computed by [Transcript::prehash]
+    pub fn append_prehashed(&mut self, label: &'static [u8], digest: &[u8; DIGEST_SIZE]) {
+        self.absorb(OP_PREHASHED, label, digest);
+    }
+
+    /// Derives a challenge from everything appended so far
+    pub fn challenge_scalar<S: ECScalar>(&mut self, label: &'static [u8]) -> S {
+        // 512 bits reduced modulo the group order, so the bias is negligible for every curve
+        let mut bytes = Vec::with_capacity(2 * DIGEST_SIZE);
+        for i in 0..2u8 {
+            let block = Sha256::new()
+                .chain(self.state)
+                .chain([OP_CHALLENGE, i])
+                .chain(label)
+                .result();
+            bytes.extend_from_slice(&block);
+        }
+        self.absorb(OP_CHALLENGE, label, &[]);
+        let n = BigInt::from_bytes(&bytes);
+        ECScalar::from(&n)
+    }
+
+    /// Derives `n` points from everything appended so far, for instance the generators of a
+    /// proof system that every verifier must be able to reproduce.
+    ///
+    /// Points are obtained by hashing the transcript state together with an index to the curve,
+    /// so nobody knows the discrete logarithm between any two of them. None of them is the
+    /// identity and they are pairwise distinct.
+    pub fn challenge_points<P>(&mut self, label: &'static [u8], n: usize) -> Vec<P>
+    where
+        P: ECPoint + Clone,
+    {
+        let mut points = Vec::with_capacity(n);
+        let mut seen = HashSet::with_capacity(n);
+        let mut index = 0u64;
+        while points.len() < n {
+            let mut message = Vec::with_capacity(2 * DIGEST_SIZE);
+            message.extend_from_slice(&self.state);
+            message.push(OP_CHALLENGE_POINTS);
+            message.extend_from_slice(&(label.len() as u64).to_be_bytes());
+            message.extend_from_slice(label);
+            message.extend_from_slice(&index.to_be_bytes());
+            index += 1;
+
+            let point = P::hash_to_curve(&message);
+            // P + P = P holds only for the identity
+            if point.clone() + point.clone() == point {
+                continue;
+            }
+            if seen.insert(point.pk_to_key_slice()) {
+                points.push(point);
+            }
+        }
+        self.absorb(OP_CHALLENGE_POINTS, label, &(n as u64).to_be_bytes());
+        points
+    }
+
+    fn absorb(&mut self, op: u8, label: &[u8], data: &[u8]) {
+        let result = Sha256::new()
+            .chain(self.state)
+            .chain([op])
+            .chain((label.len() as u64).to_be_bytes())
+            .chain(label)
+            .chain((data.len() as u64).to_be_bytes())
+            .chain(data)
+            .result();
+        self.state.copy_from_slice(&result);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_for_all_curves;
+
+    /// Schnorr signature whose challenge is derived from a transcript `append` the message was
+    /// fed into
+    fn sign<P>(x: &P::Scalar, append: impl Fn(&mut Transcript)) -> (P, P::Scalar)
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Clone,
+    {
+        let k: P::Scalar = ECScalar::new_random();
+        let r = P::generator() * k.clone();
+        let mut transcript = Transcript::new(b"test schnorr");
+        append(&mut transcript);
+        transcript.append_message(b"R", &r.pk_to_key_slice());
+        let e: P::Scalar = transcript.challenge_scalar(b"e");
+        (r, k + e * x.clone())
+    }
+
+    fn verify<P>(pk: &P, sig: &(P, P::Scalar), append: impl Fn(&mut Transcript)) -> bool
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Clone,
+    {
+        let mut transcript = Transcript::new(b"test schnorr");
+        append(&mut transcript);
+        transcript.append_message(b"R", &sig.0.pk_to_key_slice());
+        let e: P::Scalar = transcript.challenge_scalar(b"e");
+        P::generator() * sig.1.clone() == sig.0.clone() + pk.clone() * e
+    }
+
+    test_for_all_curves!(test_prehashed_message_verifies_like_inline);
+    fn test_prehashed_message_verifies_like_inline<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Clone,
+    {
+        let x: P::Scalar = ECScalar::new_random();
+        let pk = P::generator() * x.clone();
+        let message = vec![42u8; 1 << 16];
+        let mut tampered = message.clone();
+        tampered[1000] ^= 1;
+
+        let inline = |m: &[u8]| {
+            let m = m.to_vec();
+            move |t: &mut Transcript| t.append_message(b"message", &m)
+        };
+        let prehashed = |m: &[u8]| {
+            let digest = Transcript::prehash(m);
+            move |t: &mut Transcript| t.append_prehashed(b"message", &digest)
+        };
+
+        let sig = sign::<P>(&x, inline(&message));
+        assert!(verify(&pk, &sig, inline(&message)));
+        assert!(!verify(&pk, &sig, inline(&tampered)));
+
+        let sig = sign::<P>(&x, prehashed(&message));
+        assert!(verify(&pk, &sig, prehashed(&message)));
+        assert!(!verify(&pk, &sig, prehashed(&tampered)));
+    }
+
+    #[test]
+    fn test_prehashed_is_not_a_plain_message() {
+        let digest = Transcript::prehash(b"message");
+        let mut t1 = Transcript::new(b"test");
+        t1.append_prehashed(b"m", &digest);
+        let mut t2 = Transcript::new(b"test");
+        t2.append_message(b"m", &digest);
+        assert_ne!(t1, t2);
+    }
+
+    test_for_all_curves!(test_challenge_points);
+    fn test_challenge_points<P>()
+    where
+        P: ECPoint + Clone,
+    {
+        let derive = |context: &[u8], n| {
+            let mut transcript = Transcript::new(b"test setup");
+            transcript.append_message(b"context", context);
+            let points: Vec<P> = transcript.challenge_points(b"generators", n);
+            points
+                .iter()
+                .map(|p| p.pk_to_key_slice())
+                .collect::<Vec<_>>()
+        };
+        let points = derive(b"A", 16);
+        assert_eq!(points, derive(b"A", 16));
+        // a shorter vector is a prefix of a longer one
+        assert_eq!(points[..4], derive(b"A", 4)[..]);
+        let distinct: HashSet<_> = points.iter().collect();
+        assert_eq!(distinct.len(), points.len());
+        assert!(!points.contains(&P::generator().pk_to_key_slice()));
+        assert!(derive(b"B", 16).iter().all(|p| !distinct.contains(p)));
+
+        // deriving points moves the transcript forward
+        let mut t1 = Transcript::new(b"test setup");
+        let mut t2 = t1.clone();
+        let _: Vec<P> = t1.challenge_points(b"generators", 2);
+        assert_ne!(t1, t2);
+        let e1: P::Scalar = t1.challenge_scalar(b"e");
+        let e2: P::Scalar = t2.challenge_scalar(b"e");
+        assert_ne!(e1.to_big_int(), e2.to_big_int());
+    }
+}
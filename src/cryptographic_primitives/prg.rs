@@ -0,0 +1,60 @@
+/*
+    This file is part of Curv library
+    Copyright 2018 by Kzen Networks
+    (https://github.com/KZen-networks/curv)
+    License MIT: https://github.com/KZen-networks/curv/blob/master/LICENSE
+*/
+
+//! Expansion of a shared seed into scalars, e.g. for the correlated randomness of MPC
+//! preprocessing: parties holding the same seed derive the same scalars without interacting.
+
+use rand::SeedableRng;
+use rand_chacha::ChaChaRng;
+
+use crate::cryptographic_primitives::proofs::sample_scalar_with_rng;
+use crate::elliptic::curves::traits::ECScalar;
+
+/// Expands `seed` into `count` scalars, sampled uniformly from the ChaCha20 stream keyed by
+/// `seed`. The output for a smaller `count` is a prefix of the output for a larger one.
+pub fn expand_to_scalars<S: ECScalar>(seed: &[u8; 32], count: usize) -> Vec<S> {
+    let mut rng = ChaChaRng::from_seed(*seed);
+    (0..count)
+        .map(|_| sample_scalar_with_rng(&mut rng))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::arithmetic::traits::*;
+    use crate::elliptic::curves::traits::ECPoint;
+    use crate::test_for_all_curves;
+    use crate::BigInt;
+
+    test_for_all_curves!(test_expand_to_scalars);
+    fn test_expand_to_scalars<P: ECPoint>() {
+        let expand = |seed: &[u8; 32], count| -> Vec<BigInt> {
+            expand_to_scalars::<P::Scalar>(seed, count)
+                .iter()
+                .map(|s| s.to_big_int())
+                .collect()
+        };
+        let scalars = expand(&[1u8; 32], 64);
+        assert_eq!(scalars, expand(&[1u8; 32], 64));
+        assert_eq!(scalars[..8], expand(&[1u8; 32], 8)[..]);
+
+        // no zero, no repetition, different from the output of another seed
+        assert!(scalars.iter().all(|s| *s != BigInt::zero()));
+        let distinct: HashSet<String> = scalars.iter().map(|s| s.to_hex()).collect();
+        assert_eq!(distinct.len(), scalars.len());
+        assert!(expand(&[2u8; 32], 64)
+            .iter()
+            .all(|s| !distinct.contains(&s.to_hex())));
+        // the scalars spread over the whole range
+        let q = P::Scalar::q();
+        assert!(scalars.iter().any(|s| *s > &q / BigInt::from(2)));
+        assert!(scalars.iter().any(|s| *s < &q / BigInt::from(2)));
+    }
+}
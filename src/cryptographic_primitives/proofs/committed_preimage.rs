@@ -0,0 +1,121 @@
+/*
+    This file is part of Curv library
+    Copyright 2018 by Kzen Networks
+    (https://github.com/KZen-networks/curv)
+    License MIT: https://github.com/KZen-networks/curv/blob/master/LICENSE
+*/
+
+//! Commitment side of a proof that the value `v` committed in `c = vG + rH` is the preimage of a
+//! public hash lock `h = SHA-256(v)`.
+//!
+//! ## Design note
+//! SHA-256 has no algebraic structure a sigma protocol could follow, so a proof that
+//! `SHA-256(v) = h` for the same `v` that is committed in `c` requires a general purpose
+//! argument over the SHA-256 circuit (a SNARK, or an MPC-in-the-head proof such as ZKBoo),
+//! together with a commitment to `v` that this argument opens. This crate doesn't implement such
+//! arguments.
+//!
+//! What is provided here is the part that lives on the curve: a proof of knowledge of an opening
+//! `(v, r)` of `c` whose challenge is bound to `h`, so the proof can't be replayed against
+//! another hash lock. It shows that the prover knows the committed value, but not that this
+//! value hashes to `h`: a complete protocol must combine it with a hash-preimage argument over
+//! the same `v`. Once `v` is revealed, e.g. when the hash lock is redeemed, anyone can check both
+//! relations directly with [hash_lock].
+//!
+//! The preimage is the 32 bytes big-endian encoding of `v`, so hash locks built over random
+//! 32 bytes preimages can only be used if the preimage is smaller than the group order.
+
+use sha2::{Digest, Sha256};
+use zeroize::Zeroize;
+
+use super::sigma_valid_pedersen::PedersenProof;
+use super::ProofError;
+use crate::arithmetic::traits::*;
+use crate::elliptic::curves::traits::*;
+use crate::BigInt;
+
+/// Domain tag the hash lock is appended to when computing the challenge
+pub const COMMITTED_PREIMAGE_DOMAIN: &[u8] = b"curv committed preimage";
+
+pub type CommittedPreimageProof<P> = PedersenProof<P>;
+
+/// Returns the hash lock of `v`, SHA-256 of its 32 bytes big-endian encoding
+pub fn hash_lock<S: ECScalar>(v: &S) -> [u8; 32] {
+    let bytes = BigInt::to_bytes(&v.to_big_int());
+    let mut preimage = [0u8; 32];
+    preimage[32 - bytes.len()..].copy_from_slice(&bytes);
+    let mut lock = [0u8; 32];
+    lock.copy_from_slice(&Sha256::digest(&preimage));
+    preimage.zeroize();
+    lock
+}
+
+/// Proves knowledge of the opening `(v, r)` of `c = vG + rH`, bound to `hash_lock`. The
+/// commitment is part of the proof.
+pub fn prove_knowledge_of_committed_preimage<P>(
+    v: &P::Scalar,
+    r: &P::Scalar,
+    hash_lock: &[u8; 32],
+) -> CommittedPreimageProof<P>
+where
+    P: ECPoint + Clone,
+    P::Scalar: Zeroize,
+{
+    PedersenProof::builder()
+        .message(v)
+        .blinding(r)
+        .domain(&domain(hash_lock))
+        .prove()
+}
+
+pub fn verify_knowledge_of_committed_preimage<P>(
+    proof: &CommittedPreimageProof<P>,
+    hash_lock: &[u8; 32],
+) -> Result<(), ProofError>
+where
+    P: ECPoint + Clone,
+    P::Scalar: Zeroize,
+{
+    PedersenProof::builder()
+        .domain(&domain(hash_lock))
+        .verify(proof)
+}
+
+fn domain(hash_lock: &[u8; 32]) -> Vec<u8> {
+    let mut domain = COMMITTED_PREIMAGE_DOMAIN.to_vec();
+    domain.extend_from_slice(hash_lock);
+    domain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cryptographic_primitives::commitments::pedersen_commitment::PedersenCommitment;
+    use crate::cryptographic_primitives::commitments::traits::Commitment;
+    use crate::test_for_all_curves;
+
+    test_for_all_curves!(test_committed_preimage);
+    fn test_committed_preimage<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize,
+    {
+        let v: P::Scalar = ECScalar::new_random();
+        let r: P::Scalar = ECScalar::new_random();
+        let lock = hash_lock(&v);
+        let proof = prove_knowledge_of_committed_preimage::<P>(&v, &r, &lock);
+        assert!(verify_knowledge_of_committed_preimage(&proof, &lock).is_ok());
+
+        let other: P::Scalar = ECScalar::new_random();
+        assert!(verify_knowledge_of_committed_preimage(&proof, &hash_lock(&other)).is_err());
+        assert!(PedersenProof::verify(&proof).is_err());
+
+        // once the preimage is revealed, both relations can be checked directly
+        let com: P = PedersenCommitment::create_commitment_with_user_defined_randomness(
+            &v.to_big_int(),
+            &r.to_big_int(),
+        );
+        assert!(com == proof.com);
+        assert_eq!(hash_lock(&v), lock);
+    }
+}
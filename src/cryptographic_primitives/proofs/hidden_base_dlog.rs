@@ -0,0 +1,221 @@
+/*
+    This file is part of Curv library
+    Copyright 2018 by Kzen Networks
+    (https://github.com/KZen-networks/curv)
+    License MIT: https://github.com/KZen-networks/curv/blob/master/LICENSE
+*/
+
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use super::{bind_session_id, proof_transcript, EncodeProof, ProofEncoder, ProofError};
+use crate::elliptic::curves::traits::*;
+
+/// protocol for proving that Q = xB for a base point B = bG that is only known through a Pedersen
+/// commitment c = bG + rH, and a secret x known through its public key X = xG.
+/// Neither B nor x is revealed, this is a proof of the product Q = (xb)G.
+/// witness: (x,b,r), statement: (X,c,Q), The Relation R outputs 1 if :
+/// X = xG, c = bG + rH, Q = bX
+/// The protocol:
+/// 1: Prover chooses A1 = s1*G, A2 = s2*G + s3*H, A3 = s2*X for random s1,s2,s3
+/// prover calculates challenge e = H(G,H,X,c,Q,A1,A2,A3)
+/// prover calculates z1 = s1 + e*x, z2 = s2 + e*b, z3 = s3 + e*r
+/// prover sends pi = {A1,A2,A3,z1,z2,z3}
+///
+/// verifier checks that z1*G = A1 + eX, z2*G + z3*H = A2 + ec, z2*X = A3 + eQ
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct HiddenBaseDLogProof<P: ECPoint> {
+    pub a1: P,
+    pub a2: P,
+    pub a3: P,
+    pub z1: P::Scalar,
+    pub z2: P::Scalar,
+    pub z3: P::Scalar,
+}
+
+impl<P: ECPoint> EncodeProof<P> for HiddenBaseDLogProof<P> {
+    fn encode<E: ProofEncoder<P>>(&self, encoder: &mut E) {
+        encoder.point(&self.a1);
+        encoder.point(&self.a2);
+        encoder.point(&self.a3);
+        encoder.scalar(&self.z1);
+        encoder.scalar(&self.z2);
+        encoder.scalar(&self.z3);
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct HiddenBaseDLogStatement<P: ECPoint> {
+    pub pk: P,
+    pub base_commitment: P,
+    pub q: P,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct HiddenBaseDLogWitness<S: ECScalar> {
+    pub x: S,
+    pub b: S,
+    pub r: S,
+}
+
+impl<P> HiddenBaseDLogProof<P>
+where
+    P: ECPoint + Clone,
+    P::Scalar: Zeroize + Clone,
+{
+    pub fn prove(
+        w: &HiddenBaseDLogWitness<P::Scalar>,
+        delta: &HiddenBaseDLogStatement<P>,
+    ) -> HiddenBaseDLogProof<P> {
+        Self::prove_with_session_id(w, delta, &[])
+    }
+
+    /// Same as [prove](Self::prove), with the challenge bound to `session_id`. The proof is only
+    /// accepted by [verify_with_session_id](Self::verify_with_session_id) given the same id.
+    pub fn prove_with_session_id(
+        w: &HiddenBaseDLogWitness<P::Scalar>,
+        delta: &HiddenBaseDLogStatement<P>,
+        session_id: &[u8],
+    ) -> HiddenBaseDLogProof<P> {
+        let g: P = ECPoint::generator();
+        let h: P = ECPoint::base_point2();
+        let mut s1: P::Scalar = ECScalar::new_random();
+        let mut s2: P::Scalar = ECScalar::new_random();
+        let mut s3: P::Scalar = ECScalar::new_random();
+        let a1 = g.clone() * s1.clone();
+        let a2 = g * s2.clone() + h * s3.clone();
+        let a3 = delta.pk.clone() * s2.clone();
+        let e = Self::challenge(delta, &a1, &a2, &a3, session_id);
+        let z1 = s1.clone() + e.clone() * w.x.clone();
+        let z2 = s2.clone() + e.clone() * w.b.clone();
+        let z3 = s3.clone() + e * w.r.clone();
+        s1.zeroize();
+        s2.zeroize();
+        s3.zeroize();
+        HiddenBaseDLogProof {
+            a1,
+            a2,
+            a3,
+            z1,
+            z2,
+            z3,
+        }
+    }
+
+    pub fn verify(&self, delta: &HiddenBaseDLogStatement<P>) -> Result<(), ProofError> {
+        self.verify_with_session_id(delta, &[])
+    }
+
+    pub fn verify_with_session_id(
+        &self,
+        delta: &HiddenBaseDLogStatement<P>,
+        session_id: &[u8],
+    ) -> Result<(), ProofError> {
+        let g: P = ECPoint::generator();
+        let h: P = ECPoint::base_point2();
+        let e = Self::challenge(delta, &self.a1, &self.a2, &self.a3, session_id);
+        let z1_g = g.clone() * self.z1.clone();
+        let z2_g_z3_h = g * self.z2.clone() + h * self.z3.clone();
+        let z2_pk = delta.pk.clone() * self.z2.clone();
+        let a1_plus_e_pk = self.a1.clone() + delta.pk.clone() * e.clone();
+        let a2_plus_e_c = self.a2.clone() + delta.base_commitment.clone() * e.clone();
+        let a3_plus_e_q = self.a3.clone() + delta.q.clone() * e;
+        if z1_g == a1_plus_e_pk && z2_g_z3_h == a2_plus_e_c && z2_pk == a3_plus_e_q {
+            Ok(())
+        } else {
+            Err(ProofError)
+        }
+    }
+
+    fn challenge(
+        delta: &HiddenBaseDLogStatement<P>,
+        a1: &P,
+        a2: &P,
+        a3: &P,
+        session_id: &[u8],
+    ) -> P::Scalar {
+        let g: P = ECPoint::generator();
+        let h: P = ECPoint::base_point2();
+        let mut transcript = proof_transcript::<P>(b"curv hidden base dlog proof");
+        transcript.append_point(b"G", &g);
+        transcript.append_point(b"H", &h);
+        transcript.append_point(b"X", &delta.pk);
+        transcript.append_point(b"c", &delta.base_commitment);
+        transcript.append_point(b"Q", &delta.q);
+        transcript.append_point(b"A1", a1);
+        transcript.append_point(b"A2", a2);
+        transcript.append_point(b"A3", a3);
+        bind_session_id(transcript.challenge_scalar(b"e"), session_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cryptographic_primitives::commitments::pedersen_commitment::PedersenCommitment;
+    use crate::cryptographic_primitives::commitments::traits::Commitment;
+    use crate::cryptographic_primitives::proofs::hidden_base_dlog::*;
+    use crate::test_for_all_curves;
+
+    fn statement<P>(w: &HiddenBaseDLogWitness<P::Scalar>) -> HiddenBaseDLogStatement<P>
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Clone,
+    {
+        let g: P = ECPoint::generator();
+        let base = g.clone() * w.b.clone();
+        HiddenBaseDLogStatement {
+            pk: g * w.x.clone(),
+            base_commitment: PedersenCommitment::create_commitment_with_user_defined_randomness(
+                &w.b.to_big_int(),
+                &w.r.to_big_int(),
+            ),
+            q: base * w.x.clone(),
+        }
+    }
+
+    test_for_all_curves!(test_hidden_base_dlog_proof);
+    fn test_hidden_base_dlog_proof<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize + Clone,
+    {
+        let w = HiddenBaseDLogWitness {
+            x: ECScalar::new_random(),
+            b: ECScalar::new_random(),
+            r: ECScalar::new_random(),
+        };
+        let delta = statement::<P>(&w);
+        let proof = HiddenBaseDLogProof::prove(&w, &delta);
+        assert!(proof.verify(&delta).is_ok());
+
+        let proof = HiddenBaseDLogProof::prove_with_session_id(&w, &delta, b"session A");
+        assert!(proof.verify_with_session_id(&delta, b"session A").is_ok());
+        assert!(proof.verify_with_session_id(&delta, b"session B").is_err());
+    }
+
+    test_for_all_curves!(test_wrong_hidden_base_dlog_proof);
+    fn test_wrong_hidden_base_dlog_proof<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize + Clone,
+    {
+        let w = HiddenBaseDLogWitness {
+            x: ECScalar::new_random(),
+            b: ECScalar::new_random(),
+            r: ECScalar::new_random(),
+        };
+        // Q is x times a base other than the committed one
+        let mut delta = statement::<P>(&w);
+        let other_base = P::generator() * P::Scalar::new_random();
+        delta.q = other_base * w.x.clone();
+        let proof = HiddenBaseDLogProof::prove(&w, &delta);
+        assert!(proof.verify(&delta).is_err());
+
+        // a valid proof doesn't verify for another Q
+        let delta = statement::<P>(&w);
+        let proof = HiddenBaseDLogProof::prove(&w, &delta);
+        let mut other_delta = delta.clone();
+        other_delta.q = delta.q.clone() + P::generator();
+        assert!(proof.verify(&other_delta).is_err());
+    }
+}
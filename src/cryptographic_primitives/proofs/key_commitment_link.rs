@@ -0,0 +1,199 @@
+/*
+    This file is part of Curv library
+    Copyright 2018 by Kzen Networks
+    (https://github.com/KZen-networks/curv)
+    License MIT: https://github.com/KZen-networks/curv/blob/master/LICENSE
+*/
+
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use super::{bind_session_id, proof_transcript, EncodeProof, ProofEncoder, ProofError};
+use crate::elliptic::curves::traits::*;
+
+/// protocol for proving that a public key P and a Pedersen commitment c = skG + rH are built over
+/// the same secret key sk.
+/// witness: (sk,r), statement: (P,c), The Relation R outputs 1 if :
+/// P = skG, c - P = rH
+/// The protocol:
+/// 1: Prover chooses A1 = s1*G , A2 = s2*H for random s1,s2
+/// prover calculates challenge e = H(G,H,P,c,A1,A2)
+/// prover calculates z1  = s1 + e*sk, z2 = s2 + e*r
+/// prover sends pi = {A1,A2,z1,z2}
+///
+/// verifier checks that z1*G = A1 + eP, z2*H = A2 + e(c - P)
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct KeyCommitmentLinkProof<P: ECPoint> {
+    pub a1: P,
+    pub a2: P,
+    pub z1: P::Scalar,
+    pub z2: P::Scalar,
+}
+
+impl<P: ECPoint> EncodeProof<P> for KeyCommitmentLinkProof<P> {
+    fn encode<E: ProofEncoder<P>>(&self, encoder: &mut E) {
+        encoder.point(&self.a1);
+        encoder.point(&self.a2);
+        encoder.scalar(&self.z1);
+        encoder.scalar(&self.z2);
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct KeyCommitmentLinkStatement<P: ECPoint> {
+    pub pk: P,
+    pub com: P,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct KeyCommitmentLinkWitness<S: ECScalar> {
+    pub sk: S,
+    pub r: S,
+}
+
+impl<P> KeyCommitmentLinkProof<P>
+where
+    P: ECPoint + Clone,
+    P::Scalar: Zeroize + Clone,
+{
+    pub fn prove(
+        w: &KeyCommitmentLinkWitness<P::Scalar>,
+        delta: &KeyCommitmentLinkStatement<P>,
+    ) -> KeyCommitmentLinkProof<P> {
+        Self::prove_with_session_id(w, delta, &[])
+    }
+
+    /// Same as [prove](Self::prove), with the challenge bound to `session_id`. The proof is only
+    /// accepted by [verify_with_session_id](Self::verify_with_session_id) given the same id.
+    pub fn prove_with_session_id(
+        w: &KeyCommitmentLinkWitness<P::Scalar>,
+        delta: &KeyCommitmentLinkStatement<P>,
+        session_id: &[u8],
+    ) -> KeyCommitmentLinkProof<P> {
+        let g: P = ECPoint::generator();
+        let h: P = ECPoint::base_point2();
+        let mut s1: P::Scalar = ECScalar::new_random();
+        let mut s2: P::Scalar = ECScalar::new_random();
+        let a1 = g.clone() * s1.clone();
+        let a2 = h.clone() * s2.clone();
+        let e = Self::challenge(delta, &a1, &a2, session_id);
+        let z1 = s1.clone() + e.clone() * w.sk.clone();
+        let z2 = s2.clone() + e * w.r.clone();
+        s1.zeroize();
+        s2.zeroize();
+        KeyCommitmentLinkProof { a1, a2, z1, z2 }
+    }
+
+    pub fn verify(&self, delta: &KeyCommitmentLinkStatement<P>) -> Result<(), ProofError> {
+        self.verify_with_session_id(delta, &[])
+    }
+
+    pub fn verify_with_session_id(
+        &self,
+        delta: &KeyCommitmentLinkStatement<P>,
+        session_id: &[u8],
+    ) -> Result<(), ProofError> {
+        let g: P = ECPoint::generator();
+        let h: P = ECPoint::base_point2();
+        let e = Self::challenge(delta, &self.a1, &self.a2, session_id);
+        let r_h = delta.com.sub_point(&delta.pk.get_element());
+        let z1_g = g * self.z1.clone();
+        let z2_h = h * self.z2.clone();
+        let a1_plus_e_pk = self.a1.clone() + delta.pk.clone() * e.clone();
+        let a2_plus_e_rh = self.a2.clone() + r_h * e;
+        if z1_g == a1_plus_e_pk && z2_h == a2_plus_e_rh {
+            Ok(())
+        } else {
+            Err(ProofError)
+        }
+    }
+
+    fn challenge(
+        delta: &KeyCommitmentLinkStatement<P>,
+        a1: &P,
+        a2: &P,
+        session_id: &[u8],
+    ) -> P::Scalar {
+        let g: P = ECPoint::generator();
+        let h: P = ECPoint::base_point2();
+        let mut transcript = proof_transcript::<P>(b"curv key commitment link proof");
+        transcript.append_point(b"G", &g);
+        transcript.append_point(b"H", &h);
+        transcript.append_point(b"pk", &delta.pk);
+        transcript.append_point(b"com", &delta.com);
+        transcript.append_point(b"A1", a1);
+        transcript.append_point(b"A2", a2);
+        bind_session_id(transcript.challenge_scalar(b"e"), session_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cryptographic_primitives::commitments::pedersen_commitment::PedersenCommitment;
+    use crate::cryptographic_primitives::commitments::traits::Commitment;
+    use crate::cryptographic_primitives::proofs::key_commitment_link::*;
+    use crate::elliptic::curves::traits::{ECPoint, ECScalar};
+    use crate::test_for_all_curves;
+
+    test_for_all_curves!(test_key_commitment_link_proof);
+    fn test_key_commitment_link_proof<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize + Clone,
+    {
+        let sk: P::Scalar = ECScalar::new_random();
+        let r: P::Scalar = ECScalar::new_random();
+        let g: P = ECPoint::generator();
+        let pk = g * sk.clone();
+        let com: P = PedersenCommitment::create_commitment_with_user_defined_randomness(
+            &sk.to_big_int(),
+            &r.to_big_int(),
+        );
+        let delta = KeyCommitmentLinkStatement { pk, com };
+        let w = KeyCommitmentLinkWitness { sk, r };
+        let proof = KeyCommitmentLinkProof::prove(&w, &delta);
+        assert!(proof.verify(&delta).is_ok());
+    }
+
+    test_for_all_curves!(test_wrong_key_commitment_link_proof);
+    fn test_wrong_key_commitment_link_proof<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize + Clone,
+    {
+        let sk: P::Scalar = ECScalar::new_random();
+        let sk2: P::Scalar = ECScalar::new_random();
+        let r: P::Scalar = ECScalar::new_random();
+        let g: P = ECPoint::generator();
+        let pk = g * sk.clone();
+        let com: P = PedersenCommitment::create_commitment_with_user_defined_randomness(
+            &sk2.to_big_int(),
+            &r.to_big_int(),
+        );
+        let delta = KeyCommitmentLinkStatement { pk, com };
+        let w = KeyCommitmentLinkWitness { sk, r };
+        let proof = KeyCommitmentLinkProof::prove(&w, &delta);
+        assert!(proof.verify(&delta).is_err());
+    }
+
+    test_for_all_curves!(test_key_commitment_link_proof_session_id);
+    fn test_key_commitment_link_proof_session_id<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize + Clone,
+    {
+        let sk: P::Scalar = ECScalar::new_random();
+        let r: P::Scalar = ECScalar::new_random();
+        let g: P = ECPoint::generator();
+        let pk = g * sk.clone();
+        let com: P = PedersenCommitment::create_commitment_with_user_defined_randomness(
+            &sk.to_big_int(),
+            &r.to_big_int(),
+        );
+        let delta = KeyCommitmentLinkStatement { pk, com };
+        let w = KeyCommitmentLinkWitness { sk, r };
+        let proof = KeyCommitmentLinkProof::prove_with_session_id(&w, &delta, b"session A");
+        assert!(proof.verify_with_session_id(&delta, b"session A").is_ok());
+        assert!(proof.verify_with_session_id(&delta, b"session B").is_err());
+    }
+}
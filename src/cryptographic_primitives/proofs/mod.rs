@@ -5,15 +5,36 @@
     License MIT: https://github.com/KZen-networks/curv/blob/master/LICENSE
 */
 
+use std::convert::TryFrom;
 use std::error::Error;
 use std::fmt;
 
+use rand::RngCore;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
+use subtle::Choice;
+use zeroize::Zeroize;
+
+use crate::arithmetic::traits::*;
+use crate::cbor;
+use crate::cryptographic_primitives::hashing::transcript::Transcript;
+use crate::elliptic::curves::traits::{ECPoint, ECScalar};
+use crate::BigInt;
+
+pub mod committed_preimage;
+pub mod hidden_base_dlog;
+pub mod key_commitment_link;
+pub mod oprf_eval;
+pub mod range_proof;
 pub mod sigma_correct_homomorphic_elgamal_enc;
 pub mod sigma_correct_homomorphic_elgamal_encryption_of_dlog;
 pub mod sigma_dlog;
 pub mod sigma_ec_ddh;
+pub mod sigma_pedersen_equality;
 pub mod sigma_valid_pedersen;
 pub mod sigma_valid_pedersen_blind;
+pub mod solvency;
 
 #[derive(Debug, Clone, Copy)]
 pub struct ProofError;
@@ -29,3 +50,633 @@ impl Error for ProofError {
         "Error while verifying"
     }
 }
+
+/// Public parameters a prover and a verifier agree on, set at once on a proof builder with
+/// `params`, e.g. [DLogProofBuilder::params](sigma_dlog::DLogProofBuilder::params) or
+/// [PedersenProofBuilder::params](sigma_valid_pedersen::PedersenProofBuilder::params), instead of
+/// one by one. A proof only verifies under the parameters it was made with.
+///
+/// Proofs over a single base point use `generator`, proofs over two use both. [new](Self::new)
+/// sets the default generators `P::generator()` and `P::base_point2()`, a proof made with it
+/// verifies with a builder left with its defaults. Challenges are always derived with the
+/// SHA-256 transcript, there is no choice of hash function to agree on.
+#[derive(Clone, Debug)]
+pub struct ProofParams<P: ECPoint> {
+    pub generator: P,
+    pub second_generator: P,
+    pub domain: Vec<u8>,
+    pub session_id: Vec<u8>,
+}
+
+impl<P: ECPoint> ProofParams<P> {
+    pub fn new() -> ProofParams<P> {
+        ProofParams {
+            generator: P::generator(),
+            second_generator: P::base_point2(),
+            domain: Vec::new(),
+            session_id: Vec::new(),
+        }
+    }
+
+    pub fn with_domain(mut self, domain: &[u8]) -> Self {
+        self.domain = domain.to_vec();
+        self
+    }
+
+    pub fn with_session_id(mut self, session_id: &[u8]) -> Self {
+        self.session_id = session_id.to_vec();
+        self
+    }
+
+    pub fn with_generators(mut self, g: P, h: P) -> Self {
+        self.generator = g;
+        self.second_generator = h;
+        self
+    }
+}
+
+impl<P: ECPoint> Default for ProofParams<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Starts the transcript the Fiat-Shamir challenge of a proof is derived from.
+///
+/// Challenges used to be computed by hashing the concatenation of the values of the statement
+/// as big integers. Leading zero bytes are dropped from such encodings, so two different
+/// statements could be hashed the same. A transcript frames every value with its label and
+/// length, and starts with the protocol and the curve, so a challenge commits to the whole
+/// statement of exactly one protocol.
+pub(crate) fn proof_transcript<P: ECPoint>(protocol: &'static [u8]) -> Transcript {
+    let mut transcript = Transcript::new(protocol);
+    transcript.append_message(b"curve", P::CURVE_NAME.as_bytes());
+    transcript
+}
+
+/// Runs `f` over the transcript a challenge is derived from: the transcript given by the caller
+/// continued with the protocol and the curve if there is one, a fresh [proof_transcript]
+/// otherwise.
+///
+/// Several proofs made over the same transcript are chained, each challenge committing to the
+/// proofs appended before it, so the proofs of a bundle can't be replayed separately, reordered
+/// or mixed with the proofs of another bundle.
+pub(crate) fn with_proof_transcript<P: ECPoint, T>(
+    transcript: Option<&mut Transcript>,
+    protocol: &'static [u8],
+    f: impl FnOnce(&mut Transcript) -> T,
+) -> T {
+    match transcript {
+        Some(transcript) => {
+            transcript.append_message(b"protocol", protocol);
+            transcript.append_message(b"curve", P::CURVE_NAME.as_bytes());
+            f(transcript)
+        }
+        None => f(&mut proof_transcript::<P>(protocol)),
+    }
+}
+
+/// Encodes the state of the transcript given by the caller, for deterministic provers to bind it
+/// into their commitments. Otherwise proving the same statement over two transcripts would reuse
+/// a commitment under two challenges, which reveals the witness. Empty without a transcript.
+pub(crate) fn transcript_state<S: ECScalar>(transcript: &Option<&mut Transcript>) -> Vec<u8> {
+    match transcript {
+        Some(transcript) => {
+            let state: S = Transcript::clone(transcript).challenge_scalar(b"deterministic prover");
+            BigInt::to_bytes(&state.to_big_int())
+        }
+        None => Vec::new(),
+    }
+}
+
+/// Mixes a session id into a Fiat-Shamir challenge, so that a proof made within one session
+/// doesn't verify within another. An empty session id leaves the challenge unchanged.
+pub(crate) fn bind_session_id<S: ECScalar>(challenge: S, session_id: &[u8]) -> S {
+    if session_id.is_empty() {
+        return challenge;
+    }
+    let mut transcript = Transcript::new(b"curv session id");
+    transcript.append_message(b"session id", session_id);
+    transcript.append_scalar(b"challenge", &challenge);
+    transcript.challenge_scalar(b"challenge")
+}
+
+/// Compares two points in constant time, with [ECPoint::ct_eq]. Verification equations are
+/// checked with it by the `verify_ct` methods, so the running time doesn't depend on which part
+/// of an invalid proof is wrong.
+pub(crate) fn points_ct_eq<P: ECPoint>(a: &P, b: &P) -> Choice {
+    a.ct_eq(b)
+}
+
+const DETERMINISTIC_RNG_DST: &[u8] = b"curv deterministic nonce";
+
+/// Source of the prover's randomness derived from the witness and the statement, in the spirit
+/// of deterministic Schnorr signatures, so proofs are reproducible and made without an RNG.
+///
+/// The output is SHA-256 in counter mode over a seed hashing a label, the witness and every
+/// public value the challenge is computed from. The same nonce is only ever drawn for an
+/// identical (label, witness, statement) triple, which also leads to an identical challenge, so
+/// two proofs can't be combined to recover the witness.
+pub struct DeterministicRng {
+    seed: [u8; 32],
+    counter: u64,
+    block: [u8; 32],
+    used: usize,
+}
+
+impl DeterministicRng {
+    /// `label` should be unique to the proof system, so that proofs of different relations over
+    /// the same witness draw different nonces
+    pub fn new(label: &[u8], witness: &[&BigInt], statement: &[&[u8]]) -> Self {
+        fn absorb(hasher: Sha256, data: &[u8]) -> Sha256 {
+            hasher.chain((data.len() as u64).to_be_bytes()).chain(data)
+        }
+        let mut hasher = absorb(absorb(Sha256::new(), DETERMINISTIC_RNG_DST), label);
+        hasher = hasher.chain((witness.len() as u64).to_be_bytes());
+        for w in witness {
+            let mut bytes = BigInt::to_bytes(w);
+            hasher = absorb(hasher, &bytes);
+            bytes.zeroize();
+        }
+        for value in statement {
+            hasher = absorb(hasher, value);
+        }
+        let mut seed = [0u8; 32];
+        seed.copy_from_slice(&hasher.result());
+        DeterministicRng {
+            seed,
+            counter: 0,
+            block: [0u8; 32],
+            used: 32,
+        }
+    }
+}
+
+impl RngCore for DeterministicRng {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0u8; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_be_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0u8; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_be_bytes(bytes)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for byte in dest.iter_mut() {
+            if self.used == self.block.len() {
+                let block = Sha256::new()
+                    .chain(self.seed)
+                    .chain(self.counter.to_be_bytes())
+                    .result();
+                self.block.copy_from_slice(&block);
+                self.counter += 1;
+                self.used = 0;
+            }
+            *byte = self.block[self.used];
+            self.used += 1;
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl Drop for DeterministicRng {
+    fn drop(&mut self) {
+        self.seed.zeroize();
+        self.block.zeroize();
+    }
+}
+
+/// Samples a random scalar in `[1; q)` using the given source of randomness. Zero is rejected
+/// like values above `q`, as in [rfc6979](crate::cryptographic_primitives::hashing::rfc6979):
+/// a zero nonce would reveal the witness, and some backends can't represent it.
+pub(crate) fn sample_scalar_with_rng<S: ECScalar>(rng: &mut dyn RngCore) -> S {
+    let q = S::q();
+    let bits = q.bit_length();
+    let bytes = (bits - 1) / 8 + 1;
+    let mut buf: Vec<u8> = vec![0; bytes];
+    loop {
+        rng.fill_bytes(&mut buf);
+        let mut n = BigInt::from_bytes(&buf) >> (bytes * 8 - bits);
+        if n > BigInt::zero() && n < q {
+            let scalar = ECScalar::from(&n);
+            n.zeroize();
+            buf.zeroize();
+            return scalar;
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct CborProof<'a, T> {
+    curve: &'static str,
+    proof: &'a T,
+}
+
+#[derive(Deserialize)]
+struct OwnedCborProof<T> {
+    curve: String,
+    proof: T,
+}
+
+/// Largest vector accepted by `precheck_bytes`, as many as the bits of a range proof over any of
+/// the supported curves. Proofs holding longer vectors, such as solvency proofs of more balances,
+/// can be checked with `from_cbor_bounded` and a limit of their own.
+pub const PRECHECK_MAX_VECTOR_LENGTH: usize = 256;
+
+/// Sink of the fixed-width encoding of the proofs, see `to_bytes`
+trait ProofEncoder<P: ECPoint> {
+    fn point(&mut self, point: &P);
+    fn scalar(&mut self, scalar: &P::Scalar);
+    fn length(&mut self, length: usize);
+}
+
+impl<P: ECPoint> ProofEncoder<P> for Vec<u8> {
+    fn point(&mut self, point: &P) {
+        self.extend_from_slice(&point.serialize_compressed());
+    }
+
+    fn scalar(&mut self, scalar: &P::Scalar) {
+        self.extend_from_slice(&scalar.to_bytes());
+    }
+
+    fn length(&mut self, length: usize) {
+        let length = u32::try_from(length).expect("vectors of proofs are shorter than 2^32");
+        self.extend_from_slice(&length.to_be_bytes());
+    }
+}
+
+/// Adds up the lengths of the encoding without encoding anything
+struct SizeCounter(usize);
+
+impl<P: ECPoint> ProofEncoder<P> for SizeCounter {
+    fn point(&mut self, _point: &P) {
+        self.0 += P::COMPRESSED_SIZE;
+    }
+
+    fn scalar(&mut self, _scalar: &P::Scalar) {
+        self.0 += P::Scalar::q().bit_length().div_ceil(8);
+    }
+
+    fn length(&mut self, _length: usize) {
+        self.0 += 4;
+    }
+}
+
+/// Writes the points and scalars of a proof, in the order of its fields
+trait EncodeProof<P: ECPoint> {
+    fn encode<E: ProofEncoder<P>>(&self, encoder: &mut E);
+}
+
+macro_rules! impl_cbor {
+    ($($module:ident::$proof:ident),* $(,)?) => {$(
+        impl<P: ECPoint> $module::$proof<P>
+        where
+            Self: Serialize + DeserializeOwned,
+        {
+            /// Serializes the proof to CBOR, with named fields and tagged with the name of the
+            /// curve. Meant for debugging and interoperability with other implementations, this
+            /// encoding is much larger than the serde binary forms.
+            pub fn to_cbor(&self) -> Vec<u8> {
+                let tagged = CborProof {
+                    curve: P::CURVE_NAME,
+                    proof: self,
+                };
+                cbor::to_vec(&tagged).expect("proofs are always serializable")
+            }
+
+            /// Returns the length of [to_cbor](Self::to_cbor)'s output, without encoding the
+            /// proof. The compact encoding is measured by
+            /// [serialized_size](Self::serialized_size).
+            pub fn cbor_size(&self) -> usize {
+                let tagged = CborProof {
+                    curve: P::CURVE_NAME,
+                    proof: self,
+                };
+                cbor::serialized_size(&tagged).expect("proofs are always serializable")
+            }
+
+            /// Encodes the proof in a compact binary form: the points and scalars in the order
+            /// of the fields, every point in its
+            /// [serialize_compressed](ECPoint::serialize_compressed) encoding of
+            /// `P::COMPRESSED_SIZE` bytes and every scalar in its fixed-width
+            /// [to_bytes](ECScalar::to_bytes) encoding, vectors being preceded by their length as
+            /// a big-endian `u32`. Unlike [to_cbor](Self::to_cbor), the curve isn't encoded.
+            pub fn to_bytes(&self) -> Vec<u8> {
+                let mut bytes = Vec::new();
+                EncodeProof::<P>::encode(self, &mut bytes);
+                bytes
+            }
+
+            /// Returns the length of [to_bytes](Self::to_bytes)'s output, without encoding the
+            /// proof, e.g. to estimate the bandwidth of a protocol or choose the number of bits
+            /// of a range proof.
+            pub fn serialized_size(&self) -> usize {
+                let mut counter = SizeCounter(0);
+                EncodeProof::<P>::encode(self, &mut counter);
+                counter.0
+            }
+
+            /// Parses a proof produced by [to_cbor](Self::to_cbor). Fails if the proof was made
+            /// over another curve.
+            pub fn from_cbor(bytes: &[u8]) -> Result<Self, cbor::Error> {
+                Self::from_cbor_bounded(bytes, usize::MAX)
+            }
+
+            /// Like [from_cbor](Self::from_cbor), but fails as soon as a vector of the proof
+            /// declares more than `max_vector_length` items, before decoding any of them. For
+            /// untrusted input, such as a range proof claiming 2^40 bits.
+            pub fn from_cbor_bounded(
+                bytes: &[u8],
+                max_vector_length: usize,
+            ) -> Result<Self, cbor::Error> {
+                let tagged: OwnedCborProof<Self> =
+                    cbor::from_slice_with_max_len(bytes, max_vector_length)?;
+                if tagged.curve != P::CURVE_NAME {
+                    return Err(serde::de::Error::custom(format!(
+                        "expected a proof over {}, found {}",
+                        P::CURVE_NAME,
+                        tagged.curve
+                    )));
+                }
+                Ok(tagged.proof)
+            }
+
+            /// Checks that `bytes` are a well formed [to_cbor](Self::to_cbor) encoding of a proof
+            /// over this curve, every point being a valid encoding of a point of the curve,
+            /// without checking the proof itself, so services can drop malformed input early.
+            ///
+            /// The CBOR items are walked first without decoding them, which rejects truncated
+            /// input and vectors of more than [PRECHECK_MAX_VECTOR_LENGTH] items before anything
+            /// is allocated. Only then are the fields decoded, which validates the points at a
+            /// small fraction of the cost of a verification.
+            pub fn precheck_bytes(bytes: &[u8]) -> Result<(), cbor::Error> {
+                cbor::check_well_formed(bytes, PRECHECK_MAX_VECTOR_LENGTH)?;
+                Self::from_cbor_bounded(bytes, PRECHECK_MAX_VECTOR_LENGTH).map(|_| ())
+            }
+        }
+    )*};
+}
+
+impl_cbor! {
+    hidden_base_dlog::HiddenBaseDLogProof,
+    key_commitment_link::KeyCommitmentLinkProof,
+    range_proof::RangeProof,
+    sigma_correct_homomorphic_elgamal_enc::HomoELGamalProof,
+    sigma_correct_homomorphic_elgamal_encryption_of_dlog::HomoELGamalDlogProof,
+    sigma_dlog::DLogProof,
+    sigma_ec_ddh::ECDDHProof,
+    sigma_pedersen_equality::PedersenEqualityProof,
+    sigma_valid_pedersen::PedersenProof,
+    sigma_valid_pedersen_blind::PedersenBlindingProof,
+    solvency::SolvencyProof,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sigma_dlog::DLogProof;
+    use super::sigma_ec_ddh::{ECDDHProof, ECDDHStatement, ECDDHWitness};
+    use super::sigma_valid_pedersen::PedersenProof;
+    use super::sigma_valid_pedersen_blind::PedersenBlindingProof;
+    use super::*;
+
+    struct Bundle<P: ECPoint> {
+        dlog: DLogProof<P>,
+        pedersen: PedersenProof<P>,
+        blinding: PedersenBlindingProof<P>,
+        ddh: ECDDHProof<P>,
+        ddh_statement: ECDDHStatement<P>,
+    }
+
+    fn prove_bundle<P>(transcript: &mut Transcript) -> Bundle<P>
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize + Clone,
+    {
+        let x: P::Scalar = ECScalar::new_random();
+        let dlog = DLogProof::<P>::builder()
+            .witness(&x)
+            .transcript(transcript)
+            .prove();
+        let (m, r) = (ECScalar::new_random(), ECScalar::new_random());
+        let pedersen = PedersenProof::builder()
+            .message(&m)
+            .blinding(&r)
+            .transcript(transcript)
+            .prove();
+        let blinding = PedersenBlindingProof::prove_with_transcript(&m, &r, transcript);
+        let g2: P = ECPoint::base_point2();
+        let ddh_statement = ECDDHStatement {
+            g1: P::generator(),
+            h1: dlog.pk.clone(),
+            g2: g2.clone(),
+            h2: g2 * x.clone(),
+        };
+        let ddh =
+            ECDDHProof::prove_with_transcript(&ECDDHWitness { x }, &ddh_statement, transcript);
+        Bundle {
+            dlog,
+            pedersen,
+            blinding,
+            ddh,
+            ddh_statement,
+        }
+    }
+
+    fn verify_bundle<P>(bundle: &Bundle<P>, transcript: &mut Transcript) -> Result<(), ProofError>
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize + Clone,
+    {
+        DLogProof::builder()
+            .transcript(transcript)
+            .verify(&bundle.dlog)?;
+        PedersenProof::builder()
+            .transcript(transcript)
+            .verify(&bundle.pedersen)?;
+        PedersenBlindingProof::verify_with_transcript(&bundle.blinding, transcript)?;
+        bundle
+            .ddh
+            .verify_with_transcript(&bundle.ddh_statement, transcript)
+    }
+
+    crate::test_for_all_curves!(test_proofs_composed_over_one_transcript);
+    fn test_proofs_composed_over_one_transcript<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize + Clone,
+    {
+        let mut transcript = Transcript::new(b"bundle");
+        transcript.append_message(b"context", b"session 1");
+        let mut prover_transcript = transcript.clone();
+        let bundle = prove_bundle::<P>(&mut prover_transcript);
+
+        // the verifier replays the transcript and ends up in the prover's final state
+        let mut verifier_transcript = transcript.clone();
+        assert!(verify_bundle(&bundle, &mut verifier_transcript).is_ok());
+        assert_eq!(verifier_transcript, prover_transcript);
+
+        // a different prefix
+        let mut other = Transcript::new(b"bundle");
+        other.append_message(b"context", b"session 2");
+        assert!(verify_bundle(&bundle, &mut other).is_err());
+
+        // the proofs of the bundle don't verify on their own
+        assert!(DLogProof::verify(&bundle.dlog).is_err());
+        assert!(PedersenProof::verify(&bundle.pedersen).is_err());
+        assert!(PedersenBlindingProof::verify(&bundle.blinding).is_err());
+        assert!(bundle.ddh.verify(&bundle.ddh_statement).is_err());
+
+        // nor in another order
+        let mut reordered = transcript.clone();
+        assert!(PedersenProof::builder()
+            .transcript(&mut reordered)
+            .verify(&bundle.pedersen)
+            .is_err());
+        assert!(DLogProof::builder()
+            .transcript(&mut reordered)
+            .verify(&bundle.dlog)
+            .is_err());
+    }
+
+    crate::test_for_all_curves!(test_deterministic_proofs_bind_the_transcript);
+    fn test_deterministic_proofs_bind_the_transcript<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize,
+    {
+        let x: P::Scalar = ECScalar::new_random();
+        let prove = |context: &[u8]| {
+            let mut transcript = Transcript::new(b"bundle");
+            transcript.append_message(b"context", context);
+            DLogProof::<P>::builder()
+                .witness(&x)
+                .deterministic()
+                .transcript(&mut transcript)
+                .prove()
+        };
+        let commitment = |context: &[u8]| prove(context).pk_t_rand_commitment.pk_to_key_slice();
+        assert_eq!(commitment(b"session 1"), commitment(b"session 1"));
+        // reusing the commitment under another challenge would reveal x
+        assert_ne!(commitment(b"session 1"), commitment(b"session 2"));
+    }
+
+    /// Fills the first buffer with zeros and the next ones with ones
+    struct ZeroFirstRng {
+        fills: usize,
+    }
+
+    impl RngCore for ZeroFirstRng {
+        fn next_u32(&mut self) -> u32 {
+            let mut bytes = [0u8; 4];
+            self.fill_bytes(&mut bytes);
+            u32::from_be_bytes(bytes)
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            let mut bytes = [0u8; 8];
+            self.fill_bytes(&mut bytes);
+            u64::from_be_bytes(bytes)
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            let byte = if self.fills == 0 { 0 } else { 1 };
+            dest.iter_mut().for_each(|b| *b = byte);
+            self.fills += 1;
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    crate::test_for_all_curves!(test_sample_scalar_with_rng_rejects_zero);
+    fn test_sample_scalar_with_rng_rejects_zero<P: ECPoint>() {
+        let mut rng = ZeroFirstRng { fills: 0 };
+        let scalar: P::Scalar = sample_scalar_with_rng(&mut rng);
+        assert_eq!(rng.fills, 2);
+        assert_ne!(scalar.to_big_int(), BigInt::zero());
+    }
+}
+
+/// Timing tests of the `verify_ct` methods, see [crate::elliptic::curves::traits::ct_tests]
+#[cfg(all(test, feature = "ct-test"))]
+mod ct_tests {
+    use super::sigma_dlog::DLogProof;
+    use super::sigma_valid_pedersen::PedersenProof;
+    use crate::elliptic::curves::traits::ct_tests::{two_classes_t, SAMPLES, THRESHOLD};
+    use crate::elliptic::curves::traits::*;
+
+    /// Valid proofs against proofs whose verification equation fails
+    fn assert_verify_ct<T, F>(valid: impl Fn() -> T, invalid: impl Fn() -> T, verify_ct: F)
+    where
+        F: Fn(&T) -> subtle::Choice,
+    {
+        let inputs: Vec<(bool, T)> = (0..SAMPLES)
+            .map(|_| {
+                if rand::random() {
+                    (true, valid())
+                } else {
+                    (false, invalid())
+                }
+            })
+            .collect();
+        for (class, proof) in &inputs {
+            assert_eq!(bool::from(verify_ct(proof)), *class);
+        }
+        let t = two_classes_t(&inputs, |proof| {
+            let _ = verify_ct(proof);
+        });
+        assert!(t.abs() < THRESHOLD, "t = {}", t);
+    }
+
+    fn assert_dlog_verify_ct<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: zeroize::Zeroize,
+    {
+        let prove = || DLogProof::<P>::prove(&ECScalar::new_random());
+        let forge = || {
+            let mut proof = prove();
+            proof.pk_t_rand_commitment =
+                P::generator().scalar_mul(&P::Scalar::new_random().get_element());
+            proof
+        };
+        assert_verify_ct(prove, forge, DLogProof::verify_ct);
+    }
+
+    fn assert_pedersen_verify_ct<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: zeroize::Zeroize,
+    {
+        let prove = || PedersenProof::<P>::prove(&ECScalar::new_random(), &ECScalar::new_random());
+        // a valid proof of another commitment
+        let forge = || {
+            let mut proof = prove();
+            proof.com = prove().com;
+            proof
+        };
+        assert_verify_ct(prove, forge, PedersenProof::verify_ct);
+    }
+
+    #[test]
+    fn test_verify_ct_secp256k1() {
+        assert_dlog_verify_ct::<crate::elliptic::curves::secp256_k1::GE>();
+        assert_pedersen_verify_ct::<crate::elliptic::curves::secp256_k1::GE>();
+    }
+
+    #[test]
+    fn test_verify_ct_ristretto() {
+        assert_dlog_verify_ct::<crate::elliptic::curves::curve_ristretto::GE>();
+        assert_pedersen_verify_ct::<crate::elliptic::curves::curve_ristretto::GE>();
+    }
+}
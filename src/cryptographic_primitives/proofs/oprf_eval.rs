@@ -0,0 +1,106 @@
+/*
+    This file is part of Curv library
+    Copyright 2018 by Kzen Networks
+    (https://github.com/KZen-networks/curv)
+    License MIT: https://github.com/KZen-networks/curv/blob/master/LICENSE
+*/
+
+//! Proof that an OPRF server evaluated a blinded input with its committed key.
+//!
+//! The server publishes `pk = kG`. Given a blinded input `X` it returns `Y = kX` together with a
+//! Chaum-Pedersen proof that `log_G(pk) = log_X(Y)`, as in the verifiable OPRF of
+//! https://tools.ietf.org/html/draft-irtf-cfrg-voprf. This is an [ECDDHProof] over the statement
+//! `(G, pk, X, Y)`, with a challenge bound to the OPRF use case so the proof can't be replayed
+//! as a proof of another protocol.
+
+use zeroize::Zeroize;
+
+use super::sigma_ec_ddh::{ECDDHProof, ECDDHStatement, ECDDHWitness};
+use super::ProofError;
+use crate::elliptic::curves::traits::*;
+
+/// Session id the challenge of an OPRF evaluation proof is bound to
+pub const OPRF_EVAL_DOMAIN: &[u8] = b"curv oprf evaluation";
+
+pub type OprfEvalProof<P> = ECDDHProof<P>;
+
+/// Evaluates `Y = k * blinded` and proves it was computed with the key of `pk = kG`
+pub fn prove_oprf_eval<P>(k: &P::Scalar, blinded: &P) -> (P, OprfEvalProof<P>)
+where
+    P: ECPoint + Clone,
+    P::Scalar: Zeroize + Clone,
+{
+    let evaluation = blinded.clone() * k.clone();
+    let delta = statement(P::generator() * k.clone(), blinded, &evaluation);
+    let w = ECDDHWitness { x: k.clone() };
+    let proof = ECDDHProof::prove_with_session_id(&w, &delta, OPRF_EVAL_DOMAIN);
+    (evaluation, proof)
+}
+
+/// Checks that `evaluation` is `blinded` multiplied by the discrete log of `pk`
+pub fn verify_oprf_eval<P>(
+    pk: &P,
+    blinded: &P,
+    evaluation: &P,
+    proof: &OprfEvalProof<P>,
+) -> Result<(), ProofError>
+where
+    P: ECPoint + Clone,
+    P::Scalar: Zeroize + Clone,
+{
+    let delta = statement(pk.clone(), blinded, evaluation);
+    proof.verify_with_session_id(&delta, OPRF_EVAL_DOMAIN)
+}
+
+fn statement<P: ECPoint + Clone>(pk: P, blinded: &P, evaluation: &P) -> ECDDHStatement<P> {
+    ECDDHStatement {
+        g1: P::generator(),
+        h1: pk,
+        g2: blinded.clone(),
+        h2: evaluation.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_for_all_curves;
+
+    test_for_all_curves!(test_oprf_eval);
+    fn test_oprf_eval<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize + Clone,
+    {
+        let k: P::Scalar = ECScalar::new_random();
+        let pk = P::generator() * k.clone();
+        let r: P::Scalar = ECScalar::new_random();
+        let blinded = P::generator() * r;
+
+        let (evaluation, proof) = prove_oprf_eval(&k, &blinded);
+        assert!(verify_oprf_eval(&pk, &blinded, &evaluation, &proof).is_ok());
+        assert!(proof
+            .verify(&statement(pk.clone(), &blinded, &evaluation))
+            .is_err());
+    }
+
+    test_for_all_curves!(test_oprf_eval_with_another_key);
+    fn test_oprf_eval_with_another_key<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize + Clone,
+    {
+        let k: P::Scalar = ECScalar::new_random();
+        let pk = P::generator() * k.clone();
+        let r: P::Scalar = ECScalar::new_random();
+        let blinded = P::generator() * r;
+
+        let other_k: P::Scalar = ECScalar::new_random();
+        let (evaluation, proof) = prove_oprf_eval(&other_k, &blinded);
+        assert!(verify_oprf_eval(&pk, &blinded, &evaluation, &proof).is_err());
+
+        // an honest proof doesn't vouch for another evaluation
+        let (_, honest_proof) = prove_oprf_eval(&k, &blinded);
+        assert!(verify_oprf_eval(&pk, &blinded, &evaluation, &honest_proof).is_err());
+    }
+}
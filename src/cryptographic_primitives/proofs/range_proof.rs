@@ -0,0 +1,533 @@
+/*
+    This file is part of Curv library
+    Copyright 2018 by Kzen Networks
+    (https://github.com/KZen-networks/curv)
+    License MIT: https://github.com/KZen-networks/curv/blob/master/LICENSE
+*/
+
+use std::fmt::Debug;
+
+use derivative::Derivative;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use super::{proof_transcript, EncodeProof, ProofEncoder, ProofError};
+use crate::arithmetic::traits::*;
+use crate::cryptographic_primitives::hashing::transcript::Transcript;
+use crate::elliptic::curves::traits::*;
+use crate::BigInt;
+
+/// protocol for proving that a Pedersen commitment c = vG + rH hides a value v in [0, 2^n).
+/// witness: (v,r), statement: (c,n), The Relation R outputs 1 if c = vG + rH and 0 <= v < 2^n.
+/// The protocol:
+/// 1: Prover commits to every bit b_j of v as c_j = b_j*G + r_j*H, the blindings being chosen
+///    such that sum 2^j*r_j = r
+/// 2: for every bit, prover proves knowledge of the discrete log of either c_j or c_j - G in
+///    respect to H, by an OR composition (Cramer, Damgard, Schoenmakers) of two Schnorr proofs:
+///    the branch of the actual bit is proved, the other one is simulated, and the challenges
+///    e0, e1 of the branches sum up to the challenge e_j = H(c, n, c_0, .., c_j, A0_j, A1_j)
+/// prover sends pi = {c_j, e0_j, e1_j, z0_j, z1_j for every j}
+///
+/// verifier checks that sum 2^j*c_j = c, and for every bit that e0_j + e1_j = e_j with
+/// A0_j = z0_j*H - e0_j*c_j, A1_j = z1_j*H - e1_j*(c_j - G).
+///
+/// The proof grows linearly with n, 4 scalars and a point per bit.
+#[derive(Serialize, Deserialize, Derivative)]
+#[derivative(Clone(bound = "P: Clone, P::Scalar: Clone"))]
+#[derivative(Debug(bound = "P: Debug, P::Scalar: Debug"))]
+#[derivative(PartialEq(bound = "P: PartialEq, P::Scalar: PartialEq"))]
+#[serde(bound(serialize = "P: Serialize, P::Scalar: Serialize"))]
+#[serde(bound(deserialize = "P: Deserialize<'de>, P::Scalar: Deserialize<'de>"))]
+pub struct RangeProof<P: ECPoint> {
+    pub bit_commitments: Vec<P>,
+    bit_proofs: Vec<BitProof<P>>,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+struct BitProof<P: ECPoint> {
+    e0: P::Scalar,
+    e1: P::Scalar,
+    z0: P::Scalar,
+    z1: P::Scalar,
+}
+
+impl<P: ECPoint> EncodeProof<P> for RangeProof<P> {
+    fn encode<E: ProofEncoder<P>>(&self, encoder: &mut E) {
+        encoder.length(self.bit_commitments.len());
+        for c in &self.bit_commitments {
+            encoder.point(c);
+        }
+        encoder.length(self.bit_proofs.len());
+        for bit_proof in &self.bit_proofs {
+            encoder.scalar(&bit_proof.e0);
+            encoder.scalar(&bit_proof.e1);
+            encoder.scalar(&bit_proof.z0);
+            encoder.scalar(&bit_proof.z1);
+        }
+    }
+}
+
+impl<P> RangeProof<P>
+where
+    P: ECPoint + Clone,
+    P::Scalar: Zeroize + Clone + PartialEq,
+{
+    /// Proves that `com` = `value`*G + `blinding`*H hides a value in [0, 2^`bits`).
+    ///
+    /// ## Panics
+    /// Panics if 2^`bits` isn't smaller than the group order, if `value` is out of range or if
+    /// `com` doesn't open to `value` with `blinding`.
+    pub fn prove(com: &P, value: &BigInt, blinding: &P::Scalar, bits: usize) -> RangeProof<P> {
+        assert!(bits > 0 && Self::fits(bits), "2^bits isn't smaller than q");
+        assert!(
+            *value >= BigInt::zero() && value.bit_length() <= bits,
+            "value isn't in [0, 2^bits)"
+        );
+        assert!(
+            commit::<P>(value, blinding).pk_to_key_slice() == com.pk_to_key_slice(),
+            "com doesn't open to value with blinding"
+        );
+        let q = P::Scalar::q();
+        let h = P::base_point2();
+        let g = P::generator();
+
+        // r_{n-1} = (r - sum_{j < n-1} 2^j*r_j) / 2^{n-1}
+        let mut blindings: Vec<P::Scalar> = (0..bits - 1).map(|_| ECScalar::new_random()).collect();
+        let mut rest = blinding.to_big_int();
+        for (j, r_j) in blindings.iter().enumerate() {
+            rest = BigInt::mod_sub(&rest, &(r_j.to_big_int() << j), &q);
+        }
+        let last_inv = BigInt::mod_inv(&(BigInt::one() << (bits - 1)), &q).unwrap();
+        let last = BigInt::mod_mul(&rest, &last_inv, &q);
+        blindings.push(ECScalar::from(&last));
+
+        let mut transcript = Self::transcript(com, bits);
+        let mut bit_commitments = Vec::with_capacity(bits);
+        let mut bit_proofs = Vec::with_capacity(bits);
+        for (j, r_j) in blindings.iter().enumerate() {
+            let bit = value.test_bit(j);
+            let c_j = commit::<P>(&BigInt::from(bit as u32), r_j);
+            let c_j_minus_g = c_j.sub_point(&g.get_element());
+
+            // the branch of the other bit is simulated with random challenge and response
+            let mut k: P::Scalar = ECScalar::new_random();
+            let e_sim: P::Scalar = ECScalar::new_random();
+            let z_sim: P::Scalar = ECScalar::new_random();
+            let simulated_base = if bit { &c_j } else { &c_j_minus_g };
+            let a_real = h.scalar_mul(&k.get_element());
+            let a_sim = h.scalar_mul(&z_sim.get_element()).sub_point(
+                &simulated_base
+                    .scalar_mul(&e_sim.get_element())
+                    .get_element(),
+            );
+            let (a0, a1) = if bit {
+                (a_sim, a_real)
+            } else {
+                (a_real, a_sim)
+            };
+
+            let e = Self::challenge(&mut transcript, &c_j, &a0, &a1);
+            let e_real = e.sub(&e_sim.get_element());
+            let z_real = k.add(&e_real.mul(&r_j.get_element()).get_element());
+            k.zeroize();
+            let bit_proof = if bit {
+                BitProof {
+                    e0: e_sim,
+                    e1: e_real,
+                    z0: z_sim,
+                    z1: z_real,
+                }
+            } else {
+                BitProof {
+                    e0: e_real,
+                    e1: e_sim,
+                    z0: z_real,
+                    z1: z_sim,
+                }
+            };
+            bit_commitments.push(c_j);
+            bit_proofs.push(bit_proof);
+        }
+        blindings.iter_mut().for_each(|r_j| r_j.zeroize());
+
+        RangeProof {
+            bit_commitments,
+            bit_proofs,
+        }
+    }
+
+    /// Verifies that `com` hides a value in [0, 2^`bits`)
+    pub fn verify(&self, com: &P, bits: usize) -> Result<(), ProofError> {
+        let mut verifier = RangeProofVerifier::new(com, bits);
+        verifier.absorb(self)?;
+        verifier.finalize()
+    }
+
+    /// Splits the proof into partial proofs of `bits_per_chunk` consecutive bits each, starting
+    /// from the least significant one, to be fed to a [RangeProofVerifier] in order.
+    ///
+    /// ## Panics
+    /// Panics if `bits_per_chunk` is 0.
+    pub fn chunks(&self, bits_per_chunk: usize) -> Vec<RangeProof<P>> {
+        self.bit_commitments
+            .chunks(bits_per_chunk)
+            .zip(self.bit_proofs.chunks(bits_per_chunk))
+            .map(|(bit_commitments, bit_proofs)| RangeProof {
+                bit_commitments: bit_commitments.to_vec(),
+                bit_proofs: bit_proofs.to_vec(),
+            })
+            .collect()
+    }
+
+    // 2^bits < q, so that sum 2^j*b_j doesn't wrap around. q isn't a power of 2, this holds iff
+    // bits is smaller than its bit length, which doesn't compute 2^bits for a peer's huge bits
+    fn fits(bits: usize) -> bool {
+        bits < P::Scalar::q().bit_length()
+    }
+
+    fn transcript(com: &P, bits: usize) -> Transcript {
+        let mut transcript = proof_transcript::<P>(b"curv range proof");
+        transcript.append_point(b"G", &P::generator());
+        transcript.append_point(b"H", &P::base_point2());
+        transcript.append_point(b"com", com);
+        transcript.append_message(b"bits", &(bits as u64).to_be_bytes());
+        transcript
+    }
+
+    fn challenge(transcript: &mut Transcript, c_j: &P, a0: &P, a1: &P) -> P::Scalar {
+        transcript.append_point(b"c_j", c_j);
+        transcript.append_point(b"A0", a0);
+        transcript.append_point(b"A1", a1);
+        transcript.challenge_scalar(b"e")
+    }
+}
+
+/// Verifies a [RangeProof] received in chunks, without holding the whole proof in memory.
+///
+/// The chunks, as given by [RangeProof::chunks], are absorbed in order and the proof of every
+/// bit is checked as soon as it arrives; only the bit commitments are kept, for the final check
+/// that sum 2^j*c_j = c. The outcome is the one of [RangeProof::verify] on the whole proof.
+pub struct RangeProofVerifier<P: ECPoint> {
+    com: P,
+    bits: usize,
+    transcript: Transcript,
+    bit_commitments: Vec<P>,
+    failed: bool,
+}
+
+impl<P> RangeProofVerifier<P>
+where
+    P: ECPoint + Clone,
+    P::Scalar: Zeroize + Clone + PartialEq,
+{
+    /// Starts verifying that `com` hides a value in [0, 2^`bits`)
+    pub fn new(com: &P, bits: usize) -> RangeProofVerifier<P> {
+        RangeProofVerifier {
+            com: com.clone(),
+            bits,
+            transcript: RangeProof::transcript(com, bits),
+            bit_commitments: Vec::new(),
+            failed: bits == 0 || !RangeProof::<P>::fits(bits),
+        }
+    }
+
+    /// Checks the proofs of the next bits. Once a chunk is rejected, every later call fails too.
+    pub fn absorb(&mut self, chunk: &RangeProof<P>) -> Result<(), ProofError> {
+        if self.failed
+            || chunk.bit_commitments.len() != chunk.bit_proofs.len()
+            || chunk.bit_commitments.len() > self.bits - self.bit_commitments.len()
+        {
+            self.failed = true;
+            return Err(ProofError);
+        }
+        let q = P::Scalar::q();
+        let h = P::base_point2();
+        let g = P::generator();
+        let identity = P::identity().map(|identity| identity.pk_to_key_slice());
+        for (c_j, proof) in chunk.bit_commitments.iter().zip(&chunk.bit_proofs) {
+            // c_j = G or the identity can't be the commitment of a bit with a nonzero blinding,
+            // and would lead to points at infinity below
+            let c_j_bytes = c_j.pk_to_key_slice();
+            if c_j_bytes == g.pk_to_key_slice() || Some(c_j_bytes) == identity {
+                self.failed = true;
+                return Err(ProofError);
+            }
+            // A0 = z0*H - e0*c_j, A1 = z1*H - e1*c_j + e1*G, either of which a malformed proof
+            // can bring to infinity on a curve that can't represent it
+            let a0 = P::multi_scalar_mul(
+                &[proof.z0.clone(), negate(&proof.e0)],
+                &[h.clone(), c_j.clone()],
+            );
+            let a1 = P::multi_scalar_mul(
+                &[proof.z1.clone(), negate(&proof.e1), proof.e1.clone()],
+                &[h.clone(), c_j.clone(), g.clone()],
+            );
+            let (a0, a1) = match (a0, a1) {
+                (Some(a0), Some(a1)) => (a0, a1),
+                _ => {
+                    self.failed = true;
+                    return Err(ProofError);
+                }
+            };
+            let e = RangeProof::challenge(&mut self.transcript, c_j, &a0, &a1);
+            // added up as integers, the scalars of some curves can't be zero
+            let e0_plus_e1 = BigInt::mod_add(&proof.e0.to_big_int(), &proof.e1.to_big_int(), &q);
+            if e0_plus_e1 != e.to_big_int() {
+                self.failed = true;
+                return Err(ProofError);
+            }
+            self.bit_commitments.push(c_j.clone());
+        }
+        Ok(())
+    }
+
+    /// Checks that the proofs of all the bits were absorbed, and that they add up to `com`
+    pub fn finalize(self) -> Result<(), ProofError> {
+        if self.failed || self.bit_commitments.len() != self.bits {
+            return Err(ProofError);
+        }
+        // sum 2^j*c_j, None at infinity on secp256k1 and p256
+        let powers: Vec<P::Scalar> = (0..self.bits)
+            .map(|j| ECScalar::from(&(BigInt::one() << j)))
+            .collect();
+        match P::multi_scalar_mul(&powers, &self.bit_commitments) {
+            Some(sum) if sum.pk_to_key_slice() == self.com.pk_to_key_slice() => Ok(()),
+            _ => Err(ProofError),
+        }
+    }
+}
+
+// -s, built from its integer as secp256k1 can't add up or subtract scalars to zero
+fn negate<S: ECScalar>(s: &S) -> S {
+    let s = s.to_big_int();
+    if s == BigInt::zero() {
+        S::zero()
+    } else {
+        ECScalar::from(&(S::q() - s))
+    }
+}
+
+/// Computes vG + rH. The zero scalar can't be built on every curve, a zero `value` leads to rH.
+pub(crate) fn commit<P: ECPoint>(value: &BigInt, blinding: &P::Scalar) -> P {
+    let rh = P::base_point2().scalar_mul(&blinding.get_element());
+    if *value == BigInt::zero() {
+        rh
+    } else {
+        let v: P::Scalar = ECScalar::from(value);
+        P::generator()
+            .scalar_mul(&v.get_element())
+            .add_point(&rh.get_element())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::de::DeserializeOwned;
+
+    use super::*;
+    use crate::cbor;
+
+    crate::test_for_all_curves!(test_range_proof);
+    fn test_range_proof<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize + Clone + PartialEq,
+    {
+        let bits = 8;
+        for value in [0u32, 1, 2, 100, 255] {
+            let value = BigInt::from(value);
+            let r: P::Scalar = ECScalar::new_random();
+            let com = commit::<P>(&value, &r);
+            let proof = RangeProof::prove(&com, &value, &r, bits);
+            assert!(proof.verify(&com, bits).is_ok());
+            assert!(proof.verify(&com, bits + 1).is_err());
+
+            let other = commit::<P>(&value, &ECScalar::new_random());
+            assert!(proof.verify(&other, bits).is_err());
+        }
+    }
+
+    crate::test_for_all_curves!(test_range_proof_rejects_a_forged_bit);
+    fn test_range_proof_rejects_a_forged_bit<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize + Clone + PartialEq,
+    {
+        let value = BigInt::from(5);
+        let r: P::Scalar = ECScalar::new_random();
+        let com = commit::<P>(&value, &r);
+        let mut proof = RangeProof::<P>::prove(&com, &value, &r, 4);
+        // c_0 + G commits to 2, the sum then opens to 6 and not 5
+        let g = P::generator();
+        proof.bit_commitments[0] = proof.bit_commitments[0].add_point(&g.get_element());
+        assert!(proof.verify(&com, 4).is_err());
+        let six = commit::<P>(&BigInt::from(6), &r);
+        assert!(proof.verify(&six, 4).is_err());
+    }
+
+    crate::test_for_all_curves!(test_range_proof_rejects_degenerate_bits);
+    fn test_range_proof_rejects_degenerate_bits<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize + Clone + PartialEq,
+    {
+        let value = BigInt::from(5);
+        let r: P::Scalar = ECScalar::new_random();
+        let com = commit::<P>(&value, &r);
+        let proof = RangeProof::<P>::prove(&com, &value, &r, 4);
+
+        // c_0 - G is the identity
+        let mut forged = proof.clone();
+        forged.bit_commitments[0] = P::generator();
+        assert!(forged.verify(&com, 4).is_err());
+
+        // z0*H = e0*c_0, so that A0 is the identity
+        let x: P::Scalar = ECScalar::new_random();
+        let mut forged = proof.clone();
+        forged.bit_commitments[0] = P::base_point2() * x.clone();
+        forged.bit_proofs[0].z0 = forged.bit_proofs[0].e0.clone() * x;
+        assert!(forged.verify(&com, 4).is_err());
+
+        // zero scalars, which secp256k1 can't multiply by
+        let mut forged = proof;
+        forged.bit_proofs[0].e0 = P::Scalar::zero();
+        forged.bit_proofs[0].z0 = P::Scalar::zero();
+        assert!(forged.verify(&com, 4).is_err());
+    }
+
+    crate::test_for_all_curves!(test_range_proof_verifier);
+    fn test_range_proof_verifier<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize + Clone + PartialEq,
+    {
+        let bits = 8;
+        let value = BigInt::from(201);
+        let r: P::Scalar = ECScalar::new_random();
+        let com = commit::<P>(&value, &r);
+        let proof = RangeProof::prove(&com, &value, &r, bits);
+        let streamed = |proof: &RangeProof<P>, com: &P, bits_per_chunk: usize| {
+            let mut verifier = RangeProofVerifier::new(com, bits);
+            for chunk in proof.chunks(bits_per_chunk) {
+                if verifier.absorb(&chunk).is_err() {
+                    return Err(ProofError);
+                }
+            }
+            verifier.finalize()
+        };
+
+        let other = commit::<P>(&BigInt::from(200), &r);
+        let mut forged = proof.clone();
+        forged.bit_proofs[5] = forged.bit_proofs[4].clone();
+        let mut truncated = proof.clone();
+        truncated.bit_commitments.pop();
+        truncated.bit_proofs.pop();
+        for (proof, com) in [
+            (&proof, &com),
+            (&proof, &other),
+            (&forged, &com),
+            (&truncated, &com),
+        ] {
+            for bits_per_chunk in [1, 3, 8, 9] {
+                assert_eq!(
+                    streamed(proof, com, bits_per_chunk).is_ok(),
+                    proof.verify(com, bits).is_ok()
+                );
+            }
+        }
+        assert!(streamed(&proof, &com, 3).is_ok());
+        assert!(streamed(&forged, &com, 3).is_err());
+
+        // a chunk with a degenerate bit, and any chunk after it, are rejected
+        let mut forged = proof.clone();
+        forged.bit_commitments[4] = P::generator();
+        let x: P::Scalar = ECScalar::new_random();
+        let mut at_infinity = proof.clone();
+        at_infinity.bit_commitments[4] = P::base_point2() * x.clone();
+        at_infinity.bit_proofs[4].z0 = at_infinity.bit_proofs[4].e0.clone() * x;
+        for forged in [forged, at_infinity] {
+            let chunks = forged.chunks(4);
+            let mut verifier = RangeProofVerifier::new(&com, bits);
+            assert!(verifier.absorb(&chunks[0]).is_ok());
+            assert!(verifier.absorb(&chunks[1]).is_err());
+            assert!(verifier.absorb(&proof.chunks(4)[1]).is_err());
+            assert!(verifier.finalize().is_err());
+        }
+
+        // a number of bits no proof can have
+        let mut verifier = RangeProofVerifier::new(&com, usize::MAX);
+        assert!(verifier.absorb(&proof).is_err());
+        assert!(verifier.finalize().is_err());
+
+        // absorbing more bits than announced
+        let mut verifier = RangeProofVerifier::new(&com, bits);
+        assert!(verifier.absorb(&proof).is_ok());
+        assert!(verifier.absorb(&proof.chunks(1)[0]).is_err());
+        assert!(verifier.finalize().is_err());
+    }
+
+    crate::test_for_all_curves!(test_range_proof_from_cbor_bounded);
+    fn test_range_proof_from_cbor_bounded<P>()
+    where
+        P: ECPoint + Clone + Serialize + DeserializeOwned,
+        P::Scalar: Zeroize + Clone + PartialEq + Serialize + DeserializeOwned,
+    {
+        let value = BigInt::from(200);
+        let r: P::Scalar = ECScalar::new_random();
+        let com = commit::<P>(&value, &r);
+        let proof = RangeProof::<P>::prove(&com, &value, &r, 8);
+        let bytes = proof.to_cbor();
+        let decoded = RangeProof::<P>::from_cbor_bounded(&bytes, 8).ok();
+        assert!(decoded == RangeProof::<P>::from_cbor(&bytes).ok());
+        assert!(decoded.is_some());
+        assert!(RangeProof::<P>::from_cbor_bounded(&bytes, 7).is_err());
+
+        // a header claiming 2^40 bit commitments, followed by nothing
+        let mut bytes = vec![0xa2];
+        bytes.extend(cbor::to_vec("curve").unwrap());
+        bytes.extend(cbor::to_vec(P::CURVE_NAME).unwrap());
+        bytes.extend(cbor::to_vec("proof").unwrap());
+        bytes.push(0xa2);
+        bytes.extend(cbor::to_vec("bit_commitments").unwrap());
+        bytes.extend([0x9b, 0, 0, 1, 0, 0, 0, 0, 0]);
+        let err = RangeProof::<P>::from_cbor_bounded(&bytes, 256)
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("more than the limit of 256"));
+    }
+
+    crate::test_for_all_curves!(
+        #[should_panic]
+        test_range_proof_of_out_of_range_value
+    );
+    fn test_range_proof_of_out_of_range_value<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize + Clone + PartialEq,
+    {
+        let value = BigInt::from(256);
+        let r: P::Scalar = ECScalar::new_random();
+        let com = commit::<P>(&value, &r);
+        RangeProof::<P>::prove(&com, &value, &r, 8);
+    }
+
+    crate::test_for_all_curves!(test_range_proof_serialized_size);
+    fn test_range_proof_serialized_size<P>()
+    where
+        P: ECPoint + Clone + Serialize + DeserializeOwned,
+        P::Scalar: Zeroize + Clone + PartialEq + Serialize + DeserializeOwned,
+    {
+        let value = BigInt::one();
+        let r: P::Scalar = ECScalar::new_random();
+        let com = commit::<P>(&value, &r);
+        for bits in [1, 8, 16] {
+            let proof = RangeProof::<P>::prove(&com, &value, &r, bits);
+            assert_eq!(proof.serialized_size(), proof.to_bytes().len());
+            // two lengths, a point and four scalars per bit
+            assert_eq!(
+                proof.serialized_size(),
+                8 + bits * (P::COMPRESSED_SIZE + 4 * 32)
+            );
+        }
+    }
+}
@@ -9,9 +9,7 @@
 use serde::{Deserialize, Serialize};
 use zeroize::Zeroize;
 
-use super::ProofError;
-use crate::cryptographic_primitives::hashing::hash_sha256::HSha256;
-use crate::cryptographic_primitives::hashing::traits::Hash;
+use super::{bind_session_id, proof_transcript, EncodeProof, ProofEncoder, ProofError};
 use crate::elliptic::curves::traits::*;
 
 /// This is a proof of knowledge that a pair of group elements {D, E}
@@ -28,6 +26,15 @@ pub struct HomoELGamalProof<P: ECPoint> {
     pub z2: P::Scalar,
 }
 
+impl<P: ECPoint> EncodeProof<P> for HomoELGamalProof<P> {
+    fn encode<E: ProofEncoder<P>>(&self, encoder: &mut E) {
+        encoder.point(&self.T);
+        encoder.point(&self.A3);
+        encoder.scalar(&self.z1);
+        encoder.scalar(&self.z2);
+    }
+}
+
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub struct HomoElGamalWitness<S: ECScalar> {
     pub r: S,
@@ -51,6 +58,16 @@ where
     pub fn prove(
         w: &HomoElGamalWitness<P::Scalar>,
         delta: &HomoElGamalStatement<P>,
+    ) -> HomoELGamalProof<P> {
+        Self::prove_with_session_id(w, delta, &[])
+    }
+
+    /// Same as [prove](Self::prove), with the challenge bound to `session_id`. The proof is only
+    /// accepted by [verify_with_session_id](Self::verify_with_session_id) given the same id.
+    pub fn prove_with_session_id(
+        w: &HomoElGamalWitness<P::Scalar>,
+        delta: &HomoElGamalStatement<P>,
+        session_id: &[u8],
     ) -> HomoELGamalProof<P> {
         let mut s1: P::Scalar = ECScalar::new_random();
         let mut s2: P::Scalar = ECScalar::new_random();
@@ -58,9 +75,7 @@ where
         let mut A2 = delta.Y.clone() * s2.clone();
         let A3 = delta.G.clone() * s2.clone();
         let T = A1.clone() + A2.clone();
-        let e = HSha256::create_hash_from_ge(&[
-            &T, &A3, &delta.G, &delta.H, &delta.Y, &delta.D, &delta.E,
-        ]);
+        let e = Self::challenge(delta, &T, &A3, session_id);
         // dealing with zero field element
         let z1 = if w.x != P::Scalar::zero() {
             s1.clone() + w.x.clone() * e.clone()
@@ -75,9 +90,15 @@ where
         HomoELGamalProof { T, A3, z1, z2 }
     }
     pub fn verify(&self, delta: &HomoElGamalStatement<P>) -> Result<(), ProofError> {
-        let e = HSha256::create_hash_from_ge(&[
-            &self.T, &self.A3, &delta.G, &delta.H, &delta.Y, &delta.D, &delta.E,
-        ]);
+        self.verify_with_session_id(delta, &[])
+    }
+
+    pub fn verify_with_session_id(
+        &self,
+        delta: &HomoElGamalStatement<P>,
+        session_id: &[u8],
+    ) -> Result<(), ProofError> {
+        let e = Self::challenge(delta, &self.T, &self.A3, session_id);
         let z1H_plus_z2Y = delta.H.clone() * self.z1.clone() + delta.Y.clone() * self.z2.clone();
         let T_plus_eD = self.T.clone() + delta.D.clone() * e.clone();
         let z2G = delta.G.clone() * self.z2.clone();
@@ -88,6 +109,18 @@ where
             Err(ProofError)
         }
     }
+
+    fn challenge(delta: &HomoElGamalStatement<P>, T: &P, A3: &P, session_id: &[u8]) -> P::Scalar {
+        let mut transcript = proof_transcript::<P>(b"curv homomorphic elgamal proof");
+        transcript.append_point(b"G", &delta.G);
+        transcript.append_point(b"H", &delta.H);
+        transcript.append_point(b"Y", &delta.Y);
+        transcript.append_point(b"D", &delta.D);
+        transcript.append_point(b"E", &delta.E);
+        transcript.append_point(b"T", T);
+        transcript.append_point(b"A3", A3);
+        bind_session_id(transcript.challenge_scalar(b"e"), session_id)
+    }
 }
 
 #[cfg(test)]
@@ -168,4 +201,26 @@ mod tests {
         let proof = HomoELGamalProof::prove(&witness, &delta);
         assert!(proof.verify(&delta).is_ok());
     }
+
+    test_for_all_curves!(test_homo_elgamal_session_id);
+    fn test_homo_elgamal_session_id<P>()
+    where
+        P: ECPoint + Clone + Zeroize,
+        P::Scalar: PartialEq + Clone + Zeroize,
+    {
+        let witness = HomoElGamalWitness::<P::Scalar> {
+            r: ECScalar::new_random(),
+            x: ECScalar::new_random(),
+        };
+        let G: P = ECPoint::generator();
+        let H: P = ECPoint::base_point2();
+        let y: P::Scalar = ECScalar::new_random();
+        let Y = G.clone() * y;
+        let D = H.clone() * witness.x.clone() + Y.clone() * witness.r.clone();
+        let E = G.clone() * witness.r.clone();
+        let delta = HomoElGamalStatement { G, H, Y, D, E };
+        let proof = HomoELGamalProof::prove_with_session_id(&witness, &delta, b"session A");
+        assert!(proof.verify_with_session_id(&delta, b"session A").is_ok());
+        assert!(proof.verify_with_session_id(&delta, b"session B").is_err());
+    }
 }
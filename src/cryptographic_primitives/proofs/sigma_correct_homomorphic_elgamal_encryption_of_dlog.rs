@@ -9,9 +9,7 @@
 use serde::{Deserialize, Serialize};
 use zeroize::Zeroize;
 
-use super::ProofError;
-use crate::cryptographic_primitives::hashing::hash_sha256::HSha256;
-use crate::cryptographic_primitives::hashing::traits::Hash;
+use super::{bind_session_id, proof_transcript, EncodeProof, ProofEncoder, ProofError};
 use crate::elliptic::curves::traits::*;
 
 /// This is a proof of knowledge that a pair of group elements {D, E}
@@ -29,6 +27,16 @@ pub struct HomoELGamalDlogProof<P: ECPoint> {
     pub z2: P::Scalar,
 }
 
+impl<P: ECPoint> EncodeProof<P> for HomoELGamalDlogProof<P> {
+    fn encode<E: ProofEncoder<P>>(&self, encoder: &mut E) {
+        encoder.point(&self.A1);
+        encoder.point(&self.A2);
+        encoder.point(&self.A3);
+        encoder.scalar(&self.z1);
+        encoder.scalar(&self.z2);
+    }
+}
+
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub struct HomoElGamalDlogWitness<S: ECScalar> {
     pub r: S,
@@ -52,14 +60,23 @@ where
     pub fn prove(
         w: &HomoElGamalDlogWitness<P::Scalar>,
         delta: &HomoElGamalDlogStatement<P>,
+    ) -> HomoELGamalDlogProof<P> {
+        Self::prove_with_session_id(w, delta, &[])
+    }
+
+    /// Same as [prove](Self::prove), with the challenge bound to `session_id`. The proof is only
+    /// accepted by [verify_with_session_id](Self::verify_with_session_id) given the same id.
+    pub fn prove_with_session_id(
+        w: &HomoElGamalDlogWitness<P::Scalar>,
+        delta: &HomoElGamalDlogStatement<P>,
+        session_id: &[u8],
     ) -> HomoELGamalDlogProof<P> {
         let mut s1: P::Scalar = ECScalar::new_random();
         let mut s2: P::Scalar = ECScalar::new_random();
         let A1 = delta.G.clone() * s1.clone();
         let A2 = delta.Y.clone() * s2.clone();
         let A3 = delta.G.clone() * s2.clone();
-        let e =
-            HSha256::create_hash_from_ge(&[&A1, &A2, &A3, &delta.G, &delta.Y, &delta.D, &delta.E]);
+        let e = Self::challenge(delta, &A1, &A2, &A3, session_id);
         let z1 = s1.clone() + e.clone() * w.x.clone();
         let z2 = s2.clone() + e * w.r.clone();
         s1.zeroize();
@@ -68,9 +85,15 @@ where
     }
 
     pub fn verify(&self, delta: &HomoElGamalDlogStatement<P>) -> Result<(), ProofError> {
-        let e = HSha256::create_hash_from_ge(&[
-            &self.A1, &self.A2, &self.A3, &delta.G, &delta.Y, &delta.D, &delta.E,
-        ]);
+        self.verify_with_session_id(delta, &[])
+    }
+
+    pub fn verify_with_session_id(
+        &self,
+        delta: &HomoElGamalDlogStatement<P>,
+        session_id: &[u8],
+    ) -> Result<(), ProofError> {
+        let e = Self::challenge(delta, &self.A1, &self.A2, &self.A3, session_id);
         let z1G = delta.G.clone() * self.z1.clone();
         let z2Y = delta.Y.clone() * self.z2.clone();
         let z2G = delta.G.clone() * self.z2.clone();
@@ -84,10 +107,33 @@ where
             Err(ProofError)
         }
     }
+
+    fn challenge(
+        delta: &HomoElGamalDlogStatement<P>,
+        A1: &P,
+        A2: &P,
+        A3: &P,
+        session_id: &[u8],
+    ) -> P::Scalar {
+        // Q is part of the statement too: a challenge computed without it lets a prover knowing
+        // the discrete logarithm of Y choose Q once the challenge is known
+        let mut transcript = proof_transcript::<P>(b"curv homomorphic elgamal dlog proof");
+        transcript.append_point(b"G", &delta.G);
+        transcript.append_point(b"Y", &delta.Y);
+        transcript.append_point(b"Q", &delta.Q);
+        transcript.append_point(b"D", &delta.D);
+        transcript.append_point(b"E", &delta.E);
+        transcript.append_point(b"A1", A1);
+        transcript.append_point(b"A2", A2);
+        transcript.append_point(b"A3", A3);
+        bind_session_id(transcript.challenge_scalar(b"e"), session_id)
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::cryptographic_primitives::hashing::hash_sha256::HSha256;
+    use crate::cryptographic_primitives::hashing::traits::Hash;
     use crate::cryptographic_primitives::proofs::sigma_correct_homomorphic_elgamal_encryption_of_dlog::*;
     use crate::test_for_all_curves;
 
@@ -137,4 +183,83 @@ mod tests {
         let proof = HomoELGamalDlogProof::prove(&witness, &delta);
         assert!(proof.verify(&delta).is_ok());
     }
+
+    test_for_all_curves!(test_homo_elgamal_dlog_session_id);
+    fn test_homo_elgamal_dlog_session_id<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize + Clone,
+    {
+        let witness = HomoElGamalDlogWitness::<P::Scalar> {
+            r: ECScalar::new_random(),
+            x: ECScalar::new_random(),
+        };
+        let G: P = ECPoint::generator();
+        let y: P::Scalar = ECScalar::new_random();
+        let Y = G.clone() * y;
+        let D = G.clone() * witness.x.clone() + Y.clone() * witness.r.clone();
+        let E = G.clone() * witness.r.clone();
+        let Q = G.clone() * witness.x.clone();
+        let delta = HomoElGamalDlogStatement { G, Y, Q, D, E };
+        let proof = HomoELGamalDlogProof::prove_with_session_id(&witness, &delta, b"session A");
+        assert!(proof.verify_with_session_id(&delta, b"session A").is_ok());
+        assert!(proof.verify_with_session_id(&delta, b"session B").is_err());
+    }
+
+    fn equations_hold<P>(
+        proof: &HomoELGamalDlogProof<P>,
+        delta: &HomoElGamalDlogStatement<P>,
+        e: &P::Scalar,
+    ) -> bool
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Clone,
+    {
+        let eq = |lhs: P, rhs: P| lhs.pk_to_key_slice() == rhs.pk_to_key_slice();
+        let D_minus_Q = delta.D.sub_point(&delta.Q.get_element());
+        eq(
+            delta.G.clone() * proof.z1.clone(),
+            proof.A1.clone() + delta.Q.clone() * e.clone(),
+        ) && eq(
+            delta.G.clone() * proof.z2.clone(),
+            proof.A3.clone() + delta.E.clone() * e.clone(),
+        ) && eq(
+            delta.Y.clone() * proof.z2.clone(),
+            proof.A2.clone() + D_minus_Q * e.clone(),
+        )
+    }
+
+    test_for_all_curves!(test_homo_elgamal_dlog_challenge_binds_q);
+    fn test_homo_elgamal_dlog_challenge_binds_q<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize + Clone,
+    {
+        // The challenge used to be H(A1, A2, A3, G, Y, D, E), leaving Q out. A prover knowing the
+        // discrete logarithm y of Y could then pick Q after the challenge so that all equations
+        // hold while D - Q != rY, i.e. D is not an encryption of the discrete logarithm of Q
+        let G: P = ECPoint::generator();
+        let random = || -> P::Scalar { ECScalar::new_random() };
+        let (y, r, s, u, t, d) = (random(), random(), random(), random(), random(), random());
+        let Y = G.clone() * y.clone();
+        let E = G.clone() * r.clone();
+        let D = G.clone() * d.clone();
+        let A1 = G.clone() * t.clone();
+        let A2 = Y.clone() * u.clone();
+        let A3 = G.clone() * s.clone();
+        let e: P::Scalar = HSha256::create_hash_from_ge(&[&A1, &A2, &A3, &G, &Y, &D, &E]);
+
+        let z2 = s + e.clone() * r.clone();
+        // D - Q = cY with c = (z2 - u) / e, which is not r since u != s
+        let c = z2.sub(&u.get_element()) * e.invert();
+        assert_ne!(c.to_big_int(), r.to_big_int());
+        let Q = D.sub_point(&(Y.clone() * c.clone()).get_element());
+        let q = d.sub(&(c * y).get_element());
+        let z1 = t + e.clone() * q;
+
+        let delta = HomoElGamalDlogStatement { G, Y, Q, D, E };
+        let proof = HomoELGamalDlogProof { A1, A2, A3, z1, z2 };
+        assert!(equations_hold(&proof, &delta, &e));
+        assert!(proof.verify(&delta).is_err());
+    }
 }
@@ -5,14 +5,20 @@
     License MIT: https://github.com/KZen-networks/curv/blob/master/LICENSE
 */
 
+use rand::RngCore;
 use serde::{Deserialize, Serialize};
+use subtle::Choice;
 use zeroize::Zeroize;
 
-use crate::cryptographic_primitives::hashing::hash_sha256::HSha256;
-use crate::cryptographic_primitives::hashing::traits::Hash;
+use crate::arithmetic::traits::*;
+use crate::cryptographic_primitives::hashing::transcript::Transcript;
 use crate::elliptic::curves::traits::*;
+use crate::BigInt;
 
-use super::ProofError;
+use super::{
+    bind_session_id, points_ct_eq, sample_scalar_with_rng, transcript_state, with_proof_transcript,
+    DeterministicRng, EncodeProof, ProofEncoder, ProofError, ProofParams,
+};
 
 /// This is implementation of Schnorr's identification protocol for elliptic curve groups or a
 /// sigma protocol for Proof of knowledge of the discrete log of an Elliptic-curve point:
@@ -31,24 +37,220 @@ pub struct DLogProof<P: ECPoint> {
     pub challenge_response: P::Scalar,
 }
 
+impl<P: ECPoint> EncodeProof<P> for DLogProof<P> {
+    fn encode<E: ProofEncoder<P>>(&self, encoder: &mut E) {
+        encoder.point(&self.pk);
+        encoder.point(&self.pk_t_rand_commitment);
+        encoder.scalar(&self.challenge_response);
+    }
+}
+
 impl<P> DLogProof<P>
 where
     P: ECPoint + Clone,
     P::Scalar: Zeroize,
 {
     pub fn prove(sk: &P::Scalar) -> DLogProof<P> {
-        let base_point: P = ECPoint::generator();
-        let generator_x = base_point.bytes_compressed_to_big_int();
-        let mut sk_t_rand_commitment: P::Scalar = ECScalar::new_random();
-        let pk_t_rand_commitment = base_point.scalar_mul(&sk_t_rand_commitment.get_element());
-        let ec_point: P = ECPoint::generator();
-        let pk = ec_point.scalar_mul(&sk.get_element());
-        let challenge = HSha256::create_hash(&[
-            &pk_t_rand_commitment.bytes_compressed_to_big_int(),
-            &generator_x,
-            &pk.bytes_compressed_to_big_int(),
-        ]);
-        let challenge_fe: P::Scalar = ECScalar::from(&challenge);
+        DLogProof::builder().witness(sk).prove()
+    }
+
+    pub fn verify(proof: &DLogProof<P>) -> Result<(), ProofError> {
+        DLogProof::builder().verify(proof)
+    }
+
+    /// Same as [verify](Self::verify), the verification equation being compared in constant
+    /// time
+    pub fn verify_ct(&self) -> Choice {
+        DLogProof::builder().verify_ct(self)
+    }
+
+    /// Computes `Q = witness * base` together with a proof of knowledge of `witness` with
+    /// respect to `base`. The proof is verified with
+    /// `DLogProof::builder().generator(base).verify(&proof)`.
+    pub fn commit_with_proof(witness: &P::Scalar, base: &P) -> (P, DLogProof<P>) {
+        let proof = DLogProof::builder()
+            .witness(witness)
+            .generator(base)
+            .prove();
+        (proof.pk.clone(), proof)
+    }
+
+    /// Special soundness extractor: recovers the witness from two accepting transcripts
+    /// `(commitment, e1, z1)` and `(commitment, e2, z2)` of a proof with respect to
+    /// `P::generator()` sharing the same commitment.
+    ///
+    /// Returns `None` if the challenges are equal, if the transcripts aren't consistent with
+    /// `commitment` or if they would give the zero witness. Meant for soundness tests.
+    pub fn extract_witness(
+        commitment: &P,
+        (e1, z1): (&P::Scalar, &P::Scalar),
+        (e2, z2): (&P::Scalar, &P::Scalar),
+    ) -> Option<P::Scalar> {
+        // z = k - e * x, so x = (z2 - z1) / (e1 - e2). Computed over big integers since some
+        // backends can't represent a zero scalar
+        let q = P::Scalar::q();
+        let e_diff = BigInt::mod_sub(&e1.to_big_int(), &e2.to_big_int(), &q);
+        let z_diff = BigInt::mod_sub(&z2.to_big_int(), &z1.to_big_int(), &q);
+        if e_diff == BigInt::zero() || z_diff == BigInt::zero() {
+            return None;
+        }
+        let e_diff_inv = BigInt::mod_inv(&e_diff, &q)?;
+        let witness: P::Scalar = ECScalar::from(&BigInt::mod_mul(&z_diff, &e_diff_inv, &q));
+
+        let g: P = ECPoint::generator();
+        let pk = g.scalar_mul(&witness.get_element());
+        let consistent = |e: &P::Scalar, z: &P::Scalar| {
+            g.scalar_mul(&z.get_element())
+                .add_point(&pk.scalar_mul(&e.get_element()).get_element())
+                == *commitment
+        };
+        if consistent(e1, z1) && consistent(e2, z2) {
+            Some(witness)
+        } else {
+            None
+        }
+    }
+
+    /// Returns a [DLogProofBuilder] allowing to set optional parameters of the proof
+    pub fn builder<'a>() -> DLogProofBuilder<'a, P> {
+        DLogProofBuilder {
+            witness: None,
+            domain: &[],
+            session_id: &[],
+            generator: None,
+            rng: None,
+            deterministic: false,
+            transcript: None,
+        }
+    }
+}
+
+/// Builder for [DLogProof].
+///
+/// By default the proof is made with respect to `P::generator()`, with no domain tag and with
+/// randomness taken from `ECScalar::new_random()`, so a proof built with defaults is checked by
+/// [DLogProof::verify]. A proof built with a domain tag or a custom generator must be verified by
+/// a builder set with the same domain and generator.
+///
+/// ## Example
+/// ```
+/// # use curv::cryptographic_primitives::proofs::sigma_dlog::DLogProof;
+/// # use curv::elliptic::curves::secp256_k1::{FE, GE};
+/// # use curv::elliptic::curves::traits::*;
+/// let x: FE = ECScalar::new_random();
+/// let g: GE = ECPoint::base_point2();
+/// let proof = DLogProof::builder().witness(&x).domain(b"ctx").generator(&g).prove();
+/// assert!(DLogProof::builder().domain(b"ctx").generator(&g).verify(&proof).is_ok());
+/// ```
+pub struct DLogProofBuilder<'a, P: ECPoint> {
+    witness: Option<&'a P::Scalar>,
+    domain: &'a [u8],
+    session_id: &'a [u8],
+    generator: Option<&'a P>,
+    rng: Option<&'a mut dyn RngCore>,
+    deterministic: bool,
+    transcript: Option<&'a mut Transcript>,
+}
+
+impl<'a, P> DLogProofBuilder<'a, P>
+where
+    P: ECPoint + Clone,
+    P::Scalar: Zeroize,
+{
+    /// Sets the secret `x` such that `pk = x * generator`. Required by [prove](Self::prove).
+    pub fn witness(mut self, sk: &'a P::Scalar) -> Self {
+        self.witness = Some(sk);
+        self
+    }
+
+    /// Sets a domain tag that is bound into the challenge
+    pub fn domain(mut self, domain: &'a [u8]) -> Self {
+        self.domain = domain;
+        self
+    }
+
+    /// Sets an id of the session the proof is made within, bound into the challenge separately
+    /// from the domain tag. A proof only verifies under the same session id.
+    pub fn session_id(mut self, session_id: &'a [u8]) -> Self {
+        self.session_id = session_id;
+        self
+    }
+
+    /// Sets the base point the discrete log is taken in respect to
+    pub fn generator(mut self, generator: &'a P) -> Self {
+        self.generator = Some(generator);
+        self
+    }
+
+    /// Sets the domain tag, session id and generator ([generator](ProofParams::generator)) at
+    /// once
+    pub fn params(mut self, params: &'a ProofParams<P>) -> Self {
+        self.domain = &params.domain;
+        self.session_id = &params.session_id;
+        self.generator = Some(&params.generator);
+        self
+    }
+
+    /// Sets the source of randomness used to sample the prover's commitment
+    pub fn rng(mut self, rng: &'a mut dyn RngCore) -> Self {
+        self.rng = Some(rng);
+        self
+    }
+
+    /// Derives the prover's commitment from the witness and the statement with a
+    /// [DeterministicRng] instead of sampling it, so proving twice the same statement yields the
+    /// same proof. Takes precedence over [rng](Self::rng).
+    pub fn deterministic(mut self) -> Self {
+        self.deterministic = true;
+        self
+    }
+
+    /// Derives the challenge from `transcript`, continued with the statement and the commitment,
+    /// instead of from a transcript of its own, so several proofs can be composed into one
+    /// bundle. The proof only verifies against a transcript in the same state.
+    pub fn transcript(mut self, transcript: &'a mut Transcript) -> Self {
+        self.transcript = Some(transcript);
+        self
+    }
+
+    /// Produces the proof.
+    ///
+    /// ## Panics
+    /// Panics if witness wasn't set.
+    pub fn prove(self) -> DLogProof<P> {
+        let sk = self
+            .witness
+            .expect("witness is required to produce a proof");
+        let base_point = self.base_point();
+        let pk = Self::base_point_mul(self.generator, sk);
+        let mut sk_t_rand_commitment: P::Scalar = if self.deterministic {
+            let mut rng = DeterministicRng::new(
+                b"curv sigma_dlog",
+                &[&sk.to_big_int()],
+                &[
+                    self.domain,
+                    self.session_id,
+                    &base_point.pk_to_key_slice(),
+                    &pk.pk_to_key_slice(),
+                    &transcript_state::<P::Scalar>(&self.transcript),
+                ],
+            );
+            sample_scalar_with_rng(&mut rng)
+        } else {
+            match self.rng {
+                Some(rng) => sample_scalar_with_rng(rng),
+                None => ECScalar::new_random(),
+            }
+        };
+        let pk_t_rand_commitment = Self::base_point_mul(self.generator, &sk_t_rand_commitment);
+        let challenge_fe = Self::challenge(
+            self.transcript,
+            self.domain,
+            self.session_id,
+            &base_point,
+            &pk_t_rand_commitment,
+            &pk,
+        );
         let challenge_mul_sk = challenge_fe.mul(&sk.get_element());
         let challenge_response = sk_t_rand_commitment.sub(&challenge_mul_sk.get_element());
         sk_t_rand_commitment.zeroize();
@@ -59,30 +261,67 @@ where
         }
     }
 
-    pub fn verify(proof: &DLogProof<P>) -> Result<(), ProofError> {
-        let ec_point: P = ECPoint::generator();
-        let challenge = HSha256::create_hash(&[
-            &proof.pk_t_rand_commitment.bytes_compressed_to_big_int(),
-            &ec_point.bytes_compressed_to_big_int(),
-            &proof.pk.bytes_compressed_to_big_int(),
-        ]);
-
-        let sk_challenge: P::Scalar = ECScalar::from(&challenge);
-        let pk = proof.pk.clone();
-        let pk_challenge = pk.scalar_mul(&sk_challenge.get_element());
+    /// Verifies the proof against the domain tag, session id and generator set in the builder
+    pub fn verify(self, proof: &DLogProof<P>) -> Result<(), ProofError> {
+        if bool::from(self.verify_ct(proof)) {
+            Ok(())
+        } else {
+            Err(ProofError)
+        }
+    }
 
-        let base_point: P = ECPoint::generator();
+    /// Same as [verify](Self::verify), returning the result of a constant-time comparison
+    pub fn verify_ct(self, proof: &DLogProof<P>) -> Choice {
+        let base_point = self.base_point();
+        let sk_challenge = Self::challenge(
+            self.transcript,
+            self.domain,
+            self.session_id,
+            &base_point,
+            &proof.pk_t_rand_commitment,
+            &proof.pk,
+        );
+        let pk_challenge = proof.pk.scalar_mul(&sk_challenge.get_element());
 
         let mut pk_verifier = base_point.scalar_mul(&proof.challenge_response.get_element());
 
         pk_verifier = pk_verifier.add_point(&pk_challenge.get_element());
 
-        if pk_verifier == proof.pk_t_rand_commitment {
-            Ok(())
-        } else {
-            Err(ProofError)
+        points_ct_eq(&pk_verifier, &proof.pk_t_rand_commitment)
+    }
+
+    fn base_point(&self) -> P {
+        match self.generator {
+            Some(generator) => generator.clone(),
+            None => ECPoint::generator(),
         }
     }
+
+    // multiplications of the default generator go through its precomputed table
+    fn base_point_mul(generator: Option<&P>, k: &P::Scalar) -> P {
+        match generator {
+            Some(generator) => generator.scalar_mul(&k.get_element()),
+            None => P::generator_mul(k),
+        }
+    }
+
+    fn challenge(
+        transcript: Option<&mut Transcript>,
+        domain: &[u8],
+        session_id: &[u8],
+        base_point: &P,
+        pk_t_rand_commitment: &P,
+        pk: &P,
+    ) -> P::Scalar {
+        let e = with_proof_transcript::<P, _>(transcript, b"curv dlog proof", |transcript| {
+            transcript.append_message(b"domain", domain);
+            transcript.append_point(b"G", base_point);
+            transcript.append_point(b"pk", pk);
+            transcript.append_point(b"commitment", pk_t_rand_commitment);
+            transcript.challenge_scalar(b"e")
+        });
+        bind_session_id(e, session_id)
+    }
 }
 
 #[cfg(test)]
@@ -99,4 +338,273 @@ mod tests {
         let dlog_proof = DLogProof::<P>::prove(&witness);
         assert!(DLogProof::verify(&dlog_proof).is_ok());
     }
+
+    crate::test_for_all_curves!(test_dlog_proof_params);
+    fn test_dlog_proof_params<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize,
+    {
+        let witness: P::Scalar = ECScalar::new_random();
+        let h = P::base_point2();
+        let params = ProofParams::<P>::new()
+            .with_domain(b"ctx")
+            .with_session_id(b"session")
+            .with_generators(h.clone(), P::generator());
+        let proof = DLogProof::builder()
+            .witness(&witness)
+            .params(&params)
+            .prove();
+        assert!(DLogProof::builder().params(&params).verify(&proof).is_ok());
+        // same as setting them one by one
+        let builder = DLogProof::builder()
+            .domain(b"ctx")
+            .session_id(b"session")
+            .generator(&h);
+        assert!(builder.verify(&proof).is_ok());
+
+        let mismatched = [
+            params.clone().with_domain(b"another ctx"),
+            params.clone().with_session_id(b"another session"),
+            params.clone().with_generators(P::generator(), h.clone()),
+            ProofParams::new(),
+        ];
+        for other in mismatched.iter() {
+            assert!(DLogProof::builder().params(other).verify(&proof).is_err());
+        }
+
+        // default parameters are the builder's defaults
+        let params = ProofParams::<P>::new();
+        let proof = DLogProof::builder()
+            .witness(&witness)
+            .params(&params)
+            .prove();
+        assert!(DLogProof::verify(&proof).is_ok());
+    }
+
+    crate::test_for_all_curves!(test_dlog_proof_verify_ct);
+    fn test_dlog_proof_verify_ct<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize,
+    {
+        let witness: P::Scalar = ECScalar::new_random();
+        let mut proof = DLogProof::<P>::prove(&witness);
+        assert!(bool::from(proof.verify_ct()));
+        proof.pk = proof.pk.add_point(&P::generator().get_element());
+        assert!(!bool::from(proof.verify_ct()));
+        assert!(DLogProof::verify(&proof).is_err());
+    }
+
+    crate::test_for_all_curves!(test_dlog_proof_builder);
+    fn test_dlog_proof_builder<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize,
+    {
+        let witness: P::Scalar = ECScalar::new_random();
+
+        // with default parameters the builder is interchangeable with prove / verify
+        let proof = DLogProof::<P>::builder().witness(&witness).prove();
+        assert!(DLogProof::verify(&proof).is_ok());
+        let proof = DLogProof::<P>::prove(&witness);
+        assert!(DLogProof::<P>::builder().verify(&proof).is_ok());
+
+        let h: P = ECPoint::base_point2();
+        let mut rng = rand::thread_rng();
+        let proof = DLogProof::<P>::builder()
+            .witness(&witness)
+            .domain(b"ctx")
+            .generator(&h)
+            .rng(&mut rng)
+            .prove();
+        assert!(proof.pk == h.scalar_mul(&witness.get_element()));
+        assert!(DLogProof::builder()
+            .domain(b"ctx")
+            .generator(&h)
+            .verify(&proof)
+            .is_ok());
+        assert!(DLogProof::builder().generator(&h).verify(&proof).is_err());
+        assert!(DLogProof::builder()
+            .domain(b"another ctx")
+            .generator(&h)
+            .verify(&proof)
+            .is_err());
+        assert!(DLogProof::builder().domain(b"ctx").verify(&proof).is_err());
+    }
+
+    crate::test_for_all_curves!(test_commit_with_proof);
+    fn test_commit_with_proof<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize,
+    {
+        let witness: P::Scalar = ECScalar::new_random();
+        let base: P = ECPoint::base_point2();
+        let (q, proof) = DLogProof::commit_with_proof(&witness, &base);
+        assert!(q == base.scalar_mul(&witness.get_element()));
+        assert!(q == proof.pk);
+        assert!(DLogProof::builder().generator(&base).verify(&proof).is_ok());
+        assert!(DLogProof::verify(&proof).is_err());
+    }
+
+    crate::test_for_all_curves!(test_extract_witness);
+    fn test_extract_witness<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize + Clone,
+    {
+        let witness: P::Scalar = ECScalar::new_random();
+        let g: P = ECPoint::generator();
+        let pk = g.scalar_mul(&witness.get_element());
+
+        // a prover rewound after sending its commitment answers two challenges
+        let k: P::Scalar = ECScalar::new_random();
+        let commitment = g.scalar_mul(&k.get_element());
+        let e1: P::Scalar = ECScalar::new_random();
+        let e2: P::Scalar = ECScalar::new_random();
+        let z1 = k.sub(&e1.mul(&witness.get_element()).get_element());
+        let z2 = k.sub(&e2.mul(&witness.get_element()).get_element());
+        for (e, z) in &[(&e1, &z1), (&e2, &z2)] {
+            let lhs = g
+                .scalar_mul(&z.get_element())
+                .add_point(&pk.scalar_mul(&e.get_element()).get_element());
+            assert!(lhs == commitment);
+        }
+
+        let extracted = DLogProof::<P>::extract_witness(&commitment, (&e1, &z1), (&e2, &z2));
+        assert_eq!(
+            extracted.map(|x| x.to_big_int()),
+            Some(witness.to_big_int())
+        );
+        assert!(DLogProof::<P>::extract_witness(&commitment, (&e1, &z1), (&e1, &z1)).is_none());
+        // a response that doesn't answer e2 for this commitment
+        let forged = z2.add(&e1.get_element());
+        assert!(DLogProof::<P>::extract_witness(&commitment, (&e1, &z1), (&e2, &forged)).is_none());
+    }
+
+    crate::test_for_all_curves!(test_dlog_proof_session_id);
+    fn test_dlog_proof_session_id<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize,
+    {
+        let witness: P::Scalar = ECScalar::new_random();
+        let proof = DLogProof::<P>::builder()
+            .witness(&witness)
+            .domain(b"protocol")
+            .session_id(b"session A")
+            .prove();
+        let verifier = || DLogProof::<P>::builder().domain(b"protocol");
+        assert!(verifier().session_id(b"session A").verify(&proof).is_ok());
+        assert!(verifier().session_id(b"session B").verify(&proof).is_err());
+        assert!(verifier().verify(&proof).is_err());
+        // the session id is not interchangeable with the domain tag
+        let proof = DLogProof::<P>::builder()
+            .witness(&witness)
+            .session_id(b"protocol")
+            .prove();
+        assert!(verifier().verify(&proof).is_err());
+    }
+
+    crate::test_for_all_curves!(test_dlog_proof_deterministic);
+    fn test_dlog_proof_deterministic<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize,
+    {
+        let witness: P::Scalar = ECScalar::new_random();
+        let prove = |witness: &P::Scalar, domain: &[u8]| {
+            let proof = DLogProof::<P>::builder()
+                .witness(witness)
+                .domain(domain)
+                .deterministic()
+                .prove();
+            assert!(DLogProof::builder().domain(domain).verify(&proof).is_ok());
+            (
+                proof.pk_t_rand_commitment.pk_to_key_slice(),
+                proof.challenge_response.to_big_int(),
+            )
+        };
+        let proof = prove(&witness, b"ctx");
+        assert_eq!(proof, prove(&witness, b"ctx"));
+        // the nonce changes along with the statement
+        assert_ne!(proof.0, prove(&witness, b"another ctx").0);
+        assert_ne!(proof.0, prove(&ECScalar::new_random(), b"ctx").0);
+    }
+
+    crate::test_for_all_curves!(test_dlog_proof_cbor_field_names);
+    fn test_dlog_proof_cbor_field_names<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize,
+        DLogProof<P>: Serialize + serde::de::DeserializeOwned,
+    {
+        let witness: P::Scalar = ECScalar::new_random();
+        let bytes = DLogProof::<P>::prove(&witness).to_cbor();
+        assert!(DLogProof::<P>::from_cbor(&bytes).is_ok());
+
+        let contains_text = |text: &str| {
+            let needle = crate::cbor::to_vec(text).unwrap();
+            bytes.windows(needle.len()).any(|w| w == &needle[..])
+        };
+        for field in &[
+            "curve",
+            P::CURVE_NAME,
+            "proof",
+            "pk",
+            "pk_t_rand_commitment",
+            "challenge_response",
+        ] {
+            assert!(contains_text(field), "missing {}", field);
+        }
+    }
+
+    #[test]
+    fn test_dlog_proof_cbor_round_trip() {
+        use crate::elliptic::curves::secp256_k1::{FE, GE};
+        let witness: FE = ECScalar::new_random();
+        let proof = DLogProof::<GE>::prove(&witness);
+        let decoded = DLogProof::<GE>::from_cbor(&proof.to_cbor()).unwrap();
+        assert_eq!(decoded, proof);
+        assert!(DLogProof::verify(&decoded).is_ok());
+    }
+
+    #[test]
+    fn test_dlog_proof_cbor_wrong_curve() {
+        use crate::elliptic::curves::{ed25519, secp256_k1};
+        let witness: secp256_k1::FE = ECScalar::new_random();
+        let bytes = DLogProof::<secp256_k1::GE>::prove(&witness).to_cbor();
+        assert!(DLogProof::<ed25519::GE>::from_cbor(&bytes).is_err());
+    }
+
+    crate::test_for_all_curves!(test_dlog_proof_cbor_size);
+    fn test_dlog_proof_cbor_size<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize,
+        DLogProof<P>: Serialize + serde::de::DeserializeOwned,
+    {
+        let witness: P::Scalar = ECScalar::new_random();
+        let proof = DLogProof::<P>::prove(&witness);
+        assert_eq!(proof.cbor_size(), proof.to_cbor().len());
+    }
+
+    crate::test_for_all_curves!(test_dlog_proof_serialized_size);
+    fn test_dlog_proof_serialized_size<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize,
+        DLogProof<P>: Serialize + serde::de::DeserializeOwned,
+    {
+        let witness: P::Scalar = ECScalar::new_random();
+        let proof = DLogProof::<P>::prove(&witness);
+        let bytes = proof.to_bytes();
+        assert_eq!(proof.serialized_size(), bytes.len());
+        assert_eq!(bytes.len(), 2 * P::COMPRESSED_SIZE + 32);
+        assert_eq!(
+            bytes[..P::COMPRESSED_SIZE],
+            proof.pk.serialize_compressed()[..]
+        );
+    }
 }
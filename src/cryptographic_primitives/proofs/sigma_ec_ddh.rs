@@ -6,11 +6,13 @@
 */
 
 use serde::{Deserialize, Serialize};
+use subtle::Choice;
 use zeroize::Zeroize;
 
-use super::ProofError;
-use crate::cryptographic_primitives::hashing::hash_sha256::HSha256;
-use crate::cryptographic_primitives::hashing::traits::Hash;
+use super::{
+    bind_session_id, points_ct_eq, with_proof_transcript, EncodeProof, ProofEncoder, ProofError,
+};
+use crate::cryptographic_primitives::hashing::transcript::Transcript;
 use crate::elliptic::curves::traits::*;
 
 /// This protocol is the elliptic curve form of the protocol from :
@@ -32,6 +34,14 @@ pub struct ECDDHProof<P: ECPoint> {
     pub z: P::Scalar,
 }
 
+impl<P: ECPoint> EncodeProof<P> for ECDDHProof<P> {
+    fn encode<E: ProofEncoder<P>>(&self, encoder: &mut E) {
+        encoder.point(&self.a1);
+        encoder.point(&self.a2);
+        encoder.scalar(&self.z);
+    }
+}
+
 #[derive(Clone, PartialEq, Debug)]
 pub struct ECDDHStatement<P: ECPoint> {
     pub g1: P,
@@ -51,30 +61,120 @@ where
     P::Scalar: Zeroize + Clone,
 {
     pub fn prove(w: &ECDDHWitness<P::Scalar>, delta: &ECDDHStatement<P>) -> ECDDHProof<P> {
+        Self::prove_with_session_id(w, delta, &[])
+    }
+
+    /// Same as [prove](Self::prove), with the challenge bound to `session_id`. The proof is only
+    /// accepted by [verify_with_session_id](Self::verify_with_session_id) given the same id.
+    pub fn prove_with_session_id(
+        w: &ECDDHWitness<P::Scalar>,
+        delta: &ECDDHStatement<P>,
+        session_id: &[u8],
+    ) -> ECDDHProof<P> {
+        Self::prove_bound(w, delta, session_id, None)
+    }
+
+    /// Same as [prove](Self::prove), with the challenge derived from `transcript` continued with
+    /// the statement and the commitments, so several proofs can be composed into one bundle. The
+    /// proof is only accepted by [verify_with_transcript](Self::verify_with_transcript) given a
+    /// transcript in the same state.
+    pub fn prove_with_transcript(
+        w: &ECDDHWitness<P::Scalar>,
+        delta: &ECDDHStatement<P>,
+        transcript: &mut Transcript,
+    ) -> ECDDHProof<P> {
+        Self::prove_bound(w, delta, &[], Some(transcript))
+    }
+
+    fn prove_bound(
+        w: &ECDDHWitness<P::Scalar>,
+        delta: &ECDDHStatement<P>,
+        session_id: &[u8],
+        transcript: Option<&mut Transcript>,
+    ) -> ECDDHProof<P> {
         let mut s: P::Scalar = ECScalar::new_random();
         let a1 = delta.g1.clone() * s.clone();
         let a2 = delta.g2.clone() * s.clone();
-        let e =
-            HSha256::create_hash_from_ge(&[&delta.g1, &delta.h1, &delta.g2, &delta.h2, &a1, &a2]);
+        let e = Self::challenge(transcript, delta, &a1, &a2, session_id);
         let z = s.clone() + e * w.x.clone();
         s.zeroize();
         ECDDHProof { a1, a2, z }
     }
 
     pub fn verify(&self, delta: &ECDDHStatement<P>) -> Result<(), ProofError> {
-        let e = HSha256::create_hash_from_ge(&[
-            &delta.g1, &delta.h1, &delta.g2, &delta.h2, &self.a1, &self.a2,
-        ]);
-        let z_g1 = delta.g1.clone() * self.z.clone();
-        let z_g2 = delta.g2.clone() * self.z.clone();
-        let a1_plus_e_h1 = self.a1.clone() + delta.h1.clone() * e.clone();
-        let a2_plus_e_h2 = self.a2.clone() + delta.h2.clone() * e;
-        if z_g1 == a1_plus_e_h1 && z_g2 == a2_plus_e_h2 {
+        self.verify_with_session_id(delta, &[])
+    }
+
+    pub fn verify_with_session_id(
+        &self,
+        delta: &ECDDHStatement<P>,
+        session_id: &[u8],
+    ) -> Result<(), ProofError> {
+        if bool::from(self.verify_ct_with_session_id(delta, session_id)) {
+            Ok(())
+        } else {
+            Err(ProofError)
+        }
+    }
+
+    pub fn verify_with_transcript(
+        &self,
+        delta: &ECDDHStatement<P>,
+        transcript: &mut Transcript,
+    ) -> Result<(), ProofError> {
+        if bool::from(self.verify_ct_bound(delta, &[], Some(transcript))) {
             Ok(())
         } else {
             Err(ProofError)
         }
     }
+
+    /// Same as [verify](Self::verify), both verification equations being computed and compared
+    /// in constant time
+    pub fn verify_ct(&self, delta: &ECDDHStatement<P>) -> Choice {
+        self.verify_ct_with_session_id(delta, &[])
+    }
+
+    pub fn verify_ct_with_session_id(
+        &self,
+        delta: &ECDDHStatement<P>,
+        session_id: &[u8],
+    ) -> Choice {
+        self.verify_ct_bound(delta, session_id, None)
+    }
+
+    fn verify_ct_bound(
+        &self,
+        delta: &ECDDHStatement<P>,
+        session_id: &[u8],
+        transcript: Option<&mut Transcript>,
+    ) -> Choice {
+        let e = Self::challenge(transcript, delta, &self.a1, &self.a2, session_id);
+        let z_g1 = delta.g1.clone() * self.z.clone();
+        let z_g2 = delta.g2.clone() * self.z.clone();
+        let a1_plus_e_h1 = self.a1.clone() + delta.h1.clone() * e.clone();
+        let a2_plus_e_h2 = self.a2.clone() + delta.h2.clone() * e;
+        points_ct_eq(&z_g1, &a1_plus_e_h1) & points_ct_eq(&z_g2, &a2_plus_e_h2)
+    }
+
+    fn challenge(
+        transcript: Option<&mut Transcript>,
+        delta: &ECDDHStatement<P>,
+        a1: &P,
+        a2: &P,
+        session_id: &[u8],
+    ) -> P::Scalar {
+        let e = with_proof_transcript::<P, _>(transcript, b"curv ec ddh proof", |transcript| {
+            transcript.append_point(b"G1", &delta.g1);
+            transcript.append_point(b"H1", &delta.h1);
+            transcript.append_point(b"G2", &delta.g2);
+            transcript.append_point(b"H2", &delta.h2);
+            transcript.append_point(b"A1", a1);
+            transcript.append_point(b"A2", a2);
+            transcript.challenge_scalar(b"e")
+        });
+        bind_session_id(e, session_id)
+    }
 }
 
 #[cfg(test)]
@@ -100,6 +200,30 @@ mod tests {
         assert!(proof.verify(&delta).is_ok());
     }
 
+    test_for_all_curves!(test_ecddh_proof_rejects_another_h2);
+    fn test_ecddh_proof_rejects_another_h2<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize + Clone,
+    {
+        let x: P::Scalar = ECScalar::new_random();
+        let g1: P = ECPoint::generator();
+        let g2: P = ECPoint::base_point2();
+        let h1 = g1.clone() * x.clone();
+        let h2 = g2.clone() * x.clone();
+        let delta = ECDDHStatement { g1, g2, h1, h2 };
+        let proof = ECDDHProof::prove(&ECDDHWitness { x }, &delta);
+        assert!(proof.verify(&delta).is_ok());
+
+        // H2 swapped for an unrelated point, and for H1
+        let mut swapped = delta.clone();
+        swapped.h2 = P::generator() * P::Scalar::new_random();
+        assert!(proof.verify(&swapped).is_err());
+        let mut swapped = delta.clone();
+        swapped.h2 = delta.h1.clone();
+        assert!(proof.verify(&swapped).is_err());
+    }
+
     test_for_all_curves!(
         #[should_panic]
         test_wrong_ecddh_proof
@@ -120,4 +244,40 @@ mod tests {
         let proof = ECDDHProof::prove(&w, &delta);
         assert!(proof.verify(&delta).is_ok());
     }
+
+    test_for_all_curves!(test_ecddh_proof_session_id);
+    fn test_ecddh_proof_session_id<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize + Clone,
+    {
+        let x: P::Scalar = ECScalar::new_random();
+        let g1: P = ECPoint::generator();
+        let g2: P = ECPoint::base_point2();
+        let h1 = g1.clone() * x.clone();
+        let h2 = g2.clone() * x.clone();
+        let delta = ECDDHStatement { g1, g2, h1, h2 };
+        let w = ECDDHWitness { x };
+        let proof = ECDDHProof::prove_with_session_id(&w, &delta, b"session A");
+        assert!(proof.verify_with_session_id(&delta, b"session A").is_ok());
+        assert!(proof.verify_with_session_id(&delta, b"session B").is_err());
+        assert!(proof.verify(&delta).is_err());
+    }
+
+    test_for_all_curves!(test_ecddh_proof_cbor_size);
+    fn test_ecddh_proof_cbor_size<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize + Clone,
+        ECDDHProof<P>: serde::Serialize + serde::de::DeserializeOwned,
+    {
+        let x: P::Scalar = ECScalar::new_random();
+        let g1: P = ECPoint::generator();
+        let g2: P = ECPoint::base_point2();
+        let h1 = g1.clone() * x.clone();
+        let h2 = g2.clone() * x.clone();
+        let delta = ECDDHStatement { g1, g2, h1, h2 };
+        let proof = ECDDHProof::prove(&ECDDHWitness { x }, &delta);
+        assert_eq!(proof.cbor_size(), proof.to_cbor().len());
+    }
 }
@@ -0,0 +1,113 @@
+/*
+    This file is part of Curv library
+    Copyright 2018 by Kzen Networks
+    (https://github.com/KZen-networks/curv)
+    License MIT: https://github.com/KZen-networks/curv/blob/master/LICENSE
+*/
+
+use std::fmt::Debug;
+
+use derivative::Derivative;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use super::{EncodeProof, ProofEncoder, ProofError};
+use crate::cryptographic_primitives::commitments::pedersen_commitment::{
+    EqualityProof, PedersenCommitment,
+};
+use crate::cryptographic_primitives::commitments::traits::Commitment;
+use crate::elliptic::curves::traits::*;
+
+/// protocol for proving that two Pedersen commitments hide the same message.
+/// witness: (m, r1, r2), statement: (c1, c2), The Relation R outputs 1 if c1 = mG + r1H and
+/// c2 = mG + r2H. The protocol:
+/// 1: c1 - c2 = (r1 - r2)H, prover proves knowledge of r1 - r2 as the discrete log of c1 - c2
+///    in respect to H, see [PedersenCommitment::prove_equal]
+/// prover sends pi = {c1, c2, the dlog proof}
+///
+/// verifier checks that the dlog proof is a proof for c1 - c2.
+#[derive(Serialize, Deserialize, Derivative)]
+#[derivative(Clone(bound = "P: Clone, P::Scalar: Clone"))]
+#[derivative(Debug(bound = "P: Debug, P::Scalar: Debug"))]
+#[derivative(PartialEq(bound = "P: PartialEq, P::Scalar: PartialEq"))]
+#[serde(bound(serialize = "P: Serialize, P::Scalar: Serialize"))]
+#[serde(bound(deserialize = "P: Deserialize<'de>, P::Scalar: Deserialize<'de>"))]
+pub struct PedersenEqualityProof<P: ECPoint> {
+    pub com1: P,
+    pub com2: P,
+    pub proof: EqualityProof<P>,
+}
+
+impl<P: ECPoint> EncodeProof<P> for PedersenEqualityProof<P> {
+    fn encode<E: ProofEncoder<P>>(&self, encoder: &mut E) {
+        encoder.point(&self.com1);
+        encoder.point(&self.com2);
+        self.proof.encode(encoder);
+    }
+}
+
+impl<P> PedersenEqualityProof<P>
+where
+    P: ECPoint + Clone,
+    P::Scalar: Zeroize + Clone,
+{
+    /// Commits to `m` with the blindings `r1` and `r2`, and proves that the two commitments
+    /// hide the same message
+    pub fn prove(m: &P::Scalar, r1: &P::Scalar, r2: &P::Scalar) -> PedersenEqualityProof<P> {
+        let commit = |r: &P::Scalar| -> P {
+            PedersenCommitment::create_commitment_with_user_defined_randomness(
+                &m.to_big_int(),
+                &r.to_big_int(),
+            )
+        };
+        let com1 = commit(r1);
+        let com2 = commit(r2);
+        let proof = PedersenCommitment::prove_equal(&com1, m, r1, &com2, r2);
+        PedersenEqualityProof { com1, com2, proof }
+    }
+
+    pub fn verify(proof: &PedersenEqualityProof<P>) -> Result<(), ProofError> {
+        PedersenCommitment::verify_equal(&proof.com1, &proof.com2, &proof.proof)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_for_all_curves;
+
+    test_for_all_curves!(test_pedersen_equality_proof);
+    fn test_pedersen_equality_proof<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize + Clone,
+    {
+        let m: P::Scalar = ECScalar::new_random();
+        let r1: P::Scalar = ECScalar::new_random();
+        let r2: P::Scalar = ECScalar::new_random();
+        let proof = PedersenEqualityProof::<P>::prove(&m, &r1, &r2);
+        assert!(PedersenEqualityProof::verify(&proof).is_ok());
+
+        let mut swapped = proof.clone();
+        std::mem::swap(&mut swapped.com1, &mut swapped.com2);
+        assert!(PedersenEqualityProof::verify(&swapped).is_err());
+    }
+
+    test_for_all_curves!(test_pedersen_equality_proof_rejects_another_message);
+    fn test_pedersen_equality_proof_rejects_another_message<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize + Clone,
+    {
+        let m: P::Scalar = ECScalar::new_random();
+        let r1: P::Scalar = ECScalar::new_random();
+        let r2: P::Scalar = ECScalar::new_random();
+        let mut proof = PedersenEqualityProof::<P>::prove(&m, &r1, &r2);
+        let other: P::Scalar = ECScalar::new_random();
+        proof.com2 = PedersenCommitment::create_commitment_with_user_defined_randomness(
+            &other.to_big_int(),
+            &r2.to_big_int(),
+        );
+        assert!(PedersenEqualityProof::verify(&proof).is_err());
+    }
+}
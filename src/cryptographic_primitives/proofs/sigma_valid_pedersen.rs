@@ -5,14 +5,18 @@
     License MIT: https://github.com/KZen-networks/curv/blob/master/LICENSE
 */
 
+use rand::RngCore;
 use serde::{Deserialize, Serialize};
+use subtle::Choice;
 use zeroize::Zeroize;
 
-use super::ProofError;
+use super::{
+    bind_session_id, points_ct_eq, sample_scalar_with_rng, transcript_state, with_proof_transcript,
+    DeterministicRng, EncodeProof, ProofEncoder, ProofError, ProofParams,
+};
 use crate::cryptographic_primitives::commitments::pedersen_commitment::PedersenCommitment;
 use crate::cryptographic_primitives::commitments::traits::Commitment;
-use crate::cryptographic_primitives::hashing::hash_sha256::HSha256;
-use crate::cryptographic_primitives::hashing::traits::Hash;
+use crate::cryptographic_primitives::hashing::transcript::Transcript;
 use crate::elliptic::curves::traits::*;
 
 /// protocol for proving that Pedersen commitment c was constructed correctly which is the same as
@@ -34,33 +38,190 @@ pub struct PedersenProof<P: ECPoint> {
     z2: P::Scalar,
 }
 
+impl<P: ECPoint> EncodeProof<P> for PedersenProof<P> {
+    fn encode<E: ProofEncoder<P>>(&self, encoder: &mut E) {
+        encoder.scalar(&self.e);
+        encoder.point(&self.a1);
+        encoder.point(&self.a2);
+        encoder.point(&self.com);
+        encoder.scalar(&self.z1);
+        encoder.scalar(&self.z2);
+    }
+}
+
 impl<P> PedersenProof<P>
 where
     P: ECPoint + Clone,
     P::Scalar: Zeroize,
 {
-    #[allow(clippy::many_single_char_names)]
     pub fn prove(m: &P::Scalar, r: &P::Scalar) -> PedersenProof<P> {
-        let g: P = ECPoint::generator();
-        let h: P = ECPoint::base_point2();
-        let mut s1: P::Scalar = ECScalar::new_random();
-        let mut s2: P::Scalar = ECScalar::new_random();
+        PedersenProof::builder().message(m).blinding(r).prove()
+    }
+
+    pub fn verify(proof: &PedersenProof<P>) -> Result<(), ProofError> {
+        PedersenProof::builder().verify(proof)
+    }
+
+    /// Same as [verify](Self::verify), the verification equation being compared in constant
+    /// time
+    pub fn verify_ct(&self) -> Choice {
+        PedersenProof::builder().verify_ct(self)
+    }
+
+    /// Returns a [PedersenProofBuilder] allowing to set optional parameters of the proof
+    pub fn builder<'a>() -> PedersenProofBuilder<'a, P> {
+        PedersenProofBuilder {
+            message: None,
+            blinding: None,
+            domain: &[],
+            session_id: &[],
+            generators: None,
+            rng: None,
+            deterministic: false,
+            transcript: None,
+        }
+    }
+}
+
+/// Builder for [PedersenProof].
+///
+/// By default the commitment is computed over `G = P::generator()` and `H = P::base_point2()`,
+/// with no domain tag and with randomness taken from `ECScalar::new_random()`, so a proof built
+/// with defaults is checked by [PedersenProof::verify]. A proof built with a domain tag or custom
+/// generators must be verified by a builder set with the same domain and generators.
+pub struct PedersenProofBuilder<'a, P: ECPoint> {
+    message: Option<&'a P::Scalar>,
+    blinding: Option<&'a P::Scalar>,
+    domain: &'a [u8],
+    session_id: &'a [u8],
+    generators: Option<(&'a P, &'a P)>,
+    rng: Option<&'a mut dyn RngCore>,
+    deterministic: bool,
+    transcript: Option<&'a mut Transcript>,
+}
+
+impl<'a, P> PedersenProofBuilder<'a, P>
+where
+    P: ECPoint + Clone,
+    P::Scalar: Zeroize,
+{
+    /// Sets the committed value `m`. Required by [prove](Self::prove).
+    pub fn message(mut self, m: &'a P::Scalar) -> Self {
+        self.message = Some(m);
+        self
+    }
+
+    /// Sets the blinding factor `r`. Required by [prove](Self::prove).
+    pub fn blinding(mut self, r: &'a P::Scalar) -> Self {
+        self.blinding = Some(r);
+        self
+    }
+
+    /// Sets a domain tag that is bound into the challenge
+    pub fn domain(mut self, domain: &'a [u8]) -> Self {
+        self.domain = domain;
+        self
+    }
+
+    /// Sets an id of the session the proof is made within, bound into the challenge separately
+    /// from the domain tag. A proof only verifies under the same session id.
+    pub fn session_id(mut self, session_id: &'a [u8]) -> Self {
+        self.session_id = session_id;
+        self
+    }
+
+    /// Sets the pair of generators `(G, H)` the commitment `c = mG + rH` is computed over
+    pub fn generators(mut self, g: &'a P, h: &'a P) -> Self {
+        self.generators = Some((g, h));
+        self
+    }
+
+    /// Sets the domain tag, session id and generators at once
+    pub fn params(mut self, params: &'a ProofParams<P>) -> Self {
+        self.domain = &params.domain;
+        self.session_id = &params.session_id;
+        self.generators = Some((&params.generator, &params.second_generator));
+        self
+    }
+
+    /// Sets the source of randomness used to sample the prover's commitments
+    pub fn rng(mut self, rng: &'a mut dyn RngCore) -> Self {
+        self.rng = Some(rng);
+        self
+    }
+
+    /// Derives the prover's commitments from the witness and the statement with a
+    /// [DeterministicRng] instead of sampling them, so proving twice the same statement yields
+    /// the same proof. Takes precedence over [rng](Self::rng).
+    pub fn deterministic(mut self) -> Self {
+        self.deterministic = true;
+        self
+    }
+
+    /// Derives the challenge from `transcript`, continued with the statement and the commitments,
+    /// instead of from a transcript of its own, so several proofs can be composed into one
+    /// bundle. The proof only verifies against a transcript in the same state.
+    pub fn transcript(mut self, transcript: &'a mut Transcript) -> Self {
+        self.transcript = Some(transcript);
+        self
+    }
+
+    /// Produces the proof.
+    ///
+    /// ## Panics
+    /// Panics if message or blinding factor wasn't set.
+    #[allow(clippy::many_single_char_names)]
+    pub fn prove(self) -> PedersenProof<P> {
+        let m = self
+            .message
+            .expect("message is required to produce a proof");
+        let r = self
+            .blinding
+            .expect("blinding is required to produce a proof");
+        let (g, h) = self.bases();
+        let com = match self.generators {
+            Some(_) => g
+                .scalar_mul(&m.get_element())
+                .add_point(&h.scalar_mul(&r.get_element()).get_element()),
+            None => PedersenCommitment::create_commitment_with_user_defined_randomness(
+                &m.to_big_int(),
+                &r.to_big_int(),
+            ),
+        };
+        let mut deterministic_rng;
+        let rng = if self.deterministic {
+            deterministic_rng = DeterministicRng::new(
+                b"curv sigma_valid_pedersen",
+                &[&m.to_big_int(), &r.to_big_int()],
+                &[
+                    self.domain,
+                    self.session_id,
+                    &g.pk_to_key_slice(),
+                    &h.pk_to_key_slice(),
+                    &com.pk_to_key_slice(),
+                    &transcript_state::<P::Scalar>(&self.transcript),
+                ],
+            );
+            Some(&mut deterministic_rng as &mut dyn RngCore)
+        } else {
+            self.rng
+        };
+        let (mut s1, mut s2): (P::Scalar, P::Scalar) = match rng {
+            Some(rng) => (sample_scalar_with_rng(rng), sample_scalar_with_rng(rng)),
+            None => (ECScalar::new_random(), ECScalar::new_random()),
+        };
         let a1 = g.scalar_mul(&s1.get_element());
         let a2 = h.scalar_mul(&s2.get_element());
-        let com: P = PedersenCommitment::create_commitment_with_user_defined_randomness(
-            &m.to_big_int(),
-            &r.to_big_int(),
+        let e = Self::challenge(
+            self.transcript,
+            self.domain,
+            self.session_id,
+            &g,
+            &h,
+            &com,
+            &a1,
+            &a2,
         );
-        let g: P = ECPoint::generator();
-        let challenge = HSha256::create_hash(&[
-            &g.bytes_compressed_to_big_int(),
-            &h.bytes_compressed_to_big_int(),
-            &com.bytes_compressed_to_big_int(),
-            &a1.bytes_compressed_to_big_int(),
-            &a2.bytes_compressed_to_big_int(),
-        ]);
-
-        let e: P::Scalar = ECScalar::from(&challenge);
 
         let em = e.mul(&m.get_element());
         let z1 = s1.add(&em.get_element());
@@ -79,17 +240,28 @@ where
         }
     }
 
-    pub fn verify(proof: &PedersenProof<P>) -> Result<(), ProofError> {
-        let g: P = ECPoint::generator();
-        let h: P = ECPoint::base_point2();
-        let challenge = HSha256::create_hash(&[
-            &g.bytes_compressed_to_big_int(),
-            &h.bytes_compressed_to_big_int(),
-            &proof.com.bytes_compressed_to_big_int(),
-            &proof.a1.bytes_compressed_to_big_int(),
-            &proof.a2.bytes_compressed_to_big_int(),
-        ]);
-        let e: P::Scalar = ECScalar::from(&challenge);
+    /// Verifies the proof against the domain tag, session id and generators set in the builder
+    pub fn verify(self, proof: &PedersenProof<P>) -> Result<(), ProofError> {
+        if bool::from(self.verify_ct(proof)) {
+            Ok(())
+        } else {
+            Err(ProofError)
+        }
+    }
+
+    /// Same as [verify](Self::verify), returning the result of a constant-time comparison
+    pub fn verify_ct(self, proof: &PedersenProof<P>) -> Choice {
+        let (g, h) = self.bases();
+        let e = Self::challenge(
+            self.transcript,
+            self.domain,
+            self.session_id,
+            &g,
+            &h,
+            &proof.com,
+            &proof.a1,
+            &proof.a2,
+        );
 
         let z1g = g.scalar_mul(&proof.z1.get_element());
         let z2h = h.scalar_mul(&proof.z2.get_element());
@@ -99,18 +271,83 @@ where
         let ecom = com_clone.scalar_mul(&e.get_element());
         let rhs = rhs.add_point(&ecom.get_element());
 
-        if lhs == rhs {
-            Ok(())
-        } else {
-            Err(ProofError)
+        points_ct_eq(&lhs, &rhs)
+    }
+
+    fn bases(&self) -> (P, P) {
+        match self.generators {
+            Some((g, h)) => (g.clone(), h.clone()),
+            None => (ECPoint::generator(), ECPoint::base_point2()),
         }
     }
+
+    #[allow(clippy::too_many_arguments)]
+    fn challenge(
+        transcript: Option<&mut Transcript>,
+        domain: &[u8],
+        session_id: &[u8],
+        g: &P,
+        h: &P,
+        com: &P,
+        a1: &P,
+        a2: &P,
+    ) -> P::Scalar {
+        let e = with_proof_transcript::<P, _>(transcript, b"curv pedersen proof", |transcript| {
+            transcript.append_message(b"domain", domain);
+            transcript.append_point(b"G", g);
+            transcript.append_point(b"H", h);
+            transcript.append_point(b"com", com);
+            transcript.append_point(b"A1", a1);
+            transcript.append_point(b"A2", a2);
+            transcript.challenge_scalar(b"e")
+        });
+        bind_session_id(e, session_id)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    crate::test_for_all_curves!(test_pedersen_proof_params);
+    fn test_pedersen_proof_params<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize,
+    {
+        let m: P::Scalar = ECScalar::new_random();
+        let r: P::Scalar = ECScalar::new_random();
+        let params = ProofParams::<P>::new().with_domain(b"ctx");
+        let proof = PedersenProof::builder()
+            .message(&m)
+            .blinding(&r)
+            .params(&params)
+            .prove();
+        assert!(PedersenProof::builder()
+            .params(&params)
+            .verify(&proof)
+            .is_ok());
+        let other = params
+            .clone()
+            .with_generators(P::base_point2(), P::generator());
+        assert!(PedersenProof::builder()
+            .params(&other)
+            .verify(&proof)
+            .is_err());
+        let other = params.with_domain(b"another ctx");
+        assert!(PedersenProof::builder()
+            .params(&other)
+            .verify(&proof)
+            .is_err());
+
+        let proof = PedersenProof::builder()
+            .message(&m)
+            .blinding(&r)
+            .params(&ProofParams::new())
+            .prove();
+        assert!(PedersenProof::<P>::verify(&proof).is_ok());
+    }
+
     crate::test_for_all_curves!(test_pedersen_proof);
     fn test_pedersen_proof<P>()
     where
@@ -122,4 +359,105 @@ mod tests {
         let pedersen_proof = PedersenProof::<P>::prove(&m, &r);
         PedersenProof::verify(&pedersen_proof).expect("error pedersen");
     }
+
+    crate::test_for_all_curves!(test_pedersen_proof_builder);
+    fn test_pedersen_proof_builder<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize,
+    {
+        let m: P::Scalar = ECScalar::new_random();
+        let r: P::Scalar = ECScalar::new_random();
+
+        let proof = PedersenProof::<P>::builder()
+            .message(&m)
+            .blinding(&r)
+            .prove();
+        PedersenProof::verify(&proof).expect("error pedersen");
+
+        // swapping the default generators
+        let g: P = ECPoint::base_point2();
+        let h: P = ECPoint::generator();
+        let mut rng = rand::thread_rng();
+        let proof = PedersenProof::<P>::builder()
+            .message(&m)
+            .blinding(&r)
+            .domain(b"ctx")
+            .generators(&g, &h)
+            .rng(&mut rng)
+            .prove();
+        assert!(PedersenProof::builder()
+            .domain(b"ctx")
+            .generators(&g, &h)
+            .verify(&proof)
+            .is_ok());
+        assert!(PedersenProof::builder()
+            .generators(&g, &h)
+            .verify(&proof)
+            .is_err());
+        assert!(PedersenProof::verify(&proof).is_err());
+    }
+
+    crate::test_for_all_curves!(test_pedersen_proof_session_id);
+    fn test_pedersen_proof_session_id<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize,
+    {
+        let m: P::Scalar = ECScalar::new_random();
+        let r: P::Scalar = ECScalar::new_random();
+        let proof = PedersenProof::<P>::builder()
+            .message(&m)
+            .blinding(&r)
+            .session_id(b"session A")
+            .prove();
+        assert!(PedersenProof::<P>::builder()
+            .session_id(b"session A")
+            .verify(&proof)
+            .is_ok());
+        assert!(PedersenProof::<P>::builder()
+            .session_id(b"session B")
+            .verify(&proof)
+            .is_err());
+    }
+
+    crate::test_for_all_curves!(test_pedersen_proof_deterministic);
+    fn test_pedersen_proof_deterministic<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize,
+    {
+        let m: P::Scalar = ECScalar::new_random();
+        let r: P::Scalar = ECScalar::new_random();
+        let prove = |r: &P::Scalar| {
+            let proof = PedersenProof::<P>::builder()
+                .message(&m)
+                .blinding(r)
+                .deterministic()
+                .prove();
+            PedersenProof::verify(&proof).expect("error pedersen");
+            (
+                proof.a1.pk_to_key_slice(),
+                proof.a2.pk_to_key_slice(),
+                proof.z1.to_big_int(),
+                proof.z2.to_big_int(),
+            )
+        };
+        let proof = prove(&r);
+        assert_eq!(proof, prove(&r));
+        assert_ne!(proof.0, prove(&ECScalar::new_random()).0);
+    }
+
+    crate::test_for_all_curves!(test_pedersen_proof_cbor_size);
+    fn test_pedersen_proof_cbor_size<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize,
+        PedersenProof<P>: Serialize + serde::de::DeserializeOwned,
+    {
+        let m: P::Scalar = ECScalar::new_random();
+        let r: P::Scalar = ECScalar::new_random();
+        let proof = PedersenProof::<P>::prove(&m, &r);
+        assert_eq!(proof.cbor_size(), proof.to_cbor().len());
+    }
 }
@@ -6,20 +6,27 @@
 */
 
 use serde::{Deserialize, Serialize};
+use subtle::Choice;
 use zeroize::Zeroize;
 
-use super::ProofError;
+use super::{
+    bind_session_id, points_ct_eq, with_proof_transcript, EncodeProof, ProofEncoder, ProofError,
+};
+use crate::arithmetic::traits::*;
 use crate::cryptographic_primitives::commitments::pedersen_commitment::PedersenCommitment;
 use crate::cryptographic_primitives::commitments::traits::Commitment;
 use crate::cryptographic_primitives::hashing::hash_sha256::HSha256;
 use crate::cryptographic_primitives::hashing::traits::Hash;
+use crate::cryptographic_primitives::hashing::transcript::Transcript;
+use crate::elliptic::curves::multiscalar::multiscalar_mul;
 use crate::elliptic::curves::traits::*;
+use crate::BigInt;
 
 /// protocol for proving that Pedersen commitment c was constructed correctly which is the same as
 /// proof of knowledge of (r) such that c = mG + rH.
 /// witness: (r), statement: (c,m), The Relation R outputs 1 if c = mG + rH. The protocol:
 /// 1: Prover chooses A = s*H for random s
-/// prover calculates challenge e = H(G,H,c,A,m)
+/// prover calculates challenge e = H(G,H,c,m,A)
 /// prover calculates z  = s + er,
 /// prover sends pi = {e, m,A,c, z}
 /// verifier checks that emG + zH  = A + ec
@@ -32,14 +39,55 @@ pub struct PedersenBlindingProof<P: ECPoint> {
     z: P::Scalar,
 }
 
+impl<P: ECPoint> EncodeProof<P> for PedersenBlindingProof<P> {
+    fn encode<E: ProofEncoder<P>>(&self, encoder: &mut E) {
+        encoder.scalar(&self.e);
+        encoder.scalar(&self.m);
+        encoder.point(&self.a);
+        encoder.point(&self.com);
+        encoder.scalar(&self.z);
+    }
+}
+
 impl<P> PedersenBlindingProof<P>
 where
     P: ECPoint + Clone,
     P::Scalar: Zeroize + Clone,
 {
-    #[allow(clippy::many_single_char_names)]
     //TODO: add self verification to prover proof
     pub fn prove(m: &P::Scalar, r: &P::Scalar) -> PedersenBlindingProof<P> {
+        Self::prove_with_session_id(m, r, &[])
+    }
+
+    /// Same as [prove](Self::prove), with the challenge bound to `session_id`. The proof is only
+    /// accepted by [verify_with_session_id](Self::verify_with_session_id) given the same id.
+    pub fn prove_with_session_id(
+        m: &P::Scalar,
+        r: &P::Scalar,
+        session_id: &[u8],
+    ) -> PedersenBlindingProof<P> {
+        Self::prove_bound(m, r, session_id, None)
+    }
+
+    /// Same as [prove](Self::prove), with the challenge derived from `transcript` continued with
+    /// the statement and the commitment, so several proofs can be composed into one bundle. The
+    /// proof is only accepted by [verify_with_transcript](Self::verify_with_transcript) given a
+    /// transcript in the same state.
+    pub fn prove_with_transcript(
+        m: &P::Scalar,
+        r: &P::Scalar,
+        transcript: &mut Transcript,
+    ) -> PedersenBlindingProof<P> {
+        Self::prove_bound(m, r, &[], Some(transcript))
+    }
+
+    #[allow(clippy::many_single_char_names)]
+    fn prove_bound(
+        m: &P::Scalar,
+        r: &P::Scalar,
+        session_id: &[u8],
+        transcript: Option<&mut Transcript>,
+    ) -> PedersenBlindingProof<P> {
         let h: P = ECPoint::base_point2();
         let mut s: P::Scalar = ECScalar::new_random();
         let a = h.scalar_mul(&s.get_element());
@@ -47,15 +95,7 @@ where
             &m.to_big_int(),
             &r.to_big_int(),
         );
-        let g: P = ECPoint::generator();
-        let challenge = HSha256::create_hash(&[
-            &g.bytes_compressed_to_big_int(),
-            &h.bytes_compressed_to_big_int(),
-            &com.bytes_compressed_to_big_int(),
-            &a.bytes_compressed_to_big_int(),
-            &m.to_big_int(),
-        ]);
-        let e: P::Scalar = ECScalar::from(&challenge);
+        let e = Self::challenge(transcript, session_id, &com, m, &a);
 
         let er = e.mul(&r.get_element());
         let z = s.add(&er.get_element());
@@ -70,6 +110,37 @@ where
     }
 
     pub fn verify(proof: &PedersenBlindingProof<P>) -> Result<(), ProofError> {
+        Self::verify_with_session_id(proof, &[])
+    }
+
+    pub fn verify_with_session_id(
+        proof: &PedersenBlindingProof<P>,
+        session_id: &[u8],
+    ) -> Result<(), ProofError> {
+        if bool::from(proof.verify_ct_with_session_id(session_id)) {
+            Ok(())
+        } else {
+            Err(ProofError)
+        }
+    }
+
+    pub fn verify_with_transcript(
+        proof: &PedersenBlindingProof<P>,
+        transcript: &mut Transcript,
+    ) -> Result<(), ProofError> {
+        if bool::from(proof.verify_ct_bound(&[], Some(transcript))) {
+            Ok(())
+        } else {
+            Err(ProofError)
+        }
+    }
+
+    /// Verifies a proof made by the versions of curv before challenges were derived from a
+    /// [Transcript], that is e = H(G, H, c, A, m): SHA-256 over the compressed points and m as
+    /// concatenated big integers, with no session id. Those inputs aren't framed, so this is
+    /// only meant for proofs serialized by these versions, which [verify](Self::verify)
+    /// rejects.
+    pub fn verify_legacy(proof: &PedersenBlindingProof<P>) -> Result<(), ProofError> {
         let g: P = ECPoint::generator();
         let h: P = ECPoint::base_point2();
         let challenge = HSha256::create_hash(&[
@@ -79,28 +150,117 @@ where
             &proof.a.bytes_compressed_to_big_int(),
             &proof.m.to_big_int(),
         ]);
+        let e = P::Scalar::from_be_bytes_mod_order(&BigInt::to_bytes(&challenge));
+        if bool::from(proof.check(&e)) {
+            Ok(())
+        } else {
+            Err(ProofError)
+        }
+    }
 
-        let e: P::Scalar = ECScalar::from(&challenge);
-
-        let zh = h.scalar_mul(&proof.z.get_element());
-        let mg = g.scalar_mul(&proof.m.get_element());
-        let emg = mg.scalar_mul(&e.get_element());
-        let lhs = zh.add_point(&emg.get_element());
-        let com_clone = proof.com.clone();
-        let ecom = com_clone.scalar_mul(&e.get_element());
-        let rhs = ecom.add_point(&proof.a.get_element());
+    /// Verifies all `proofs` at once, as [verify](Self::verify) would one by one.
+    ///
+    /// The verification equations are combined with random weights w_i sampled by the verifier,
+    /// sum w_i*A_i + sum w_i*e_i*c_i - (sum w_i*e_i*m_i)*G = (sum w_i*z_i)*H, and checked with
+    /// a single multi-scalar multiplication. The prover can't predict the weights, so a batch
+    /// with an invalid proof is rejected except with negligible probability. The error doesn't
+    /// tell which proof is invalid.
+    pub fn verify_batch(proofs: &[PedersenBlindingProof<P>]) -> Result<(), ProofError> {
+        if proofs.is_empty() {
+            return Ok(());
+        }
+        let q = P::Scalar::q();
+        let mut points = Vec::with_capacity(2 * proofs.len() + 1);
+        let mut scalars = Vec::with_capacity(2 * proofs.len() + 1);
+        let mut wem = BigInt::zero();
+        let mut wz = BigInt::zero();
+        for proof in proofs {
+            let w: P::Scalar = ECScalar::new_random();
+            let e = Self::challenge(None, &[], &proof.com, &proof.m, &proof.a);
+            let we = w.mul(&e.get_element());
+            wem = BigInt::mod_add(
+                &wem,
+                &BigInt::mod_mul(&we.to_big_int(), &proof.m.to_big_int(), &q),
+                &q,
+            );
+            wz = BigInt::mod_add(
+                &wz,
+                &BigInt::mod_mul(&w.to_big_int(), &proof.z.to_big_int(), &q),
+                &q,
+            );
+            points.push(proof.a.clone());
+            scalars.push(w);
+            points.push(proof.com.clone());
+            scalars.push(we);
+        }
+        points.push(P::generator());
+        scalars.push(ECScalar::from(&BigInt::mod_sub(&q, &wem, &q)));
+        let wz: P::Scalar = ECScalar::from(&wz);
 
-        if lhs == rhs {
+        let lhs = multiscalar_mul(&scalars, &points);
+        let rhs = P::base_point2() * wz;
+        if lhs.map(|lhs| lhs.pk_to_key_slice()) == Some(rhs.pk_to_key_slice()) {
             Ok(())
         } else {
             Err(ProofError)
         }
     }
+
+    /// Same as [verify](Self::verify), the verification equation being compared in constant
+    /// time
+    pub fn verify_ct(&self) -> Choice {
+        self.verify_ct_with_session_id(&[])
+    }
+
+    pub fn verify_ct_with_session_id(&self, session_id: &[u8]) -> Choice {
+        self.verify_ct_bound(session_id, None)
+    }
+
+    fn verify_ct_bound(&self, session_id: &[u8], transcript: Option<&mut Transcript>) -> Choice {
+        let e = Self::challenge(transcript, session_id, &self.com, &self.m, &self.a);
+        self.check(&e)
+    }
+
+    fn check(&self, e: &P::Scalar) -> Choice {
+        let g: P = ECPoint::generator();
+        let h: P = ECPoint::base_point2();
+        let zh = h.scalar_mul(&self.z.get_element());
+        let mg = g.scalar_mul(&self.m.get_element());
+        let emg = mg.scalar_mul(&e.get_element());
+        let lhs = zh.add_point(&emg.get_element());
+        let com_clone = self.com.clone();
+        let ecom = com_clone.scalar_mul(&e.get_element());
+        let rhs = ecom.add_point(&self.a.get_element());
+
+        points_ct_eq(&lhs, &rhs)
+    }
+
+    fn challenge(
+        transcript: Option<&mut Transcript>,
+        session_id: &[u8],
+        com: &P,
+        m: &P::Scalar,
+        a: &P,
+    ) -> P::Scalar {
+        let g: P = ECPoint::generator();
+        let h: P = ECPoint::base_point2();
+        let protocol = b"curv pedersen blinding proof";
+        let e = with_proof_transcript::<P, _>(transcript, protocol, |transcript| {
+            transcript.append_point(b"G", &g);
+            transcript.append_point(b"H", &h);
+            transcript.append_point(b"com", com);
+            transcript.append_scalar(b"m", m);
+            transcript.append_point(b"A", a);
+            transcript.challenge_scalar(b"e")
+        });
+        bind_session_id(e, session_id)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::elliptic::curves::secp256_k1::FE;
 
     crate::test_for_all_curves!(test_pedersen_blind_proof);
     fn test_pedersen_blind_proof<P>()
@@ -114,4 +274,145 @@ mod tests {
         let _verified =
             PedersenBlindingProof::verify(&pedersen_proof).expect("error pedersen blind");
     }
+
+    crate::test_for_all_curves!(test_pedersen_blind_proof_session_id);
+    fn test_pedersen_blind_proof_session_id<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize + Clone,
+    {
+        let m: P::Scalar = ECScalar::new_random();
+        let r: P::Scalar = ECScalar::new_random();
+        let proof = PedersenBlindingProof::<P>::prove_with_session_id(&m, &r, b"session A");
+        assert!(PedersenBlindingProof::verify_with_session_id(&proof, b"session A").is_ok());
+        assert!(PedersenBlindingProof::verify_with_session_id(&proof, b"session B").is_err());
+    }
+
+    crate::test_for_all_curves!(test_pedersen_blind_proof_batch);
+    fn test_pedersen_blind_proof_batch<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize + Clone,
+    {
+        let proofs: Vec<PedersenBlindingProof<P>> = (0..5)
+            .map(|_| PedersenBlindingProof::prove(&ECScalar::new_random(), &ECScalar::new_random()))
+            .collect();
+        assert!(PedersenBlindingProof::verify_batch(&proofs).is_ok());
+        assert!(PedersenBlindingProof::verify_batch(&proofs[..1]).is_ok());
+        assert!(PedersenBlindingProof::<P>::verify_batch(&[]).is_ok());
+
+        let mut invalid = proofs.clone();
+        invalid[3].m = ECScalar::new_random();
+        assert!(PedersenBlindingProof::verify(&invalid[3]).is_err());
+        assert!(PedersenBlindingProof::verify_batch(&invalid).is_err());
+
+        // errors cancelling out in the plain sum of the equations
+        let delta: P::Scalar = ECScalar::new_random();
+        let mut invalid = proofs;
+        invalid[1].z = invalid[1].z.add(&delta.get_element());
+        invalid[2].z = invalid[2].z.sub(&delta.get_element());
+        assert!(PedersenBlindingProof::verify(&invalid[1]).is_err());
+        assert!(PedersenBlindingProof::verify(&invalid[2]).is_err());
+        assert!(PedersenBlindingProof::verify_batch(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_verify_legacy() {
+        use crate::elliptic::curves::secp256_k1::GE;
+
+        // serialized by the version before challenges were derived from a transcript
+        let json = concat!(
+            r#"{"e":"a5e3f2eb06a69f3a092e9b32a108a069f043ad5caa0aaf626f1ccb93f7e707c1","#,
+            r#""m":"eeffd9f1a464fb9feaaf5ed62815ef731a1d6f84d7d3107f92ba7ad96b14b216","#,
+            r#""a":{"x":"636cf59acf1073caeff2f03636b81e1c68e5fd59c691d3b004688b25ad992713","#,
+            r#""y":"ee6540c21ce8fc914b286c39320abcd8e803dbee44550f1cf2748e92c2cc7800"},"#,
+            r#""com":{"x":"c8bf8d31699cb21cd97d57c8017c6586d32a04be49d8c18d31e34cbbbfdc051d","#,
+            r#""y":"2d74d4c1da74ea9a1fed246068b774092eeea1afbf1365aeb046800a11e729be"},"#,
+            r#""z":"603b5d7ed7bab0b9e12d1f0ad0594c8c9748d31d3a4ac8bff6384c6b82abebde"}"#
+        );
+        let proof: PedersenBlindingProof<GE> = serde_json::from_str(json).unwrap();
+        assert!(PedersenBlindingProof::verify_legacy(&proof).is_ok());
+        assert!(PedersenBlindingProof::verify(&proof).is_err());
+
+        let mut tampered = proof;
+        tampered.m = ECScalar::new_random();
+        assert!(PedersenBlindingProof::verify_legacy(&tampered).is_err());
+
+        let m: FE = ECScalar::new_random();
+        let r: FE = ECScalar::new_random();
+        let proof = PedersenBlindingProof::<GE>::prove(&m, &r);
+        assert!(PedersenBlindingProof::verify_legacy(&proof).is_err());
+    }
+
+    #[test]
+    fn test_precheck_bytes() {
+        use crate::cbor;
+        use crate::elliptic::curves::{ed25519, secp256_k1::GE};
+        use serde::Serialize;
+
+        let m: FE = ECScalar::new_random();
+        let r: FE = ECScalar::new_random();
+        let proof = PedersenBlindingProof::<GE>::prove(&m, &r);
+        let bytes = proof.to_cbor();
+        assert!(PedersenBlindingProof::<GE>::precheck_bytes(&bytes).is_ok());
+
+        // truncated, with trailing bytes, and over another curve
+        assert!(PedersenBlindingProof::<GE>::precheck_bytes(&bytes[..bytes.len() - 1]).is_err());
+        let mut extended = bytes.clone();
+        extended.push(0);
+        assert!(PedersenBlindingProof::<GE>::precheck_bytes(&extended).is_err());
+        assert!(PedersenBlindingProof::<ed25519::GE>::precheck_bytes(&bytes).is_err());
+
+        // well formed, but A isn't a point of the curve
+        #[derive(Serialize)]
+        struct Point {
+            x: String,
+            y: String,
+        }
+        #[derive(Serialize)]
+        struct Proof<'a> {
+            e: &'a FE,
+            m: &'a FE,
+            a: Point,
+            com: &'a GE,
+            z: &'a FE,
+        }
+        #[derive(Serialize)]
+        struct Tagged<'a> {
+            curve: &'static str,
+            proof: Proof<'a>,
+        }
+        let tampered = cbor::to_vec(&Tagged {
+            curve: GE::CURVE_NAME,
+            proof: Proof {
+                e: &proof.e,
+                m: &proof.m,
+                a: Point {
+                    x: "5".to_string(),
+                    y: "5".to_string(),
+                },
+                com: &proof.com,
+                z: &proof.z,
+            },
+        })
+        .unwrap();
+        assert!(PedersenBlindingProof::<GE>::precheck_bytes(&tampered).is_err());
+
+        // A declared as a vector of 2^40 items, rejected before any of them is read
+        let mut oversized = vec![0xa2];
+        oversized.extend(cbor::to_vec("curve").unwrap());
+        oversized.extend(cbor::to_vec(GE::CURVE_NAME).unwrap());
+        oversized.extend(cbor::to_vec("proof").unwrap());
+        oversized.push(0xa5);
+        oversized.extend(cbor::to_vec("e").unwrap());
+        oversized.extend(cbor::to_vec(&proof.e).unwrap());
+        oversized.extend(cbor::to_vec("m").unwrap());
+        oversized.extend(cbor::to_vec(&proof.m).unwrap());
+        oversized.extend(cbor::to_vec("a").unwrap());
+        oversized.extend([0x9b, 0, 0, 1, 0, 0, 0, 0, 0]);
+        let err = PedersenBlindingProof::<GE>::precheck_bytes(&oversized)
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("more than the limit"));
+    }
 }
@@ -0,0 +1,273 @@
+/*
+    This file is part of Curv library
+    Copyright 2018 by Kzen Networks
+    (https://github.com/KZen-networks/curv)
+    License MIT: https://github.com/KZen-networks/curv/blob/master/LICENSE
+*/
+
+use std::fmt::Debug;
+
+use derivative::Derivative;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use super::range_proof::RangeProof;
+use super::sigma_dlog::DLogProof;
+use super::{EncodeProof, ProofEncoder, ProofError};
+use crate::arithmetic::traits::*;
+use crate::cryptographic_primitives::commitments::pedersen_commitment::PedersenCommitment;
+use crate::elliptic::curves::traits::*;
+use crate::BigInt;
+
+/// Proof of solvency, for proofs of reserves: the Pedersen commitments c_i = b_i*G + r_i*H to a
+/// set of balances add up to a public total, and none of the balances is negative.
+/// witness: (b_i, r_i), statement: (c_i, total, n), The Relation R outputs 1 if every c_i opens to
+/// b_i in [0, 2^n) and sum b_i = total.
+/// The protocol:
+/// 1: Prover proves that every c_i hides a value in [0, 2^n) with a [RangeProof]
+/// 2: Prover proves that sum c_i - total*G = (sum r_i)*H is a commitment to zero, see
+///    [PedersenCommitment::prove_commits_to_zero]
+///
+/// verifier checks all the proofs, and that the number of balances is small enough for their sum
+/// not to wrap around the group order.
+#[derive(Serialize, Deserialize, Derivative)]
+#[derivative(Clone(bound = "P: Clone, P::Scalar: Clone"))]
+#[derivative(Debug(bound = "P: Debug, P::Scalar: Debug"))]
+#[derivative(PartialEq(bound = "P: PartialEq, P::Scalar: PartialEq"))]
+#[serde(bound(serialize = "P: Serialize, P::Scalar: Serialize"))]
+#[serde(bound(deserialize = "P: Deserialize<'de>, P::Scalar: Deserialize<'de>"))]
+pub struct SolvencyProof<P: ECPoint> {
+    pub range_proofs: Vec<RangeProof<P>>,
+    pub total_proof: DLogProof<P>,
+}
+
+impl<P: ECPoint> EncodeProof<P> for SolvencyProof<P> {
+    fn encode<E: ProofEncoder<P>>(&self, encoder: &mut E) {
+        encoder.length(self.range_proofs.len());
+        for range_proof in &self.range_proofs {
+            range_proof.encode(encoder);
+        }
+        self.total_proof.encode(encoder);
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct SolvencyStatement<P: ECPoint> {
+    pub commitments: Vec<P>,
+    pub total: BigInt,
+    pub bits: usize,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct SolvencyWitness<S: ECScalar> {
+    pub balances: Vec<BigInt>,
+    pub blindings: Vec<S>,
+}
+
+impl<P> SolvencyProof<P>
+where
+    P: ECPoint + Clone,
+    P::Scalar: Zeroize + Clone + PartialEq,
+{
+    /// ## Panics
+    /// Panics if there's no balance, if the witness and the statement differ in length, if a
+    /// commitment doesn't open to its balance, if a balance is out of range, if balances don't
+    /// add up to the total or if the blindings add up to zero.
+    pub fn prove(w: &SolvencyWitness<P::Scalar>, delta: &SolvencyStatement<P>) -> SolvencyProof<P> {
+        let n = delta.commitments.len();
+        assert!(n > 0, "no balance to prove");
+        assert!(
+            w.balances.len() == n && w.blindings.len() == n,
+            "witness and statement differ in length"
+        );
+        assert!(
+            w.balances.iter().fold(BigInt::zero(), |sum, b| sum + b) == delta.total,
+            "balances don't add up to the total"
+        );
+        let range_proofs = delta
+            .commitments
+            .iter()
+            .zip(&w.balances)
+            .zip(&w.blindings)
+            .map(|((c, b), r)| RangeProof::prove(c, b, r, delta.bits))
+            .collect();
+
+        let mut blinding_sum = w.blindings[0].clone();
+        for r in &w.blindings[1..] {
+            blinding_sum = blinding_sum.add(&r.get_element());
+        }
+        let excess = Self::excess(delta).expect("the blindings add up to zero");
+        let total_proof = PedersenCommitment::prove_commits_to_zero(&excess, &blinding_sum);
+        blinding_sum.zeroize();
+
+        SolvencyProof {
+            range_proofs,
+            total_proof,
+        }
+    }
+
+    pub fn verify(&self, delta: &SolvencyStatement<P>) -> Result<(), ProofError> {
+        let n = delta.commitments.len();
+        // n balances below 2^bits add up to less than n * 2^bits
+        if n == 0
+            || self.range_proofs.len() != n
+            || (BigInt::from(n as u64) << delta.bits) >= P::Scalar::q()
+        {
+            return Err(ProofError);
+        }
+        for (c, proof) in delta.commitments.iter().zip(&self.range_proofs) {
+            proof.verify(c, delta.bits)?;
+        }
+        let excess = Self::excess(delta).ok_or(ProofError)?;
+        PedersenCommitment::verify_commits_to_zero(&excess, &self.total_proof)
+    }
+
+    // sum c_i - total*G, None at infinity on secp256k1 and p256
+    fn excess(delta: &SolvencyStatement<P>) -> Option<P> {
+        let one: P::Scalar = ECScalar::from(&BigInt::one());
+        let mut scalars = vec![one; delta.commitments.len()];
+        let mut points = delta.commitments.clone();
+        let minus_total = BigInt::mod_sub(&BigInt::zero(), &delta.total, &P::Scalar::q());
+        // the zero scalar can't be built on every curve
+        if minus_total != BigInt::zero() {
+            scalars.push(ECScalar::from(&minus_total));
+            points.push(P::generator());
+        }
+        P::multi_scalar_mul(&scalars, &points)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cryptographic_primitives::proofs::range_proof::commit;
+
+    fn setup<P>(balances: &[u32]) -> (SolvencyWitness<P::Scalar>, SolvencyStatement<P>)
+    where
+        P: ECPoint + Clone,
+    {
+        let balances: Vec<BigInt> = balances.iter().map(|b| BigInt::from(*b)).collect();
+        let blindings: Vec<P::Scalar> = balances.iter().map(|_| ECScalar::new_random()).collect();
+        let commitments = balances
+            .iter()
+            .zip(&blindings)
+            .map(|(b, r)| commit::<P>(b, r))
+            .collect();
+        let total = balances.iter().fold(BigInt::zero(), |sum, b| sum + b);
+        (
+            SolvencyWitness {
+                balances,
+                blindings,
+            },
+            SolvencyStatement {
+                commitments,
+                total,
+                bits: 16,
+            },
+        )
+    }
+
+    crate::test_for_all_curves!(test_solvency_proof);
+    fn test_solvency_proof<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize + Clone + PartialEq,
+    {
+        let (w, delta) = setup::<P>(&[1200, 0, 35_000, 65_535, 7]);
+        let proof = SolvencyProof::prove(&w, &delta);
+        assert!(proof.verify(&delta).is_ok());
+
+        // another reserve
+        let mut claimed = delta.clone();
+        claimed.total += BigInt::one();
+        assert!(proof.verify(&claimed).is_err());
+
+        // a balance left out
+        let mut claimed = delta.clone();
+        claimed.commitments.pop();
+        assert!(proof.verify(&claimed).is_err());
+
+        // a narrower range than proved
+        let mut claimed = delta;
+        claimed.bits = 8;
+        assert!(proof.verify(&claimed).is_err());
+    }
+
+    crate::test_for_all_curves!(test_solvency_proof_rejects_swapped_commitments);
+    fn test_solvency_proof_rejects_swapped_commitments<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize + Clone + PartialEq,
+    {
+        let (w, delta) = setup::<P>(&[10, 20]);
+        let proof = SolvencyProof::prove(&w, &delta);
+        let mut swapped = delta;
+        swapped.commitments.swap(0, 1);
+        assert!(proof.verify(&swapped).is_err());
+    }
+
+    crate::test_for_all_curves!(test_solvency_proof_rejects_an_excess_at_infinity);
+    fn test_solvency_proof_rejects_an_excess_at_infinity<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize + Clone + PartialEq,
+    {
+        // blindings adding up to zero, so that sum c_i - total*G is the identity
+        let r: P::Scalar = ECScalar::new_random();
+        let minus_r: P::Scalar = ECScalar::from(&(P::Scalar::q() - r.to_big_int()));
+        let balances = [BigInt::from(5), BigInt::from(6)];
+        let blindings = [r, minus_r];
+        let commitments: Vec<P> = balances
+            .iter()
+            .zip(&blindings)
+            .map(|(b, r)| commit::<P>(b, r))
+            .collect();
+        let range_proofs = commitments
+            .iter()
+            .zip(&balances)
+            .zip(&blindings)
+            .map(|((c, b), r)| RangeProof::prove(c, b, r, 16))
+            .collect();
+        let (w, delta) = setup::<P>(&[5, 6]);
+        let proof = SolvencyProof {
+            range_proofs,
+            total_proof: SolvencyProof::prove(&w, &delta).total_proof,
+        };
+        let claimed = SolvencyStatement {
+            commitments,
+            total: BigInt::from(11),
+            bits: 16,
+        };
+        assert!(proof.verify(&claimed).is_err());
+    }
+
+    crate::test_for_all_curves!(
+        #[should_panic]
+        test_solvency_proof_of_a_wrong_total
+    );
+    fn test_solvency_proof_of_a_wrong_total<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize + Clone + PartialEq,
+    {
+        let (w, mut delta) = setup::<P>(&[10, 20]);
+        delta.total = BigInt::from(31);
+        SolvencyProof::prove(&w, &delta);
+    }
+
+    crate::test_for_all_curves!(test_solvency_proof_serialized_size);
+    fn test_solvency_proof_serialized_size<P>()
+    where
+        P: ECPoint + Clone + Serialize + serde::de::DeserializeOwned,
+        P::Scalar: Zeroize + Clone + PartialEq + Serialize + serde::de::DeserializeOwned,
+    {
+        let (w, delta) = setup::<P>(&[1200, 0, 35_000]);
+        let proof = SolvencyProof::prove(&w, &delta);
+        assert_eq!(proof.serialized_size(), proof.to_bytes().len());
+        let range_proof_size = proof.range_proofs[0].serialized_size();
+        assert_eq!(
+            proof.serialized_size(),
+            4 + 3 * range_proof_size + proof.total_proof.serialized_size()
+        );
+    }
+}
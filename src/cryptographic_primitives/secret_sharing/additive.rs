@@ -0,0 +1,103 @@
+/*
+    This file is part of Curv library
+    Copyright 2018 by Kzen Networks
+    (https://github.com/KZen-networks/curv)
+    License MIT: <https://github.com/KZen-networks/curv/blob/master/LICENSE>
+*/
+
+//! n-of-n additive secret sharing: a secret x is split into shares x_1, ..., x_n with
+//! x = x_1 + ... + x_n. All n shares are needed to reconstruct the secret, and any n-1 of them
+//! are uniformly random and reveal nothing about it.
+
+use crate::elliptic::curves::traits::*;
+
+/// Splits `secret` into `n` shares summing up to it.
+///
+/// The first n-1 shares are sampled at random and the last one is the secret minus their sum.
+///
+/// ## Panics
+/// Panics if `n` is zero.
+pub fn additive_share<S>(secret: &S, n: usize) -> Vec<S>
+where
+    S: ECScalar + Clone,
+{
+    assert!(n > 0, "a secret can't be split into zero shares");
+    let mut shares: Vec<S> = (1..n).map(|_| ECScalar::new_random()).collect();
+    let last = shares
+        .iter()
+        .fold(secret.clone(), |acc, share| acc.sub(&share.get_element()));
+    shares.push(last);
+    shares
+}
+
+/// Reconstructs the secret shared with [additive_share] as the sum of all the shares.
+///
+/// ## Panics
+/// Panics if `shares` is empty.
+pub fn additive_reconstruct<S>(shares: &[S]) -> S
+where
+    S: ECScalar + Clone,
+{
+    assert!(!shares.is_empty(), "no shares to reconstruct from");
+    shares[1..]
+        .iter()
+        .fold(shares[0].clone(), |acc, share| acc + share.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_for_all_curves;
+
+    test_for_all_curves!(test_additive_sharing);
+    fn test_additive_sharing<P>()
+    where
+        P: ECPoint,
+        P::Scalar: Clone,
+    {
+        let secret: P::Scalar = ECScalar::new_random();
+        for n in 1..6 {
+            let shares = additive_share(&secret, n);
+            assert_eq!(shares.len(), n);
+            let sum = shares
+                .iter()
+                .fold(P::Scalar::zero().to_big_int(), |acc, share| {
+                    (acc + share.to_big_int()) % P::Scalar::q()
+                });
+            assert_eq!(sum, secret.to_big_int());
+            assert_eq!(
+                additive_reconstruct(&shares).to_big_int(),
+                secret.to_big_int()
+            );
+        }
+
+        // sharing twice gives different shares of the same secret
+        let shares = additive_share(&secret, 3);
+        let other_shares = additive_share(&secret, 3);
+        assert_ne!(shares[0].to_big_int(), other_shares[0].to_big_int());
+        assert_eq!(
+            additive_reconstruct(&other_shares).to_big_int(),
+            secret.to_big_int()
+        );
+    }
+
+    test_for_all_curves!(test_additive_shares_of_sum);
+    fn test_additive_shares_of_sum<P>()
+    where
+        P: ECPoint,
+        P::Scalar: Clone,
+    {
+        // adding up shares of two secrets party-wise gives shares of the sum
+        let a: P::Scalar = ECScalar::new_random();
+        let b: P::Scalar = ECScalar::new_random();
+        let shares: Vec<P::Scalar> = additive_share(&a, 4)
+            .into_iter()
+            .zip(additive_share(&b, 4))
+            .map(|(a_i, b_i)| a_i + b_i)
+            .collect();
+        assert_eq!(
+            additive_reconstruct(&shares).to_big_int(),
+            (a + b).to_big_int()
+        );
+    }
+}
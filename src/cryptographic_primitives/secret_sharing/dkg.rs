@@ -0,0 +1,173 @@
+/*
+    This file is part of Curv library
+    Copyright 2018 by Kzen Networks
+    (https://github.com/KZen-networks/curv)
+    License MIT: https://github.com/KZen-networks/curv/blob/master/LICENSE
+*/
+
+//! Auditable transcript of a Feldman VSS key generation.
+//!
+//! Every party i samples u_i, shares it with [VerifiableSS::share] and broadcasts the VSS scheme
+//! together with a proof of knowledge of u_i made by [Transcript::prove_secret]. The group public
+//! key is y = sum u_i*G. The broadcast messages form a [Transcript] that anyone can check with
+//! [Transcript::verify], without learning any share.
+//!
+//! The proof of party i is bound to i and to its whole VSS scheme, so a party can't reuse the
+//! proof of another party to claim a key it doesn't know, and no commitment of a scheme can be
+//! changed after the fact.
+
+use std::fmt::Debug;
+
+use derivative::Derivative;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use super::feldman_vss::VerifiableSS;
+use crate::cryptographic_primitives::hashing::transcript;
+use crate::cryptographic_primitives::proofs::sigma_dlog::DLogProof;
+use crate::cryptographic_primitives::proofs::ProofError;
+use crate::elliptic::curves::traits::*;
+
+const DKG_DOMAIN: &[u8] = b"curv feldman dkg";
+
+#[derive(Serialize, Deserialize, Derivative)]
+#[derivative(Clone(bound = "P: Clone, P::Scalar: Clone"))]
+#[derivative(Debug(bound = "P: Debug, P::Scalar: Debug"))]
+#[serde(bound(serialize = "P: Serialize, P::Scalar: Serialize"))]
+#[serde(bound(deserialize = "P: Deserialize<'de>, P::Scalar: Deserialize<'de>"))]
+pub struct Transcript<P: ECPoint> {
+    /// VSS scheme broadcast by each party, in the order of their indices
+    pub schemes: Vec<VerifiableSS<P>>,
+    /// Proof of knowledge of the secret shared by each party
+    pub proofs: Vec<DLogProof<P>>,
+}
+
+impl<P> Transcript<P>
+where
+    P: ECPoint + Clone,
+    P::Scalar: Zeroize + Clone,
+{
+    /// Proves knowledge of `secret`, shared by party `index` (starting from 0) with `vss`
+    pub fn prove_secret(index: usize, vss: &VerifiableSS<P>, secret: &P::Scalar) -> DLogProof<P> {
+        let session_id = Self::session_id(index, vss);
+        DLogProof::builder()
+            .witness(secret)
+            .domain(DKG_DOMAIN)
+            .session_id(&session_id)
+            .prove()
+    }
+
+    /// Checks that every party used the same parameters and proved knowledge of the secret it
+    /// shared, and returns the group public key
+    pub fn verify(&self) -> Result<P, ProofError> {
+        if self.schemes.is_empty() || self.schemes.len() != self.proofs.len() {
+            return Err(ProofError);
+        }
+        let parameters = &self.schemes[0].parameters;
+        if parameters.share_count != self.schemes.len() {
+            return Err(ProofError);
+        }
+        for (index, (vss, proof)) in self.schemes.iter().zip(&self.proofs).enumerate() {
+            if vss.parameters != *parameters
+                || vss.commitments.len() != parameters.threshold + 1
+                || proof.pk.pk_to_key_slice() != vss.commitments[0].pk_to_key_slice()
+            {
+                return Err(ProofError);
+            }
+            let session_id = Self::session_id(index, vss);
+            DLogProof::builder()
+                .domain(DKG_DOMAIN)
+                .session_id(&session_id)
+                .verify(proof)?;
+        }
+        Ok(VerifiableSS::aggregate(&self.schemes).commitments[0].clone())
+    }
+
+    fn session_id(index: usize, vss: &VerifiableSS<P>) -> [u8; transcript::DIGEST_SIZE] {
+        let mut message = Vec::new();
+        message.extend_from_slice(&(vss.parameters.threshold as u64).to_be_bytes());
+        message.extend_from_slice(&(vss.parameters.share_count as u64).to_be_bytes());
+        message.extend_from_slice(&(index as u64).to_be_bytes());
+        for commitment in &vss.commitments {
+            let bytes = commitment.pk_to_key_slice();
+            message.extend_from_slice(&(bytes.len() as u64).to_be_bytes());
+            message.extend_from_slice(&bytes);
+        }
+        transcript::Transcript::prehash(&message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_for_all_curves;
+
+    fn run_dkg<P>(t: usize, n: usize) -> (Transcript<P>, P)
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize + Clone,
+    {
+        let secrets: Vec<P::Scalar> = (0..n).map(|_| ECScalar::new_random()).collect();
+        let schemes: Vec<VerifiableSS<P>> = secrets
+            .iter()
+            .map(|u_i| VerifiableSS::share(t, n, u_i).0)
+            .collect();
+        let proofs = schemes
+            .iter()
+            .zip(&secrets)
+            .enumerate()
+            .map(|(i, (vss, u_i))| Transcript::prove_secret(i, vss, u_i))
+            .collect();
+        let y = P::generator() * VerifiableSS::<P>::aggregate_shares(&secrets);
+        (Transcript { schemes, proofs }, y)
+    }
+
+    test_for_all_curves!(test_dkg_transcript);
+    fn test_dkg_transcript<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize + Clone,
+    {
+        let (transcript, y) = run_dkg::<P>(1, 3);
+        let group_key = transcript.verify().unwrap();
+        assert_eq!(group_key.pk_to_key_slice(), y.pk_to_key_slice());
+    }
+
+    #[test]
+    fn test_dkg_transcript_serde() {
+        use crate::elliptic::curves::secp256_k1::GE;
+        let (transcript, y) = run_dkg::<GE>(2, 4);
+        // the transcript is audited from its serialization
+        let serialized = serde_json::to_string(&transcript).unwrap();
+        let deserialized: Transcript<GE> = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized.verify().unwrap(), y);
+    }
+
+    test_for_all_curves!(test_tampered_dkg_transcript);
+    fn test_tampered_dkg_transcript<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize + Clone,
+    {
+        let (transcript, _) = run_dkg::<P>(1, 3);
+        let g: P = ECPoint::generator();
+
+        let mut tampered = transcript.clone();
+        tampered.schemes[1].commitments[1] = tampered.schemes[1].commitments[1].clone() + g.clone();
+        assert!(tampered.verify().is_err());
+
+        // a party claiming the key of another party
+        let mut tampered = transcript.clone();
+        tampered.schemes[2] = tampered.schemes[0].clone();
+        tampered.proofs[2] = tampered.proofs[0].clone();
+        assert!(tampered.verify().is_err());
+
+        let mut tampered = transcript.clone();
+        tampered.proofs.pop();
+        assert!(tampered.verify().is_err());
+
+        let mut tampered = transcript;
+        tampered.schemes[0].commitments[0] = tampered.schemes[0].commitments[0].clone() + g;
+        assert!(tampered.verify().is_err());
+    }
+}
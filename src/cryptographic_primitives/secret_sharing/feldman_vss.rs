@@ -8,7 +8,9 @@
 
 use serde::{Deserialize, Serialize};
 
+use super::validate_unique_indices;
 use crate::arithmetic::traits::*;
+use crate::cryptographic_primitives::proofs::ProofError;
 use crate::elliptic::curves::traits::*;
 use crate::BigInt;
 use crate::ErrorSS::{self, VerifyShareError};
@@ -38,6 +40,28 @@ where
         self.parameters.threshold + 1
     }
 
+    /// Checks that the scheme is well formed before its shares are distributed: the threshold is
+    /// below the number of shares, there are exactly t+1 commitments, and neither the commitment
+    /// to the secret nor the one to the leading coefficient is the identity.
+    ///
+    /// An identity in the first position means that the secret is zero, and in the last one that
+    /// the polynomial has a lower degree than t, so fewer than t+1 shares reconstruct it. The
+    /// other coefficients may be zero.
+    pub fn self_check(&self) -> Result<(), ProofError> {
+        let t = self.parameters.threshold;
+        if t >= self.parameters.share_count || self.commitments.len() != t + 1 {
+            return Err(ProofError);
+        }
+        // P + P = P holds only for the identity
+        let is_identity = |point: &P| {
+            (point.clone() + point.clone()).pk_to_key_slice() == point.pk_to_key_slice()
+        };
+        if is_identity(&self.commitments[0]) || is_identity(&self.commitments[t]) {
+            return Err(ProofError);
+        }
+        Ok(())
+    }
+
     // generate VerifiableSS from a secret
     pub fn share(t: usize, n: usize, secret: &P::Scalar) -> (VerifiableSS<P>, Vec<P::Scalar>) {
         assert!(t < n);
@@ -63,7 +87,7 @@ where
 
     // takes given VSS and generates a new VSS for the same secret and a secret shares vector to match the new commitments
     pub fn reshare(&self) -> (VerifiableSS<P>, Vec<P::Scalar>) {
-        let one: P::Scalar = ECScalar::from(&BigInt::one());
+        let one: P::Scalar = ECScalar::one();
         let poly = VerifiableSS::<P>::sample_polynomial(self.parameters.threshold, &one);
         let index_vec: Vec<usize> = (1..=self.parameters.share_count).collect();
         let secret_shares_biased = VerifiableSS::<P>::evaluate_polynomial(&poly, &index_vec);
@@ -84,6 +108,44 @@ where
         )
     }
 
+    /// Sums VSS schemes dealt with the same parameters into the VSS of the sum of their secrets,
+    /// e.g. to derive a joint random value no single dealer knows. The matching share of a party
+    /// is the sum of the shares it received, see [aggregate_shares](Self::aggregate_shares).
+    ///
+    /// ## Panics
+    /// Panics if `schemes` is empty, or if the schemes don't all have the same parameters and
+    /// number of commitments.
+    pub fn aggregate(schemes: &[VerifiableSS<P>]) -> VerifiableSS<P> {
+        assert!(!schemes.is_empty(), "no VSS schemes to aggregate");
+        let parameters = schemes[0].parameters.clone();
+        assert!(
+            schemes.iter().all(|vss| vss.parameters == parameters
+                && vss.commitments.len() == schemes[0].commitments.len()),
+            "the VSS schemes were dealt with different parameters"
+        );
+        let commitments = (0..schemes[0].commitments.len())
+            .map(|i| {
+                let mut coef_iter = schemes.iter().map(|vss| &vss.commitments[i]);
+                let head = coef_iter.next().unwrap().clone();
+                coef_iter.fold(head, |acc, x| acc + x.clone())
+            })
+            .collect();
+        VerifiableSS {
+            parameters,
+            commitments,
+        }
+    }
+
+    /// Sums the shares a party received from each of the aggregated schemes, in the same order.
+    ///
+    /// ## Panics
+    /// Panics if `shares` is empty.
+    pub fn aggregate_shares(shares: &[P::Scalar]) -> P::Scalar {
+        let mut shares_iter = shares.iter();
+        let head = shares_iter.next().expect("no shares to aggregate").clone();
+        shares_iter.fold(head, |acc, x| acc + x.clone())
+    }
+
     // generate VerifiableSS from a secret and user defined x values (in case user wants to distribute point f(1), f(4), f(6) and not f(1),f(2),f(3))
     pub fn share_at_indices(
         t: usize,
@@ -121,6 +183,20 @@ where
         coefficients
     }
 
+    // compares two polynomials given by their coefficients, lowest degree first, ignoring zero
+    // coefficients of the highest degrees, so [1, 2, 0] equals [1, 2]
+    pub fn polynomials_equal(a: &[P::Scalar], b: &[P::Scalar]) -> bool {
+        let normalize = |coefficients: &[P::Scalar]| {
+            let mut coefficients: Vec<BigInt> =
+                coefficients.iter().map(|c| c.to_big_int()).collect();
+            while coefficients.last() == Some(&BigInt::zero()) {
+                coefficients.pop();
+            }
+            coefficients
+        };
+        normalize(a) == normalize(b)
+    }
+
     pub fn evaluate_polynomial(coefficients: &[P::Scalar], index_vec: &[usize]) -> Vec<P::Scalar> {
         (0..index_vec.len())
             .map(|point| {
@@ -144,9 +220,17 @@ where
         })
     }
 
+    /// ## Panics
+    /// Panics if there are fewer shares than the threshold requires, or if an index appears more
+    /// than once, see [validate_unique_indices].
     pub fn reconstruct(&self, indices: &[usize], shares: &[P::Scalar]) -> P::Scalar {
         assert_eq!(shares.len(), indices.len());
         assert!(shares.len() >= self.reconstruct_limit());
+        let indexed_shares: Vec<(usize, &P::Scalar)> =
+            indices.iter().cloned().zip(shares).collect();
+        if let Err(index) = validate_unique_indices(&indexed_shares) {
+            panic!("share index {} appears more than once", index);
+        }
         // add one to indices to get points
         let points = indices
             .iter()
@@ -178,8 +262,8 @@ where
             .map(|i| {
                 let xi = &points[i];
                 let yi = &values[i];
-                let num: P::Scalar = ECScalar::from(&BigInt::one());
-                let denum: P::Scalar = ECScalar::from(&BigInt::one());
+                let num: P::Scalar = ECScalar::one();
+                let denum: P::Scalar = ECScalar::one();
                 let num = points.iter().zip(0..vec_len).fold(num, |acc, x| {
                     if i != x.1 {
                         acc * x.0.clone()
@@ -205,6 +289,41 @@ where
         tail.fold(head.clone(), |acc, x| acc.add(&x.get_element()))
     }
 
+    // checks that `shares`, given as pairs (index, f(index)) with the same indexing as in
+    // `validate_share`, all lie on a single polynomial of degree at most `t`: every extra share is
+    // checked against the first t+1 shares. Returns false if an index is zero or appears more
+    // than once. Doesn't use the commitments, so it can be run on shares gathered from the
+    // parties before any of them is validated.
+    pub fn check_degree(shares: &[(usize, P::Scalar)], t: usize) -> bool {
+        if shares.iter().any(|(i, _)| *i == 0) || validate_unique_indices(shares).is_err() {
+            return false;
+        }
+        if shares.len() <= t + 1 {
+            return true;
+        }
+
+        // The t+2 values y_i at x_i lie on a polynomial f of degree at most t iff the polynomial
+        // of degree at most t+1 going through the x_i*y_i, x*f(x), is zero at zero. Sums are
+        // reduced as BigInt since secp256k1 scalars can't be zero.
+        let q = P::Scalar::q();
+        let (base, extra) = shares.split_at(t + 1);
+        extra.iter().all(|extra_share| {
+            let points: Vec<&(usize, P::Scalar)> =
+                base.iter().chain(std::iter::once(extra_share)).collect();
+            let indices: Vec<usize> = points.iter().map(|(i, _)| i - 1).collect();
+            let coefficients = P::Scalar::lagrange_coefficients(&indices);
+            let at_zero =
+                points
+                    .iter()
+                    .zip(&coefficients)
+                    .fold(BigInt::zero(), |acc, ((i, y), l)| {
+                        let x_y = BigInt::mod_mul(&BigInt::from(*i as u64), &y.to_big_int(), &q);
+                        BigInt::mod_add(&acc, &BigInt::mod_mul(&l.to_big_int(), &x_y, &q), &q)
+                    });
+            at_zero == BigInt::zero()
+        })
+    }
+
     pub fn validate_share(&self, secret_share: &P::Scalar, index: usize) -> Result<(), ErrorSS> {
         let G: P = ECPoint::generator();
         let ss_point = G * secret_share.clone();
@@ -220,6 +339,13 @@ where
         }
     }
 
+    // checks a public share f(index)·G broadcast by a party against the commitment evaluated at
+    // `index`, with the same indexing as in `validate_share`. Used during the complaint round,
+    // where only the public shares are available.
+    pub fn verify_public_share(&self, index: usize, public_share: &P) -> bool {
+        self.validate_share_public(public_share, index).is_ok()
+    }
+
     pub fn get_point_commitment(&self, index: usize) -> P {
         let index_fe: P::Scalar = ECScalar::from(&BigInt::from(index as u32));
         let mut comm_iterator = self.commitments.iter().rev();
@@ -248,8 +374,8 @@ where
             .collect();
 
         let xi = &points[index];
-        let num: P::Scalar = ECScalar::from(&BigInt::one());
-        let denum: P::Scalar = ECScalar::from(&BigInt::one());
+        let num: P::Scalar = ECScalar::one();
+        let denum: P::Scalar = ECScalar::one();
         let num = (0..s_len).fold(num, |acc, i| {
             if s[i] != index {
                 acc * points[s[i]].clone()
@@ -300,6 +426,26 @@ mod tests {
         assert_eq!(secret, secret_reconstructed);
     }
 
+    test_for_all_curves!(
+        #[should_panic]
+        test_reconstruct_with_duplicated_index
+    );
+
+    fn test_reconstruct_with_duplicated_index<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Clone,
+    {
+        let secret: P::Scalar = ECScalar::new_random();
+        let (vss_scheme, secret_shares) = VerifiableSS::<P>::share(2, 5, &secret);
+        let shares_vec = vec![
+            secret_shares[0].clone(),
+            secret_shares[1].clone(),
+            secret_shares[1].clone(),
+        ];
+        vss_scheme.reconstruct(&[0, 1, 1], &shares_vec);
+    }
+
     test_for_all_curves!(test_secret_sharing_3_out_of_5);
 
     fn test_secret_sharing_3_out_of_5<P>()
@@ -469,6 +615,164 @@ mod tests {
         assert_eq!(w, secret_reconstructed);
     }
 
+    test_for_all_curves!(test_corrupted_share_is_detected);
+
+    fn test_corrupted_share_is_detected<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Clone + PartialEq + std::fmt::Debug,
+    {
+        // 2 out of 3 and 3 out of 5, leaving t+1 honest shares
+        for (t, n) in [(1, 3), (2, 5)] {
+            let secret: P::Scalar = ECScalar::new_random();
+            let (vss_scheme, mut secret_shares) = VerifiableSS::<P>::share(t, n, &secret);
+            let one: P::Scalar = ECScalar::from(&BigInt::one());
+            secret_shares[1] = secret_shares[1].clone() + one;
+
+            for (i, share) in secret_shares.iter().enumerate() {
+                assert_eq!(vss_scheme.validate_share(share, i + 1).is_ok(), i != 1);
+            }
+            // the honest shares still reconstruct the secret, the corrupted one doesn't
+            let honest: Vec<usize> = (0..n).filter(|i| *i != 1).take(t + 1).collect();
+            let honest_shares: Vec<P::Scalar> =
+                honest.iter().map(|i| secret_shares[*i].clone()).collect();
+            assert_eq!(vss_scheme.reconstruct(&honest, &honest_shares), secret);
+            let with_corrupted: Vec<usize> = (0..=t).collect();
+            let with_corrupted_shares: Vec<P::Scalar> = with_corrupted
+                .iter()
+                .map(|i| secret_shares[*i].clone())
+                .collect();
+            assert_ne!(
+                vss_scheme.reconstruct(&with_corrupted, &with_corrupted_shares),
+                secret
+            );
+        }
+    }
+
+    test_for_all_curves!(test_self_check);
+
+    fn test_self_check<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Clone,
+    {
+        let secret: P::Scalar = ECScalar::new_random();
+        let (vss_scheme, _) = VerifiableSS::<P>::share(2, 5, &secret);
+        assert!(vss_scheme.self_check().is_ok());
+        let (vss_scheme_1_of_2, _) = VerifiableSS::<P>::share(1, 2, &secret);
+        assert!(vss_scheme_1_of_2.self_check().is_ok());
+
+        let mut tampered = vss_scheme.clone();
+        tampered.commitments.pop();
+        assert!(tampered.self_check().is_err());
+        let mut tampered = vss_scheme.clone();
+        tampered.commitments.push(P::generator());
+        assert!(tampered.self_check().is_err());
+        let mut tampered = vss_scheme;
+        tampered.parameters.share_count = 2;
+        assert!(tampered.self_check().is_err());
+    }
+
+    #[test]
+    fn test_self_check_rejects_identity_commitments() {
+        use crate::elliptic::curves::ed25519::{FE, GE};
+
+        let (zero_secret, _) = VerifiableSS::<GE>::share(2, 5, &FE::zero());
+        assert!(zero_secret.self_check().is_err());
+
+        let secret: FE = ECScalar::new_random();
+        let (vss_scheme, _) = VerifiableSS::<GE>::share(2, 5, &secret);
+        let identity = GE::generator() * FE::zero();
+        let mut low_degree = vss_scheme.clone();
+        low_degree.commitments[2] = identity;
+        assert!(low_degree.self_check().is_err());
+        let mut zero_middle_coefficient = vss_scheme;
+        zero_middle_coefficient.commitments[1] = identity;
+        assert!(zero_middle_coefficient.self_check().is_ok());
+    }
+
+    test_for_all_curves!(test_check_degree);
+
+    fn test_check_degree<P>()
+    where
+        P: ECPoint + Clone + std::fmt::Debug,
+        P::Scalar: Clone + PartialEq + std::fmt::Debug,
+    {
+        let secret: P::Scalar = ECScalar::new_random();
+        let (_vss_scheme, secret_shares) = VerifiableSS::<P>::share(3, 7, &secret);
+        let mut shares: Vec<(usize, P::Scalar)> = secret_shares
+            .iter()
+            .enumerate()
+            .map(|(i, share)| (i + 1, share.clone()))
+            .collect();
+
+        assert!(VerifiableSS::<P>::check_degree(&shares, 3));
+        assert!(VerifiableSS::<P>::check_degree(&shares[2..], 3));
+        // the shares lie on a polynomial of degree 3, not 2
+        assert!(!VerifiableSS::<P>::check_degree(&shares, 2));
+
+        // a single inconsistent share is caught
+        let one: P::Scalar = ECScalar::from(&BigInt::one());
+        shares[5].1 = shares[5].1.clone() + one;
+        assert!(!VerifiableSS::<P>::check_degree(&shares, 3));
+        assert!(VerifiableSS::<P>::check_degree(&shares[..5], 3));
+
+        // duplicated index
+        let duplicated = vec![shares[0].clone(), shares[0].clone()];
+        assert!(!VerifiableSS::<P>::check_degree(&duplicated, 3));
+
+        // index 0 is the secret, not a share
+        let mut with_secret = shares[..4].to_vec();
+        with_secret.push((0, secret));
+        assert!(!VerifiableSS::<P>::check_degree(&with_secret, 3));
+    }
+
+    test_for_all_curves!(test_check_degree_of_large_indices);
+    fn test_check_degree_of_large_indices<P>()
+    where
+        P: ECPoint + Clone + std::fmt::Debug,
+        P::Scalar: Clone + PartialEq + std::fmt::Debug,
+    {
+        let coefficients: Vec<P::Scalar> = (0..3).map(|_| ECScalar::new_random()).collect();
+        // indices that don't fit in a u32
+        let mut shares: Vec<(usize, P::Scalar)> = [1, 2, 1 << 33, (1 << 33) + 1]
+            .iter()
+            .map(|i: &u64| {
+                let x = ECScalar::from(&BigInt::from(*i));
+                let y = VerifiableSS::<P>::mod_evaluate_polynomial(&coefficients, x);
+                (*i as usize, y)
+            })
+            .collect();
+        assert!(VerifiableSS::<P>::check_degree(&shares, 2));
+        shares[3].0 += 1;
+        assert!(!VerifiableSS::<P>::check_degree(&shares, 2));
+    }
+
+    test_for_all_curves!(test_verify_public_share);
+
+    fn test_verify_public_share<P>()
+    where
+        P: ECPoint + Clone + std::fmt::Debug,
+        P::Scalar: Clone + PartialEq + std::fmt::Debug,
+    {
+        let secret: P::Scalar = ECScalar::new_random();
+        let (vss_scheme, secret_shares) = VerifiableSS::<P>::share(3, 5, &secret);
+        let g: P = ECPoint::generator();
+        let public_shares: Vec<P> = secret_shares
+            .iter()
+            .map(|s| g.clone() * s.clone())
+            .collect();
+
+        for (i, public_share) in public_shares.iter().enumerate() {
+            assert!(vss_scheme.verify_public_share(i + 1, public_share));
+        }
+        // the public share of another party
+        assert!(!vss_scheme.verify_public_share(1, &public_shares[1]));
+        // a falsified public share
+        let falsified = public_shares[0].clone() + g;
+        assert!(!vss_scheme.verify_public_share(1, &falsified));
+    }
+
     test_for_all_curves!(test_secret_resharing);
 
     fn test_secret_resharing<P>()
@@ -500,4 +804,103 @@ mod tests {
         assert!(valid2.is_ok());
         assert!(valid3.is_ok());
     }
+
+    test_for_all_curves!(test_aggregate);
+
+    fn test_aggregate<P>()
+    where
+        P: ECPoint + Clone + std::fmt::Debug,
+        P::Scalar: Clone + PartialEq + std::fmt::Debug,
+    {
+        let secrets: Vec<P::Scalar> = (0..3).map(|_| ECScalar::new_random()).collect();
+        let (schemes, dealt_shares): (Vec<_>, Vec<_>) = secrets
+            .iter()
+            .map(|secret| VerifiableSS::<P>::share(2, 5, secret))
+            .unzip();
+
+        let joint_vss = VerifiableSS::aggregate(&schemes);
+        let joint_shares: Vec<P::Scalar> = (0..5)
+            .map(|i| {
+                let received: Vec<_> = dealt_shares.iter().map(|s| s[i].clone()).collect();
+                VerifiableSS::<P>::aggregate_shares(&received)
+            })
+            .collect();
+        for (i, share) in joint_shares.iter().enumerate() {
+            assert!(joint_vss.validate_share(share, i + 1).is_ok());
+        }
+
+        let joint_secret = VerifiableSS::<P>::aggregate_shares(&secrets);
+        let reconstructed = joint_vss.reconstruct(
+            &[0, 2, 4],
+            &[
+                joint_shares[0].clone(),
+                joint_shares[2].clone(),
+                joint_shares[4].clone(),
+            ],
+        );
+        assert_eq!(reconstructed, joint_secret);
+        assert!(joint_vss.commitments[0] == P::generator() * joint_secret);
+    }
+
+    test_for_all_curves!(
+        [#[should_panic(expected = "different parameters")]]
+        test_aggregate_with_different_parameters
+    );
+
+    fn test_aggregate_with_different_parameters<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Clone,
+    {
+        let secret: P::Scalar = ECScalar::new_random();
+        let (two_of_five, _) = VerifiableSS::<P>::share(2, 5, &secret);
+        let (two_of_four, _) = VerifiableSS::<P>::share(2, 4, &secret);
+        VerifiableSS::aggregate(&[two_of_five, two_of_four]);
+    }
+
+    test_for_all_curves!(
+        [#[should_panic(expected = "no shares")]]
+        test_aggregate_of_no_shares
+    );
+
+    fn test_aggregate_of_no_shares<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Clone,
+    {
+        VerifiableSS::<P>::aggregate_shares(&[]);
+    }
+
+    test_for_all_curves!(test_polynomials_equal);
+
+    fn test_polynomials_equal<P>()
+    where
+        P: ECPoint + Clone + std::fmt::Debug,
+        P::Scalar: Clone + PartialEq + std::fmt::Debug,
+    {
+        let coef = |n: u32| -> P::Scalar { ECScalar::from(&BigInt::from(n)) };
+        let zero: P::Scalar = ECScalar::zero();
+        let poly = vec![coef(1), coef(2)];
+        let padded = vec![coef(1), coef(2), zero.clone(), zero.clone()];
+        assert!(VerifiableSS::<P>::polynomials_equal(&poly, &padded));
+        assert!(VerifiableSS::<P>::polynomials_equal(&padded, &poly));
+        assert!(VerifiableSS::<P>::polynomials_equal(
+            std::slice::from_ref(&zero),
+            &[]
+        ));
+
+        assert!(!VerifiableSS::<P>::polynomials_equal(
+            &poly,
+            &[coef(1), coef(3)]
+        ));
+        assert!(!VerifiableSS::<P>::polynomials_equal(
+            &poly,
+            &[coef(1), coef(2), coef(1)]
+        ));
+        // a zero constant term isn't stripped
+        assert!(!VerifiableSS::<P>::polynomials_equal(
+            &poly,
+            &[zero, coef(1), coef(2)]
+        ));
+    }
 }
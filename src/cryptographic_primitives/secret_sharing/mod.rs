@@ -5,4 +5,110 @@
     License MIT: https://github.com/KZen-networks/curv/blob/master/LICENSE
 */
 
+pub mod additive;
+pub mod dkg;
 pub mod feldman_vss;
+pub mod pvss;
+
+use std::collections::HashSet;
+
+use crate::elliptic::curves::traits::ECScalar;
+use crate::BigInt;
+
+/// Checks that no two shares, given as pairs (index, share), have the same index. Returns the
+/// first index found a second time.
+///
+/// Shares gathered from several parties with a repeated index are either a mistake or an attack:
+/// interpolating over a repeated point divides by zero, so it must be rejected before any
+/// reconstruction.
+pub fn validate_unique_indices<S>(shares: &[(usize, S)]) -> Result<(), usize> {
+    let mut seen = HashSet::with_capacity(shares.len());
+    for (index, _) in shares {
+        if !seen.insert(*index) {
+            return Err(*index);
+        }
+    }
+    Ok(())
+}
+
+/// Computes the Lagrange coefficient `l_i(0)` of the party of index `index` among the parties of
+/// `indices`, such that `sum l_i(0)*f(x_i) = f(0)` over `indices` for any polynomial f of degree
+/// smaller than the number of parties. Returns the first index found a second time in `indices`.
+///
+/// Indices are counted from zero as everywhere in this crate: the party of index `i` holds the
+/// share `f(i+1)`, as returned by
+/// [VerifiableSS::share](feldman_vss::VerifiableSS::share). Use
+/// [ECScalar::lagrange_coefficients] to get the coefficients of all the parties at once.
+///
+/// ## Panics
+/// Panics if `index` isn't one of `indices`.
+pub fn lagrange_coefficient<S>(index: u16, indices: &[u16]) -> Result<S, u16>
+where
+    S: ECScalar + Clone,
+{
+    let indexed: Vec<(usize, ())> = indices.iter().map(|i| (*i as usize, ())).collect();
+    validate_unique_indices(&indexed).map_err(|i| i as u16)?;
+    assert!(indices.contains(&index), "index isn't one of indices");
+    // l_i(0) = prod_{j != i} x_j / (x_j - x_i)
+    let point = |i: u16| -> S { ECScalar::from(&BigInt::from(u64::from(i) + 1)) };
+    let x_i = point(index);
+    let one: S = ECScalar::one();
+    let (numerator, denominator) =
+        indices
+            .iter()
+            .filter(|j| **j != index)
+            .fold((one.clone(), one), |(num, den), j| {
+                let x_j = point(*j);
+                (num * x_j.clone(), den * x_j.sub(&x_i.get_element()))
+            });
+    Ok(numerator * denominator.invert())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elliptic::curves::traits::ECPoint;
+    use crate::test_for_all_curves;
+
+    #[test]
+    fn test_validate_unique_indices() {
+        assert_eq!(validate_unique_indices::<()>(&[]), Ok(()));
+        assert_eq!(
+            validate_unique_indices(&[(1, 'a'), (2, 'b'), (3, 'c')]),
+            Ok(())
+        );
+        assert_eq!(
+            validate_unique_indices(&[(1, 'a'), (3, 'b'), (2, 'c'), (3, 'd'), (1, 'e')]),
+            Err(3)
+        );
+    }
+
+    test_for_all_curves!(test_lagrange_coefficient);
+    fn test_lagrange_coefficient<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Clone,
+    {
+        let indices = [0u16, 2, 3, 5, 8];
+        let wide: Vec<usize> = indices.iter().map(|i| *i as usize).collect();
+        let coefficients = P::Scalar::lagrange_coefficients(&wide);
+        for (index, expected) in indices.iter().zip(&coefficients) {
+            let coefficient: P::Scalar = lagrange_coefficient(*index, &indices).unwrap();
+            assert_eq!(coefficient.to_big_int(), expected.to_big_int());
+        }
+        let alone: P::Scalar = lagrange_coefficient(7, &[7]).unwrap();
+        assert_eq!(alone.to_big_int(), BigInt::from(1));
+
+        assert_eq!(
+            lagrange_coefficient::<P::Scalar>(0, &[0, 2, 3, 2]).err(),
+            Some(2)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_lagrange_coefficient_of_an_absent_index() {
+        use crate::elliptic::curves::secp256_k1::FE;
+        let _: Result<FE, u16> = lagrange_coefficient(1, &[0, 2]);
+    }
+}
@@ -0,0 +1,218 @@
+/*
+    This file is part of Curv library
+    Copyright 2018 by Kzen Networks
+    (https://github.com/KZen-networks/curv)
+    License MIT: https://github.com/KZen-networks/curv/blob/master/LICENSE
+*/
+
+//! Publicly verifiable secret sharing, the elliptic curve form of
+//! Berry Schoenmakers. A simple publicly verifiable secret sharing scheme and its application to
+//! electronic voting. In CRYPTO 1999, volume 1666 of Lecture Notes in Computer Science.
+//!
+//! The dealer shares the secret s with [VerifiableSS::share], s_i = f(i+1) going to the party
+//! of index i with public key PK_i = x_i*G. The share is encrypted as Y_i = s_i*PK_i, next to
+//! a proof that log_G(X_i) = log_PK_i(Y_i), X_i = s_i*G being computed from the commitments of
+//! the scheme. Anyone can check every encrypted share with [PvssOutput::verify], without
+//! learning anything about the shares.
+//!
+//! The party of index i decrypts S_i = x_i^-1*Y_i = s_i*G with [decrypt_share]: the scheme
+//! shares s*G, which t+1 decrypted shares reconstruct with [PvssOutput::reconstruct], and not s
+//! itself.
+
+use std::fmt::Debug;
+
+use derivative::Derivative;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use super::feldman_vss::VerifiableSS;
+use super::validate_unique_indices;
+use crate::cryptographic_primitives::proofs::sigma_ec_ddh::{
+    ECDDHProof, ECDDHStatement, ECDDHWitness,
+};
+use crate::elliptic::curves::traits::*;
+
+#[derive(Serialize, Deserialize, Derivative)]
+#[derivative(Clone(bound = "P: Clone, P::Scalar: Clone"))]
+#[derivative(Debug(bound = "P: Debug, P::Scalar: Debug"))]
+#[serde(bound(serialize = "P: Serialize, P::Scalar: Serialize"))]
+#[serde(bound(deserialize = "P: Deserialize<'de>, P::Scalar: Deserialize<'de>"))]
+pub struct PvssOutput<P: ECPoint> {
+    pub vss: VerifiableSS<P>,
+    /// Public keys of the recipients, in the order of their indices
+    pub pubkeys: Vec<P>,
+    /// Y_i = s_i*PK_i
+    pub encrypted_shares: Vec<P>,
+    /// Proofs that log_G(X_i) = log_PK_i(Y_i)
+    pub proofs: Vec<ECDDHProof<P>>,
+}
+
+/// Shares `secret` among the owners of `pubkeys`, any `t`+1 of them being able to reconstruct
+/// `secret`*G.
+///
+/// ## Panics
+/// Panics if `t` isn't smaller than the number of public keys.
+pub fn distribute<P>(secret: &P::Scalar, pubkeys: &[P], t: usize) -> PvssOutput<P>
+where
+    P: ECPoint + Clone,
+    P::Scalar: Zeroize + Clone,
+{
+    let (vss, mut shares) = VerifiableSS::<P>::share(t, pubkeys.len(), secret);
+    let g = P::generator();
+    let (encrypted_shares, proofs) = shares
+        .iter()
+        .zip(pubkeys)
+        .map(|(s_i, pk_i)| {
+            let y_i = pk_i.scalar_mul(&s_i.get_element());
+            let delta = ECDDHStatement {
+                g1: g.clone(),
+                h1: P::generator_mul(s_i),
+                g2: pk_i.clone(),
+                h2: y_i.clone(),
+            };
+            let w = ECDDHWitness { x: s_i.clone() };
+            (y_i, ECDDHProof::prove(&w, &delta))
+        })
+        .unzip();
+    shares.iter_mut().for_each(|s_i| s_i.zeroize());
+    PvssOutput {
+        vss,
+        pubkeys: pubkeys.to_vec(),
+        encrypted_shares,
+        proofs,
+    }
+}
+
+/// Decrypts the share s_i*G of the owner of `sk` from `output`. Returns `None` if `sk*G`
+/// isn't one of the public keys of `output`.
+pub fn decrypt_share<P>(sk: &P::Scalar, output: &PvssOutput<P>) -> Option<P>
+where
+    P: ECPoint + Clone,
+    P::Scalar: Clone,
+{
+    let pk = P::generator_mul(sk).pk_to_key_slice();
+    let index = output
+        .pubkeys
+        .iter()
+        .position(|pk_i| pk_i.pk_to_key_slice() == pk)?;
+    let y_i = output.encrypted_shares.get(index)?;
+    Some(y_i.scalar_mul(&sk.invert().get_element()))
+}
+
+impl<P> PvssOutput<P>
+where
+    P: ECPoint + Clone,
+    P::Scalar: Zeroize + Clone,
+{
+    /// Checks that the scheme is well formed and that every encrypted share is the encryption
+    /// of the share committed in the scheme under the public key of its recipient
+    pub fn verify(&self) -> bool {
+        let n = self.pubkeys.len();
+        if self.vss.parameters.share_count != n
+            || self.encrypted_shares.len() != n
+            || self.proofs.len() != n
+            || self.vss.self_check().is_err()
+        {
+            return false;
+        }
+        let g = P::generator();
+        self.pubkeys
+            .iter()
+            .zip(&self.encrypted_shares)
+            .zip(&self.proofs)
+            .enumerate()
+            .all(|(i, ((pk_i, y_i), proof))| {
+                let delta = ECDDHStatement {
+                    g1: g.clone(),
+                    h1: self.vss.get_point_commitment(i + 1),
+                    g2: pk_i.clone(),
+                    h2: y_i.clone(),
+                };
+                proof.verify(&delta).is_ok()
+            })
+    }
+
+    /// Reconstructs `secret`*G from the decrypted shares of the parties of indices `indices`,
+    /// counted from zero.
+    ///
+    /// ## Panics
+    /// Panics if there are fewer than t+1 shares, if the slices differ in length, if an index
+    /// appears more than once, or if the secret is zero on a curve that can't represent the
+    /// identity.
+    pub fn reconstruct(&self, indices: &[usize], decrypted_shares: &[P]) -> P {
+        assert_eq!(indices.len(), decrypted_shares.len());
+        assert!(indices.len() >= self.vss.reconstruct_limit());
+        let indexed_shares: Vec<(usize, &P)> =
+            indices.iter().cloned().zip(decrypted_shares).collect();
+        if let Err(index) = validate_unique_indices(&indexed_shares) {
+            panic!("share index {} appears more than once", index);
+        }
+        let coefficients = P::Scalar::lagrange_coefficients(indices);
+        P::multi_scalar_mul(&coefficients, decrypted_shares)
+            .expect("the secret is zero, and its point can't be represented")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_for_all_curves;
+
+    test_for_all_curves!(test_pvss_2_of_3);
+    fn test_pvss_2_of_3<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize + Clone,
+    {
+        let sks: Vec<P::Scalar> = (0..3).map(|_| ECScalar::new_random()).collect();
+        let pubkeys: Vec<P> = sks.iter().map(|sk| P::generator_mul(sk)).collect();
+        let secret: P::Scalar = ECScalar::new_random();
+        let output = distribute(&secret, &pubkeys, 1);
+        assert!(output.verify());
+
+        let shares: Vec<P> = sks
+            .iter()
+            .map(|sk| decrypt_share(sk, &output).unwrap())
+            .collect();
+        for (i, share) in shares.iter().enumerate() {
+            assert!(output.vss.validate_share_public(share, i + 1).is_ok());
+        }
+        let secret_point = P::generator_mul(&secret).pk_to_key_slice();
+        for indices in [[0, 1], [0, 2], [2, 1]].iter() {
+            let subset: Vec<P> = indices.iter().map(|i| shares[*i].clone()).collect();
+            let reconstructed = output.reconstruct(indices, &subset);
+            assert_eq!(reconstructed.pk_to_key_slice(), secret_point);
+        }
+
+        let outsider: P::Scalar = ECScalar::new_random();
+        assert!(decrypt_share(&outsider, &output).is_none());
+    }
+
+    test_for_all_curves!(test_pvss_rejects_a_wrong_encrypted_share);
+    fn test_pvss_rejects_a_wrong_encrypted_share<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Zeroize + Clone,
+    {
+        let pubkeys: Vec<P> = (0..3)
+            .map(|_| P::generator_mul(&ECScalar::new_random()))
+            .collect();
+        let output = distribute(&ECScalar::new_random(), &pubkeys, 1);
+
+        // a share encrypted to another recipient
+        let mut forged = output.clone();
+        forged.encrypted_shares.swap(0, 1);
+        assert!(!forged.verify());
+
+        // a recipient left out
+        let mut forged = output.clone();
+        forged.pubkeys.pop();
+        assert!(!forged.verify());
+
+        // the shares of another scheme
+        let other = distribute(&ECScalar::new_random(), &pubkeys, 1);
+        let mut forged = output;
+        forged.vss = other.vss;
+        assert!(!forged.verify());
+    }
+}
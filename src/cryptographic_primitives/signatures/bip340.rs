@@ -0,0 +1,327 @@
+/*
+    This file is part of Curv library
+    Copyright 2018 by Kzen Networks
+    (https://github.com/KZen-networks/curv)
+    License MIT: https://github.com/KZen-networks/curv/blob/master/LICENSE
+*/
+
+//! Schnorr signatures over secp256k1 as specified by BIP-340
+//! (https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki).
+//!
+//! Unlike the generic [schnorr](super::schnorr) signatures, public keys and nonce commitments
+//! are x-only: a point is encoded by its 32 bytes x-coordinate and stands for the point with
+//! that x-coordinate and an even y. Signers negate their secret key or nonce when needed, so
+//! that the point they commit to has an even y. Hashes are tagged by the name of their use,
+//! H_tag(x) = SHA256(SHA256(tag) || SHA256(tag) || x).
+
+use sha2::{Digest, Sha256};
+use zeroize::Zeroize;
+
+use super::schnorr::Error;
+use crate::arithmetic::traits::*;
+use crate::elliptic::curves::secp256_k1::{FE, GE};
+use crate::elliptic::curves::traits::*;
+use crate::BigInt;
+use crate::ErrorKey;
+
+pub const SIGNATURE_SIZE: usize = 64;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Signature {
+    /// x-coordinate of the nonce commitment R
+    pub r: [u8; 32],
+    pub s: FE,
+}
+
+impl Signature {
+    /// Encodes the signature as x(R) || s, s being big-endian
+    pub fn to_bytes(&self) -> [u8; SIGNATURE_SIZE] {
+        let mut bytes = [0u8; SIGNATURE_SIZE];
+        bytes[..32].copy_from_slice(&self.r);
+        bytes[32..].copy_from_slice(&bytes32(&self.s.to_big_int()));
+        bytes
+    }
+
+    /// Decodes x(R) || s. s must be smaller than the group order, x(R) is only checked by
+    /// [verify].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Signature, ErrorKey> {
+        if bytes.len() != SIGNATURE_SIZE {
+            return Err(ErrorKey::InvalidPublicKey);
+        }
+        let mut r = [0u8; 32];
+        r.copy_from_slice(&bytes[..32]);
+        let s = FE::from_bytes_exact(&bytes[32..]).ok_or(ErrorKey::InvalidPublicKey)?;
+        Ok(Signature { r, s })
+    }
+}
+
+/// Returns the x-only public key of `sk`
+pub fn public_key(sk: &FE) -> [u8; 32] {
+    x_only(&GE::scalar_mul_base_ct(sk))
+}
+
+/// Decodes an x-only public key to the point with an even y it stands for. Fails if `x` isn't
+/// the x-coordinate of a point of the curve.
+pub fn lift_x(x: &[u8; 32]) -> Result<GE, ErrorKey> {
+    let mut compressed = [0u8; 33];
+    compressed[0] = 2;
+    compressed[1..].copy_from_slice(x);
+    GE::deserialize_canonical(&compressed)
+}
+
+/// Signs `message` with `sk`. `aux_rand` should be 32 fresh random bytes: it is mixed into the
+/// nonce to protect against side channels, but signing stays secure if it is constant.
+pub fn sign(sk: &FE, message: &[u8], aux_rand: &[u8; 32]) -> Signature {
+    let q = FE::q();
+    let pk = GE::scalar_mul_base_ct(sk);
+    let pk_x = x_only(&pk);
+    let mut d = even_y_secret(sk, &pk);
+
+    let mut t = bytes32(&d.to_big_int());
+    let aux_hash = tagged_hash(b"BIP0340/aux", &[aux_rand]);
+    t.iter_mut().zip(&aux_hash).for_each(|(t, a)| *t ^= a);
+    let nonce_hash = tagged_hash(b"BIP0340/nonce", &[&t, &pk_x, message]);
+    t.zeroize();
+    let mut k_prime = BigInt::from_bytes(&nonce_hash).modulus(&q);
+    assert!(
+        k_prime != BigInt::zero(),
+        "the nonce is zero with negligible probability"
+    );
+    let k_prime_fe: FE = ECScalar::from(&k_prime);
+    k_prime.zeroize();
+    let big_r = GE::scalar_mul_base_ct(&k_prime_fe);
+    let mut k = even_y_secret(&k_prime_fe, &big_r);
+    let r = x_only(&big_r);
+
+    let e = challenge(&r, &pk_x, message);
+    let s = k + e * d;
+    k.zeroize();
+    d.zeroize();
+    Signature { r, s }
+}
+
+/// Verifies a signature on `message` under the x-only public key `pk`. A signature with s = 0,
+/// which signers only produce with negligible probability, is rejected.
+pub fn verify(pk: &[u8; 32], message: &[u8], sig: &Signature) -> Result<(), Error> {
+    let point = lift_x(pk).map_err(|_| Error::InvalidPublicKey)?;
+    if sig.s.to_big_int() == BigInt::zero() {
+        return Err(Error::InvalidSignature);
+    }
+    let e = challenge(&sig.r, pk, message);
+    let minus_e: FE = ECScalar::from(&(FE::q() - e.to_big_int()));
+    // R = sG - eP, the sum failing if R is the point at infinity
+    let sg = GE::scalar_mul_base_ct(&sig.s);
+    let minus_ep = point.scalar_mul(&minus_e.get_element());
+    match sg.get_element().combine(&minus_ep.get_element()) {
+        Ok(big_r) => {
+            let compressed = big_r.serialize();
+            if compressed[0] == 2 && compressed[1..] == sig.r[..] {
+                Ok(())
+            } else {
+                Err(Error::InvalidSignature)
+            }
+        }
+        Err(_) => Err(Error::InvalidSignature),
+    }
+}
+
+/// Computes e = H_BIP0340/challenge(x(R) || x(P) || m) reduced modulo the group order
+fn challenge(r: &[u8; 32], pk: &[u8; 32], message: &[u8]) -> FE {
+    let hash = tagged_hash(b"BIP0340/challenge", &[r, pk, message]);
+    let e = BigInt::from_bytes(&hash).modulus(&FE::q());
+    ECScalar::from(&e)
+}
+
+/// Returns `secret` if `point` = `secret`*G has an even y, its negation otherwise
+fn even_y_secret(secret: &FE, point: &GE) -> FE {
+    if point.y_coor().unwrap().test_bit(0) {
+        ECScalar::from(&(FE::q() - secret.to_big_int()))
+    } else {
+        *secret
+    }
+}
+
+fn x_only(point: &GE) -> [u8; 32] {
+    bytes32(&point.x_coor().unwrap())
+}
+
+fn tagged_hash(tag: &[u8], data: &[&[u8]]) -> [u8; 32] {
+    let tag_hash = Sha256::digest(tag);
+    let mut hasher = Sha256::new().chain(tag_hash).chain(tag_hash);
+    for d in data {
+        hasher = hasher.chain(d);
+    }
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(&hasher.result());
+    hash
+}
+
+fn bytes32(n: &BigInt) -> [u8; 32] {
+    let bytes = BigInt::to_bytes(n);
+    let mut padded = [0u8; 32];
+    padded[32 - bytes.len()..].copy_from_slice(&bytes);
+    padded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode<const N: usize>(s: &str) -> [u8; N] {
+        let mut bytes = [0u8; N];
+        bytes.copy_from_slice(&hex::decode(s).unwrap());
+        bytes
+    }
+
+    // test vectors 0 and 1 of BIP-340
+    #[test]
+    fn test_bip340_vectors() {
+        let vectors = [
+            (
+                "0000000000000000000000000000000000000000000000000000000000000003",
+                "F9308A019258C31049344F85F89D5229B531C845836F99B08601F113BCE036F9",
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "E907831F80848D1069A5371B402410364BDF1C5F8307B0084C55F1CE2DCA8215\
+                 25F66A4A85EA8B71E482A74F382D2CE5EBEEE8FDB2172F477DF4900D310536C0",
+            ),
+            (
+                "B7E151628AED2A6ABF7158809CF4F3C762E7160F38B4DA56A784D9045190CFEF",
+                "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659",
+                "0000000000000000000000000000000000000000000000000000000000000001",
+                "243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89",
+                "6896BD60EEAE296DB48A229FF71DFE071BDE413E6D43F917DC8DCF8C78DE3341\
+                 8906D11AC976ABCCB20B091292BFF4EA897EFCB639EA871CFA95F6DE339E4B0A",
+            ),
+        ];
+        for (sk, pk, aux_rand, message, sig) in vectors.iter() {
+            let sk: FE = ECScalar::from(&BigInt::from_hex(sk).unwrap());
+            let pk: [u8; 32] = decode(pk);
+            assert_eq!(public_key(&sk), pk);
+            let message: [u8; 32] = decode(message);
+            let signature = sign(&sk, &message, &decode(aux_rand));
+            assert_eq!(signature.to_bytes().to_vec(), hex::decode(sig).unwrap());
+            assert!(verify(&pk, &message, &signature).is_ok());
+        }
+    }
+
+    // test vectors 5 to 14 of BIP-340, none of which verifies
+    #[test]
+    fn test_bip340_failing_vectors() {
+        let pk = "DFF1D77F2A671C5F36183726DB2341BE58FEAE1DA2DECED843240F7B502BA659";
+        let vectors = [
+            // public key not on the curve
+            (
+                "EEFDEA4CDB677750A420FEE807EACF21EB9898AE79B9768766E4FAA04A2D4A34",
+                "6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E177769\
+                 69E89B4C5564D00349106B8497785DD7D1D713A8AE82B32FA79D5F7FC407D39B",
+            ),
+            // R has an odd y
+            (
+                pk,
+                "FFF97BD5755EEEA420453A14355235D382F6472F8568A18B2F057A1460297556\
+                 3CC27944640AC607CD107AE10923D9EF7A73C643E166BE5EBEAFA34B1AC553E2",
+            ),
+            // negated message
+            (
+                pk,
+                "1FA62E331EDBC21C394792D2AB1100A7B432B013DF3F6FF4F99FCB33E0E1515F\
+                 28890B3EDB6E7189B630448B515CE4F8622A954CFE545735AAEA5134FCCDB2BD",
+            ),
+            // negated s
+            (
+                pk,
+                "6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E177769\
+                 961764B3AA9B2FFCB6EF947B6887A226E8D7C93E00C5ED0C1834FF0D0C2E6DA6",
+            ),
+            // sG - eP is the point at infinity, with x(R) = 0
+            (
+                pk,
+                "0000000000000000000000000000000000000000000000000000000000000000\
+                 123DDA8328AF9C23A94C1FEECFD123BA4FB73476F0D594DCB65C6425BD186051",
+            ),
+            // sG - eP is the point at infinity, with x(R) = 1
+            (
+                pk,
+                "0000000000000000000000000000000000000000000000000000000000000001\
+                 7615FBAF5AE28864013C099742DEADB4DBA87F11AC6754F93780D5A1837CF197",
+            ),
+            // x(R) isn't the x-coordinate of a point of the curve
+            (
+                pk,
+                "4A298DACAE57395A15D0795DDBFD1DCB564DA82B0F269BC70A74F8220429BA1D\
+                 69E89B4C5564D00349106B8497785DD7D1D713A8AE82B32FA79D5F7FC407D39B",
+            ),
+            // x(R) is the field size
+            (
+                pk,
+                "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC2F\
+                 69E89B4C5564D00349106B8497785DD7D1D713A8AE82B32FA79D5F7FC407D39B",
+            ),
+            // s is the group order
+            (
+                pk,
+                "6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E177769\
+                 FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141",
+            ),
+            // the public key exceeds the field size
+            (
+                "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC30",
+                "6CFF5C3BA86C69EA4B7376F31A9BCB4F74C1976089B2D9963DA2E5543E177769\
+                 69E89B4C5564D00349106B8497785DD7D1D713A8AE82B32FA79D5F7FC407D39B",
+            ),
+        ];
+        let message: [u8; 32] =
+            decode("243F6A8885A308D313198A2E03707344A4093822299F31D0082EFA98EC4E6C89");
+        for (pk, sig) in vectors.iter() {
+            let pk: [u8; 32] = decode(pk);
+            let sig: [u8; SIGNATURE_SIZE] = decode(sig);
+            // only an s out of range is rejected by decoding
+            let verified =
+                Signature::from_bytes(&sig).map(|sig| verify(&pk, &message, &sig).is_ok());
+            assert_ne!(verified, Ok(true));
+        }
+    }
+
+    #[test]
+    fn test_bip340_signature() {
+        let sk: FE = ECScalar::new_random();
+        let pk = public_key(&sk);
+        let sig = sign(&sk, b"message", &[7u8; 32]);
+        assert!(verify(&pk, b"message", &sig).is_ok());
+        assert_eq!(
+            verify(&pk, b"another message", &sig),
+            Err(Error::InvalidSignature)
+        );
+        assert!(verify(&public_key(&FE::new_random()), b"message", &sig).is_err());
+
+        let decoded = Signature::from_bytes(&sig.to_bytes()).unwrap();
+        assert_eq!(decoded, sig);
+
+        let mut forged = sig;
+        forged.r[31] ^= 1;
+        assert!(verify(&pk, b"message", &forged).is_err());
+        let forged = Signature {
+            r: sig.r,
+            s: sig.s + FE::new_random(),
+        };
+        assert!(verify(&pk, b"message", &forged).is_err());
+    }
+
+    #[test]
+    fn test_bip340_rejects_malformed_input() {
+        let sk: FE = ECScalar::new_random();
+        let sig = sign(&sk, b"message", &[0u8; 32]);
+        // no point of the curve has x = 5
+        assert!(lift_x(&bytes32(&BigInt::from(5))).is_err());
+        assert_eq!(
+            verify(&bytes32(&BigInt::from(5)), b"message", &sig),
+            Err(Error::InvalidPublicKey)
+        );
+
+        let mut bytes = sig.to_bytes();
+        bytes[32..].copy_from_slice(&bytes32(&FE::q()));
+        assert!(Signature::from_bytes(&bytes).is_err());
+        assert!(Signature::from_bytes(&bytes[..63]).is_err());
+    }
+}
@@ -0,0 +1,205 @@
+/*
+    This file is part of Curv library
+    Copyright 2018 by Kzen Networks
+    (https://github.com/KZen-networks/curv)
+    License MIT: https://github.com/KZen-networks/curv/blob/master/LICENSE
+*/
+
+//! Ed25519 signatures as specified by RFC 8032 (https://tools.ietf.org/html/rfc8032).
+//!
+//! A signature (R, S) on M under the public key A is accepted if [S]B = R + [k]A with
+//! k = SHA-512(R || A || M). RFC 8032 allows verifiers to check the cofactored equation
+//! [8][S]B = [8]R + [8][k]A instead, and the two disagree on signatures whose R or A has a small
+//! order component. Verifiers of a protocol must all use the same equation, otherwise a signature
+//! accepted by one party can be rejected by another:
+//! * [verify_cofactorless] checks [S]B = R + [k]A, like libsodium and ed25519-dalek's `verify`.
+//!   This is what [verify] does.
+//! * [verify_cofactored] checks [8][S]B = [8]R + [8][k]A, which also accepts the signatures
+//!   accepted by batch verification.
+
+use sha2::{Digest, Sha512};
+use zeroize::Zeroize;
+
+use crate::arithmetic::traits::*;
+use crate::elliptic::curves::ed25519::{FE, GE};
+use crate::elliptic::curves::traits::*;
+use crate::BigInt;
+use crate::ErrorKey;
+
+pub const SIGNATURE_SIZE: usize = 64;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Signature {
+    pub r: GE,
+    pub s: FE,
+}
+
+impl Signature {
+    /// Encodes the signature as R || S, S being little-endian
+    pub fn to_bytes(&self) -> [u8; SIGNATURE_SIZE] {
+        let mut bytes = [0u8; SIGNATURE_SIZE];
+        bytes[..32].copy_from_slice(&self.r.pk_to_key_slice());
+        bytes[32..].copy_from_slice(&scalar_to_bytes(&self.s));
+        bytes
+    }
+
+    /// Decodes R || S. R may have a small order component, but S must be smaller than the group
+    /// order.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Signature, ErrorKey> {
+        if bytes.len() != SIGNATURE_SIZE {
+            return Err(ErrorKey::InvalidPublicKey);
+        }
+        let r = GE::from_bytes_with_torsion(&bytes[..32])?;
+        let s = FE::from_bytes_exact(&bytes[32..]).ok_or(ErrorKey::InvalidPublicKey)?;
+        Ok(Signature { r, s })
+    }
+}
+
+/// Returns the public key of the 32 bytes secret key `seed`
+pub fn public_key(seed: &[u8; 32]) -> GE {
+    let (mut a, mut prefix) = expand_seed(seed);
+    let pk = GE::scalar_mul_base_ct(&a);
+    a.zeroize();
+    prefix.zeroize();
+    pk
+}
+
+pub fn sign(seed: &[u8; 32], message: &[u8]) -> Signature {
+    let (mut a, mut prefix) = expand_seed(seed);
+    let pk = GE::scalar_mul_base_ct(&a);
+    let mut r = hash_to_scalar(&[&prefix, message]);
+    let big_r = GE::scalar_mul_base_ct(&r);
+    let k = challenge(&big_r, &pk, message);
+    let s = r + k * a;
+    a.zeroize();
+    prefix.zeroize();
+    r.zeroize();
+    Signature { r: big_r, s }
+}
+
+/// Verifies the signature with the cofactorless equation, see [verify_cofactorless]
+pub fn verify(pk: &GE, message: &[u8], sig: &Signature) -> bool {
+    verify_cofactorless(pk, message, sig)
+}
+
+/// Checks [S]B = R + [k]A
+pub fn verify_cofactorless(pk: &GE, message: &[u8], sig: &Signature) -> bool {
+    let k = challenge(&sig.r, pk, message);
+    let lhs = GE::generator() * sig.s;
+    let rhs = sig.r + *pk * k;
+    lhs == rhs
+}
+
+/// Checks [8][S]B = [8]R + [8][k]A
+pub fn verify_cofactored(pk: &GE, message: &[u8], sig: &Signature) -> bool {
+    let k = challenge(&sig.r, pk, message);
+    let eight: FE = ECScalar::from(&BigInt::from(8));
+    let lhs = GE::generator() * sig.s * eight;
+    let rhs = (sig.r + *pk * k) * eight;
+    lhs == rhs
+}
+
+/// Computes k = SHA-512(R || A || M) reduced modulo the group order
+fn challenge(r: &GE, pk: &GE, message: &[u8]) -> FE {
+    hash_to_scalar(&[&r.pk_to_key_slice(), &pk.pk_to_key_slice(), message])
+}
+
+/// Splits SHA-512(seed) into the clamped secret scalar and the prefix the nonces are derived
+/// from
+fn expand_seed(seed: &[u8; 32]) -> (FE, [u8; 32]) {
+    let mut h = Sha512::digest(seed);
+    h[0] &= 248;
+    h[31] &= 127;
+    h[31] |= 64;
+    let a = scalar_from_le_bytes(&h[..32]);
+    let mut prefix = [0u8; 32];
+    prefix.copy_from_slice(&h[32..]);
+    h.as_mut_slice().zeroize();
+    (a, prefix)
+}
+
+fn hash_to_scalar(data: &[&[u8]]) -> FE {
+    let mut hasher = Sha512::new();
+    for d in data {
+        hasher.input(d);
+    }
+    scalar_from_le_bytes(&hasher.result())
+}
+
+fn scalar_from_le_bytes(bytes: &[u8]) -> FE {
+    let mut be = bytes.to_vec();
+    be.reverse();
+    let n = BigInt::from_bytes(&be);
+    be.zeroize();
+    ECScalar::from(&n)
+}
+
+fn scalar_to_bytes(s: &FE) -> [u8; 32] {
+    let be = BigInt::to_bytes(&s.to_big_int());
+    let mut bytes = [0u8; 32];
+    bytes[32 - be.len()..].copy_from_slice(&be);
+    bytes.reverse();
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 8032, section 7.1, test 2
+    const SEED: &str = "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb";
+    const PUBLIC_KEY: &str = "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c";
+    const MESSAGE: &str = "72";
+    const SIGNATURE: &str = "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da\
+                             085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00";
+
+    fn hex(s: &str) -> Vec<u8> {
+        let bytes = BigInt::to_bytes(&BigInt::from_hex(s).unwrap());
+        let mut padded = vec![0u8; s.len() / 2 - bytes.len()];
+        padded.extend_from_slice(&bytes);
+        padded
+    }
+
+    #[test]
+    fn test_rfc8032_vector() {
+        let mut seed = [0u8; 32];
+        seed.copy_from_slice(&hex(SEED));
+        let message = hex(MESSAGE);
+        let pk = public_key(&seed);
+        assert_eq!(pk.pk_to_key_slice(), hex(PUBLIC_KEY));
+
+        let sig = sign(&seed, &message);
+        assert_eq!(sig.to_bytes()[..], hex(SIGNATURE)[..]);
+        assert_eq!(Signature::from_bytes(&sig.to_bytes()).unwrap(), sig);
+        assert!(verify_cofactorless(&pk, &message, &sig));
+        assert!(verify_cofactored(&pk, &message, &sig));
+        assert!(!verify(&pk, b"another message", &sig));
+    }
+
+    #[test]
+    fn test_small_order_nonce_passes_cofactored_only() {
+        let seed = [7u8; 32];
+        let message = b"message";
+        let (a, prefix) = expand_seed(&seed);
+        let pk = GE::generator() * a;
+
+        // (0, -1) has order 2, so it vanishes when multiplied by the cofactor
+        let mut torsion_bytes = [0xffu8; 32];
+        torsion_bytes[0] = 0xec;
+        torsion_bytes[31] = 0x7f;
+        let torsion = GE::from_bytes_with_torsion(&torsion_bytes).unwrap();
+
+        let r = hash_to_scalar(&[&prefix, message]);
+        let big_r = GE::generator() * r + torsion;
+        let k = challenge(&big_r, &pk, message);
+        let sig = Signature {
+            r: big_r,
+            s: r + k * a,
+        };
+        let sig = Signature::from_bytes(&sig.to_bytes()).unwrap();
+
+        assert!(verify_cofactored(&pk, message, &sig));
+        assert!(!verify_cofactorless(&pk, message, &sig));
+        assert!(!verify(&pk, message, &sig));
+    }
+}
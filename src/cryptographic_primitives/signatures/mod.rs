@@ -0,0 +1,18 @@
+/*
+    This file is part of Curv library
+    Copyright 2018 by Kzen Networks
+    (https://github.com/KZen-networks/curv)
+    License MIT: https://github.com/KZen-networks/curv/blob/master/LICENSE
+*/
+
+/// BIP-340 Schnorr signatures over secp256k1, with x-only public keys.
+pub mod bip340;
+/// Ed25519 signatures, with both the cofactored and the cofactorless verification equations.
+pub mod ed25519;
+/// Schnorr signatures over any curve or following BIP-340, the variant being a type parameter,
+/// with verification of the partial signatures produced with a shared key.
+pub mod schnorr;
+/// t-of-n threshold ECDSA over secp256k1, following the signing protocol of Gennaro and
+/// Goldfeder "Fast Multiparty Threshold ECDSA with Fast Trustless Setup"
+/// (http://stevengoldfeder.com/papers/GG18.pdf). Keys are generated with Feldman VSS.
+pub mod threshold_ecdsa;
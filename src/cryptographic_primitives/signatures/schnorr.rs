@@ -0,0 +1,451 @@
+/*
+    This file is part of Curv library
+    Copyright 2018 by Kzen Networks
+    (https://github.com/KZen-networks/curv)
+    License MIT: https://github.com/KZen-networks/curv/blob/master/LICENSE
+*/
+
+//! Schnorr signatures over a generic curve, and the checks needed to sign with shared keys.
+//!
+//! A signature on m under the public key X = xG is (R, s) with R = kG for a random nonce k,
+//! e = H(R, X, m) and s = k + ex. It is accepted if sG = R + eX.
+//!
+//! With a key shared among signers such that X = sum X_i, each signer i contributes a nonce
+//! commitment R_i = k_i*G. Given R = sum R_i and e = H(R, X, m), signer i sends the partial
+//! signature s_i = k_i + e*x_i, and (R, sum s_i) is a signature under X. Every partial signature
+//! can be checked with [verify_partial] before aggregating them, so a signer sending a wrong one
+//! is identified. In a threshold setting X_i and x_i are the shares multiplied by the Lagrange
+//! coefficient of the signer.
+//!
+//! A [SignedProof] attaches to a signature a proof of knowledge of a discrete log, the
+//! challenges of both being derived from one transcript: the proof commits to the public key
+//! and the message, and the signature challenge to the proof. Neither can be replaced without
+//! invalidating the other.
+//!
+//! [sign] and [verify] are generic over the [Variant] of the scheme: [Generic] signs as above
+//! over any curve implementing [ConstantTimeBaseMul], which the nonces and keys are multiplied
+//! with, so not over BLS12-381, the challenge being drawn from a [Transcript] framing R, X and m. [Bip340]
+//! follows BIP-340 over secp256k1, with x-only public keys and tagged hashes, see
+//! [bip340](super::bip340).
+//!
+//! ```
+//! # use curv::cryptographic_primitives::signatures::schnorr::{self, Bip340, Generic};
+//! # use curv::elliptic::curves::secp256_k1::{FE, GE};
+//! # use curv::elliptic::curves::traits::ECScalar;
+//! let sk: FE = ECScalar::new_random();
+//! let sig = schnorr::sign::<Generic<GE>>(&sk, b"message");
+//! let pk = schnorr::public_key::<Generic<GE>>(&sk);
+//! assert!(schnorr::verify::<Generic<GE>>(&pk, b"message", &sig).is_ok());
+//!
+//! let sig = schnorr::sign::<Bip340>(&sk, b"message");
+//! let pk = schnorr::public_key::<Bip340>(&sk);
+//! assert!(schnorr::verify::<Bip340>(&pk, b"message", &sig).is_ok());
+//! ```
+
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+use derivative::Derivative;
+use rand::{thread_rng, Rng};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use super::bip340;
+use crate::cryptographic_primitives::hashing::transcript::Transcript;
+use crate::cryptographic_primitives::proofs::proof_transcript;
+use crate::cryptographic_primitives::proofs::sigma_dlog::DLogProof;
+use crate::elliptic::curves::secp256_k1::FE;
+use crate::elliptic::curves::traits::*;
+
+#[derive(Copy, PartialEq, Eq, Clone, Debug)]
+pub enum Error {
+    InvalidPublicKey,
+    InvalidSignature,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Signature<P: ECPoint> {
+    pub r: P,
+    pub s: P::Scalar,
+}
+
+/// A variant of Schnorr signatures, setting the encoding of keys and signatures and how the
+/// challenge is derived
+pub trait Variant {
+    type SecretKey;
+    type PublicKey;
+    type Signature;
+
+    fn public_key(sk: &Self::SecretKey) -> Self::PublicKey;
+    fn sign(sk: &Self::SecretKey, message: &[u8]) -> Self::Signature;
+    fn verify(pk: &Self::PublicKey, message: &[u8], sig: &Self::Signature) -> Result<(), Error>;
+}
+
+/// Schnorr signatures over the curve of `P`, with the challenge of [challenge]
+pub struct Generic<P>(PhantomData<P>);
+
+/// BIP-340 signatures over secp256k1. The auxiliary randomness of [bip340::sign] is drawn for
+/// every signature.
+pub struct Bip340;
+
+impl<P> Variant for Generic<P>
+where
+    P: ConstantTimeBaseMul + Clone,
+    P::Scalar: Zeroize + Clone,
+{
+    type SecretKey = P::Scalar;
+    type PublicKey = P;
+    type Signature = Signature<P>;
+
+    fn public_key(sk: &P::Scalar) -> P {
+        P::scalar_mul_base_ct(sk)
+    }
+
+    fn sign(sk: &P::Scalar, message: &[u8]) -> Signature<P> {
+        let mut k: P::Scalar = ECScalar::new_random();
+        let r = P::scalar_mul_base_ct(&k);
+        let pk = P::scalar_mul_base_ct(sk);
+        let e = challenge(&r, &pk, message);
+        let s = k.clone() + e * sk.clone();
+        k.zeroize();
+        Signature { r, s }
+    }
+
+    fn verify(pk: &P, message: &[u8], sig: &Signature<P>) -> Result<(), Error> {
+        let e = challenge(&sig.r, pk, message);
+        if verify_partial(&sig.s, &sig.r, pk, &e) {
+            Ok(())
+        } else {
+            Err(Error::InvalidSignature)
+        }
+    }
+}
+
+impl Variant for Bip340 {
+    type SecretKey = FE;
+    type PublicKey = [u8; 32];
+    type Signature = bip340::Signature;
+
+    fn public_key(sk: &FE) -> [u8; 32] {
+        bip340::public_key(sk)
+    }
+
+    fn sign(sk: &FE, message: &[u8]) -> bip340::Signature {
+        let mut aux_rand = [0u8; 32];
+        thread_rng().fill(&mut aux_rand);
+        bip340::sign(sk, message, &aux_rand)
+    }
+
+    fn verify(pk: &[u8; 32], message: &[u8], sig: &bip340::Signature) -> Result<(), Error> {
+        bip340::verify(pk, message, sig)
+    }
+}
+
+/// Computes the challenge e = H(R, X, m) of the [Generic] variant
+pub fn challenge<P>(r: &P, pk: &P, message: &[u8]) -> P::Scalar
+where
+    P: ECPoint,
+{
+    let mut transcript = proof_transcript::<P>(b"curv schnorr signature");
+    transcript.append_point(b"R", r);
+    transcript.append_point(b"X", pk);
+    transcript.append_message(b"message", message);
+    transcript.challenge_scalar(b"e")
+}
+
+pub fn public_key<V: Variant>(sk: &V::SecretKey) -> V::PublicKey {
+    V::public_key(sk)
+}
+
+pub fn sign<V: Variant>(sk: &V::SecretKey, message: &[u8]) -> V::Signature {
+    V::sign(sk, message)
+}
+
+pub fn verify<V: Variant>(
+    pk: &V::PublicKey,
+    message: &[u8],
+    sig: &V::Signature,
+) -> Result<(), Error> {
+    V::verify(pk, message, sig)
+}
+
+/// Checks the partial signature s_i of a signer against its nonce commitment R_i and its public
+/// key share X_i, that is s_i*G = R_i + e*X_i.
+///
+/// The equation is checked as s_i*G - e*X_i = R_i, so both multiplications are done at once by
+/// [double_scalar_mul_base](ECPoint::double_scalar_mul_base). On secp256k1, a left-hand side at
+/// the point at infinity, which no R_i can be equal to, is rejected.
+pub fn verify_partial<P>(
+    partial_sig: &P::Scalar,
+    commitment: &P,
+    pubkey_share: &P,
+    challenge: &P::Scalar,
+) -> bool
+where
+    P: ECPoint + Clone,
+    P::Scalar: Clone,
+{
+    let minus_e: P::Scalar = ECScalar::from(&(P::Scalar::q() - challenge.to_big_int()));
+    match P::double_scalar_mul_base(partial_sig, pubkey_share, &minus_e) {
+        // points are compared by their encoding, which doesn't depend on how they were computed
+        Some(lhs) => lhs.pk_to_key_slice() == commitment.pk_to_key_slice(),
+        None => false,
+    }
+}
+
+/// Adds up the nonce commitments R_i and the partial signatures s_i of all signers
+///
+/// ## Panics
+/// Panics if there are no signers.
+pub fn aggregate<P>(commitments: &[P], partial_sigs: &[P::Scalar]) -> Signature<P>
+where
+    P: ECPoint + Clone,
+    P::Scalar: Clone,
+{
+    assert!(!commitments.is_empty() && !partial_sigs.is_empty());
+    let r = commitments[1..]
+        .iter()
+        .fold(commitments[0].clone(), |acc, r_i| acc + r_i.clone());
+    let s = partial_sigs[1..]
+        .iter()
+        .fold(partial_sigs[0].clone(), |acc, s_i| acc + s_i.clone());
+    Signature { r, s }
+}
+
+/// A signature on a message under X = xG, bundled with a [DLogProof] of knowledge of w such that
+/// W = wG. The proof is made over a transcript of X and the message, then R is appended to the
+/// same transcript and the signature challenge e is drawn from it, s = k + ex.
+///
+/// W is the `pk` of `proof`, which verifiers check against the point they expect.
+#[derive(Serialize, Deserialize, Derivative)]
+#[derivative(Clone(bound = "P: Clone, P::Scalar: Clone"))]
+#[derivative(Debug(bound = "P: Debug, P::Scalar: Debug"))]
+#[derivative(PartialEq(bound = "P: PartialEq, P::Scalar: PartialEq"))]
+#[serde(bound(serialize = "P: Serialize, P::Scalar: Serialize"))]
+#[serde(bound(deserialize = "P: Deserialize<'de>, P::Scalar: Deserialize<'de>"))]
+pub struct SignedProof<P: ECPoint> {
+    pub signature: Signature<P>,
+    pub proof: DLogProof<P>,
+}
+
+impl<P> SignedProof<P>
+where
+    P: ConstantTimeBaseMul + Clone,
+    P::Scalar: Zeroize + Clone,
+{
+    /// Signs `message` with `sk` and proves knowledge of `witness` in one pass
+    pub fn sign(sk: &P::Scalar, message: &[u8], witness: &P::Scalar) -> SignedProof<P> {
+        let pk = P::scalar_mul_base_ct(sk);
+        let mut transcript = Self::transcript(&pk, message);
+        let proof = DLogProof::builder()
+            .witness(witness)
+            .transcript(&mut transcript)
+            .prove();
+        let mut k: P::Scalar = ECScalar::new_random();
+        let r = P::scalar_mul_base_ct(&k);
+        transcript.append_point(b"R", &r);
+        let e: P::Scalar = transcript.challenge_scalar(b"e");
+        let s = k.clone() + e * sk.clone();
+        k.zeroize();
+        SignedProof {
+            signature: Signature { r, s },
+            proof,
+        }
+    }
+
+    /// Verifies both the proof and the signature on `message` under `pk`
+    pub fn verify(&self, pk: &P, message: &[u8]) -> bool {
+        let mut transcript = Self::transcript(pk, message);
+        if DLogProof::builder()
+            .transcript(&mut transcript)
+            .verify(&self.proof)
+            .is_err()
+        {
+            return false;
+        }
+        transcript.append_point(b"R", &self.signature.r);
+        let e: P::Scalar = transcript.challenge_scalar(b"e");
+        verify_partial(&self.signature.s, &self.signature.r, pk, &e)
+    }
+
+    fn transcript(pk: &P, message: &[u8]) -> Transcript {
+        let mut transcript = proof_transcript::<P>(b"curv signed proof");
+        transcript.append_point(b"X", pk);
+        transcript.append_message(b"message", message);
+        transcript
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cryptographic_primitives::secret_sharing::additive::additive_share;
+    use crate::test_for_ct_curves;
+
+    test_for_ct_curves!(test_schnorr_signature);
+    fn test_schnorr_signature<P>()
+    where
+        P: ConstantTimeBaseMul + Clone,
+        P::Scalar: Zeroize + Clone,
+    {
+        let sk: P::Scalar = ECScalar::new_random();
+        let pk = P::generator() * sk.clone();
+        let sig = sign::<Generic<P>>(&sk, b"message");
+        assert!(verify::<Generic<P>>(&pk, b"message", &sig).is_ok());
+        assert!(verify::<Generic<P>>(&pk, b"another message", &sig).is_err());
+        let other_pk = P::generator() * P::Scalar::new_random();
+        assert!(verify::<Generic<P>>(&other_pk, b"message", &sig).is_err());
+    }
+
+    test_for_ct_curves!(test_verify_rejects_a_left_hand_side_at_infinity);
+    fn test_verify_rejects_a_left_hand_side_at_infinity<P>()
+    where
+        P: ConstantTimeBaseMul + Clone,
+        P::Scalar: Zeroize + Clone,
+    {
+        // s = e*x for the key of the forger makes sG - eX the identity
+        let x: P::Scalar = ECScalar::new_random();
+        let pk = P::generator() * x.clone();
+        let r = P::generator() * P::Scalar::new_random();
+        let e = challenge(&r, &pk, b"message");
+        let sig = Signature {
+            r,
+            s: e.clone() * x,
+        };
+        assert!(verify::<Generic<P>>(&pk, b"message", &sig).is_err());
+        assert!(!verify_partial(&sig.s, &sig.r, &pk, &e));
+    }
+
+    test_for_ct_curves!(test_verify_agrees_with_naive_verify);
+    fn test_verify_agrees_with_naive_verify<P>()
+    where
+        P: ConstantTimeBaseMul + Clone,
+        P::Scalar: Zeroize + Clone,
+    {
+        let naive_verify = |sig: &Signature<P>, pk: &P, message: &[u8]| {
+            let e = challenge(&sig.r, pk, message);
+            let lhs = P::generator() * sig.s.clone();
+            let rhs = sig.r.clone() + pk.clone() * e;
+            lhs.pk_to_key_slice() == rhs.pk_to_key_slice()
+        };
+        let sk: P::Scalar = ECScalar::new_random();
+        let pk = P::generator() * sk.clone();
+        let other_pk = P::generator() * P::Scalar::new_random();
+        for _ in 0..10 {
+            let sig = sign::<Generic<P>>(&sk, b"message");
+            let forged = Signature {
+                r: sig.r.clone(),
+                s: sig.s.clone() + P::Scalar::new_random(),
+            };
+            for (sig, pk, message) in [
+                (&sig, &pk, &b"message"[..]),
+                (&sig, &pk, &b"another message"[..]),
+                (&sig, &other_pk, &b"message"[..]),
+                (&forged, &pk, &b"message"[..]),
+            ] {
+                assert_eq!(
+                    verify::<Generic<P>>(pk, message, sig).is_ok(),
+                    naive_verify(sig, pk, message)
+                );
+            }
+        }
+    }
+
+    test_for_ct_curves!(test_partial_signatures);
+    fn test_partial_signatures<P>()
+    where
+        P: ConstantTimeBaseMul + Clone,
+        P::Scalar: Zeroize + Clone,
+    {
+        let message = b"message";
+        let sk: P::Scalar = ECScalar::new_random();
+        let pk = P::generator() * sk.clone();
+        let sk_shares = additive_share(&sk, 3);
+        let pk_shares: Vec<P> = sk_shares
+            .iter()
+            .map(|x_i| P::generator() * x_i.clone())
+            .collect();
+
+        let nonces: Vec<P::Scalar> = (0..3).map(|_| ECScalar::new_random()).collect();
+        let commitments: Vec<P> = nonces
+            .iter()
+            .map(|k_i| P::generator() * k_i.clone())
+            .collect();
+        let r = aggregate(&commitments, &nonces).r;
+        let e = challenge(&r, &pk, message);
+        let mut partial_sigs: Vec<P::Scalar> = nonces
+            .iter()
+            .zip(&sk_shares)
+            .map(|(k_i, x_i)| k_i.clone() + e.clone() * x_i.clone())
+            .collect();
+        for i in 0..3 {
+            assert!(verify_partial(
+                &partial_sigs[i],
+                &commitments[i],
+                &pk_shares[i],
+                &e
+            ));
+        }
+        let sig = aggregate(&commitments, &partial_sigs);
+        assert!(verify::<Generic<P>>(&pk, message, &sig).is_ok());
+
+        // a signer sending a wrong partial signature is identified, the others are not blamed
+        partial_sigs[1] = partial_sigs[1].clone() + P::Scalar::new_random();
+        let blamed: Vec<usize> = (0..3)
+            .filter(|i| !verify_partial(&partial_sigs[*i], &commitments[*i], &pk_shares[*i], &e))
+            .collect();
+        assert_eq!(blamed, vec![1]);
+        let sig = aggregate(&commitments, &partial_sigs);
+        assert!(verify::<Generic<P>>(&pk, message, &sig).is_err());
+    }
+
+    test_for_ct_curves!(test_signed_proof);
+    fn test_signed_proof<P>()
+    where
+        P: ConstantTimeBaseMul + Clone,
+        P::Scalar: Zeroize + Clone,
+    {
+        let sk: P::Scalar = ECScalar::new_random();
+        let pk = P::generator() * sk.clone();
+        let w: P::Scalar = ECScalar::new_random();
+        let signed = SignedProof::<P>::sign(&sk, b"message", &w);
+        assert!(signed.verify(&pk, b"message"));
+        assert_eq!(
+            signed.proof.pk.pk_to_key_slice(),
+            (P::generator() * w).pk_to_key_slice()
+        );
+        assert!(!signed.verify(&pk, b"another message"));
+        let other_pk = P::generator() * P::Scalar::new_random();
+        assert!(!signed.verify(&other_pk, b"message"));
+        // the signature alone isn't a plain signature on the message
+        assert!(verify::<Generic<P>>(&pk, b"message", &signed.signature).is_err());
+    }
+
+    test_for_ct_curves!(test_signed_proof_rejects_tampering);
+    fn test_signed_proof_rejects_tampering<P>()
+    where
+        P: ConstantTimeBaseMul + Clone,
+        P::Scalar: Zeroize + Clone,
+    {
+        let sk: P::Scalar = ECScalar::new_random();
+        let pk = P::generator() * sk.clone();
+        let signed = SignedProof::<P>::sign(&sk, b"message", &ECScalar::new_random());
+        let other = SignedProof::<P>::sign(&sk, b"message", &ECScalar::new_random());
+
+        let mut forged = signed.clone();
+        forged.signature.s = forged.signature.s + P::Scalar::new_random();
+        assert!(!forged.verify(&pk, b"message"));
+
+        let mut forged = signed.clone();
+        forged.proof.challenge_response = forged.proof.challenge_response + P::Scalar::new_random();
+        assert!(!forged.verify(&pk, b"message"));
+
+        // both components are valid in their own bundle but can't be mixed
+        let mut forged = signed.clone();
+        forged.proof = other.proof.clone();
+        assert!(!forged.verify(&pk, b"message"));
+        let mut forged = signed;
+        forged.signature = other.signature;
+        assert!(!forged.verify(&pk, b"message"));
+    }
+}
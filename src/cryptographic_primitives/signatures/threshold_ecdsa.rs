@@ -0,0 +1,564 @@
+#![allow(non_snake_case)]
+/*
+    This file is part of Curv library
+    Copyright 2018 by Kzen Networks
+    (https://github.com/KZen-networks/curv)
+    License MIT: https://github.com/KZen-networks/curv/blob/master/LICENSE
+*/
+
+//! The signing protocol of GG18, run by any t+1 of the n holders of a Feldman VSS key.
+//!
+//! Key generation: every party i samples u_i, shares it with [VerifiableSS::share] and broadcasts
+//! the VSS scheme. Party i collects the shares dealt to it and calls [LocalKey::from_vss_shares].
+//! The public key is y = sum u_i*G.
+//!
+//! Signing with a set S of t+1 parties:
+//! 1. party i computes w_i = lambda_{i,S}*x_i, samples k_i, gamma_i, broadcasts a commitment to
+//!    gamma_i*G ([SignKeys::phase1_broadcast]) and E_i(k_i) under its own Paillier key
+//!    ([MessageA]).
+//! 2. every pair of parties runs two MtA conversions ([MessageB]): the first one turns k_i*gamma_j
+//!    into additive shares alpha_ij + beta_ji, the second one turns k_i*w_j into mu_ij + nu_ji.
+//!    Party i then holds delta_i, a share of k*gamma, and sigma_i, a share of k*x.
+//! 3. parties reveal delta_i and compute delta = k*gamma.
+//! 4. parties decommit gamma_i*G and compute R = delta^-1 * sum gamma_i*G = k^-1*G, r = R.x.
+//! 5. party i computes s_i = m*k_i + r*sigma_i and s = sum s_i ([LocalSignature]).
+//!
+//! The MtA conversions carry no range proofs and the values received in phase 2 are not checked
+//! against the public key shares, so the protocol is only secure against parties that follow it.
+//! A malicious party is still caught when the final signature fails to verify.
+
+use serde::{Deserialize, Serialize};
+
+use crate::arithmetic::traits::*;
+use crate::cryptographic_primitives::commitments::hash_commitment::HashCommitment;
+use crate::cryptographic_primitives::commitments::traits::Commitment;
+use crate::cryptographic_primitives::proofs::sigma_dlog::DLogProof;
+use crate::cryptographic_primitives::secret_sharing::feldman_vss::{
+    ShamirSecretSharing, VerifiableSS,
+};
+use crate::elliptic::curves::secp256_k1::{FE, GE};
+use crate::elliptic::curves::traits::*;
+use crate::BigInt;
+
+const SECURITY_BITS: usize = 256;
+const PAILLIER_PRIME_BITS: usize = 1024;
+
+#[derive(Copy, PartialEq, Eq, Clone, Debug)]
+pub enum Error {
+    InvalidKey,
+    InvalidCommitment,
+    InvalidSignature,
+}
+
+/// Paillier public key, with generator n+1
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct EncryptionKey {
+    pub n: BigInt,
+    pub nn: BigInt,
+}
+
+#[derive(Clone, Debug)]
+pub struct DecryptionKey {
+    ek: EncryptionKey,
+    lambda: BigInt,
+    mu: BigInt,
+}
+
+/// Share of the key of party `index` (counted from 0), together with the party's Paillier keys
+#[derive(Clone, Debug)]
+pub struct LocalKey {
+    pub index: usize,
+    pub params: ShamirSecretSharing,
+    pub x_i: FE,
+    pub y: GE,
+    pub ek: EncryptionKey,
+    dk: DecryptionKey,
+}
+
+#[derive(Clone, Debug)]
+pub struct SignKeys {
+    pub w_i: FE,
+    pub k_i: FE,
+    pub gamma_i: FE,
+    pub g_gamma_i: GE,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SignBroadcastPhase1 {
+    pub com: BigInt,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SignDecommitPhase1 {
+    pub blind_factor: BigInt,
+    pub g_gamma_i: GE,
+    pub gamma_proof: DLogProof<GE>,
+}
+
+/// First message of MtA: the encryption of a under the key of its owner
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MessageA {
+    pub c: BigInt,
+}
+
+/// Second message of MtA: the encryption of a*b + beta' computed homomorphically by the owner
+/// of b
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MessageB {
+    pub c: BigInt,
+}
+
+#[derive(Clone, Debug)]
+pub struct LocalSignature {
+    pub r: FE,
+    pub R: GE,
+    pub s_i: FE,
+    pub m: FE,
+    pub y: GE,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Signature {
+    pub r: FE,
+    pub s: FE,
+    pub recid: u8,
+}
+
+impl EncryptionKey {
+    fn encrypt(&self, m: &BigInt) -> BigInt {
+        let r = BigInt::sample_below(&self.n);
+        // (n+1)^m = 1 + m*n mod n^2
+        let gm = BigInt::mod_add(&BigInt::one(), &(m * &self.n), &self.nn);
+        BigInt::mod_mul(&gm, &BigInt::mod_pow(&r, &self.n, &self.nn), &self.nn)
+    }
+}
+
+impl DecryptionKey {
+    fn decrypt(&self, c: &BigInt) -> BigInt {
+        let u = BigInt::mod_pow(c, &self.lambda, &self.ek.nn);
+        let l = (u - BigInt::one()) / &self.ek.n;
+        BigInt::mod_mul(&l, &self.mu, &self.ek.n)
+    }
+}
+
+fn paillier_keypair() -> (EncryptionKey, DecryptionKey) {
+    let p = sample_prime(PAILLIER_PRIME_BITS);
+    let q = loop {
+        let q = sample_prime(PAILLIER_PRIME_BITS);
+        if q != p {
+            break q;
+        }
+    };
+    let n = &p * &q;
+    let ek = EncryptionKey {
+        nn: &n * &n,
+        n: n.clone(),
+    };
+    let lambda = (p - BigInt::one()) * (q - BigInt::one());
+    let mu = BigInt::mod_inv(&lambda, &n).unwrap();
+    let dk = DecryptionKey {
+        ek: ek.clone(),
+        lambda,
+        mu,
+    };
+    (ek, dk)
+}
+
+fn sample_prime(bit_size: usize) -> BigInt {
+    loop {
+        let candidate = BigInt::strict_sample(bit_size).next_prime();
+        if candidate.bit_length() == bit_size {
+            return candidate;
+        }
+    }
+}
+
+impl LocalKey {
+    /// Combines the shares dealt to party `index` by every party of the key generation, and
+    /// checks each of them against the VSS scheme of its dealer.
+    pub fn from_vss_shares(
+        index: usize,
+        vss_schemes: &[VerifiableSS<GE>],
+        shares: &[FE],
+    ) -> Result<LocalKey, Error> {
+        assert_eq!(vss_schemes.len(), shares.len());
+        assert!(!vss_schemes.is_empty());
+        let params = vss_schemes[0].parameters.clone();
+        let valid = vss_schemes.iter().zip(shares).all(|(vss, share)| {
+            vss.parameters == params && vss.validate_share(share, index + 1).is_ok()
+        });
+        if !valid {
+            return Err(Error::InvalidKey);
+        }
+
+        let x_i = shares[1..]
+            .iter()
+            .fold(shares[0], |acc, share| acc + *share);
+        let y = vss_schemes[1..]
+            .iter()
+            .fold(vss_schemes[0].commitments[0], |acc, vss| {
+                acc + vss.commitments[0]
+            });
+        let (ek, dk) = paillier_keypair();
+        Ok(LocalKey {
+            index,
+            params,
+            x_i,
+            y,
+            ek,
+            dk,
+        })
+    }
+}
+
+impl MessageA {
+    pub fn a(a: &FE, ek: &EncryptionKey) -> MessageA {
+        MessageA {
+            c: ek.encrypt(&a.to_big_int()),
+        }
+    }
+}
+
+impl MessageB {
+    /// Answers `m_a` with the encryption of a*b + beta'. Returns the message and beta = -beta',
+    /// the share of a*b kept by the owner of b.
+    pub fn b(b: &FE, ek: &EncryptionKey, m_a: &MessageA) -> (MessageB, FE) {
+        // a*b + beta' must not wrap around n, so beta' is much smaller than n
+        let beta_tag = BigInt::sample_below(&FE::q().pow(5));
+        let c_ab = BigInt::mod_pow(&m_a.c, &b.to_big_int(), &ek.nn);
+        let c = BigInt::mod_mul(&c_ab, &ek.encrypt(&beta_tag), &ek.nn);
+        let beta = BigInt::mod_sub(&BigInt::zero(), &beta_tag, &FE::q());
+        (MessageB { c }, ECScalar::from(&beta))
+    }
+
+    /// Returns alpha, the share of a*b kept by the owner of a
+    pub fn get_alpha(&self, key: &LocalKey) -> FE {
+        ECScalar::from(&key.dk.decrypt(&self.c))
+    }
+}
+
+impl SignKeys {
+    /// `signers` are the indices of the t+1 parties taking part in the signing
+    pub fn create(key: &LocalKey, signers: &[usize]) -> SignKeys {
+        assert!(signers.contains(&key.index));
+        let lambda_i = VerifiableSS::<GE>::map_share_to_new_params(&key.params, key.index, signers);
+        let gamma_i: FE = ECScalar::new_random();
+        SignKeys {
+            w_i: lambda_i * key.x_i,
+            k_i: ECScalar::new_random(),
+            gamma_i,
+            g_gamma_i: GE::scalar_mul_base_ct(&gamma_i),
+        }
+    }
+
+    pub fn phase1_broadcast(&self) -> (SignBroadcastPhase1, SignDecommitPhase1) {
+        let blind_factor = BigInt::sample(SECURITY_BITS);
+        let com = HashCommitment::create_commitment_with_user_defined_randomness(
+            &self.g_gamma_i.bytes_compressed_to_big_int(),
+            &blind_factor,
+        );
+        (
+            SignBroadcastPhase1 { com },
+            SignDecommitPhase1 {
+                blind_factor,
+                g_gamma_i: self.g_gamma_i,
+                gamma_proof: DLogProof::prove(&self.gamma_i),
+            },
+        )
+    }
+
+    /// delta_i = k_i*gamma_i + sum_j (alpha_ij + beta_ji), a share of k*gamma
+    pub fn phase2_delta_i(&self, alpha_vec: &[FE], beta_vec: &[FE]) -> FE {
+        assert_eq!(alpha_vec.len(), beta_vec.len());
+        alpha_vec
+            .iter()
+            .zip(beta_vec)
+            .fold(self.k_i * self.gamma_i, |acc, (alpha, beta)| {
+                acc + *alpha + *beta
+            })
+    }
+
+    /// sigma_i = k_i*w_i + sum_j (mu_ij + nu_ji), a share of k*x
+    pub fn phase2_sigma_i(&self, mu_vec: &[FE], nu_vec: &[FE]) -> FE {
+        assert_eq!(mu_vec.len(), nu_vec.len());
+        mu_vec
+            .iter()
+            .zip(nu_vec)
+            .fold(self.k_i * self.w_i, |acc, (mu, nu)| acc + *mu + *nu)
+    }
+
+    /// Returns delta^-1, computed from the delta_i of all the signers
+    pub fn phase3_reconstruct_delta(delta_vec: &[FE]) -> FE {
+        let delta = delta_vec[1..]
+            .iter()
+            .fold(delta_vec[0], |acc, delta_i| acc + *delta_i);
+        delta.invert()
+    }
+
+    /// Checks the decommitments of all the signers and returns R = k^-1*G
+    pub fn phase4(
+        delta_inv: &FE,
+        bc1_vec: &[SignBroadcastPhase1],
+        decom_vec: &[SignDecommitPhase1],
+    ) -> Result<GE, Error> {
+        assert_eq!(bc1_vec.len(), decom_vec.len());
+        let valid = bc1_vec.iter().zip(decom_vec).all(|(bc1, decom)| {
+            let com = HashCommitment::create_commitment_with_user_defined_randomness(
+                &decom.g_gamma_i.bytes_compressed_to_big_int(),
+                &decom.blind_factor,
+            );
+            com == bc1.com
+                && decom.gamma_proof.pk == decom.g_gamma_i
+                && DLogProof::verify(&decom.gamma_proof).is_ok()
+        });
+        if !valid {
+            return Err(Error::InvalidCommitment);
+        }
+        let sum_gamma = decom_vec[1..]
+            .iter()
+            .fold(decom_vec[0].g_gamma_i, |acc, decom| acc + decom.g_gamma_i);
+        Ok(sum_gamma * *delta_inv)
+    }
+}
+
+impl LocalSignature {
+    /// `message` is the hash of the signed message, reduced modulo the group order
+    pub fn phase5_local_sig(
+        k_i: &FE,
+        message: &BigInt,
+        R: &GE,
+        sigma_i: &FE,
+        y: &GE,
+    ) -> LocalSignature {
+        let m: FE = ECScalar::from(message);
+        let r: FE = ECScalar::from(&R.x_coor().unwrap());
+        LocalSignature {
+            r,
+            R: *R,
+            s_i: m * *k_i + r * *sigma_i,
+            m,
+            y: *y,
+        }
+    }
+
+    /// Combines the local signature with the s_i of the other signers, and checks the result
+    /// against the public key. The returned signature is normalized to a low s, as expected by
+    /// libsecp256k1.
+    pub fn output_signature(&self, s_vec: &[FE]) -> Result<Signature, Error> {
+        let s = s_vec.iter().fold(self.s_i, |acc, s_i| acc + *s_i);
+        let mut recid = if self.R.y_coor().unwrap().test_bit(0) {
+            1
+        } else {
+            0
+        };
+        if !s.is_low() {
+            recid ^= 1;
+        }
+        let s = s.normalize_low();
+        let sig = Signature {
+            r: self.r,
+            s,
+            recid,
+        };
+        verify_scalar(&sig, &self.y, &self.m)?;
+        Ok(sig)
+    }
+}
+
+/// Standard ECDSA verification of `sig` on the hash `message` under the public key `y`. Fails,
+/// without panicking, for any `sig`: r and s must be in [1, q), and the point u1*G + u2*y at
+/// infinity is rejected.
+pub fn verify(sig: &Signature, y: &GE, message: &BigInt) -> Result<(), Error> {
+    verify_scalar(
+        sig,
+        y,
+        &FE::from_be_bytes_mod_order(&BigInt::to_bytes(message)),
+    )
+}
+
+fn verify_scalar(sig: &Signature, y: &GE, m: &FE) -> Result<(), Error> {
+    let q = FE::q();
+    let in_range = |n: &FE| {
+        let n = n.to_big_int();
+        n > BigInt::zero() && n < q
+    };
+    if !in_range(&sig.r) || !in_range(&sig.s) {
+        return Err(Error::InvalidSignature);
+    }
+    let s_inv = sig.s.invert();
+    // a zero product can't be built by multiplying the scalars
+    let u1 = if m.to_big_int() == BigInt::zero() {
+        FE::zero()
+    } else {
+        *m * s_inv
+    };
+    let u2 = sig.r * s_inv;
+    let point = GE::double_scalar_mul_base(&u1, y, &u2).ok_or(Error::InvalidSignature)?;
+    let x = point.x_coor().ok_or(Error::InvalidSignature)?;
+    if x.modulus(&q) == sig.r.to_big_int() {
+        Ok(())
+    } else {
+        Err(Error::InvalidSignature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cryptographic_primitives::hashing::hash_sha256::HSha256;
+    use crate::cryptographic_primitives::hashing::traits::Hash;
+
+    fn keygen(t: usize, n: usize) -> Vec<LocalKey> {
+        let (vss_schemes, shares): (Vec<_>, Vec<_>) = (0..n)
+            .map(|_| {
+                let u_i: FE = ECScalar::new_random();
+                VerifiableSS::<GE>::share(t, n, &u_i)
+            })
+            .unzip();
+        (0..n)
+            .map(|i| {
+                let shares_to_i: Vec<FE> = shares.iter().map(|s| s[i]).collect();
+                LocalKey::from_vss_shares(i, &vss_schemes, &shares_to_i).unwrap()
+            })
+            .collect()
+    }
+
+    /// Runs every phase of the signing for all `signers` and returns the signature combined by
+    /// each of them
+    fn sign(keys: &[LocalKey], signers: &[usize], message: &BigInt) -> Vec<Signature> {
+        let keys: Vec<&LocalKey> = signers.iter().map(|i| &keys[*i]).collect();
+        let sign_keys: Vec<SignKeys> = keys.iter().map(|k| SignKeys::create(k, signers)).collect();
+        let (bc1_vec, decom_vec): (Vec<_>, Vec<_>) =
+            sign_keys.iter().map(|k| k.phase1_broadcast()).unzip();
+        let m_a_vec: Vec<MessageA> = keys
+            .iter()
+            .zip(&sign_keys)
+            .map(|(key, sk)| MessageA::a(&sk.k_i, &key.ek))
+            .collect();
+
+        let n = keys.len();
+        let (mut alpha, mut beta) = (vec![vec![]; n], vec![vec![]; n]);
+        let (mut mu, mut nu) = (vec![vec![]; n], vec![vec![]; n]);
+        for i in 0..n {
+            for j in (0..n).filter(|j| *j != i) {
+                // j answers the MessageA of i
+                let ek_i = &keys[i].ek;
+                let (m_b_gamma, beta_ji) = MessageB::b(&sign_keys[j].gamma_i, ek_i, &m_a_vec[i]);
+                let (m_b_w, nu_ji) = MessageB::b(&sign_keys[j].w_i, ek_i, &m_a_vec[i]);
+                alpha[i].push(m_b_gamma.get_alpha(keys[i]));
+                mu[i].push(m_b_w.get_alpha(keys[i]));
+                beta[j].push(beta_ji);
+                nu[j].push(nu_ji);
+            }
+        }
+        let delta_vec: Vec<FE> = (0..n)
+            .map(|i| sign_keys[i].phase2_delta_i(&alpha[i], &beta[i]))
+            .collect();
+        let sigma_vec: Vec<FE> = (0..n)
+            .map(|i| sign_keys[i].phase2_sigma_i(&mu[i], &nu[i]))
+            .collect();
+        let delta_inv = SignKeys::phase3_reconstruct_delta(&delta_vec);
+        let R = SignKeys::phase4(&delta_inv, &bc1_vec, &decom_vec).unwrap();
+
+        let local_sigs: Vec<LocalSignature> = (0..n)
+            .map(|i| {
+                LocalSignature::phase5_local_sig(
+                    &sign_keys[i].k_i,
+                    message,
+                    &R,
+                    &sigma_vec[i],
+                    &keys[i].y,
+                )
+            })
+            .collect();
+        local_sigs
+            .iter()
+            .enumerate()
+            .map(|(i, local_sig)| {
+                let s_vec: Vec<FE> = local_sigs
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| *j != i)
+                    .map(|(_, sig)| sig.s_i)
+                    .collect();
+                local_sig.output_signature(&s_vec).unwrap()
+            })
+            .collect()
+    }
+
+    fn assert_libsecp_verifies(sig: &Signature, y: &GE, message: &BigInt) {
+        let mut m_bytes = [0u8; 32];
+        let m = BigInt::to_bytes(message);
+        m_bytes[32 - m.len()..].copy_from_slice(&m);
+        let mut compact = [0u8; 64];
+        compact[..32].copy_from_slice(&sig.r.to_libsecp_bytes());
+        compact[32..].copy_from_slice(&sig.s.to_libsecp_bytes());
+
+        let secp = secp256k1::Secp256k1::verification_only();
+        let msg = secp256k1::Message::from_slice(&m_bytes).unwrap();
+        let libsecp_sig = secp256k1::Signature::from_compact(&compact).unwrap();
+        assert!(secp.verify(&msg, &libsecp_sig, &y.get_element()).is_ok());
+    }
+
+    #[test]
+    fn test_two_of_two_signature_verifies() {
+        let keys = keygen(1, 2);
+        let message = HSha256::create_hash(&[&BigInt::from_bytes(b"two of two")]);
+        let sigs = sign(&keys, &[0, 1], &message);
+        assert_eq!(sigs[0], sigs[1]);
+        assert!(verify(&sigs[0], &keys[0].y, &message).is_ok());
+        assert_libsecp_verifies(&sigs[0], &keys[0].y, &message);
+
+        let other_message = HSha256::create_hash(&[&BigInt::from_bytes(b"other message")]);
+        assert_eq!(
+            verify(&sigs[0], &keys[0].y, &other_message),
+            Err(Error::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn test_threshold_signature_verifies() {
+        let message = HSha256::create_hash(&[&BigInt::from_bytes(b"t of n")]);
+        // 2 of 3, signed by the first and the last party
+        let keys = keygen(1, 3);
+        let sigs = sign(&keys, &[0, 2], &message);
+        assert_libsecp_verifies(&sigs[0], &keys[2].y, &message);
+
+        // 3 of 3
+        let keys = keygen(2, 3);
+        let sigs = sign(&keys, &[0, 1, 2], &message);
+        assert_libsecp_verifies(&sigs[1], &keys[0].y, &message);
+    }
+
+    #[test]
+    fn test_verify_rejects_degenerate_signatures() {
+        let x: FE = ECScalar::new_random();
+        let y = GE::generator() * x;
+        let message = HSha256::create_hash(&[&BigInt::from_bytes(b"degenerate")]);
+        let r: FE = ECScalar::new_random();
+        let s: FE = ECScalar::new_random();
+        for (r, s) in [(FE::zero(), s), (r, FE::zero()), (FE::zero(), FE::zero())] {
+            let sig = Signature { r, s, recid: 0 };
+            assert_eq!(verify(&sig, &y, &message), Err(Error::InvalidSignature));
+        }
+
+        // m = -r*x, so that u1*G + u2*y = (m + r*x)/s*G is the point at infinity
+        let q = FE::q();
+        let m = BigInt::mod_sub(&BigInt::zero(), &(r * x).to_big_int(), &q);
+        let sig = Signature { r, s, recid: 0 };
+        assert_eq!(verify(&sig, &y, &m), Err(Error::InvalidSignature));
+
+        // a message reduced to zero
+        assert_eq!(verify(&sig, &y, &q), Err(Error::InvalidSignature));
+    }
+
+    #[test]
+    fn test_invalid_vss_share_is_rejected() {
+        let u: FE = ECScalar::new_random();
+        let (vss, shares) = VerifiableSS::<GE>::share(1, 2, &u);
+        assert_eq!(
+            LocalKey::from_vss_shares(0, &[vss], &[shares[1]]).unwrap_err(),
+            Error::InvalidKey
+        );
+    }
+}
@@ -11,8 +11,13 @@
 //! a man in the middle attacker.
 
 use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
 
+use crate::arithmetic::traits::*;
+use crate::cryptographic_primitives::hashing::hkdf_sha256;
+use crate::cryptographic_primitives::proofs::ProofError;
 use crate::elliptic::curves::traits::*;
+use crate::BigInt;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct EcKeyPair<P: ECPoint> {
@@ -20,6 +25,69 @@ pub struct EcKeyPair<P: ECPoint> {
     secret_share: P::Scalar,
 }
 
+impl<P> EcKeyPair<P>
+where
+    P: ECPoint + Clone,
+    P::Scalar: Clone,
+{
+    pub fn generate() -> EcKeyPair<P> {
+        let secret_share: P::Scalar = ECScalar::new_random_nonzero();
+        let public_share = P::generator() * secret_share.clone();
+        EcKeyPair {
+            public_share,
+            secret_share,
+        }
+    }
+
+    /// Generates `n` independent key pairs, e.g. for the parties of a test or a benchmark
+    pub fn generate_batch(n: usize) -> Vec<EcKeyPair<P>> {
+        (0..n).map(|_| Self::generate()).collect()
+    }
+}
+
+impl<P> EcKeyPair<P>
+where
+    P: ConstantTimeBaseMul + Clone,
+    P::Scalar: Clone,
+{
+    /// Checks that the public share is the secret share times the generator, and that the secret
+    /// share isn't zero, in which case the public share would be the identity. Meant for key
+    /// pairs loaded from storage or received from elsewhere.
+    pub fn validate(&self) -> Result<(), ProofError> {
+        if self.secret_share.to_big_int() == BigInt::zero() {
+            return Err(ProofError);
+        }
+        let expected = P::scalar_mul_base_ct(&self.secret_share);
+        if expected.pk_to_key_slice() != self.public_share.pk_to_key_slice() {
+            return Err(ProofError);
+        }
+        Ok(())
+    }
+
+    /// Derives the key pair bound to `context` from `master_sk` with HKDF-SHA256, so that
+    /// per-session keys can be recomputed instead of stored. Key pairs derived for different
+    /// contexts or curves are independent.
+    pub fn derive_ephemeral(master_sk: &P::Scalar, context: &[u8]) -> EcKeyPair<P> {
+        let mut ikm = BigInt::to_bytes(&master_sk.to_big_int());
+        let mut info = Vec::with_capacity(8 + P::CURVE_NAME.len() + context.len());
+        info.extend_from_slice(&(P::CURVE_NAME.len() as u64).to_be_bytes());
+        info.extend_from_slice(P::CURVE_NAME.as_bytes());
+        info.extend_from_slice(context);
+        // 512 bits reduced modulo the group order, so the bias is negligible for every curve
+        let mut okm = hkdf_sha256::hkdf(b"curv ephemeral key", &ikm, &info, 64);
+        let mut n = BigInt::from_bytes(&okm);
+        let secret_share: P::Scalar = ECScalar::from(&n);
+        ikm.zeroize();
+        okm.zeroize();
+        n.zeroize();
+        let public_share = P::scalar_mul_base_ct(&secret_share);
+        EcKeyPair {
+            public_share,
+            secret_share,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Party1FirstMessage<P: ECPoint> {
     pub public_share: P,
@@ -102,6 +170,36 @@ where
     other_share_public_share.clone() * local_share.secret_share.clone()
 }
 
+/// Derives the key of a secure channel from the `shared` point returned by [compute_pubkey],
+/// bound to both public shares and to `info`, with HKDF-SHA256.
+///
+/// The public shares are sorted by their encoding before hashing, so both parties derive the same
+/// key whichever of them is `pk_a`.
+pub fn derive_channel_key<P>(shared: &P, pk_a: &P, pk_b: &P, info: &[u8]) -> [u8; 32]
+where
+    P: ECPoint,
+{
+    let mut ikm = shared.pk_to_key_slice();
+    let mut public_shares = [pk_a.pk_to_key_slice(), pk_b.pk_to_key_slice()];
+    public_shares.sort();
+    let mut context = Vec::new();
+    for field in [
+        P::CURVE_NAME.as_bytes(),
+        &public_shares[0],
+        &public_shares[1],
+        info,
+    ] {
+        context.extend_from_slice(&(field.len() as u64).to_be_bytes());
+        context.extend_from_slice(field);
+    }
+    let mut okm = hkdf_sha256::hkdf(b"curv channel key", &ikm, &context, 32);
+    let mut key = [0u8; 32];
+    key.copy_from_slice(&okm);
+    ikm.zeroize();
+    okm.zeroize();
+    key
+}
+
 #[cfg(test)]
 mod tests {
     use std::fmt::Debug;
@@ -109,8 +207,8 @@ mod tests {
     use crate::arithmetic::traits::*;
     use crate::cryptographic_primitives::twoparty::dh_key_exchange::*;
     use crate::elliptic::curves::traits::ECScalar;
-    use crate::test_for_all_curves;
     use crate::BigInt;
+    use crate::{test_for_all_curves, test_for_ct_curves};
 
     test_for_all_curves!(test_dh_key_exchange_random_shares);
     fn test_dh_key_exchange_random_shares<P>()
@@ -166,4 +264,109 @@ mod tests {
             g * secret_party_2
         );
     }
+
+    test_for_all_curves!(test_derive_channel_key);
+    fn test_derive_channel_key<P>()
+    where
+        P: ECPoint + Clone + Debug,
+        P::Scalar: Clone,
+    {
+        let alice = EcKeyPair::<P>::generate();
+        let bob = EcKeyPair::<P>::generate();
+        let (pk_alice, pk_bob) = (&alice.public_share, &bob.public_share);
+
+        let alice_key =
+            derive_channel_key(&compute_pubkey(&alice, pk_bob), pk_alice, pk_bob, b"chat");
+        let bob_key =
+            derive_channel_key(&compute_pubkey(&bob, pk_alice), pk_bob, pk_alice, b"chat");
+        assert_eq!(alice_key, bob_key);
+
+        let shared = compute_pubkey(&alice, pk_bob);
+        assert_ne!(
+            derive_channel_key(&shared, pk_alice, pk_bob, b"file transfer"),
+            alice_key
+        );
+        let eve = EcKeyPair::<P>::generate();
+        assert_ne!(
+            derive_channel_key(&shared, pk_alice, &eve.public_share, b"chat"),
+            alice_key
+        );
+    }
+
+    test_for_all_curves!(test_generate_batch);
+    fn test_generate_batch<P>()
+    where
+        P: ECPoint + Clone + Debug,
+        P::Scalar: Clone,
+    {
+        let key_pairs = EcKeyPair::<P>::generate_batch(8);
+        assert_eq!(key_pairs.len(), 8);
+        let g: P = ECPoint::generator();
+        for (i, key_pair) in key_pairs.iter().enumerate() {
+            assert_eq!(
+                key_pair.public_share,
+                g.clone() * key_pair.secret_share.clone()
+            );
+            for other in &key_pairs[i + 1..] {
+                assert_ne!(key_pair.public_share, other.public_share);
+            }
+        }
+        assert!(EcKeyPair::<P>::generate_batch(0).is_empty());
+    }
+
+    test_for_ct_curves!(test_derive_ephemeral);
+    fn test_derive_ephemeral<P>()
+    where
+        P: ConstantTimeBaseMul + Clone + Debug,
+        P::Scalar: Clone,
+    {
+        let master_sk: P::Scalar = ECScalar::new_random();
+        let key_pair = EcKeyPair::<P>::derive_ephemeral(&master_sk, b"session 1");
+        assert_eq!(
+            key_pair.public_share,
+            P::generator() * key_pair.secret_share.clone()
+        );
+        let again = EcKeyPair::<P>::derive_ephemeral(&master_sk, b"session 1");
+        assert_eq!(key_pair.public_share, again.public_share);
+
+        let other_context = EcKeyPair::<P>::derive_ephemeral(&master_sk, b"session 2");
+        assert_ne!(key_pair.public_share, other_context.public_share);
+        let other_master: P::Scalar = ECScalar::new_random();
+        let other_master = EcKeyPair::<P>::derive_ephemeral(&other_master, b"session 1");
+        assert_ne!(key_pair.public_share, other_master.public_share);
+    }
+
+    test_for_ct_curves!(test_validate_key_pair);
+    fn test_validate_key_pair<P>()
+    where
+        P: ConstantTimeBaseMul + Clone + Debug,
+        P::Scalar: Clone,
+    {
+        let key_pair = EcKeyPair::<P>::generate();
+        assert!(key_pair.validate().is_ok());
+        let other = EcKeyPair::<P>::generate();
+
+        let mut corrupted = key_pair.clone();
+        corrupted.public_share = other.public_share;
+        assert!(corrupted.validate().is_err());
+
+        // the identity can't be built on every curve, the public share of a zero key is
+        // rejected whatever it is
+        let zero_key = EcKeyPair {
+            public_share: P::generator(),
+            secret_share: P::Scalar::zero(),
+        };
+        assert!(zero_key.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_rejects_the_identity() {
+        use crate::elliptic::curves::curve_ristretto::{FE, GE};
+        let identity = GE::generator() * FE::zero();
+        let zero_key = EcKeyPair {
+            public_share: identity,
+            secret_share: FE::zero(),
+        };
+        assert!(zero_key.validate().is_err());
+    }
 }
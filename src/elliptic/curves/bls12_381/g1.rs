@@ -8,6 +8,17 @@
 pub const SECRET_KEY_SIZE: usize = 32;
 pub const COMPRESSED_SIZE: usize = 48;
 
+const GENERATOR_X: [u8; 48] = [
+    0x17, 0xf1, 0xd3, 0xa7, 0x31, 0x97, 0xd7, 0x94, 0x26, 0x95, 0x63, 0x8c, 0x4f, 0xa9, 0xac, 0x0f,
+    0xc3, 0x68, 0x8c, 0x4f, 0x97, 0x74, 0xb9, 0x05, 0xa1, 0x4e, 0x3a, 0x3f, 0x17, 0x1b, 0xac, 0x58,
+    0x6c, 0x55, 0xe8, 0x3f, 0xf9, 0x7a, 0x1a, 0xef, 0xfb, 0x3a, 0xf0, 0x0a, 0xdb, 0x22, 0xc6, 0xbb,
+];
+const GENERATOR_Y: [u8; 48] = [
+    0x08, 0xb3, 0xf4, 0x81, 0xe3, 0xaa, 0xa0, 0xf1, 0xa0, 0x9e, 0x30, 0xed, 0x74, 0x1d, 0x8a, 0xe4,
+    0xfc, 0xf5, 0xe0, 0x95, 0xd5, 0xd0, 0x0a, 0xf6, 0x00, 0xdb, 0x18, 0xcb, 0x2c, 0x04, 0xb3, 0xed,
+    0xd0, 0x3c, 0xc7, 0x44, 0xa2, 0x88, 0x8a, 0xe4, 0x0c, 0xaa, 0x23, 0x29, 0x46, 0xc5, 0xe7, 0xe1,
+];
+
 use std::fmt;
 use std::fmt::Debug;
 use std::ops::{Add, Mul, Neg};
@@ -19,7 +30,7 @@ use pairing_plus::hash_to_curve::HashToCurve;
 use pairing_plus::hash_to_field::ExpandMsgXmd;
 use pairing_plus::serdes::SerDes;
 use pairing_plus::EncodedPoint;
-use pairing_plus::{CurveAffine, CurveProjective, Engine};
+use pairing_plus::{CurveAffine, CurveProjective, Engine, Wnaf};
 use sha2::Sha256;
 
 use serde::de::{self, Error, MapAccess, SeqAccess, Visitor};
@@ -32,6 +43,7 @@ pub type PK = <pairing_plus::bls12_381::Bls12 as Engine>::G1Affine;
 
 use crate::arithmetic::traits::*;
 use crate::BigInt;
+use crate::ErrorCoordinates;
 use crate::ErrorKey::{self};
 
 use std::ptr;
@@ -40,6 +52,8 @@ use zeroize::Zeroize;
 
 use crate::elliptic::curves::traits::ECPoint;
 use crate::elliptic::curves::traits::ECScalar;
+
+use super::field_modulus;
 #[cfg(feature = "merkle")]
 use crypto::digest::Digest;
 #[cfg(feature = "merkle")]
@@ -114,6 +128,22 @@ impl ECScalar for FieldScalar {
         }
     }
 
+    fn from_bytes_exact(bytes: &[u8]) -> Option<FieldScalar> {
+        if bytes.len() != SECRET_KEY_SIZE {
+            return None;
+        }
+        let mut repr = SK::default().into_repr();
+        repr.read_be(Cursor::new(bytes)).ok()?;
+        Fr::from_repr(repr).ok().map(|fe| FieldScalar {
+            purpose: "from_bytes_exact",
+            fe,
+        })
+    }
+
+    fn from_bytes_mod_order(bytes: &[u8; 32]) -> FieldScalar {
+        ECScalar::from(&BigInt::from_bytes(bytes))
+    }
+
     fn to_big_int(&self) -> BigInt {
         let tmp = self.fe.into_repr();
         let scalar_u64 = tmp.as_ref();
@@ -291,6 +321,15 @@ impl ECPoint for G1Point {
     type PublicKey = PK;
     type Scalar = FieldScalar;
 
+    const CURVE_NAME: &'static str = "bls12_381_1";
+    const COMPRESSED_SIZE: usize = COMPRESSED_SIZE;
+    const GENERATOR_X: &'static [u8] = &GENERATOR_X;
+    const GENERATOR_Y: &'static [u8] = &GENERATOR_Y;
+    const GENERATOR_DERIVATION: &'static str =
+        "standard G1 generator of BLS12-381, as in draft-irtf-cfrg-pairing-friendly-curves";
+    const BASE_POINT2_DERIVATION: &'static str =
+        "H = hash_to_curve(msg = [1], dst = [1]) with the expand_message_xmd SHA-256 suite";
+
     fn base_point2() -> G1Point {
         const BASE_POINT2: [u8; 96] = [
             10, 18, 122, 36, 178, 251, 236, 31, 139, 88, 242, 163, 21, 198, 168, 208, 122, 195,
@@ -315,6 +354,13 @@ impl ECPoint for G1Point {
         }
     }
 
+    fn identity() -> Option<G1Point> {
+        Some(G1Point {
+            purpose: "identity",
+            ge: PK::zero(),
+        })
+    }
+
     fn get_element(&self) -> PK {
         self.ge
     }
@@ -361,6 +407,53 @@ impl ECPoint for G1Point {
         Ok(pk)
     }
 
+    fn deserialize_canonical(bytes: &[u8]) -> Result<G1Point, ErrorKey> {
+        if bytes.len() != COMPRESSED_SIZE {
+            return Err(ErrorKey::InvalidPublicKey);
+        }
+        // fails on unset compression flag, unreduced coordinates and points out of the subgroup
+        let ge = G1::deserialize(&mut &bytes[..], true)
+            .map_err(|_| ErrorKey::InvalidPublicKey)?
+            .into_affine();
+        let point = G1Point {
+            purpose: "from_bytes",
+            ge,
+        };
+        if point.pk_to_key_slice() != bytes {
+            return Err(ErrorKey::InvalidPublicKey);
+        }
+        Ok(point)
+    }
+
+    fn serialized_len(compressed: bool) -> usize {
+        if compressed {
+            COMPRESSED_SIZE
+        } else {
+            2 * COMPRESSED_SIZE
+        }
+    }
+
+    fn is_compressed_encoding(bytes: &[u8]) -> Option<bool> {
+        // the most significant bit flags the compressed form
+        let compressed = bytes.first()? & 0x80 != 0;
+        if bytes.len() == Self::serialized_len(compressed) {
+            Some(compressed)
+        } else {
+            None
+        }
+    }
+
+    fn negate_serialized(bytes: &[u8]) -> Result<Vec<u8>, ErrorKey> {
+        Self::deserialize_canonical(bytes)?;
+        // the third most significant bit flags the larger of y and -y, the second one the
+        // identity, which is its own negation
+        let mut negated = bytes.to_vec();
+        if negated[0] & 0x40 == 0 {
+            negated[0] ^= 0x20;
+        }
+        Ok(negated)
+    }
+
     // in this case the opposite of from_bytes: takes compressed pk to COMPRESSED_SIZE bytes.
     fn pk_to_key_slice(&self) -> Vec<u8> {
         let mut compressed_vec = vec![];
@@ -378,6 +471,34 @@ impl ECPoint for G1Point {
         }
     }
 
+    fn scalar_mul_vartime(&self, k: &FieldScalar) -> G1Point {
+        let ge_proj: G1 = Wnaf::new()
+            .scalar(k.get_element().into_repr())
+            .base(self.ge.into_projective());
+        G1Point {
+            purpose: "scalar_mul_vartime",
+            ge: ge_proj.into_affine(),
+        }
+    }
+
+    fn generator_mul(k: &FieldScalar) -> G1Point {
+        // k*G = sum 16^i*k_i*G over the 4 bits digits k_i of k, read from the precomputed table.
+        // Variable time, this group doesn't implement ConstantTimeBaseMul
+        let repr = k.get_element().into_repr();
+        let limbs = repr.as_ref();
+        let mut kg = G1::zero();
+        for (i, row) in GENERATOR_TABLE.iter().enumerate() {
+            let digit = (limbs[i / 16] >> (4 * (i % 16))) & 0xf;
+            if digit != 0 {
+                kg.add_assign(&row[digit as usize]);
+            }
+        }
+        G1Point {
+            purpose: "generator_mul",
+            ge: kg.into_affine(),
+        }
+    }
+
     fn add_point(&self, other: &PK) -> G1Point {
         let mut ge_proj: G1 = self.ge.into();
         ge_proj.add_assign_mixed(other);
@@ -396,9 +517,54 @@ impl ECPoint for G1Point {
         }
     }
 
-    fn from_coor(_x: &BigInt, _y: &BigInt) -> G1Point {
-        // TODO
-        unimplemented!();
+    fn from_coor(x: &BigInt, y: &BigInt) -> G1Point {
+        Self::from_coor_checked(x, y).expect("invalid coordinates")
+    }
+
+    fn from_coor_checked(x: &BigInt, y: &BigInt) -> Result<G1Point, ErrorCoordinates> {
+        let p = field_modulus();
+        let in_field = |c: &BigInt| *c >= BigInt::zero() && *c < p;
+        if !in_field(x) || !in_field(y) {
+            return Err(ErrorCoordinates::OutOfFieldRange);
+        }
+        if Self::curve_equation_residual(x, y) != BigInt::zero() {
+            return Err(ErrorCoordinates::NotOnCurve);
+        }
+        // x || y, the coordinates being smaller than p leave the flag bits of x unset
+        let mut point = G1Uncompressed::empty();
+        for (c, bytes) in [x, y]
+            .iter()
+            .zip(point.as_mut().chunks_mut(COMPRESSED_SIZE))
+        {
+            let c = BigInt::to_bytes(c);
+            bytes[COMPRESSED_SIZE - c.len()..].copy_from_slice(&c);
+        }
+        // the point is on the curve, decoding it can only fail the sub group check
+        let ge = point
+            .into_affine()
+            .map_err(|_| ErrorCoordinates::NotInSubgroup)?;
+        Ok(G1Point {
+            purpose: "from_coor",
+            ge,
+        })
+    }
+
+    fn from_coor_reduce(x: &BigInt, y: &BigInt) -> Result<G1Point, ErrorCoordinates> {
+        let p = field_modulus();
+        Self::from_coor_checked(&x.modulus(&p), &y.modulus(&p))
+    }
+
+    fn curve_equation_residual(x: &BigInt, y: &BigInt) -> BigInt {
+        // y^2 = x^3 + 4
+        let p = field_modulus();
+        let x_cube = BigInt::mod_pow(x, &BigInt::from(3), &p);
+        let rhs = BigInt::mod_add(&x_cube, &BigInt::from(4), &p);
+        BigInt::mod_sub(&BigInt::mod_mul(y, y, &p), &rhs, &p)
+    }
+
+    fn hash_to_curve(message: &[u8]) -> G1Point {
+        // resolves to the inherent method
+        G1Point::hash_to_curve(message)
     }
 }
 
@@ -462,6 +628,25 @@ impl Neg for G1Point {
     }
 }
 
+lazy_static::lazy_static! {
+    // GENERATOR_TABLE[i][j] = j*16^i*G, for the 64 digits of 4 bits of a scalar
+    static ref GENERATOR_TABLE: Vec<[G1; 16]> = {
+        let mut base = G1::one();
+        (0..64)
+            .map(|_| {
+                let mut row = [G1::zero(); 16];
+                for j in 1..16 {
+                    row[j] = row[j - 1];
+                    row[j].add_assign(&base);
+                }
+                base = row[15];
+                base.add_assign(&row[1]);
+                row
+            })
+            .collect()
+    };
+}
+
 #[cfg(feature = "merkle")]
 impl Hashable for G1Point {
     fn update_context(&self, context: &mut Sha3) {
@@ -554,7 +739,7 @@ mod tests {
     use pairing_plus::bls12_381::{G1Uncompressed, G1};
     use pairing_plus::hash_to_curve::HashToCurve;
     use pairing_plus::hash_to_field::ExpandMsgXmd;
-    use pairing_plus::{CurveProjective, SubgroupCheck};
+    use pairing_plus::{CurveProjective, EncodedPoint, SubgroupCheck};
     use sha2::Sha256;
 
     use super::G1Point;
@@ -724,4 +909,138 @@ mod tests {
         let base_point2: GE = ECPoint::base_point2();
         assert_eq!(point, base_point2.ge);
     }
+    #[test]
+    fn test_from_bytes_exact() {
+        let a: FE = ECScalar::new_random();
+        let a_bn_bytes = BigInt::to_bytes(&a.to_big_int());
+        let mut a_bytes = vec![0u8; 32 - a_bn_bytes.len()];
+        a_bytes.extend_from_slice(&a_bn_bytes);
+        assert_eq!(FE::from_bytes_exact(&a_bytes), Some(a));
+        assert_eq!(FE::from_bytes_exact(&[0u8; 32]), Some(FE::zero()));
+        assert!(FE::from_bytes_exact(&a_bytes[1..]).is_none());
+
+        let q_bytes = BigInt::to_bytes(&FE::q());
+        assert!(FE::from_bytes_exact(&q_bytes).is_none());
+        assert!(FE::from_bytes_exact(&[0xff; 32]).is_none());
+    }
+
+    #[test]
+    fn test_from_bytes_mod_order() {
+        // 0xfffefd...e0 as a big-endian number, reduced modulo q
+        let bytes: Vec<u8> = (0..32).map(|i| 0xff - i).collect();
+        let mut arr = [0u8; 32];
+        arr.copy_from_slice(&bytes);
+        let expected =
+            BigInt::from_hex("1823af56a8bfff68918345e4e0af41e64873a5e6ebee31eae7e6e5e6e3e2e1de")
+                .unwrap();
+        assert_eq!(FE::from_bytes_mod_order(&arr).to_big_int(), expected);
+        assert_eq!(FE::from_bytes_mod_order(&[0u8; 32]), FE::zero());
+    }
+
+    #[test]
+    fn test_curve_equation_residual() {
+        let g: GE = ECPoint::generator();
+        let (x, y) = (g.x_coor().unwrap(), g.y_coor().unwrap());
+        assert_eq!(GE::curve_equation_residual(&x, &y), BigInt::zero());
+        let y_plus_one = y + BigInt::one();
+        assert_ne!(GE::curve_equation_residual(&x, &y_plus_one), BigInt::zero());
+    }
+
+    #[test]
+    fn test_negate_serialized() {
+        let mut infinity = vec![0u8; GE::COMPRESSED_SIZE];
+        infinity[0] = 0xc0;
+        assert_eq!(GE::negate_serialized(&infinity).unwrap(), infinity);
+    }
+
+    #[test]
+    fn test_deserialize_canonical() {
+        let g: GE = ECPoint::generator();
+        let compressed = g.pk_to_key_slice();
+        assert_eq!(GE::deserialize_canonical(&compressed).unwrap(), g);
+        let mut infinity = vec![0u8; compressed.len()];
+        infinity[0] = 0xc0;
+        assert!(GE::is_canonical_encoding(&infinity));
+
+        let uncompressed = G1Uncompressed::from_affine(g.get_element());
+        assert!(!GE::is_canonical_encoding(uncompressed.as_ref()));
+        // from_bytes ignores trailing bytes and left pads short encodings
+        let mut trailing = compressed.clone();
+        trailing.push(0);
+        assert!(!GE::is_canonical_encoding(&trailing));
+        assert!(!GE::is_canonical_encoding(&compressed[1..]));
+
+        let mut uncompressed_flag = compressed.clone();
+        uncompressed_flag[0] &= 0x7f;
+        assert!(!GE::is_canonical_encoding(&uncompressed_flag));
+        let mut unreduced = vec![0xffu8; compressed.len()];
+        unreduced[0] = 0x9f;
+        assert!(!GE::is_canonical_encoding(&unreduced));
+        let mut infinity_with_data = infinity;
+        infinity_with_data[compressed.len() - 1] = 1;
+        assert!(!GE::is_canonical_encoding(&infinity_with_data));
+    }
+
+    #[test]
+    fn test_from_coor_checked() {
+        use crate::ErrorCoordinates;
+        let p = super::field_modulus();
+        let g: GE = ECPoint::generator();
+        let (x, y) = (g.x_coor().unwrap(), g.y_coor().unwrap());
+        let point = GE::from_coor_checked(&x, &y).unwrap();
+        assert_eq!(point.pk_to_key_slice(), g.pk_to_key_slice());
+        assert_eq!(
+            GE::from_coor_checked(&p, &y).err(),
+            Some(ErrorCoordinates::OutOfFieldRange)
+        );
+        assert_eq!(
+            GE::from_coor_checked(&x, &BigInt::from(-1)).err(),
+            Some(ErrorCoordinates::OutOfFieldRange)
+        );
+        assert_eq!(
+            GE::from_coor_checked(&x, &(&y + BigInt::one())).err(),
+            Some(ErrorCoordinates::NotOnCurve)
+        );
+
+        // the first curve point by x, which the huge cofactor of G1 keeps out of the sub group
+        let (x, y) = (1..)
+            .find_map(|x| {
+                let x = BigInt::from(x);
+                let rhs = BigInt::mod_add(
+                    &BigInt::mod_pow(&x, &BigInt::from(3), &p),
+                    &BigInt::from(4),
+                    &p,
+                );
+                // p = 3 mod 4, a square root of a square is rhs^((p+1)/4)
+                let y = BigInt::mod_pow(&rhs, &((&p + BigInt::one()) >> 2), &p);
+                if BigInt::mod_mul(&y, &y, &p) == rhs {
+                    Some((x, y))
+                } else {
+                    None
+                }
+            })
+            .unwrap();
+        assert_eq!(
+            GE::from_coor_checked(&x, &y).err(),
+            Some(ErrorCoordinates::NotInSubgroup)
+        );
+    }
+
+    #[test]
+    fn test_from_coor_reduce() {
+        use crate::ErrorCoordinates;
+        let p = super::field_modulus();
+        let g: GE = ECPoint::generator();
+        let (x, y) = (g.x_coor().unwrap(), g.y_coor().unwrap());
+        let point = GE::from_coor_reduce(&(&x + &p), &(&y - &p)).unwrap();
+        assert_eq!(point.pk_to_key_slice(), g.pk_to_key_slice());
+        assert_eq!(
+            GE::from_coor_checked(&(&x + &p), &y).err(),
+            Some(ErrorCoordinates::OutOfFieldRange)
+        );
+        assert_eq!(
+            GE::from_coor_reduce(&(&x + &p), &(y + BigInt::one())).err(),
+            Some(ErrorCoordinates::NotOnCurve)
+        );
+    }
 }
@@ -8,6 +8,24 @@
 pub const SECRET_KEY_SIZE: usize = 32;
 pub const COMPRESSED_SIZE: usize = 96;
 
+// coordinates c1 * 2^384 + c0 over Fp2 = Fp[u]/(u^2 + 1), the layout of x_coor and y_coor
+const GENERATOR_X: [u8; 96] = [
+    0x13, 0xe0, 0x2b, 0x60, 0x52, 0x71, 0x9f, 0x60, 0x7d, 0xac, 0xd3, 0xa0, 0x88, 0x27, 0x4f, 0x65,
+    0x59, 0x6b, 0xd0, 0xd0, 0x99, 0x20, 0xb6, 0x1a, 0xb5, 0xda, 0x61, 0xbb, 0xdc, 0x7f, 0x50, 0x49,
+    0x33, 0x4c, 0xf1, 0x12, 0x13, 0x94, 0x5d, 0x57, 0xe5, 0xac, 0x7d, 0x05, 0x5d, 0x04, 0x2b, 0x7e,
+    0x02, 0x4a, 0xa2, 0xb2, 0xf0, 0x8f, 0x0a, 0x91, 0x26, 0x08, 0x05, 0x27, 0x2d, 0xc5, 0x10, 0x51,
+    0xc6, 0xe4, 0x7a, 0xd4, 0xfa, 0x40, 0x3b, 0x02, 0xb4, 0x51, 0x0b, 0x64, 0x7a, 0xe3, 0xd1, 0x77,
+    0x0b, 0xac, 0x03, 0x26, 0xa8, 0x05, 0xbb, 0xef, 0xd4, 0x80, 0x56, 0xc8, 0xc1, 0x21, 0xbd, 0xb8,
+];
+const GENERATOR_Y: [u8; 96] = [
+    0x06, 0x06, 0xc4, 0xa0, 0x2e, 0xa7, 0x34, 0xcc, 0x32, 0xac, 0xd2, 0xb0, 0x2b, 0xc2, 0x8b, 0x99,
+    0xcb, 0x3e, 0x28, 0x7e, 0x85, 0xa7, 0x63, 0xaf, 0x26, 0x74, 0x92, 0xab, 0x57, 0x2e, 0x99, 0xab,
+    0x3f, 0x37, 0x0d, 0x27, 0x5c, 0xec, 0x1d, 0xa1, 0xaa, 0xa9, 0x07, 0x5f, 0xf0, 0x5f, 0x79, 0xbe,
+    0x0c, 0xe5, 0xd5, 0x27, 0x72, 0x7d, 0x6e, 0x11, 0x8c, 0xc9, 0xcd, 0xc6, 0xda, 0x2e, 0x35, 0x1a,
+    0xad, 0xfd, 0x9b, 0xaa, 0x8c, 0xbd, 0xd3, 0xa7, 0x6d, 0x42, 0x9a, 0x69, 0x51, 0x60, 0xd1, 0x2c,
+    0x92, 0x3a, 0xc9, 0xcc, 0x3b, 0xac, 0xa2, 0x89, 0xe1, 0x93, 0x54, 0x86, 0x08, 0xb8, 0x28, 0x01,
+];
+
 use std::fmt;
 use std::fmt::Debug;
 use std::ops::{Add, Mul, Neg};
@@ -22,7 +40,7 @@ use pairing_plus::hash_to_curve::HashToCurve;
 use pairing_plus::hash_to_field::ExpandMsgXmd;
 use pairing_plus::serdes::SerDes;
 use pairing_plus::EncodedPoint;
-use pairing_plus::{CurveAffine, CurveProjective, Engine};
+use pairing_plus::{CurveAffine, CurveProjective, Engine, Wnaf};
 use sha2::Sha256;
 
 use serde::de::{self, Error, MapAccess, SeqAccess, Visitor};
@@ -35,6 +53,7 @@ pub type PK = <pairing_plus::bls12_381::Bls12 as Engine>::G2Affine;
 
 use crate::arithmetic::traits::*;
 use crate::BigInt;
+use crate::ErrorCoordinates;
 use crate::ErrorKey::{self};
 
 use std::ptr;
@@ -43,6 +62,8 @@ use zeroize::Zeroize;
 
 use crate::elliptic::curves::traits::ECPoint;
 use crate::elliptic::curves::traits::ECScalar;
+
+use super::field_modulus;
 #[cfg(feature = "merkle")]
 use crypto::digest::Digest;
 #[cfg(feature = "merkle")]
@@ -117,6 +138,22 @@ impl ECScalar for FieldScalar {
         }
     }
 
+    fn from_bytes_exact(bytes: &[u8]) -> Option<FieldScalar> {
+        if bytes.len() != SECRET_KEY_SIZE {
+            return None;
+        }
+        let mut repr = SK::default().into_repr();
+        repr.read_be(Cursor::new(bytes)).ok()?;
+        Fr::from_repr(repr).ok().map(|fe| FieldScalar {
+            purpose: "from_bytes_exact",
+            fe,
+        })
+    }
+
+    fn from_bytes_mod_order(bytes: &[u8; 32]) -> FieldScalar {
+        ECScalar::from(&BigInt::from_bytes(bytes))
+    }
+
     fn to_big_int(&self) -> BigInt {
         let tmp = self.fe.into_repr();
         let scalar_u64 = tmp.as_ref();
@@ -293,6 +330,15 @@ impl ECPoint for G2Point {
     type PublicKey = PK;
     type Scalar = FieldScalar;
 
+    const CURVE_NAME: &'static str = "bls12_381_2";
+    const COMPRESSED_SIZE: usize = COMPRESSED_SIZE;
+    const GENERATOR_X: &'static [u8] = &GENERATOR_X;
+    const GENERATOR_Y: &'static [u8] = &GENERATOR_Y;
+    const GENERATOR_DERIVATION: &'static str =
+        "standard G2 generator of BLS12-381, as in draft-irtf-cfrg-pairing-friendly-curves";
+    const BASE_POINT2_DERIVATION: &'static str =
+        "H = hash_to_curve(msg = [1], dst = [1]) with the expand_message_xmd SHA-256 suite";
+
     fn base_point2() -> G2Point {
         const BASE_POINT2: [u8; 192] = [
             0, 204, 165, 72, 21, 96, 36, 119, 117, 242, 58, 55, 105, 140, 136, 76, 180, 140, 92,
@@ -323,6 +369,13 @@ impl ECPoint for G2Point {
         }
     }
 
+    fn identity() -> Option<G2Point> {
+        Some(G2Point {
+            purpose: "identity",
+            ge: PK::zero(),
+        })
+    }
+
     fn get_element(&self) -> PK {
         self.ge
     }
@@ -370,6 +423,53 @@ impl ECPoint for G2Point {
         Ok(pk)
     }
 
+    fn deserialize_canonical(bytes: &[u8]) -> Result<G2Point, ErrorKey> {
+        if bytes.len() != COMPRESSED_SIZE {
+            return Err(ErrorKey::InvalidPublicKey);
+        }
+        // fails on unset compression flag, unreduced coordinates and points out of the subgroup
+        let ge = G2::deserialize(&mut &bytes[..], true)
+            .map_err(|_| ErrorKey::InvalidPublicKey)?
+            .into_affine();
+        let point = G2Point {
+            purpose: "from_bytes",
+            ge,
+        };
+        if point.pk_to_key_slice() != bytes {
+            return Err(ErrorKey::InvalidPublicKey);
+        }
+        Ok(point)
+    }
+
+    fn serialized_len(compressed: bool) -> usize {
+        if compressed {
+            COMPRESSED_SIZE
+        } else {
+            2 * COMPRESSED_SIZE
+        }
+    }
+
+    fn is_compressed_encoding(bytes: &[u8]) -> Option<bool> {
+        // the most significant bit flags the compressed form
+        let compressed = bytes.first()? & 0x80 != 0;
+        if bytes.len() == Self::serialized_len(compressed) {
+            Some(compressed)
+        } else {
+            None
+        }
+    }
+
+    fn negate_serialized(bytes: &[u8]) -> Result<Vec<u8>, ErrorKey> {
+        Self::deserialize_canonical(bytes)?;
+        // the third most significant bit flags the larger of y and -y, the second one the
+        // identity, which is its own negation
+        let mut negated = bytes.to_vec();
+        if negated[0] & 0x40 == 0 {
+            negated[0] ^= 0x20;
+        }
+        Ok(negated)
+    }
+
     // in this case the opposite of from_bytes: takes compressed pk to COMPRESSED_SIZE bytes.
     fn pk_to_key_slice(&self) -> Vec<u8> {
         let mut compressed_vec = vec![];
@@ -387,6 +487,34 @@ impl ECPoint for G2Point {
         }
     }
 
+    fn scalar_mul_vartime(&self, k: &FieldScalar) -> G2Point {
+        let ge_proj: G2 = Wnaf::new()
+            .scalar(k.get_element().into_repr())
+            .base(self.ge.into_projective());
+        G2Point {
+            purpose: "scalar_mul_vartime",
+            ge: ge_proj.into_affine(),
+        }
+    }
+
+    fn generator_mul(k: &FieldScalar) -> G2Point {
+        // k*G = sum 16^i*k_i*G over the 4 bits digits k_i of k, read from the precomputed table.
+        // Variable time, this group doesn't implement ConstantTimeBaseMul
+        let repr = k.get_element().into_repr();
+        let limbs = repr.as_ref();
+        let mut kg = G2::zero();
+        for (i, row) in GENERATOR_TABLE.iter().enumerate() {
+            let digit = (limbs[i / 16] >> (4 * (i % 16))) & 0xf;
+            if digit != 0 {
+                kg.add_assign(&row[digit as usize]);
+            }
+        }
+        G2Point {
+            purpose: "generator_mul",
+            ge: kg.into_affine(),
+        }
+    }
+
     fn add_point(&self, other: &PK) -> G2Point {
         let mut ge_proj: G2 = self.ge.into();
         ge_proj.add_assign_mixed(other);
@@ -405,9 +533,75 @@ impl ECPoint for G2Point {
         }
     }
 
-    fn from_coor(_x: &BigInt, _y: &BigInt) -> G2Point {
-        // TODO
-        unimplemented!();
+    fn from_coor(x: &BigInt, y: &BigInt) -> G2Point {
+        Self::from_coor_checked(x, y).expect("invalid coordinates")
+    }
+
+    /// Coordinates are elements c0 + c1*u of Fp2 encoded as c1 * 2^384 + c0, as by
+    /// [x_coor](ECPoint::x_coor), each of c0 and c1 having to be smaller than p
+    fn from_coor_checked(x: &BigInt, y: &BigInt) -> Result<G2Point, ErrorCoordinates> {
+        let p = field_modulus();
+        let in_field = |c: &BigInt| {
+            let (c0, c1) = fp2_from_big_int(c);
+            *c >= BigInt::zero() && c0 < p && c1 < p
+        };
+        if !in_field(x) || !in_field(y) {
+            return Err(ErrorCoordinates::OutOfFieldRange);
+        }
+        if Self::curve_equation_residual(x, y) != BigInt::zero() {
+            return Err(ErrorCoordinates::NotOnCurve);
+        }
+        // x || y, the components being smaller than p leave the flag bits of x unset
+        let mut point = G2Uncompressed::empty();
+        for (c, bytes) in [x, y]
+            .iter()
+            .zip(point.as_mut().chunks_mut(COMPRESSED_SIZE))
+        {
+            let c = BigInt::to_bytes(c);
+            bytes[COMPRESSED_SIZE - c.len()..].copy_from_slice(&c);
+        }
+        // the point is on the curve, decoding it can only fail the sub group check
+        let ge = point
+            .into_affine()
+            .map_err(|_| ErrorCoordinates::NotInSubgroup)?;
+        Ok(G2Point {
+            purpose: "from_coor",
+            ge,
+        })
+    }
+
+    /// Reduces both components c0 and c1 of the coordinates modulo p
+    fn from_coor_reduce(x: &BigInt, y: &BigInt) -> Result<G2Point, ErrorCoordinates> {
+        let p = field_modulus();
+        let half = 8 * COMPRESSED_SIZE / 2;
+        let reduce = |c: &BigInt| {
+            let c0 = c.modulus(&(BigInt::one() << half));
+            let c1 = (c - &c0) >> half;
+            (c1.modulus(&p) << half) + c0.modulus(&p)
+        };
+        Self::from_coor_checked(&reduce(x), &reduce(y))
+    }
+
+    fn curve_equation_residual(x: &BigInt, y: &BigInt) -> BigInt {
+        // y^2 = x^3 + 4(u + 1) over Fp2 = Fp[u]/(u^2 + 1). An element c0 + c1*u is encoded as
+        // c1 * 2^384 + c0, the layout of x_coor and y_coor
+        let p = field_modulus();
+        let x = fp2_from_big_int(x);
+        let y = fp2_from_big_int(y);
+        let x_cube = fp2_mul(&fp2_mul(&x, &x, &p), &x, &p);
+        let b = (BigInt::from(4), BigInt::from(4));
+        let rhs = fp2_add(&x_cube, &b, &p);
+        let lhs = fp2_mul(&y, &y, &p);
+        let residual = (
+            BigInt::mod_sub(&lhs.0, &rhs.0, &p),
+            BigInt::mod_sub(&lhs.1, &rhs.1, &p),
+        );
+        (residual.1 << (8 * COMPRESSED_SIZE / 2)) + residual.0
+    }
+
+    fn hash_to_curve(message: &[u8]) -> G2Point {
+        // resolves to the inherent method
+        G2Point::hash_to_curve(message)
     }
 }
 
@@ -471,6 +665,25 @@ impl Neg for G2Point {
     }
 }
 
+lazy_static::lazy_static! {
+    // GENERATOR_TABLE[i][j] = j*16^i*G, for the 64 digits of 4 bits of a scalar
+    static ref GENERATOR_TABLE: Vec<[G2; 16]> = {
+        let mut base = G2::one();
+        (0..64)
+            .map(|_| {
+                let mut row = [G2::zero(); 16];
+                for j in 1..16 {
+                    row[j] = row[j - 1];
+                    row[j].add_assign(&base);
+                }
+                base = row[15];
+                base.add_assign(&row[1]);
+                row
+            })
+            .collect()
+    };
+}
+
 #[cfg(feature = "merkle")]
 impl Hashable for G2Point {
     fn update_context(&self, context: &mut Sha3) {
@@ -558,12 +771,44 @@ impl G2Point {
     }
 }
 
+/// Element c0 + c1*u of Fp2, as (c0, c1)
+type Fp2 = (BigInt, BigInt);
+
+fn fp2_from_big_int(n: &BigInt) -> Fp2 {
+    let half = 8 * COMPRESSED_SIZE / 2;
+    let c0 = n.modulus(&(BigInt::one() << half));
+    let c1 = n >> half;
+    (c0, c1)
+}
+
+fn fp2_add(a: &Fp2, b: &Fp2, p: &BigInt) -> Fp2 {
+    (
+        BigInt::mod_add(&a.0, &b.0, p),
+        BigInt::mod_add(&a.1, &b.1, p),
+    )
+}
+
+fn fp2_mul(a: &Fp2, b: &Fp2, p: &BigInt) -> Fp2 {
+    // u^2 = -1
+    let c0 = BigInt::mod_sub(
+        &BigInt::mod_mul(&a.0, &b.0, p),
+        &BigInt::mod_mul(&a.1, &b.1, p),
+        p,
+    );
+    let c1 = BigInt::mod_add(
+        &BigInt::mod_mul(&a.0, &b.1, p),
+        &BigInt::mod_mul(&a.1, &b.0, p),
+        p,
+    );
+    (c0, c1)
+}
+
 #[cfg(test)]
 mod tests {
     use pairing_plus::bls12_381::{G2Uncompressed, G2};
     use pairing_plus::hash_to_curve::HashToCurve;
     use pairing_plus::hash_to_field::ExpandMsgXmd;
-    use pairing_plus::{CurveProjective, SubgroupCheck};
+    use pairing_plus::{CurveProjective, EncodedPoint, SubgroupCheck};
     use sha2::Sha256;
 
     use super::G2Point;
@@ -732,4 +977,152 @@ mod tests {
         let base_point2: GE = ECPoint::base_point2();
         assert_eq!(point, base_point2.ge);
     }
+    #[test]
+    fn test_from_bytes_exact() {
+        let a: FE = ECScalar::new_random();
+        let a_bn_bytes = BigInt::to_bytes(&a.to_big_int());
+        let mut a_bytes = vec![0u8; 32 - a_bn_bytes.len()];
+        a_bytes.extend_from_slice(&a_bn_bytes);
+        assert_eq!(FE::from_bytes_exact(&a_bytes), Some(a));
+        assert_eq!(FE::from_bytes_exact(&[0u8; 32]), Some(FE::zero()));
+        assert!(FE::from_bytes_exact(&a_bytes[1..]).is_none());
+
+        let q_bytes = BigInt::to_bytes(&FE::q());
+        assert!(FE::from_bytes_exact(&q_bytes).is_none());
+        assert!(FE::from_bytes_exact(&[0xff; 32]).is_none());
+    }
+
+    #[test]
+    fn test_from_bytes_mod_order() {
+        // 0xfffefd...e0 as a big-endian number, reduced modulo q
+        let bytes: Vec<u8> = (0..32).map(|i| 0xff - i).collect();
+        let mut arr = [0u8; 32];
+        arr.copy_from_slice(&bytes);
+        let expected =
+            BigInt::from_hex("1823af56a8bfff68918345e4e0af41e64873a5e6ebee31eae7e6e5e6e3e2e1de")
+                .unwrap();
+        assert_eq!(FE::from_bytes_mod_order(&arr).to_big_int(), expected);
+        assert_eq!(FE::from_bytes_mod_order(&[0u8; 32]), FE::zero());
+    }
+
+    #[test]
+    fn test_curve_equation_residual() {
+        let g: GE = ECPoint::generator();
+        let (x, y) = (g.x_coor().unwrap(), g.y_coor().unwrap());
+        assert_eq!(GE::curve_equation_residual(&x, &y), BigInt::zero());
+        let y_plus_one = y + BigInt::one();
+        assert_ne!(GE::curve_equation_residual(&x, &y_plus_one), BigInt::zero());
+    }
+
+    #[test]
+    fn test_negate_serialized() {
+        for _ in 0..10 {
+            let point = GE::generator() * FE::new_random();
+            let negated = GE::negate_serialized(&point.pk_to_key_slice()).unwrap();
+            assert_eq!(negated, (-point).pk_to_key_slice());
+        }
+        let mut infinity = vec![0u8; GE::COMPRESSED_SIZE];
+        infinity[0] = 0xc0;
+        assert_eq!(GE::negate_serialized(&infinity).unwrap(), infinity);
+    }
+
+    #[test]
+    fn test_is_compressed_encoding() {
+        let g: GE = ECPoint::generator();
+        let compressed = g.pk_to_key_slice();
+        assert_eq!(GE::is_compressed_encoding(&compressed), Some(true));
+        let uncompressed = G2Uncompressed::from_affine(g.get_element());
+        assert_eq!(GE::serialized_len(false), uncompressed.as_ref().len());
+        assert_eq!(
+            GE::is_compressed_encoding(uncompressed.as_ref()),
+            Some(false)
+        );
+        // the first half of an uncompressed encoding lacks the compression flag
+        let truncated = &uncompressed.as_ref()[..GE::COMPRESSED_SIZE];
+        assert_eq!(GE::is_compressed_encoding(truncated), None);
+    }
+
+    #[test]
+    fn test_deserialize_canonical() {
+        let g: GE = ECPoint::generator();
+        let compressed = g.pk_to_key_slice();
+        assert_eq!(GE::deserialize_canonical(&compressed).unwrap(), g);
+        let mut infinity = vec![0u8; compressed.len()];
+        infinity[0] = 0xc0;
+        assert!(GE::is_canonical_encoding(&infinity));
+
+        let uncompressed = G2Uncompressed::from_affine(g.get_element());
+        assert!(!GE::is_canonical_encoding(uncompressed.as_ref()));
+        // from_bytes ignores trailing bytes and left pads short encodings
+        let mut trailing = compressed.clone();
+        trailing.push(0);
+        assert!(!GE::is_canonical_encoding(&trailing));
+        assert!(!GE::is_canonical_encoding(&compressed[1..]));
+
+        let mut uncompressed_flag = compressed.clone();
+        uncompressed_flag[0] &= 0x7f;
+        assert!(!GE::is_canonical_encoding(&uncompressed_flag));
+        let mut unreduced = vec![0xffu8; compressed.len()];
+        unreduced[0] = 0x9f;
+        assert!(!GE::is_canonical_encoding(&unreduced));
+        let mut infinity_with_data = infinity;
+        infinity_with_data[compressed.len() - 1] = 1;
+        assert!(!GE::is_canonical_encoding(&infinity_with_data));
+    }
+
+    #[test]
+    fn test_generator_constants() {
+        let g: GE = ECPoint::generator();
+        let x = BigInt::from_bytes(GE::GENERATOR_X);
+        let y = BigInt::from_bytes(GE::GENERATOR_Y);
+        assert_eq!(GE::curve_equation_residual(&x, &y), BigInt::zero());
+        assert_eq!(g.x_coor(), Some(x));
+        assert_eq!(g.y_coor(), Some(y));
+    }
+
+    #[test]
+    fn test_from_coor_checked() {
+        use crate::ErrorCoordinates;
+        let p = super::field_modulus();
+        let half = 8 * super::COMPRESSED_SIZE / 2;
+        let g: GE = ECPoint::generator();
+        let (x, y) = (g.x_coor().unwrap(), g.y_coor().unwrap());
+        let point = GE::from_coor_checked(&x, &y).unwrap();
+        assert_eq!(point.pk_to_key_slice(), g.pk_to_key_slice());
+        // c0 or c1 of x not smaller than p
+        let (c0, c1) = super::fp2_from_big_int(&x);
+        let c0_out = (&c1 << half) + &c0 + &p;
+        let c1_out = ((&c1 + &p) << half) + &c0;
+        for x in [&c0_out, &c1_out, &BigInt::from(-1)] {
+            assert_eq!(
+                GE::from_coor_checked(x, &y).err(),
+                Some(ErrorCoordinates::OutOfFieldRange)
+            );
+        }
+        assert_eq!(
+            GE::from_coor_checked(&x, &(&y + BigInt::one())).err(),
+            Some(ErrorCoordinates::NotOnCurve)
+        );
+    }
+
+    #[test]
+    fn test_from_coor_reduce() {
+        use crate::ErrorCoordinates;
+        let p = super::field_modulus();
+        let half = 8 * super::COMPRESSED_SIZE / 2;
+        let g: GE = ECPoint::generator();
+        let (x, y) = (g.x_coor().unwrap(), g.y_coor().unwrap());
+        let (c0, c1) = super::fp2_from_big_int(&x);
+        let c0_out = (&c1 << half) + &c0 + &p;
+        let c1_out = ((&c1 + &p) << half) + &c0;
+        // reducing either component gives the point back
+        for x in [&c0_out, &c1_out] {
+            let point = GE::from_coor_reduce(x, &y).unwrap();
+            assert_eq!(point.pk_to_key_slice(), g.pk_to_key_slice());
+        }
+        assert_eq!(
+            GE::from_coor_reduce(&c0_out, &(y + BigInt::one())).err(),
+            Some(ErrorCoordinates::NotOnCurve)
+        );
+    }
 }
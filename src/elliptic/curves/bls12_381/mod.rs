@@ -1,14 +1,24 @@
 pub mod g1;
 pub mod g2;
 
+use crate::arithmetic::traits::*;
 use crate::elliptic::curves::bls12_381::g1::GE as GE1;
 use crate::elliptic::curves::bls12_381::g2::GE as GE2;
 use crate::elliptic::curves::traits::ECPoint;
+use crate::BigInt;
 
 use ff_zeroize::Field;
 use pairing_plus::bls12_381::{Bls12, Fq12};
 use pairing_plus::{CurveAffine, Engine};
 
+/// Prime of the field the coordinates of G1 points (and the components of G2 ones) lie in
+pub(crate) fn field_modulus() -> BigInt {
+    BigInt::from_hex(
+        "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffaaab",
+    )
+    .unwrap()
+}
+
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub struct Pair {
     pub e: Fq12,
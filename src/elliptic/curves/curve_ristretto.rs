@@ -6,21 +6,25 @@
     License MIT: <https://github.com/KZen-networks/curv/blob/master/LICENSE>
 */
 
-use super::traits::{ECPoint, ECScalar};
+use super::traits::{ConstantTimeBaseMul, ECPoint, ECScalar};
 use crate::arithmetic::traits::*;
 use crate::cryptographic_primitives::hashing::hash_sha256::HSha256;
 use crate::cryptographic_primitives::hashing::traits::Hash;
 use crate::BigInt;
+use crate::ErrorCoordinates;
 use crate::ErrorKey::{self, InvalidPublicKey};
 use curve25519_dalek::constants::BASEPOINT_ORDER;
 use curve25519_dalek::constants::RISTRETTO_BASEPOINT_COMPRESSED;
-use curve25519_dalek::ristretto::CompressedRistretto;
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_TABLE;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
 use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::Identity;
 use rand::thread_rng;
 use serde::de::{self, Error, MapAccess, SeqAccess, Visitor};
 use serde::ser::SerializeStruct;
 use serde::ser::{Serialize, Serializer};
 use serde::{Deserialize, Deserializer};
+use sha2::Sha512;
 use std::fmt;
 use std::ops::{Add, Mul};
 use std::str;
@@ -30,6 +34,7 @@ pub const NUM_OF_COORDINATES: usize = 4;
 
 use std::ptr;
 use std::sync::atomic;
+use subtle::{Choice, ConstantTimeEq};
 use zeroize::Zeroize;
 
 #[cfg(feature = "merkle")]
@@ -39,6 +44,15 @@ use crypto::sha3::Sha3;
 #[cfg(feature = "merkle")]
 use merkle::Hashable;
 
+const GENERATOR_X: [u8; 32] = [
+    0x21, 0x69, 0x36, 0xd3, 0xcd, 0x6e, 0x53, 0xfe, 0xc0, 0xa4, 0xe2, 0x31, 0xfd, 0xd6, 0xdc, 0x5c,
+    0x69, 0x2c, 0xc7, 0x60, 0x95, 0x25, 0xa7, 0xb2, 0xc9, 0x56, 0x2d, 0x60, 0x8f, 0x25, 0xd5, 0x1a,
+];
+const GENERATOR_Y: [u8; 32] = [
+    0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66,
+    0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x58,
+];
+
 pub type SK = Scalar;
 pub type PK = CompressedRistretto;
 
@@ -124,6 +138,25 @@ impl ECScalar for RistrettoScalar {
         }
     }
 
+    fn from_bytes_exact(bytes: &[u8]) -> Option<RistrettoScalar> {
+        if bytes.len() != SECRET_KEY_SIZE {
+            return None;
+        }
+        let mut bytes_array_32 = [0u8; SECRET_KEY_SIZE];
+        bytes_array_32.copy_from_slice(bytes);
+        SK::from_canonical_bytes(bytes_array_32).map(|fe| RistrettoScalar {
+            purpose: "from_bytes_exact",
+            fe,
+        })
+    }
+
+    fn from_bytes_mod_order(bytes: &[u8; 32]) -> RistrettoScalar {
+        RistrettoScalar {
+            purpose: "from_bytes_mod_order",
+            fe: SK::from_bytes_mod_order(*bytes),
+        }
+    }
+
     fn to_big_int(&self) -> BigInt {
         let t1 = &self.fe.to_bytes()[0..self.fe.to_bytes().len()];
         let mut t2 = t1.to_vec();
@@ -168,6 +201,20 @@ impl ECScalar for RistrettoScalar {
             fe: inv,
         }
     }
+
+    fn invert_ct(&self) -> Option<RistrettoScalar> {
+        if bool::from(self.fe.as_bytes().ct_eq(&[0u8; 32])) {
+            return None;
+        }
+        Some(RistrettoScalar {
+            purpose: "invert_ct",
+            fe: self.fe.invert(),
+        })
+    }
+
+    fn ct_eq(&self, other: &RistrettoScalar) -> Choice {
+        self.fe.ct_eq(&other.fe)
+    }
 }
 
 impl Mul<RistrettoScalar> for RistrettoScalar {
@@ -256,6 +303,16 @@ impl ECPoint for RistrettoCurvPoint {
     type PublicKey = PK;
     type Scalar = RistrettoScalar;
 
+    const CURVE_NAME: &'static str = "ristretto";
+    const COMPRESSED_SIZE: usize = 32;
+    // coordinates of the edwards25519 base point, the representative of the generator
+    const GENERATOR_X: &'static [u8] = &GENERATOR_X;
+    const GENERATOR_Y: &'static [u8] = &GENERATOR_Y;
+    const GENERATOR_DERIVATION: &'static str =
+        "ristretto255 encoding of the edwards25519 base point of RFC 8032, section 5.1";
+    const BASE_POINT2_DERIVATION: &'static str =
+        "H = decode(SHA256(G)) over the encoding of the generator G";
+
     fn base_point2() -> RistrettoCurvPoint {
         let g: GE = ECPoint::generator();
         let hash = HSha256::create_hash(&[&g.bytes_compressed_to_big_int()]);
@@ -274,6 +331,13 @@ impl ECPoint for RistrettoCurvPoint {
         }
     }
 
+    fn identity() -> Option<RistrettoCurvPoint> {
+        Some(RistrettoCurvPoint {
+            purpose: "identity",
+            ge: CompressedRistretto::identity(),
+        })
+    }
+
     fn get_element(&self) -> PK {
         self.ge
     }
@@ -338,6 +402,27 @@ impl ECPoint for RistrettoCurvPoint {
         }
     }
 
+    fn deserialize_canonical(bytes: &[u8]) -> Result<RistrettoCurvPoint, ErrorKey> {
+        if bytes.len() != 32 {
+            return Err(InvalidPublicKey);
+        }
+        let point = CompressedRistretto::from_slice(bytes)
+            .decompress()
+            .ok_or(InvalidPublicKey)?;
+        let ge = point.compress();
+        if ge.as_bytes()[..] != bytes[..] {
+            return Err(InvalidPublicKey);
+        }
+        Ok(RistrettoCurvPoint {
+            purpose: "from_bytes",
+            ge,
+        })
+    }
+
+    fn serialized_len(_compressed: bool) -> usize {
+        32
+    }
+
     fn pk_to_key_slice(&self) -> Vec<u8> {
         let result = self.ge.to_bytes();
         result.to_vec()
@@ -351,6 +436,41 @@ impl ECPoint for RistrettoCurvPoint {
         }
     }
 
+    fn scalar_mul_vartime(&self, k: &RistrettoScalar) -> RistrettoCurvPoint {
+        // k*self + 0*B
+        let point = self.ge.decompress().unwrap();
+        RistrettoCurvPoint {
+            purpose: "scalar_mul_vartime",
+            ge: RistrettoPoint::vartime_double_scalar_mul_basepoint(
+                &k.get_element(),
+                &point,
+                &Scalar::zero(),
+            )
+            .compress(),
+        }
+    }
+
+    fn generator_mul(k: &RistrettoScalar) -> RistrettoCurvPoint {
+        Self::scalar_mul_base_ct(k)
+    }
+
+    fn double_scalar_mul_base(
+        k: &RistrettoScalar,
+        point: &RistrettoCurvPoint,
+        e: &RistrettoScalar,
+    ) -> Option<RistrettoCurvPoint> {
+        let point = point.ge.decompress().unwrap();
+        Some(RistrettoCurvPoint {
+            purpose: "double_scalar_mul_base",
+            ge: RistrettoPoint::vartime_double_scalar_mul_basepoint(
+                &e.get_element(),
+                &point,
+                &k.get_element(),
+            )
+            .compress(),
+        })
+    }
+
     fn add_point(&self, other: &PK) -> RistrettoCurvPoint {
         let pkpk = self.ge.decompress().unwrap() + other.decompress().unwrap();
         RistrettoCurvPoint {
@@ -370,6 +490,47 @@ impl ECPoint for RistrettoCurvPoint {
     fn from_coor(_x: &BigInt, _y: &BigInt) -> RistrettoCurvPoint {
         unimplemented!();
     }
+
+    fn from_coor_checked(_x: &BigInt, _y: &BigInt) -> Result<RistrettoCurvPoint, ErrorCoordinates> {
+        Err(ErrorCoordinates::Unsupported)
+    }
+
+    fn from_coor_reduce(_x: &BigInt, _y: &BigInt) -> Result<RistrettoCurvPoint, ErrorCoordinates> {
+        Err(ErrorCoordinates::Unsupported)
+    }
+
+    fn curve_equation_residual(x: &BigInt, y: &BigInt) -> BigInt {
+        // ristretto points are classes of points of edwards25519, the coordinates are the ones
+        // of a representative: -x^2 + y^2 = 1 + dx^2y^2 with d = -121665/121666
+        let p = BigInt::from(2u32).pow(255u32) - BigInt::from(19u32);
+        let d = BigInt::mod_mul(
+            &BigInt::mod_sub(&BigInt::zero(), &BigInt::from(121_665), &p),
+            &BigInt::mod_inv(&BigInt::from(121_666), &p).unwrap(),
+            &p,
+        );
+        let x_sqr = BigInt::mod_mul(x, x, &p);
+        let y_sqr = BigInt::mod_mul(y, y, &p);
+        let lhs = BigInt::mod_sub(&y_sqr, &x_sqr, &p);
+        let dxy = BigInt::mod_mul(&d, &BigInt::mod_mul(&x_sqr, &y_sqr, &p), &p);
+        let rhs = BigInt::mod_add(&BigInt::one(), &dxy, &p);
+        BigInt::mod_sub(&lhs, &rhs, &p)
+    }
+
+    fn hash_to_curve(message: &[u8]) -> RistrettoCurvPoint {
+        RistrettoCurvPoint {
+            purpose: "hash_to_curve",
+            ge: RistrettoPoint::hash_from_bytes::<Sha512>(message).compress(),
+        }
+    }
+}
+
+impl ConstantTimeBaseMul for RistrettoCurvPoint {
+    fn scalar_mul_base_ct(k: &RistrettoScalar) -> RistrettoCurvPoint {
+        RistrettoCurvPoint {
+            purpose: "scalar_mul_base_ct",
+            ge: (&RISTRETTO_BASEPOINT_TABLE * &k.get_element()).compress(),
+        }
+    }
 }
 
 impl Mul<RistrettoScalar> for RistrettoCurvPoint {
@@ -461,10 +622,7 @@ impl<'de> Visitor<'de> for RistrettoCurvPointVisitor {
         let bytes_str = seq
             .next_element()?
             .ok_or_else(|| V::Error::invalid_length(0, &"a single element"))?;
-        let bytes_bn = BigInt::from_hex(bytes_str).map_err(V::Error::custom)?;
-        let bytes = BigInt::to_bytes(&bytes_bn);
-        RistrettoCurvPoint::from_bytes(&bytes[..])
-            .map_err(|_| V::Error::custom("failed to parse ristretto point"))
+        decode_bytes_str(bytes_str)
     }
 
     fn visit_map<E: MapAccess<'de>>(self, mut map: E) -> Result<RistrettoCurvPoint, E::Error> {
@@ -479,14 +637,25 @@ impl<'de> Visitor<'de> for RistrettoCurvPointVisitor {
                 _ => return Err(E::Error::unknown_field(key, &["bytes_str"])),
             }
         }
-        let bytes_bn = BigInt::from_hex(&bytes_str).map_err(E::Error::custom)?;
-        let bytes = BigInt::to_bytes(&bytes_bn);
-
-        RistrettoCurvPoint::from_bytes(&bytes[..])
-            .map_err(|_| E::Error::custom("failed to parse ristretto point"))
+        decode_bytes_str(&bytes_str)
     }
 }
 
+// The hex string drops the leading zeros of the encoding, which is left padded back to 32 bytes
+// and must then be the canonical encoding of a point: unlike from_bytes, longer strings aren't
+// truncated.
+fn decode_bytes_str<E: de::Error>(bytes_str: &str) -> Result<RistrettoCurvPoint, E> {
+    let bytes_bn = BigInt::from_hex(bytes_str).map_err(E::custom)?;
+    let bytes = BigInt::to_bytes(&bytes_bn);
+    if bytes.len() > 32 {
+        return Err(E::custom("failed to parse ristretto point"));
+    }
+    let mut padded = [0u8; 32];
+    padded[32 - bytes.len()..].copy_from_slice(&bytes);
+    RistrettoCurvPoint::deserialize_canonical(&padded)
+        .map_err(|_| E::custom("failed to parse ristretto point"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::{RistrettoCurvPoint, RistrettoScalar};
@@ -519,6 +688,24 @@ mod tests {
         assert_eq!(decoded, pk);
     }
 
+    #[test]
+    fn test_serdes_pk_rejects_non_canonical_encodings() {
+        let pk = GE::generator() * FE::new_random();
+        let s = serde_json::to_string(&pk).unwrap();
+        let bytes_str = BigInt::from_bytes(&pk.pk_to_key_slice()).to_hex();
+        assert_eq!(s, format!(r#"{{"bytes_str":"{}"}}"#, bytes_str));
+
+        // the encoding followed by a trailing byte
+        let s = format!(r#"{{"bytes_str":"{}00"}}"#, bytes_str);
+        assert!(serde_json::from_str::<GE>(&s).is_err());
+        // 1 encoded with the field prime added, 2^255 - 19 + 1 in little endian
+        let mut bytes = [0xffu8; 32];
+        bytes[0] = 0xee;
+        bytes[31] = 0x7f;
+        let s = format!(r#"{{"bytes_str":"{}"}}"#, hex::encode(bytes));
+        assert!(serde_json::from_str::<GE>(&s).is_err());
+    }
+
     #[test]
     #[should_panic]
     fn test_serdes_bad_pk() {
@@ -595,4 +782,87 @@ mod tests {
         let result = RistrettoCurvPoint::from_bytes(&test_vec);
         assert!(result.is_ok())
     }
+    #[test]
+    fn test_from_bytes_exact() {
+        let a: FE = ECScalar::new_random();
+        let a_bn_bytes = BigInt::to_bytes(&a.to_big_int());
+        let mut a_bytes = vec![0u8; 32 - a_bn_bytes.len()];
+        a_bytes.extend_from_slice(&a_bn_bytes);
+        a_bytes.reverse();
+        assert_eq!(FE::from_bytes_exact(&a_bytes), Some(a));
+        assert_eq!(FE::from_bytes_exact(&[0u8; 32]), Some(FE::zero()));
+        assert!(FE::from_bytes_exact(&a_bytes[1..]).is_none());
+
+        let mut q_bytes = BigInt::to_bytes(&FE::q());
+        q_bytes.reverse();
+        assert!(FE::from_bytes_exact(&q_bytes).is_none());
+        assert!(FE::from_bytes_exact(&[0xff; 32]).is_none());
+    }
+
+    #[test]
+    fn test_from_bytes_mod_order() {
+        // 0xe0e1e2...ff as a little-endian number, reduced modulo q
+        let bytes: Vec<u8> = (0..32).map(|i| 0xff - i).collect();
+        let mut arr = [0u8; 32];
+        arr.copy_from_slice(&bytes);
+        let expected =
+            BigInt::from_hex("00e1e2e3e4e5e6e7e8e9eaebecedeeeeccc048c70b6b633f27f88f8ae78c6809")
+                .unwrap();
+        let fe = FE::from_bytes_mod_order(&arr);
+        assert_eq!(fe.to_big_int(), expected);
+        assert_eq!(FE::from_bytes_mod_order(&[0u8; 32]), FE::zero());
+    }
+
+    #[test]
+    fn test_curve_equation_residual() {
+        // the ristretto basepoint is represented by the ed25519 one
+        let g: crate::elliptic::curves::ed25519::GE = ECPoint::generator();
+        let (x, y) = (g.x_coor().unwrap(), g.y_coor().unwrap());
+        assert_eq!(GE::curve_equation_residual(&x, &y), BigInt::zero());
+        let y_plus_one = y + BigInt::one();
+        assert_ne!(GE::curve_equation_residual(&x, &y_plus_one), BigInt::zero());
+    }
+
+    #[test]
+    fn test_deserialize_canonical() {
+        let g: GE = ECPoint::generator();
+        let point = GE::deserialize_canonical(&g.pk_to_key_slice()).unwrap();
+        assert_eq!(point, g);
+        assert!(GE::is_canonical_encoding(&[0u8; 32]));
+
+        // from_bytes left pads short encodings
+        assert!(GE::from_bytes(&[0u8; 31]).is_ok());
+        assert!(!GE::is_canonical_encoding(&[0u8; 31]));
+        let mut trailing = g.pk_to_key_slice();
+        trailing.push(0);
+        assert!(!GE::is_canonical_encoding(&trailing));
+        assert!(!GE::is_canonical_encoding(&[0xffu8; 32]));
+        // negative field elements are not canonical
+        let mut one = [0u8; 32];
+        one[0] = 1;
+        assert!(!GE::is_canonical_encoding(&one));
+    }
+
+    #[test]
+    fn test_from_coor_checked() {
+        use crate::ErrorCoordinates;
+        // the coordinates of the ed25519 basepoint, which represents the ristretto one
+        let g: crate::elliptic::curves::ed25519::GE = ECPoint::generator();
+        let (x, y) = (g.x_coor().unwrap(), g.y_coor().unwrap());
+        assert_eq!(
+            GE::from_coor_checked(&x, &y).err(),
+            Some(ErrorCoordinates::Unsupported)
+        );
+    }
+
+    #[test]
+    fn test_from_coor_reduce() {
+        use crate::ErrorCoordinates;
+        let g: crate::elliptic::curves::ed25519::GE = ECPoint::generator();
+        let (x, y) = (g.x_coor().unwrap(), g.y_coor().unwrap());
+        assert_eq!(
+            GE::from_coor_reduce(&x, &y).err(),
+            Some(ErrorCoordinates::Unsupported)
+        );
+    }
 }
@@ -0,0 +1,241 @@
+/*
+    This file is part of Curv library
+    Copyright 2018 by Kzen Networks
+    (https://github.com/KZen-networks/curv)
+    License MIT: <https://github.com/KZen-networks/curv/blob/master/LICENSE>
+*/
+
+//! Points and scalars whose curve is only known at runtime.
+//!
+//! [DynPoint] and [DynScalar] wrap a point or a scalar of any curve supported by the library.
+//! Every operation checks that both operands belong to the same curve (see
+//! [ECPoint::CURVE_NAME]) and returns [WrongCurve] otherwise.
+
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+
+use super::traits::{ECPoint, ECScalar};
+use super::{bls12_381, curve_ristretto, ed25519, p256, secp256_k1};
+
+/// Error returned when combining values that belong to different curves
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WrongCurve {
+    pub expected: &'static str,
+    pub found: &'static str,
+}
+
+impl fmt::Display for WrongCurve {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "WrongCurve: expected {}, found {}",
+            self.expected, self.found
+        )
+    }
+}
+
+impl Error for WrongCurve {}
+
+macro_rules! dynamic_types {
+    ($($variant:ident($point:ty, $scalar:ty)),* $(,)?) => {
+        #[derive(Clone, Debug, PartialEq)]
+        pub enum DynPoint {
+            $($variant($point)),*
+        }
+
+        #[derive(Clone, Debug, PartialEq)]
+        pub enum DynScalar {
+            $($variant($scalar)),*
+        }
+
+        impl DynPoint {
+            pub fn curve_name(&self) -> &'static str {
+                match self {
+                    $(DynPoint::$variant(_) => <$point as ECPoint>::CURVE_NAME),*
+                }
+            }
+
+            pub fn add_point(&self, other: &DynPoint) -> Result<DynPoint, WrongCurve> {
+                match (self, other) {
+                    $((DynPoint::$variant(a), DynPoint::$variant(b)) => {
+                        Ok(DynPoint::$variant(a.clone() + b.clone()))
+                    })*
+                    _ => Err(self.wrong_curve(other.curve_name())),
+                }
+            }
+
+            pub fn sub_point(&self, other: &DynPoint) -> Result<DynPoint, WrongCurve> {
+                match (self, other) {
+                    $((DynPoint::$variant(a), DynPoint::$variant(b)) => {
+                        Ok(DynPoint::$variant(a.sub_point(&b.get_element())))
+                    })*
+                    _ => Err(self.wrong_curve(other.curve_name())),
+                }
+            }
+
+            pub fn scalar_mul(&self, scalar: &DynScalar) -> Result<DynPoint, WrongCurve> {
+                match (self, scalar) {
+                    $((DynPoint::$variant(a), DynScalar::$variant(s)) => {
+                        Ok(DynPoint::$variant(a.clone() * s.clone()))
+                    })*
+                    _ => Err(self.wrong_curve(scalar.curve_name())),
+                }
+            }
+
+            fn wrong_curve(&self, found: &'static str) -> WrongCurve {
+                WrongCurve {
+                    expected: self.curve_name(),
+                    found,
+                }
+            }
+        }
+
+        impl DynScalar {
+            pub fn curve_name(&self) -> &'static str {
+                match self {
+                    $(DynScalar::$variant(_) => <$point as ECPoint>::CURVE_NAME),*
+                }
+            }
+
+            pub fn add(&self, other: &DynScalar) -> Result<DynScalar, WrongCurve> {
+                match (self, other) {
+                    $((DynScalar::$variant(a), DynScalar::$variant(b)) => {
+                        Ok(DynScalar::$variant(a.add(&b.get_element())))
+                    })*
+                    _ => Err(self.wrong_curve(other.curve_name())),
+                }
+            }
+
+            pub fn sub(&self, other: &DynScalar) -> Result<DynScalar, WrongCurve> {
+                match (self, other) {
+                    $((DynScalar::$variant(a), DynScalar::$variant(b)) => {
+                        Ok(DynScalar::$variant(a.sub(&b.get_element())))
+                    })*
+                    _ => Err(self.wrong_curve(other.curve_name())),
+                }
+            }
+
+            pub fn mul(&self, other: &DynScalar) -> Result<DynScalar, WrongCurve> {
+                match (self, other) {
+                    $((DynScalar::$variant(a), DynScalar::$variant(b)) => {
+                        Ok(DynScalar::$variant(a.mul(&b.get_element())))
+                    })*
+                    _ => Err(self.wrong_curve(other.curve_name())),
+                }
+            }
+
+            fn wrong_curve(&self, found: &'static str) -> WrongCurve {
+                WrongCurve {
+                    expected: self.curve_name(),
+                    found,
+                }
+            }
+        }
+
+        $(
+            impl From<$point> for DynPoint {
+                fn from(point: $point) -> Self {
+                    DynPoint::$variant(point)
+                }
+            }
+
+            impl From<$scalar> for DynScalar {
+                fn from(scalar: $scalar) -> Self {
+                    DynScalar::$variant(scalar)
+                }
+            }
+
+            impl TryFrom<DynPoint> for $point {
+                type Error = WrongCurve;
+
+                fn try_from(point: DynPoint) -> Result<Self, WrongCurve> {
+                    match point {
+                        DynPoint::$variant(p) => Ok(p),
+                        other => Err(WrongCurve {
+                            expected: <$point as ECPoint>::CURVE_NAME,
+                            found: other.curve_name(),
+                        }),
+                    }
+                }
+            }
+
+            impl TryFrom<DynScalar> for $scalar {
+                type Error = WrongCurve;
+
+                fn try_from(scalar: DynScalar) -> Result<Self, WrongCurve> {
+                    match scalar {
+                        DynScalar::$variant(s) => Ok(s),
+                        other => Err(WrongCurve {
+                            expected: <$point as ECPoint>::CURVE_NAME,
+                            found: other.curve_name(),
+                        }),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+dynamic_types! {
+    Secp256k1(secp256_k1::GE, secp256_k1::FE),
+    P256(p256::GE, p256::FE),
+    Ed25519(ed25519::GE, ed25519::FE),
+    Ristretto(curve_ristretto::GE, curve_ristretto::FE),
+    Bls12_381_1(bls12_381::g1::GE, bls12_381::g1::FE),
+    Bls12_381_2(bls12_381::g2::GE, bls12_381::g2::FE),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_curve_operations() {
+        let s: secp256_k1::FE = ECScalar::new_random();
+        let g: secp256_k1::GE = ECPoint::generator();
+        let h: secp256_k1::GE = ECPoint::base_point2();
+
+        let sum = DynPoint::from(g).add_point(&DynPoint::from(h)).unwrap();
+        assert_eq!(sum, DynPoint::from(g + h));
+        let product = DynPoint::from(g).scalar_mul(&DynScalar::from(s)).unwrap();
+        assert_eq!(secp256_k1::GE::try_from(product).unwrap(), g * s);
+    }
+
+    #[test]
+    fn test_mixing_curves_yields_wrong_curve() {
+        let secp_g = DynPoint::from(secp256_k1::GE::generator());
+        let ed_g = DynPoint::from(ed25519::GE::generator());
+        let ed_s = DynScalar::from(ed25519::FE::new_random());
+        let expected = WrongCurve {
+            expected: "secp256k1",
+            found: "ed25519",
+        };
+
+        assert_eq!(secp_g.add_point(&ed_g), Err(expected));
+        assert_eq!(secp_g.sub_point(&ed_g), Err(expected));
+        assert_eq!(secp_g.scalar_mul(&ed_s), Err(expected));
+
+        let secp_s = DynScalar::from(secp256_k1::FE::new_random());
+        assert_eq!(secp_s.add(&ed_s), Err(expected));
+        assert_eq!(secp_s.mul(&ed_s), Err(expected));
+
+        // the two groups of BLS12-381 are different curves as well
+        let g1 = DynPoint::from(bls12_381::g1::GE::generator());
+        let g2 = DynPoint::from(bls12_381::g2::GE::generator());
+        assert_eq!(
+            g1.add_point(&g2),
+            Err(WrongCurve {
+                expected: "bls12_381_1",
+                found: "bls12_381_2",
+            })
+        );
+        assert_eq!(
+            secp256_k1::GE::try_from(ed_g),
+            Err(WrongCurve {
+                expected: "secp256k1",
+                found: "ed25519",
+            })
+        );
+    }
+}
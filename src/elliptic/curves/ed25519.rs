@@ -11,19 +11,36 @@
 use std::fmt::Debug;
 use std::str;
 pub const TWO_TIMES_SECRET_KEY_SIZE: usize = 64;
-use super::traits::{ECPoint, ECScalar};
+pub const SECRET_KEY_SIZE: usize = 32;
+// order of the prime order sub group, little-endian
+const GROUP_ORDER_BYTES: [u8; SECRET_KEY_SIZE] = [
+    237, 211, 245, 92, 26, 99, 18, 88, 214, 156, 247, 162, 222, 249, 222, 20, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 16,
+];
+use super::traits::{ConstantTimeBaseMul, ECPoint, ECScalar};
 use crate::cryptographic_primitives::hashing::hash_sha256::HSha256;
 use crate::cryptographic_primitives::hashing::traits::Hash;
 use serde::de::{self, Error, MapAccess, SeqAccess, Visitor};
 use serde::ser::SerializeStruct;
 use serde::ser::{Serialize, Serializer};
 use serde::{Deserialize, Deserializer};
+use sha2::{Digest as _, Sha256};
 use std::fmt;
 use std::ops::{Add, Mul};
+const GENERATOR_X: [u8; 32] = [
+    0x21, 0x69, 0x36, 0xd3, 0xcd, 0x6e, 0x53, 0xfe, 0xc0, 0xa4, 0xe2, 0x31, 0xfd, 0xd6, 0xdc, 0x5c,
+    0x69, 0x2c, 0xc7, 0x60, 0x95, 0x25, 0xa7, 0xb2, 0xc9, 0x56, 0x2d, 0x60, 0x8f, 0x25, 0xd5, 0x1a,
+];
+const GENERATOR_Y: [u8; 32] = [
+    0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66,
+    0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x58,
+];
+
 pub type SK = Fe;
 pub type PK = GeP3;
 use crate::arithmetic::traits::*;
 use crate::BigInt;
+use crate::ErrorCoordinates;
 use crate::ErrorKey::{self, InvalidPublicKey};
 #[cfg(feature = "merkle")]
 use crypto::digest::Digest;
@@ -99,6 +116,36 @@ impl ECScalar for Ed25519Scalar {
         }
     }
 
+    fn from_bytes_exact(bytes: &[u8]) -> Option<Ed25519Scalar> {
+        if bytes.len() != SECRET_KEY_SIZE {
+            return None;
+        }
+        // the encoding is canonical only if it is smaller than q. Numbers are little-endian
+        // so we compare starting from the last byte
+        for (byte, q_byte) in bytes.iter().rev().zip(GROUP_ORDER_BYTES.iter().rev()) {
+            if byte < q_byte {
+                return Some(Ed25519Scalar {
+                    purpose: "from_bytes_exact",
+                    fe: SK::from_bytes(bytes),
+                });
+            }
+            if byte > q_byte {
+                return None;
+            }
+        }
+        None
+    }
+
+    fn from_bytes_mod_order(bytes: &[u8; 32]) -> Ed25519Scalar {
+        let mut v = [0u8; TWO_TIMES_SECRET_KEY_SIZE];
+        v[..SECRET_KEY_SIZE].copy_from_slice(bytes);
+        sc_reduce(&mut v[..]);
+        Ed25519Scalar {
+            purpose: "from_bytes_mod_order",
+            fe: SK::from_bytes(&v[..SECRET_KEY_SIZE]),
+        }
+    }
+
     fn to_big_int(&self) -> BigInt {
         let t1 = &self.fe.to_bytes()[0..self.fe.to_bytes().len()];
         let mut t2 = t1.to_vec();
@@ -107,12 +154,7 @@ impl ECScalar for Ed25519Scalar {
     }
 
     fn q() -> BigInt {
-        let q_bytes_array: [u8; 32];
-        q_bytes_array = [
-            237, 211, 245, 92, 26, 99, 18, 88, 214, 156, 247, 162, 222, 249, 222, 20, 0, 0, 0, 0,
-            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 16,
-        ];
-        let l_fe = SK::from_bytes(&q_bytes_array);
+        let l_fe = SK::from_bytes(&GROUP_ORDER_BYTES);
         let l_fe = Ed25519Scalar {
             purpose: "q",
             fe: l_fe,
@@ -273,6 +315,15 @@ impl ECPoint for Ed25519Point {
     type PublicKey = PK;
     type Scalar = Ed25519Scalar;
 
+    const CURVE_NAME: &'static str = "ed25519";
+    const COMPRESSED_SIZE: usize = 32;
+    const GENERATOR_X: &'static [u8] = &GENERATOR_X;
+    const GENERATOR_Y: &'static [u8] = &GENERATOR_Y;
+    const GENERATOR_DERIVATION: &'static str =
+        "standard base point of RFC 8032, section 5.1: the point with y = 4/5 and a positive x";
+    const BASE_POINT2_DERIVATION: &'static str =
+        "H = 8 * decode(SHA256(SHA256(G))) over the encoding of the generator G";
+
     fn base_point2() -> Ed25519Point {
         let g: GE = ECPoint::generator();
         let hash = HSha256::create_hash(&[&g.bytes_compressed_to_big_int()]);
@@ -297,6 +348,13 @@ impl ECPoint for Ed25519Point {
         }
     }
 
+    fn identity() -> Option<Ed25519Point> {
+        Some(Ed25519Point {
+            purpose: "identity",
+            ge: ge_scalarmult_base(&[0u8; 32]),
+        })
+    }
+
     fn get_element(&self) -> PK {
         self.ge
     }
@@ -382,6 +440,35 @@ impl ECPoint for Ed25519Point {
         }
     }
 
+    // unlike from_bytes the encoded point itself is returned, and points with a small order
+    // component are rejected rather than multiplied by 8
+    fn deserialize_canonical(bytes: &[u8]) -> Result<Ed25519Point, ErrorKey> {
+        let point = Ed25519Point::from_bytes_with_torsion(bytes)?;
+        // 8^-1 * (8 * point) is the prime order component of the point
+        let eight: FE = ECScalar::from(&BigInt::from(8));
+        let prime_order_component = point * eight * eight.invert();
+        if prime_order_component.pk_to_key_slice() != bytes {
+            return Err(InvalidPublicKey);
+        }
+        Ok(point)
+    }
+
+    fn serialized_len(_compressed: bool) -> usize {
+        32
+    }
+
+    fn negate_serialized(bytes: &[u8]) -> Result<Vec<u8>, ErrorKey> {
+        Self::deserialize_canonical(bytes)?;
+        // -(x, y) = (-x, y) flips the sign bit of x, unless x = 0: the canonical points with
+        // x = 0 are only the identity, as (0, -1) has order 2
+        let mut negated = bytes.to_vec();
+        let is_identity = negated[0] == 1 && negated[1..].iter().all(|b| *b == 0);
+        if !is_identity {
+            negated[31] ^= 1 << 7;
+        }
+        Ok(negated)
+    }
+
     fn pk_to_key_slice(&self) -> Vec<u8> {
         let result = self.ge.to_bytes();
         result.to_vec()
@@ -407,6 +494,29 @@ impl ECPoint for Ed25519Point {
         }
     }
 
+    fn generator_mul(k: &Ed25519Scalar) -> Ed25519Point {
+        Self::scalar_mul_base_ct(k)
+    }
+
+    fn double_scalar_mul_base(
+        k: &Ed25519Scalar,
+        point: &Ed25519Point,
+        e: &Ed25519Scalar,
+    ) -> Option<Ed25519Point> {
+        let p2_point = GeP2::double_scalarmult_vartime(
+            &e.get_element().to_bytes()[..],
+            point.get_element(),
+            &k.get_element().to_bytes()[..],
+        );
+        // same conversion as scalar_mul
+        let mut p2_bytes = p2_point.to_bytes();
+        p2_bytes[31] ^= 1 << 7;
+        Some(Ed25519Point {
+            purpose: "double_scalar_mul_base",
+            ge: GeP3::from_bytes_negate_vartime(&p2_bytes[..]).unwrap(),
+        })
+    }
+
     fn add_point(&self, other: &PK) -> Ed25519Point {
         let pkpk = self.ge + other.to_cached();
         let mut pk_p2_bytes = pkpk.to_p2().to_bytes();
@@ -428,8 +538,156 @@ impl ECPoint for Ed25519Point {
         }
     }
 
-    fn from_coor(_x: &BigInt, _y: &BigInt) -> Ed25519Point {
-        unimplemented!();
+    fn from_coor(x: &BigInt, y: &BigInt) -> Ed25519Point {
+        Self::from_coor_checked(x, y).expect("invalid coordinates")
+    }
+
+    fn from_coor_checked(x: &BigInt, y: &BigInt) -> Result<Ed25519Point, ErrorCoordinates> {
+        let p = field_prime();
+        let in_field = |c: &BigInt| *c >= BigInt::zero() && *c < p;
+        if !in_field(x) || !in_field(y) {
+            return Err(ErrorCoordinates::OutOfFieldRange);
+        }
+        if Self::curve_equation_residual(x, y) != BigInt::zero() {
+            return Err(ErrorCoordinates::NotOnCurve);
+        }
+        // the encoding is y in little-endian, its top bit holding the parity of x
+        let mut bytes = [0u8; 32];
+        let y_bytes = BigInt::to_bytes(y);
+        bytes[32 - y_bytes.len()..].copy_from_slice(&y_bytes);
+        bytes.reverse();
+        if x.test_bit(0) {
+            bytes[31] |= 0x80;
+        }
+        let point = Ed25519Point::from_bytes_with_torsion(&bytes)
+            .expect("the coordinates are those of a curve point");
+        Ok(Ed25519Point {
+            purpose: "from_coor",
+            ge: point.ge,
+        })
+    }
+
+    fn from_coor_reduce(x: &BigInt, y: &BigInt) -> Result<Ed25519Point, ErrorCoordinates> {
+        let p = field_prime();
+        Self::from_coor_checked(&x.modulus(&p), &y.modulus(&p))
+    }
+
+    fn curve_equation_residual(x: &BigInt, y: &BigInt) -> BigInt {
+        // -x^2 + y^2 = 1 + dx^2y^2 with d = -121665/121666
+        let p = field_prime();
+        let d = BigInt::mod_mul(
+            &BigInt::mod_sub(&BigInt::zero(), &BigInt::from(121_665), &p),
+            &BigInt::mod_inv(&BigInt::from(121_666), &p).unwrap(),
+            &p,
+        );
+        let x_sqr = BigInt::mod_mul(x, x, &p);
+        let y_sqr = BigInt::mod_mul(y, y, &p);
+        let lhs = BigInt::mod_sub(&y_sqr, &x_sqr, &p);
+        let dxy = BigInt::mod_mul(&d, &BigInt::mod_mul(&x_sqr, &y_sqr, &p), &p);
+        let rhs = BigInt::mod_add(&BigInt::one(), &dxy, &p);
+        BigInt::mod_sub(&lhs, &rhs, &p)
+    }
+
+    fn hash_to_curve(message: &[u8]) -> Ed25519Point {
+        // try-and-increment: the first counter for which H(counter || message) decodes to a
+        // point outside of the small order sub group gives the result, with cofactor cleared
+        let mut identity = [0u8; 32];
+        identity[0] = 1;
+        (0u32..)
+            .find_map(|counter| {
+                let hash = Sha256::new()
+                    .chain(counter.to_be_bytes())
+                    .chain(message)
+                    .result();
+                let point: Ed25519Point = ECPoint::from_bytes(&hash).ok()?;
+                if point.ge.to_bytes() == identity {
+                    None
+                } else {
+                    Some(Ed25519Point {
+                        purpose: "hash_to_curve",
+                        ge: point.ge,
+                    })
+                }
+            })
+            .unwrap()
+    }
+}
+
+impl ConstantTimeBaseMul for Ed25519Point {
+    fn scalar_mul_base_ct(k: &Ed25519Scalar) -> Ed25519Point {
+        // unlike scalar_mul, which is variable time
+        Ed25519Point {
+            purpose: "scalar_mul_base_ct",
+            ge: ge_scalarmult_base(&k.get_element().to_bytes()[..]),
+        }
+    }
+}
+
+impl Ed25519Point {
+    /// Decodes the 32 bytes encoding of any curve point, including its torsion component.
+    ///
+    /// Unlike `from_bytes`, the point is not multiplied by the co-factor and unlike
+    /// `deserialize_canonical`, points outside of the prime order sub group are accepted, as
+    /// needed to verify ed25519 signatures. Non canonical encodings are still rejected.
+    pub fn from_bytes_with_torsion(bytes: &[u8]) -> Result<Ed25519Point, ErrorKey> {
+        if bytes.len() != 32 {
+            return Err(InvalidPublicKey);
+        }
+        // decoding negates the point, so it is decoded twice. Re-encoding rejects y >= p and a
+        // sign bit set for x = 0
+        let negated = PK::from_bytes_negate_vartime(bytes).ok_or(InvalidPublicKey)?;
+        let ge = PK::from_bytes_negate_vartime(&negated.to_bytes()).ok_or(InvalidPublicKey)?;
+        if ge.to_bytes()[..] != bytes[..] {
+            return Err(InvalidPublicKey);
+        }
+        Ok(Ed25519Point {
+            purpose: "from_bytes",
+            ge,
+        })
+    }
+
+    /// Returns the component of the point lying in the prime order sub group.
+    ///
+    /// Every curve point P can be uniquely written as P = P' + T where P' belongs to the sub group
+    /// of prime order l and T belongs to the torsion sub group (of order dividing the co-factor 8).
+    /// clear_torsion returns P' computed as (8^-1 mod l) * (8 * P): the multiplication by the
+    /// co-factor kills T and the multiplication by the inverse of the co-factor modulo l cancels
+    /// the scaling of P'.
+    ///
+    /// Unlike `from_bytes`, which returns 8 * P, a point that is already in the prime order sub
+    /// group is returned unchanged.
+    pub fn clear_torsion(&self) -> Ed25519Point {
+        let eight: FE = ECScalar::from(&BigInt::from(8));
+        let eight_inv = eight.invert();
+        let torsion_free = self.scalar_mul(&eight.get_element()) * eight_inv;
+        Ed25519Point {
+            purpose: "clear_torsion",
+            ge: torsion_free.get_element(),
+        }
+    }
+
+    /// Multiplies the point by the X25519 clamped form of `bytes` (RFC 7748): the little-endian
+    /// integer k read from `bytes` with bits 0, 1, 2 and 255 cleared and bit 254 set.
+    ///
+    /// k isn't reduced modulo the group order l. Being a multiple of the co-factor, it kills the
+    /// torsion component of the point, and the result is the Edwards form of X25519 of `bytes`
+    /// on the Montgomery form of the point. Multiplying by an `FE` built from the same bytes
+    /// gives another point, because that scalar is neither clamped nor a multiple of 8.
+    pub fn scalar_mul_clamped(&self, bytes: &[u8; 32]) -> Ed25519Point {
+        let mut k = *bytes;
+        k[0] &= 248;
+        k[31] &= 127;
+        k[31] |= 64;
+        // same as scalar_mul, with k < 2^255 taken as is by the double scalar multiplication
+        let p2_point = GeP2::double_scalarmult_vartime(&k[..], self.get_element(), &[0u8; 32]);
+        k.zeroize();
+        let mut p2_bytes = p2_point.to_bytes();
+        p2_bytes[31] ^= 1 << 7;
+        let ge = GeP3::from_bytes_negate_vartime(&p2_bytes[..]).unwrap();
+        Ed25519Point {
+            purpose: "scalar_mul_clamped",
+            ge,
+        }
     }
 }
 
@@ -549,6 +807,11 @@ impl<'de> Visitor<'de> for Ed25519PointVisitor {
     }
 }
 
+// 2^255 - 19, the prime of the field of the coordinates
+fn field_prime() -> BigInt {
+    BigInt::from(2u32).pow(255u32) - BigInt::from(19u32)
+}
+
 #[allow(clippy::many_single_char_names)]
 //helper function, based on https://ed25519.cr.yp.to/python/ed25519.py
 pub fn xrecover(y_coor: BigInt) -> BigInt {
@@ -603,6 +866,7 @@ mod tests {
     use crate::elliptic::curves::traits::ECPoint;
     use crate::elliptic::curves::traits::ECScalar;
     use crate::BigInt;
+    use cryptoxide::curve25519::GeP3;
 
     type GE = Ed25519Point;
     type FE = Ed25519Scalar;
@@ -815,4 +1079,211 @@ mod tests {
             "216936d3cd6e53fec0a4e231fdd6dc5c692cc7609525a7b2c9562d608f25d51a"
         );
     }
+
+    #[test]
+    fn test_clear_torsion() {
+        // (0, -1) is the point of order 2 on the curve
+        let mut minus_one_bytes = [0xffu8; 32];
+        minus_one_bytes[0] = 0xec;
+        minus_one_bytes[31] = 0x7f;
+        let torsion = Ed25519Point {
+            purpose: "torsion",
+            ge: GeP3::from_bytes_negate_vartime(&minus_one_bytes).unwrap(),
+        };
+
+        let a: FE = ECScalar::new_random();
+        let point = GE::generator() * a;
+        let point_plus_torsion = point + torsion;
+        assert_ne!(point_plus_torsion, point);
+        assert_eq!(point_plus_torsion.clear_torsion(), point.clear_torsion());
+        assert_eq!(point.clear_torsion(), point);
+    }
+    #[test]
+    fn test_scalar_mul_clamped() {
+        use cryptoxide::curve25519::{curve25519, curve25519_base};
+        // u = (1 + y) / (1 - y), the Montgomery form of the point, in little endian
+        let montgomery_u = |point: &GE| -> Vec<u8> {
+            let p = (BigInt::one() << 255) - BigInt::from(19);
+            let y = point.y_coor().unwrap();
+            let num = BigInt::mod_add(&BigInt::one(), &y, &p);
+            let den = BigInt::mod_sub(&BigInt::one(), &y, &p);
+            let u = BigInt::mod_mul(&num, &BigInt::mod_inv(&den, &p).unwrap(), &p);
+            let mut bytes = BigInt::to_bytes(&u);
+            bytes.reverse();
+            bytes.resize(32, 0);
+            bytes
+        };
+        for i in 0..10u8 {
+            let bytes = [i.wrapping_mul(37).wrapping_add(1); 32];
+            let g = GE::generator();
+            assert_eq!(
+                montgomery_u(&g.scalar_mul_clamped(&bytes)),
+                curve25519_base(&bytes).to_vec()
+            );
+            let point = g * FE::new_random();
+            let u = montgomery_u(&point);
+            assert_eq!(
+                montgomery_u(&point.scalar_mul_clamped(&bytes)),
+                curve25519(&bytes, &u).to_vec()
+            );
+
+            // on the prime order sub group, the clamped integer reduced modulo l
+            let mut clamped = bytes;
+            clamped[0] &= 248;
+            clamped[31] = (clamped[31] & 127) | 64;
+            clamped.reverse();
+            let k: FE = ECScalar::from(&BigInt::from_bytes(&clamped));
+            assert_eq!(point.scalar_mul_clamped(&bytes), point * k);
+        }
+
+        // the torsion component is killed
+        let mut minus_one_bytes = [0xffu8; 32];
+        minus_one_bytes[0] = 0xec;
+        minus_one_bytes[31] = 0x7f;
+        let torsion = Ed25519Point {
+            purpose: "torsion",
+            ge: GeP3::from_bytes_negate_vartime(&minus_one_bytes).unwrap(),
+        };
+        let point = GE::generator() * FE::new_random();
+        let bytes = [0x5au8; 32];
+        assert_eq!(
+            (point + torsion).scalar_mul_clamped(&bytes),
+            point.scalar_mul_clamped(&bytes)
+        );
+    }
+
+    #[test]
+    fn test_from_bytes_exact() {
+        let a: FE = ECScalar::new_random();
+        let a_bn_bytes = BigInt::to_bytes(&a.to_big_int());
+        let mut a_bytes = vec![0u8; 32 - a_bn_bytes.len()];
+        a_bytes.extend_from_slice(&a_bn_bytes);
+        a_bytes.reverse();
+        assert_eq!(FE::from_bytes_exact(&a_bytes), Some(a));
+        assert_eq!(FE::from_bytes_exact(&[0u8; 32]), Some(FE::zero()));
+        assert!(FE::from_bytes_exact(&a_bytes[1..]).is_none());
+
+        let mut q_bytes = BigInt::to_bytes(&FE::q());
+        q_bytes.reverse();
+        assert!(FE::from_bytes_exact(&q_bytes).is_none());
+        assert!(FE::from_bytes_exact(&[0xff; 32]).is_none());
+    }
+
+    #[test]
+    fn test_from_bytes_mod_order() {
+        // 0xe0e1e2...ff as a little-endian number, reduced modulo q
+        let bytes: Vec<u8> = (0..32).map(|i| 0xff - i).collect();
+        let mut arr = [0u8; 32];
+        arr.copy_from_slice(&bytes);
+        let expected =
+            BigInt::from_hex("00e1e2e3e4e5e6e7e8e9eaebecedeeeeccc048c70b6b633f27f88f8ae78c6809")
+                .unwrap();
+        let fe = FE::from_bytes_mod_order(&arr);
+        assert_eq!(fe.to_big_int(), expected);
+        // the reduction of curve25519-dalek
+        let dalek = curve25519_dalek::scalar::Scalar::from_bytes_mod_order(arr);
+        assert_eq!(fe.get_element().to_bytes(), dalek.to_bytes());
+        assert_eq!(FE::from_bytes_mod_order(&[0u8; 32]), FE::zero());
+    }
+
+    #[test]
+    fn test_curve_equation_residual() {
+        let g: GE = ECPoint::generator();
+        let (x, y) = (g.x_coor().unwrap(), g.y_coor().unwrap());
+        assert_eq!(GE::curve_equation_residual(&x, &y), BigInt::zero());
+        let y_plus_one = y + BigInt::one();
+        assert_ne!(GE::curve_equation_residual(&x, &y_plus_one), BigInt::zero());
+    }
+
+    #[test]
+    fn test_negate_serialized_identity() {
+        let mut identity = [0u8; 32];
+        identity[0] = 1;
+        assert_eq!(GE::negate_serialized(&identity).unwrap(), identity.to_vec());
+    }
+
+    #[test]
+    fn test_deserialize_canonical() {
+        let g: GE = ECPoint::generator();
+        let point = GE::deserialize_canonical(&g.pk_to_key_slice()).unwrap();
+        assert_eq!(point.pk_to_key_slice(), g.pk_to_key_slice());
+        assert!(!GE::is_canonical_encoding(&g.pk_to_key_slice()[1..]));
+        let mut trailing = g.pk_to_key_slice();
+        trailing.push(0);
+        assert!(!GE::is_canonical_encoding(&trailing));
+
+        // the identity is y = 1, and also decodes from y = p + 1 and from x = -0
+        let mut identity = [0u8; 32];
+        identity[0] = 1;
+        assert!(GE::is_canonical_encoding(&identity));
+        let mut unreduced = [0xffu8; 32];
+        unreduced[0] = 0xee;
+        unreduced[31] = 0x7f;
+        assert!(GE::from_bytes(&unreduced).is_ok());
+        assert!(!GE::is_canonical_encoding(&unreduced));
+        let mut negative_zero = identity;
+        negative_zero[31] |= 0x80;
+        assert!(!GE::is_canonical_encoding(&negative_zero));
+
+        // (0, -1) has order 2
+        let mut order_two = [0xffu8; 32];
+        order_two[0] = 0xec;
+        order_two[31] = 0x7f;
+        assert!(GE::from_bytes(&order_two).is_ok());
+        assert!(!GE::is_canonical_encoding(&order_two));
+    }
+
+    #[test]
+    fn test_from_coor_checked() {
+        use crate::ErrorCoordinates;
+        let p = super::field_prime();
+        let g: GE = ECPoint::generator();
+        // x_coor gives the even x of the two points of ordinate y
+        let (x, y) = (g.x_coor().unwrap(), g.y_coor().unwrap());
+        let point = GE::from_coor_checked(&x, &y).unwrap();
+        let negated = GE::from_coor_checked(&(&p - &x), &y).unwrap();
+        assert!(
+            point.pk_to_key_slice() == g.pk_to_key_slice()
+                || negated.pk_to_key_slice() == g.pk_to_key_slice()
+        );
+        let identity = GE::identity().unwrap();
+        assert_eq!(
+            point.add_point(&negated.get_element()).pk_to_key_slice(),
+            identity.pk_to_key_slice()
+        );
+        assert_eq!(
+            GE::from_coor_checked(&BigInt::zero(), &BigInt::one())
+                .unwrap()
+                .pk_to_key_slice(),
+            identity.pk_to_key_slice()
+        );
+
+        assert_eq!(
+            GE::from_coor_checked(&p, &y).err(),
+            Some(ErrorCoordinates::OutOfFieldRange)
+        );
+        assert_eq!(
+            GE::from_coor_checked(&x, &BigInt::from(-1)).err(),
+            Some(ErrorCoordinates::OutOfFieldRange)
+        );
+        assert_eq!(
+            GE::from_coor_checked(&x, &(&y + BigInt::one())).err(),
+            Some(ErrorCoordinates::NotOnCurve)
+        );
+    }
+
+    #[test]
+    fn test_from_coor_reduce() {
+        use crate::ErrorCoordinates;
+        let p = super::field_prime();
+        let g: GE = ECPoint::generator();
+        let (x, y) = (g.x_coor().unwrap(), g.y_coor().unwrap());
+        let point = GE::from_coor_checked(&x, &y).unwrap();
+        let reduced = GE::from_coor_reduce(&(&x + &p), &(&y - &p)).unwrap();
+        assert_eq!(reduced.pk_to_key_slice(), point.pk_to_key_slice());
+        assert_eq!(
+            GE::from_coor_reduce(&(&x + &p), &(y + BigInt::one())).err(),
+            Some(ErrorCoordinates::NotOnCurve)
+        );
+    }
 }
@@ -0,0 +1,144 @@
+/*
+    This file is part of Curv library
+    Copyright 2018 by Kzen Networks
+    (https://github.com/KZen-networks/curv)
+    License MIT: <https://github.com/KZen-networks/curv/blob/master/LICENSE>
+*/
+
+use std::any::{Any, TypeId};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use super::traits::ECPoint;
+
+const GENERATOR_SET_DST: &[u8] = b"curv generator_set";
+
+type Cache = HashMap<(TypeId, Vec<u8>, usize), Arc<dyn Any + Send + Sync>>;
+
+lazy_static::lazy_static! {
+    static ref CACHE: Mutex<Cache> = Mutex::new(HashMap::new());
+}
+
+/// See [ECPoint::generator_set]
+pub(crate) fn generator_set<P>(label: &[u8], count: usize) -> Vec<P>
+where
+    P: ECPoint + Clone + Send + Sync + 'static,
+{
+    let key = (TypeId::of::<P>(), label.to_vec(), count);
+    if let Some(set) = CACHE.lock().unwrap().get(&key) {
+        if let Some(set) = set.downcast_ref::<Vec<P>>() {
+            return set.clone();
+        }
+    }
+
+    let set = derive_generator_set::<P>(label, count);
+    CACHE.lock().unwrap().insert(key, Arc::new(set.clone()));
+    set
+}
+
+/// See [ECPoint::verify_hash_to_curve]
+pub(crate) fn verify_hash_to_curve<P>(point: &P, label: &[u8], index: u32) -> bool
+where
+    P: ECPoint + Clone + Send + Sync + 'static,
+{
+    let index = index as usize;
+    let set = generator_set::<P>(label, index + 1);
+    // points are compared by their encoding, which doesn't depend on how they were computed
+    set[index].pk_to_key_slice() == point.pk_to_key_slice()
+}
+
+fn derive_generator_set<P>(label: &[u8], count: usize) -> Vec<P>
+where
+    P: ECPoint + Clone,
+{
+    let mut prefix = GENERATOR_SET_DST.to_vec();
+    prefix.extend_from_slice(&(label.len() as u64).to_be_bytes());
+    prefix.extend_from_slice(label);
+
+    let mut set = Vec::with_capacity(count);
+    let mut seen = HashSet::with_capacity(count);
+    let mut index = 0u64;
+    while set.len() < count {
+        let mut message = prefix.clone();
+        message.extend_from_slice(&index.to_be_bytes());
+        index += 1;
+
+        let point = P::hash_to_curve(&message);
+        // P + P = P holds only for the identity
+        if point.clone() + point.clone() == point {
+            continue;
+        }
+        if seen.insert(point.pk_to_key_slice()) {
+            set.push(point);
+        }
+    }
+    set
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::traits::{ECPoint, ECScalar};
+    use crate::test_for_all_curves;
+
+    test_for_all_curves!(test_generator_set_is_reproducible);
+    fn test_generator_set_is_reproducible<P>()
+    where
+        P: ECPoint + Clone + Send + Sync + std::fmt::Debug + 'static,
+    {
+        let set = P::generator_set(b"test_generator_set", 8);
+        assert_eq!(set.len(), 8);
+        assert_eq!(set, P::generator_set(b"test_generator_set", 8));
+        // bypasses the cache, and a longer set starts with the same generators
+        assert_eq!(
+            set[..],
+            super::derive_generator_set::<P>(b"test_generator_set", 10)[..8]
+        );
+    }
+
+    test_for_all_curves!(test_generator_sets_are_distinct);
+    fn test_generator_sets_are_distinct<P>()
+    where
+        P: ECPoint + Clone + Send + Sync + std::fmt::Debug + 'static,
+    {
+        let g: P = ECPoint::generator();
+        let h: P = ECPoint::base_point2();
+        let set_a = P::generator_set(b"label_a", 8);
+        let set_b = P::generator_set(b"label_b", 8);
+        let all: Vec<&P> = set_a.iter().chain(&set_b).collect();
+        for (i, p) in all.iter().enumerate() {
+            assert!(**p != g && **p != h);
+            assert!((*p).clone() + (*p).clone() != **p);
+            for q in &all[i + 1..] {
+                assert_ne!(p, q);
+            }
+        }
+    }
+
+    test_for_all_curves!(test_verify_hash_to_curve);
+    fn test_verify_hash_to_curve<P>()
+    where
+        P: ECPoint + Clone + Send + Sync + 'static,
+    {
+        let set = P::generator_set(b"test_verify_hash_to_curve", 4);
+        for (index, point) in set.iter().enumerate() {
+            assert!(P::verify_hash_to_curve(
+                point,
+                b"test_verify_hash_to_curve",
+                index as u32
+            ));
+        }
+        // another position, another label, or a point of known discrete logarithm
+        assert!(!P::verify_hash_to_curve(
+            &set[1],
+            b"test_verify_hash_to_curve",
+            2
+        ));
+        assert!(!P::verify_hash_to_curve(&set[0], b"another label", 0));
+        let substituted = P::generator() * P::Scalar::new_random();
+        assert!(!P::verify_hash_to_curve(
+            &substituted,
+            b"test_verify_hash_to_curve",
+            0
+        ));
+    }
+}
@@ -1,6 +1,11 @@
 pub mod bls12_381;
 pub mod curve_ristretto;
+pub mod dynamic;
 pub mod ed25519;
+mod generators;
+pub mod multiscalar;
 pub mod p256;
 pub mod secp256_k1;
+pub mod secret_scalar;
+pub mod serde_compressed;
 pub mod traits;
@@ -0,0 +1,154 @@
+/*
+    This file is part of Curv library
+    Copyright 2018 by Kzen Networks
+    (https://github.com/KZen-networks/curv)
+    License MIT: <https://github.com/KZen-networks/curv/blob/master/LICENSE>
+*/
+
+use crate::arithmetic::traits::*;
+use crate::BigInt;
+
+use super::traits::{ECPoint, ECScalar};
+
+/// Width in bits of a Pippenger window
+const WINDOW_BITS: usize = 4;
+
+/// Computes `s1*P1 + s2*P2 + ... + sn*Pn` over all given terms at once, with
+/// [ECPoint::multi_scalar_mul].
+///
+/// Returns the identity if there are no terms, and `None` if the sum is the identity on a curve
+/// that can't represent it. Panics if `scalars` and `points` differ in length.
+pub fn multiscalar_mul<P>(scalars: &[P::Scalar], points: &[P]) -> Option<P>
+where
+    P: ECPoint + Clone,
+    P::Scalar: Clone,
+{
+    P::multi_scalar_mul(scalars, points)
+}
+
+/// Streaming multi-scalar multiplication.
+///
+/// Terms are fed one at a time with [push](MultiscalarAccumulator::push) and sorted into
+/// Pippenger buckets right away, so the memory used is fixed by the size of the scalar field and
+/// does not grow with the number of terms. [finalize](MultiscalarAccumulator::finalize) returns
+/// the same sum as [multiscalar_mul] over all pushed terms, if it isn't the identity.
+///
+/// ```
+/// # use curv::elliptic::curves::multiscalar::MultiscalarAccumulator;
+/// # use curv::elliptic::curves::secp256_k1::{GE, FE};
+/// # use curv::elliptic::curves::traits::{ECPoint, ECScalar};
+/// let mut acc = MultiscalarAccumulator::<GE>::new();
+/// for _ in 0..10 {
+///     let s: FE = ECScalar::new_random();
+///     let p: GE = GE::generator() * s;
+///     acc.push(&s, &p);
+/// }
+/// let sum = acc.finalize();
+/// assert!(sum.is_some());
+/// ```
+pub struct MultiscalarAccumulator<P: ECPoint> {
+    // buckets[j][d - 1] holds the sum of points whose scalar has digit d in window j
+    buckets: Vec<Vec<Option<P>>>,
+}
+
+impl<P> MultiscalarAccumulator<P>
+where
+    P: ECPoint + Clone,
+    P::Scalar: Clone,
+{
+    pub fn new() -> Self {
+        let bits = P::Scalar::q().bit_length();
+        let windows = bits.div_ceil(WINDOW_BITS);
+        MultiscalarAccumulator {
+            buckets: vec![vec![None; (1 << WINDOW_BITS) - 1]; windows],
+        }
+    }
+
+    /// Adds the term `scalar * point` to the sum.
+    pub fn push(&mut self, scalar: &P::Scalar, point: &P) {
+        let s = scalar.to_big_int();
+        for (j, window) in self.buckets.iter_mut().enumerate() {
+            let digit = (0..WINDOW_BITS)
+                .filter(|i| s.test_bit(j * WINDOW_BITS + i))
+                .fold(0, |d, i| d | (1 << i));
+            if digit == 0 {
+                continue;
+            }
+            let bucket = &mut window[digit - 1];
+            *bucket = add_opt(bucket.take(), Some(point.clone()));
+        }
+    }
+
+    /// Returns the sum of all pushed terms, or `None` if every pushed scalar was zero.
+    pub fn finalize(self) -> Option<P> {
+        let two_pow_w: P::Scalar = ECScalar::from(&BigInt::from(1 << WINDOW_BITS));
+        let mut acc: Option<P> = None;
+        for window in self.buckets.into_iter().rev() {
+            // sum_d d * bucket[d], computed with running sums from the highest digit down
+            let mut running: Option<P> = None;
+            let mut window_sum: Option<P> = None;
+            for bucket in window.into_iter().rev() {
+                running = add_opt(running, bucket);
+                window_sum = add_opt(window_sum, running.clone());
+            }
+            acc = add_opt(acc.map(|a| a * two_pow_w.clone()), window_sum);
+        }
+        acc
+    }
+}
+
+impl<P> Default for MultiscalarAccumulator<P>
+where
+    P: ECPoint + Clone,
+    P::Scalar: Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn add_opt<P: ECPoint>(a: Option<P>, b: Option<P>) -> Option<P> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a + b),
+        (a, None) => a,
+        (None, b) => b,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_for_all_curves;
+
+    test_for_all_curves!(test_accumulator_matches_multiscalar_mul);
+    fn test_accumulator_matches_multiscalar_mul<P>()
+    where
+        P: ECPoint + Clone + std::fmt::Debug,
+        P::Scalar: Clone,
+    {
+        let g: P = ECPoint::generator();
+        let scalars: Vec<P::Scalar> = (0..20).map(|_| ECScalar::new_random()).collect();
+        let points: Vec<P> = (0..20)
+            .map(|_| g.clone() * ECScalar::new_random())
+            .collect();
+
+        let mut acc = MultiscalarAccumulator::new();
+        for (s, p) in scalars.iter().zip(&points) {
+            acc.push(s, p);
+        }
+        let expected = multiscalar_mul(&scalars, &points);
+        assert!(expected.is_some());
+        assert_eq!(acc.finalize(), expected);
+    }
+
+    test_for_all_curves!(test_empty_accumulator);
+    fn test_empty_accumulator<P>()
+    where
+        P: ECPoint + Clone + std::fmt::Debug,
+        P::Scalar: Clone,
+    {
+        let acc = MultiscalarAccumulator::<P>::new();
+        assert_eq!(acc.finalize(), None);
+        assert_eq!(multiscalar_mul::<P>(&[], &[]), P::identity());
+    }
+}
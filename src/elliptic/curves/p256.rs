@@ -1,22 +1,25 @@
 // NIST P-256 elliptic curve utility functions.
 
-use super::traits::{ECPoint, ECScalar};
+use super::traits::{ConstantTimeBaseMul, ECPoint, ECScalar};
 use crate::arithmetic::traits::*;
 use crate::BigInt;
-use crate::ErrorKey;
+use crate::{ErrorCoordinates, ErrorKey};
 use generic_array::typenum::U32;
 use generic_array::GenericArray;
 use p256::ecdsa::VerifyKey;
+use p256::elliptic_curve::ff::PrimeField;
 use p256::elliptic_curve::sec1::{FromEncodedPoint, ToEncodedPoint};
-use p256::{AffinePoint, EncodedPoint, ProjectivePoint, Scalar};
+use p256::{AffinePoint, EncodedPoint, FieldBytes, ProjectivePoint, Scalar};
 use rand::{thread_rng, Rng};
 use serde::de;
 use serde::de::Visitor;
 use serde::ser::{Serialize, Serializer};
 use serde::{Deserialize, Deserializer};
+use sha2::{Digest as _, Sha256};
 use std::ops::{Add, Mul, Sub};
 use std::sync::atomic;
 use std::{fmt, ptr};
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
 use zeroize::Zeroize;
 
 pub type SK = Scalar;
@@ -36,6 +39,15 @@ pub struct Secp256r1Point {
 pub type GE = Secp256r1Point;
 pub type FE = Secp256r1Scalar;
 
+const GENERATOR_X: [u8; 32] = [
+    0x6b, 0x17, 0xd1, 0xf2, 0xe1, 0x2c, 0x42, 0x47, 0xf8, 0xbc, 0xe6, 0xe5, 0x63, 0xa4, 0x40, 0xf2,
+    0x77, 0x03, 0x7d, 0x81, 0x2d, 0xeb, 0x33, 0xa0, 0xf4, 0xa1, 0x39, 0x45, 0xd8, 0x98, 0xc2, 0x96,
+];
+const GENERATOR_Y: [u8; 32] = [
+    0x4f, 0xe3, 0x42, 0xe2, 0xfe, 0x1a, 0x7f, 0x9b, 0x8e, 0xe7, 0xeb, 0x4a, 0x7c, 0x0f, 0x9e, 0x16,
+    0x2b, 0xce, 0x33, 0x57, 0x6b, 0x31, 0x5e, 0xce, 0xcb, 0xb6, 0x40, 0x68, 0x37, 0xbf, 0x51, 0xf5,
+];
+
 /* X coordinate of a point of unknown discrete logarithm.
 Computed using a deterministic algorithm with the generator as input.
 See test_base_point2 */
@@ -105,6 +117,27 @@ impl ECScalar for Secp256r1Scalar {
         }
     }
 
+    fn from_bytes_exact(bytes: &[u8]) -> Option<Secp256r1Scalar> {
+        if bytes.len() != 32 {
+            return None;
+        }
+        let mut arr = FieldBytes::default();
+        arr.copy_from_slice(bytes);
+        Scalar::from_repr(arr).map(|fe| Secp256r1Scalar {
+            purpose: "from_bytes_exact",
+            fe,
+        })
+    }
+
+    fn from_bytes_mod_order(bytes: &[u8; 32]) -> Secp256r1Scalar {
+        let mut arr = FieldBytes::default();
+        arr.copy_from_slice(bytes);
+        Secp256r1Scalar {
+            purpose: "from_bytes_mod_order",
+            fe: Scalar::from_bytes_reduced(&arr),
+        }
+    }
+
     fn to_big_int(&self) -> BigInt {
         BigInt::from_bytes(self.fe.to_bytes().as_slice())
     }
@@ -145,6 +178,22 @@ impl ECScalar for Secp256r1Scalar {
             fe: self.fe.invert().unwrap(),
         }
     }
+
+    fn invert_ct(&self) -> Option<Secp256r1Scalar> {
+        let inverse = self.fe.invert();
+        if bool::from(inverse.is_some()) {
+            Some(Secp256r1Scalar {
+                purpose: "invert_ct",
+                fe: inverse.unwrap(),
+            })
+        } else {
+            None
+        }
+    }
+
+    fn ct_eq(&self, other: &Secp256r1Scalar) -> Choice {
+        self.fe.ct_eq(&other.fe)
+    }
 }
 
 impl Mul<Secp256r1Scalar> for Secp256r1Scalar {
@@ -241,6 +290,14 @@ impl ECPoint for Secp256r1Point {
     type PublicKey = PK;
     type Scalar = Secp256r1Scalar;
 
+    const CURVE_NAME: &'static str = "p256";
+    const COMPRESSED_SIZE: usize = 33;
+    const GENERATOR_X: &'static [u8] = &GENERATOR_X;
+    const GENERATOR_Y: &'static [u8] = &GENERATOR_Y;
+    const GENERATOR_DERIVATION: &'static str = "standard generator of FIPS 186-4, appendix D.1.2.3";
+    const BASE_POINT2_DERIVATION: &'static str =
+        "x = SHA256(SHA256(G)) over the compressed encoding of the generator G, y even";
+
     fn base_point2() -> Secp256r1Point {
         let mut v = vec![4_u8];
         v.extend(BASE_POINT2_X.as_ref());
@@ -287,6 +344,45 @@ impl ECPoint for Secp256r1Point {
         test.map_err(|_err| ErrorKey::InvalidPublicKey)
     }
 
+    fn deserialize_canonical(bytes: &[u8]) -> Result<Secp256r1Point, ErrorKey> {
+        if bytes.len() != 33 || (bytes[0] != 2 && bytes[0] != 3) {
+            return Err(ErrorKey::InvalidPublicKey);
+        }
+        let ge = PK::new(bytes).map_err(|_| ErrorKey::InvalidPublicKey)?;
+        if ge.to_encoded_point(true).as_bytes() != bytes {
+            return Err(ErrorKey::InvalidPublicKey);
+        }
+        Ok(Secp256r1Point {
+            purpose: "from_bytes",
+            ge,
+        })
+    }
+
+    fn serialized_len(compressed: bool) -> usize {
+        if compressed {
+            33
+        } else {
+            65
+        }
+    }
+
+    fn is_compressed_encoding(bytes: &[u8]) -> Option<bool> {
+        // 0x02 or 0x03 followed by x, or 0x04 followed by x and y
+        match (bytes.len(), bytes.first()) {
+            (33, Some(2)) | (33, Some(3)) => Some(true),
+            (65, Some(4)) => Some(false),
+            _ => None,
+        }
+    }
+
+    fn negate_serialized(bytes: &[u8]) -> Result<Vec<u8>, ErrorKey> {
+        Self::deserialize_canonical(bytes)?;
+        // (x, y) and (x, -y) only differ by the parity of y in the prefix
+        let mut negated = bytes.to_vec();
+        negated[0] ^= 1;
+        Ok(negated)
+    }
+
     fn pk_to_key_slice(&self) -> Vec<u8> {
         let tmp = AffinePoint::from_encoded_point(&EncodedPoint::from(&self.ge)).unwrap();
         tmp.to_encoded_point(false).as_ref().to_vec()
@@ -304,6 +400,10 @@ impl ECPoint for Secp256r1Point {
         }
     }
 
+    fn generator_mul(k: &Secp256r1Scalar) -> Secp256r1Point {
+        Self::scalar_mul_base_ct(k)
+    }
+
     fn add_point(&self, other: &PK) -> Secp256r1Point {
         let point1 = ProjectivePoint::from(
             AffinePoint::from_encoded_point(&EncodedPoint::from(&self.ge)).unwrap(),
@@ -320,6 +420,33 @@ impl ECPoint for Secp256r1Point {
         }
     }
 
+    fn multi_scalar_mul(
+        scalars: &[Secp256r1Scalar],
+        points: &[Secp256r1Point],
+    ) -> Option<Secp256r1Point> {
+        assert_eq!(
+            scalars.len(),
+            points.len(),
+            "multi-scalar multiplication of vectors of different lengths"
+        );
+        // summed in projective coordinates, which unlike a VerifyKey can be the identity
+        let sum = points
+            .iter()
+            .zip(scalars)
+            .fold(ProjectivePoint::identity(), |acc, (p, s)| {
+                let point = ProjectivePoint::from(
+                    AffinePoint::from_encoded_point(&EncodedPoint::from(&p.ge)).unwrap(),
+                );
+                acc + point * s.fe
+            });
+        // the encoding of the identity would be taken for a point of x-coordinate 0
+        if sum == ProjectivePoint::identity() {
+            return None;
+        }
+        let ge = VerifyKey::from_encoded_point(&sum.to_affine().to_encoded_point(true)).unwrap();
+        Some(Secp256r1Point { purpose: "mul", ge })
+    }
+
     fn sub_point(&self, other: &PK) -> Secp256r1Point {
         let point1 = ProjectivePoint::from(
             AffinePoint::from_encoded_point(&EncodedPoint::from(&self.ge)).unwrap(),
@@ -365,6 +492,77 @@ impl ECPoint for Secp256r1Point {
             .unwrap(),
         }
     }
+
+    fn from_coor_checked(x: &BigInt, y: &BigInt) -> Result<Secp256r1Point, ErrorCoordinates> {
+        let p = field_prime();
+        let in_field = |c: &BigInt| *c >= BigInt::zero() && *c < p;
+        if !in_field(x) || !in_field(y) {
+            return Err(ErrorCoordinates::OutOfFieldRange);
+        }
+        if Self::curve_equation_residual(x, y) != BigInt::zero() {
+            return Err(ErrorCoordinates::NotOnCurve);
+        }
+        Ok(Self::from_coor(x, y))
+    }
+
+    fn from_coor_reduce(x: &BigInt, y: &BigInt) -> Result<Secp256r1Point, ErrorCoordinates> {
+        let p = field_prime();
+        Self::from_coor_checked(&x.modulus(&p), &y.modulus(&p))
+    }
+
+    fn curve_equation_residual(x: &BigInt, y: &BigInt) -> BigInt {
+        // y^2 = x^3 - 3x + b
+        let p = field_prime();
+        let b =
+            BigInt::from_hex("5ac635d8aa3a93e7b3ebbd55769886bc651d06b0cc53b0f63bce3c3e27d2604b")
+                .unwrap();
+        let x_cube = BigInt::mod_pow(x, &BigInt::from(3), &p);
+        let three_x = BigInt::mod_mul(x, &BigInt::from(3), &p);
+        let rhs = BigInt::mod_add(&BigInt::mod_sub(&x_cube, &three_x, &p), &b, &p);
+        BigInt::mod_sub(&BigInt::mod_mul(y, y, &p), &rhs, &p)
+    }
+
+    fn hash_to_curve(message: &[u8]) -> Secp256r1Point {
+        // try-and-increment: the first counter for which H(counter || message) is the x
+        // coordinate of a curve point gives the result
+        let ge = (0u32..)
+            .find_map(|counter| {
+                let hash = Sha256::new()
+                    .chain(counter.to_be_bytes())
+                    .chain(message)
+                    .result();
+                let mut bytes = vec![2u8];
+                bytes.extend_from_slice(&hash);
+                PK::new(&bytes).ok()
+            })
+            .unwrap();
+        Secp256r1Point {
+            purpose: "hash_to_curve",
+            ge,
+        }
+    }
+}
+
+impl ConstantTimeBaseMul for Secp256r1Point {
+    fn scalar_mul_base_ct(k: &Secp256r1Scalar) -> Secp256r1Point {
+        // k*G = sum 16^i*k_i*G over the 4 bits digits k_i of k, every 16^i*k_i*G being selected
+        // from the precomputed table in constant time
+        let bytes = k.get_element().to_bytes();
+        let mut kg = ProjectivePoint::identity();
+        for (i, row) in GENERATOR_TABLE.iter().enumerate() {
+            let digit = (bytes[31 - i / 2] >> (4 * (i % 2))) & 0xf;
+            let mut point = ProjectivePoint::identity();
+            for (j, multiple) in row.iter().enumerate() {
+                point.conditional_assign(multiple, (j as u8).ct_eq(&digit));
+            }
+            kg += point;
+        }
+        // the purpose of scalar_mul, as points compare equal only if their purposes do
+        Secp256r1Point {
+            purpose: "mul",
+            ge: VerifyKey::from_encoded_point(&kg.to_affine().to_encoded_point(true)).unwrap(),
+        }
+    }
 }
 
 impl Secp256r1Point {
@@ -487,9 +685,30 @@ impl<'de> Visitor<'de> for Secp256r1PointVisitor {
     }
 }
 
+lazy_static::lazy_static! {
+    // GENERATOR_TABLE[i][j] = j*16^i*G, for the 64 digits of 4 bits of a scalar
+    static ref GENERATOR_TABLE: Vec<[ProjectivePoint; 16]> = {
+        let mut base = ProjectivePoint::generator();
+        (0..64)
+            .map(|_| {
+                let mut row = [ProjectivePoint::identity(); 16];
+                for j in 1..16 {
+                    row[j] = row[j - 1] + base;
+                }
+                base = row[15] + base;
+                row
+            })
+            .collect()
+    };
+}
+
+fn field_prime() -> BigInt {
+    BigInt::from_hex("ffffffff00000001000000000000000000000000ffffffffffffffffffffffff").unwrap()
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{BigInt, ErrorKey};
+    use super::{field_prime, BigInt, ErrorCoordinates, ErrorKey};
     use super::{Secp256r1Point, Secp256r1Scalar};
     use crate::arithmetic::traits::*;
     use crate::cryptographic_primitives::hashing::hash_sha256::HSha256;
@@ -881,4 +1100,122 @@ mod tests {
         let j: Secp256r1Scalar = ECScalar::from(&BigInt::from(2));
         assert_eq!((j * i).to_big_int(), j.to_big_int());
     }
+    #[test]
+    fn test_from_bytes_exact() {
+        let a: Secp256r1Scalar = ECScalar::new_random();
+        let a_bn_bytes = BigInt::to_bytes(&a.to_big_int());
+        let mut a_bytes = vec![0u8; 32 - a_bn_bytes.len()];
+        a_bytes.extend_from_slice(&a_bn_bytes);
+        assert_eq!(Secp256r1Scalar::from_bytes_exact(&a_bytes), Some(a));
+        assert_eq!(
+            Secp256r1Scalar::from_bytes_exact(&[0u8; 32]),
+            Some(Secp256r1Scalar::zero())
+        );
+        assert!(Secp256r1Scalar::from_bytes_exact(&a_bytes[1..]).is_none());
+
+        let q_bytes = BigInt::to_bytes(&Secp256r1Scalar::q());
+        assert!(Secp256r1Scalar::from_bytes_exact(&q_bytes).is_none());
+        assert!(Secp256r1Scalar::from_bytes_exact(&[0xff; 32]).is_none());
+    }
+
+    #[test]
+    fn test_from_bytes_mod_order() {
+        // 0xfffefd...e0 as a big-endian number, reduced modulo q
+        let bytes: Vec<u8> = (0..32).map(|i| 0xff - i).collect();
+        let mut arr = [0u8; 32];
+        arr.copy_from_slice(&bytes);
+        let expected =
+            BigInt::from_hex("fffefdfcfbfaf9f8f7f6f5f4f3f2f1f0efeeedecebeae9e8e7e6e5e4e3e2e1e0")
+                .unwrap();
+        assert_eq!(
+            Secp256r1Scalar::from_bytes_mod_order(&arr).to_big_int(),
+            expected
+        );
+        assert_eq!(
+            Secp256r1Scalar::from_bytes_mod_order(&[0u8; 32]),
+            Secp256r1Scalar::zero()
+        );
+    }
+
+    #[test]
+    fn test_curve_equation_residual() {
+        let g: Secp256r1Point = ECPoint::generator();
+        let (x, y) = (g.x_coor().unwrap(), g.y_coor().unwrap());
+        assert_eq!(
+            Secp256r1Point::curve_equation_residual(&x, &y),
+            BigInt::zero()
+        );
+        let y_plus_one = y + BigInt::one();
+        assert_ne!(
+            Secp256r1Point::curve_equation_residual(&x, &y_plus_one),
+            BigInt::zero()
+        );
+    }
+
+    #[test]
+    fn test_from_coor_checked() {
+        let g: Secp256r1Point = ECPoint::generator();
+        let (x, y) = (g.x_coor().unwrap(), g.y_coor().unwrap());
+        let point = Secp256r1Point::from_coor_checked(&x, &y).unwrap();
+        assert_eq!(point.pk_to_key_slice(), g.pk_to_key_slice());
+        assert_eq!(
+            Secp256r1Point::from_coor_checked(&field_prime(), &y).err(),
+            Some(ErrorCoordinates::OutOfFieldRange)
+        );
+        assert_eq!(
+            Secp256r1Point::from_coor_checked(&x, &BigInt::from(-1)).err(),
+            Some(ErrorCoordinates::OutOfFieldRange)
+        );
+        assert_eq!(
+            Secp256r1Point::from_coor_checked(&x, &(y + BigInt::one())).err(),
+            Some(ErrorCoordinates::NotOnCurve)
+        );
+    }
+
+    #[test]
+    fn test_from_coor_reduce() {
+        let g: Secp256r1Point = ECPoint::generator();
+        let (x, y) = (g.x_coor().unwrap(), g.y_coor().unwrap());
+        let p = field_prime();
+        let point = Secp256r1Point::from_coor_reduce(&(&x + &p), &y).unwrap();
+        assert_eq!(point.pk_to_key_slice(), g.pk_to_key_slice());
+        let point = Secp256r1Point::from_coor_reduce(&x, &(&y - &p)).unwrap();
+        assert_eq!(point.pk_to_key_slice(), g.pk_to_key_slice());
+        assert_eq!(
+            Secp256r1Point::from_coor_checked(&(&x + &p), &y).err(),
+            Some(ErrorCoordinates::OutOfFieldRange)
+        );
+        assert_eq!(
+            Secp256r1Point::from_coor_reduce(&(&x + &p), &(y + BigInt::one())).err(),
+            Some(ErrorCoordinates::NotOnCurve)
+        );
+    }
+
+    #[test]
+    fn test_deserialize_canonical() {
+        let g: Secp256r1Point = ECPoint::generator();
+        let compressed = BigInt::to_bytes(&g.bytes_compressed_to_big_int());
+        let point = Secp256r1Point::deserialize_canonical(&compressed).unwrap();
+        assert_eq!(point.pk_to_key_slice(), g.pk_to_key_slice());
+
+        // from_bytes accepts the uncompressed encoding
+        assert!(!Secp256r1Point::is_canonical_encoding(&g.pk_to_key_slice()));
+        assert!(!Secp256r1Point::is_canonical_encoding(&compressed[1..]));
+        let mut padded = vec![0u8];
+        padded.extend_from_slice(&compressed);
+        assert!(!Secp256r1Point::is_canonical_encoding(&padded));
+        let mut trailing = compressed.clone();
+        trailing.push(0);
+        assert!(!Secp256r1Point::is_canonical_encoding(&trailing));
+
+        let mut wrong_tag = compressed;
+        wrong_tag[0] = 4;
+        assert!(!Secp256r1Point::is_canonical_encoding(&wrong_tag));
+        let p =
+            BigInt::from_hex("ffffffff00000001000000000000000000000000ffffffffffffffffffffffff")
+                .unwrap();
+        let mut unreduced = vec![2u8];
+        unreduced.extend_from_slice(&BigInt::to_bytes(&p));
+        assert!(!Secp256r1Point::is_canonical_encoding(&unreduced));
+    }
 }
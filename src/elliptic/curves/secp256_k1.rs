@@ -16,10 +16,11 @@
 // The Public Key codec: Point <> SecretKey
 //
 
-use super::traits::{ECPoint, ECScalar};
+use super::traits::{ConstantTimeBaseMul, ECPoint, ECScalar};
 use crate::arithmetic::traits::*;
+use crate::cryptographic_primitives::hashing::expand_message::expand_message_xmd_sha256;
 use crate::BigInt;
-use crate::ErrorKey;
+use crate::{ErrorCoordinates, ErrorKey};
 
 #[cfg(feature = "merkle")]
 use crypto::digest::Digest;
@@ -29,17 +30,20 @@ use crypto::sha3::Sha3;
 use merkle::Hashable;
 use rand::thread_rng;
 use secp256k1::constants::{
-    CURVE_ORDER, GENERATOR_X, GENERATOR_Y, SECRET_KEY_SIZE, UNCOMPRESSED_PUBLIC_KEY_SIZE,
+    CURVE_ORDER, FIELD_SIZE, GENERATOR_X, GENERATOR_Y, SECRET_KEY_SIZE,
+    UNCOMPRESSED_PUBLIC_KEY_SIZE,
 };
-use secp256k1::{PublicKey, Secp256k1, SecretKey, VerifyOnly};
+use secp256k1::{PublicKey, Secp256k1, SecretKey, SignOnly, VerifyOnly};
 use serde::de::{self, Error, MapAccess, SeqAccess, Visitor};
 use serde::ser::SerializeStruct;
 use serde::ser::{Serialize, Serializer};
 use serde::{Deserialize, Deserializer};
+use sha2::{Digest as _, Sha256};
 use std::fmt;
 use std::ops::{Add, Mul};
 use std::ptr;
 use std::sync::{atomic, Once};
+use subtle::{Choice, ConstantTimeEq};
 use zeroize::Zeroize;
 /* X coordinate of a point of unknown discrete logarithm.
 Computed using a deterministic algorithm with the generator as input.
@@ -80,6 +84,90 @@ impl Secp256k1Point {
             ge: pk.get_element(),
         }
     }
+
+    /// Returns `self + tweak * G`, computed by libsecp256k1, as used by BIP32 derivation and
+    /// Taproot output keys.
+    ///
+    /// Fails if the result is the point at infinity.
+    pub fn tweak_add(&self, tweak: &Secp256k1Scalar) -> Result<Secp256k1Point, ErrorKey> {
+        let mut ge = self.ge;
+        ge.add_exp_assign(get_context(), &tweak.fe[..])
+            .map_err(|_| ErrorKey::InvalidPublicKey)?;
+        Ok(Secp256k1Point {
+            purpose: "tweak_add",
+            ge,
+        })
+    }
+
+    /// Hashes `msg` to the curve with the `secp256k1_XMD:SHA-256_SSWU_RO_` suite of RFC 9380
+    /// (https://www.rfc-editor.org/rfc/rfc9380#section-8.7), bound to the domain separation tag
+    /// `dst`, so the output matches other implementations of the suite.
+    ///
+    /// Unlike [hash_to_curve](ECPoint::hash_to_curve), the output is indistinguishable from a
+    /// random point. Points are computed with big integers, in variable time, so `msg` must not
+    /// be secret.
+    pub fn hash_to_curve_rfc9380(msg: &[u8], dst: &[u8]) -> Secp256k1Point {
+        let uniform_bytes = expand_message_xmd_sha256(msg, dst, 2 * SSWU_L);
+        let p = field_prime();
+        let q0 = map_to_curve_sswu(&BigInt::from_bytes(&uniform_bytes[..SSWU_L]).modulus(&p));
+        let q1 = map_to_curve_sswu(&BigInt::from_bytes(&uniform_bytes[SSWU_L..]).modulus(&p));
+        // the cofactor of secp256k1 is 1
+        Secp256k1Point {
+            purpose: "hash_to_curve_rfc9380",
+            ge: q0.add_point(&q1.ge).ge,
+        }
+    }
+
+    // like `from_coor`, but fails instead of panicking on coordinates of a point that isn't on
+    // the curve. Used when deserializing untrusted points
+    fn try_from_coor(x: &BigInt, y: &BigInt) -> Result<Secp256k1Point, ErrorKey> {
+        let vec_x = BigInt::to_bytes(x);
+        let vec_y = BigInt::to_bytes(y);
+        if vec_x.len() > 32 || vec_y.len() > 32 {
+            return Err(ErrorKey::InvalidPublicKey);
+        }
+        let mut v = [0u8; 65];
+        v[0] = 4;
+        v[33 - vec_x.len()..33].copy_from_slice(&vec_x);
+        v[65 - vec_y.len()..].copy_from_slice(&vec_y);
+        PK::from_slice(&v)
+            .map(|ge| Secp256k1Point {
+                purpose: "base_fe",
+                ge,
+            })
+            .map_err(|_| ErrorKey::InvalidPublicKey)
+    }
+}
+
+impl Secp256k1Scalar {
+    /// Returns the scalar in the layout libsecp256k1 uses for secret keys: 32 bytes, big-endian.
+    pub fn to_libsecp_bytes(&self) -> [u8; SECRET_KEY_SIZE] {
+        let mut bytes = [0u8; SECRET_KEY_SIZE];
+        bytes.copy_from_slice(&self.fe[..]);
+        bytes
+    }
+
+    /// Parses a scalar from the layout returned by [to_libsecp_bytes](Self::to_libsecp_bytes).
+    ///
+    /// Returns `None` if the bytes encode a number that is not smaller than the group order.
+    /// Unlike libsecp256k1, all-zero bytes are accepted and give the zero scalar.
+    pub fn from_libsecp_bytes(bytes: &[u8; SECRET_KEY_SIZE]) -> Option<Secp256k1Scalar> {
+        Self::from_bytes_exact(bytes)
+    }
+
+    /// Returns `self + tweak` modulo the group order, computed by libsecp256k1. This is the
+    /// secret key matching [Secp256k1Point::tweak_add].
+    ///
+    /// Fails if `self` or the result is zero.
+    pub fn tweak_add(&self, tweak: &Secp256k1Scalar) -> Result<Secp256k1Scalar, ErrorKey> {
+        let mut fe = self.fe;
+        fe.add_assign(&tweak.fe[..])
+            .map_err(|_| ErrorKey::InvalidPublicKey)?;
+        Ok(Secp256k1Scalar {
+            purpose: "tweak_add",
+            fe,
+        })
+    }
 }
 
 impl Zeroize for Secp256k1Scalar {
@@ -134,6 +222,29 @@ impl ECScalar for Secp256k1Scalar {
         }
     }
 
+    fn from_bytes_exact(bytes: &[u8]) -> Option<Secp256k1Scalar> {
+        if bytes.len() != SECRET_KEY_SIZE {
+            return None;
+        }
+        // secp256k1 doesn't accept zero as a secret key
+        if bytes.iter().all(|b| *b == 0) {
+            return Some(FE::zero());
+        }
+        SK::from_slice(bytes).ok().map(|fe| Secp256k1Scalar {
+            purpose: "from_bytes_exact",
+            fe,
+        })
+    }
+
+    fn from_bytes_mod_order(bytes: &[u8; 32]) -> Secp256k1Scalar {
+        let n = BigInt::modulus(&BigInt::from_bytes(bytes), &FE::q());
+        // secp256k1 doesn't accept zero as a secret key
+        if n == BigInt::zero() {
+            return FE::zero();
+        }
+        ECScalar::from(&n)
+    }
+
     fn to_big_int(&self) -> BigInt {
         BigInt::from_bytes(&(self.fe[0..self.fe.len()]))
     }
@@ -189,6 +300,35 @@ impl ECScalar for Secp256k1Scalar {
         let bn_inv = BigInt::mod_inv(&bignum, &FE::q()).unwrap();
         ECScalar::from(&bn_inv)
     }
+
+    fn invert_ct(&self) -> Option<Secp256k1Scalar> {
+        // zero isn't a valid secret key, and libsecp256k1 refuses to multiply it
+        if bool::from(self.fe[..].ct_eq(&[0u8; SECRET_KEY_SIZE])) {
+            return None;
+        }
+        let mut exponent = CURVE_ORDER;
+        exponent[SECRET_KEY_SIZE - 1] -= 2;
+        let mut one = [0u8; SECRET_KEY_SIZE];
+        one[SECRET_KEY_SIZE - 1] = 1;
+        let mut inverse = SK::from_slice(&one).unwrap();
+        for byte in exponent.iter() {
+            for i in (0..8).rev() {
+                let square = inverse;
+                inverse.mul_assign(&square[..]).unwrap();
+                if (byte >> i) & 1 == 1 {
+                    inverse.mul_assign(&self.fe[..]).unwrap();
+                }
+            }
+        }
+        Some(Secp256k1Scalar {
+            purpose: "invert_ct",
+            fe: inverse,
+        })
+    }
+
+    fn ct_eq(&self, other: &Secp256k1Scalar) -> Choice {
+        self.fe[..].ct_eq(&other.fe[..])
+    }
 }
 impl Mul<Secp256k1Scalar> for Secp256k1Scalar {
     type Output = Secp256k1Scalar;
@@ -276,6 +416,14 @@ impl ECPoint for Secp256k1Point {
     type PublicKey = PK;
     type Scalar = Secp256k1Scalar;
 
+    const CURVE_NAME: &'static str = "secp256k1";
+    const COMPRESSED_SIZE: usize = 33;
+    const GENERATOR_X: &'static [u8] = &GENERATOR_X;
+    const GENERATOR_Y: &'static [u8] = &GENERATOR_Y;
+    const GENERATOR_DERIVATION: &'static str = "standard generator of SEC 2 v2, section 2.4.1";
+    const BASE_POINT2_DERIVATION: &'static str =
+        "x = SHA256(SHA256(SHA256(G))) over the compressed encoding of the generator G, y even";
+
     fn base_point2() -> Secp256k1Point {
         let mut v = vec![4_u8];
         v.extend(BASE_POINT2_X.as_ref());
@@ -330,6 +478,20 @@ impl ECPoint for Secp256k1Point {
 
         let byte_len = bytes_vec.len();
         match byte_len {
+            // SEC1 hybrid encoding: 0x06 (even y) or 0x07 (odd y) followed by x and y
+            65 if bytes[0] == 6 || bytes[0] == 7 => {
+                bytes_array_65[0] = 4;
+                bytes_array_65[1..].copy_from_slice(&bytes[1..]);
+                let ge = PK::from_slice(&bytes_array_65).map_err(|_| ErrorKey::InvalidPublicKey)?;
+                // the compressed prefix, 0x02 or 0x03, carries the parity of y
+                if ge.serialize()[0] & 1 != bytes[0] & 1 {
+                    return Err(ErrorKey::InvalidPublicKey);
+                }
+                Ok(Secp256k1Point {
+                    purpose: "from_bytes",
+                    ge,
+                })
+            }
             33..=63 => {
                 let mut template = vec![0; 64 - bytes_vec.len()];
                 template.extend_from_slice(&bytes);
@@ -380,6 +542,45 @@ impl ECPoint for Secp256k1Point {
             }
         }
     }
+    fn deserialize_canonical(bytes: &[u8]) -> Result<Secp256k1Point, ErrorKey> {
+        if bytes.len() != 33 || (bytes[0] != 2 && bytes[0] != 3) {
+            return Err(ErrorKey::InvalidPublicKey);
+        }
+        let ge = PK::from_slice(bytes).map_err(|_| ErrorKey::InvalidPublicKey)?;
+        if ge.serialize()[..] != bytes[..] {
+            return Err(ErrorKey::InvalidPublicKey);
+        }
+        Ok(Secp256k1Point {
+            purpose: "from_bytes",
+            ge,
+        })
+    }
+
+    fn serialized_len(compressed: bool) -> usize {
+        if compressed {
+            33
+        } else {
+            65
+        }
+    }
+
+    fn is_compressed_encoding(bytes: &[u8]) -> Option<bool> {
+        // 0x02 or 0x03 followed by x, or 0x04 followed by x and y
+        match (bytes.len(), bytes.first()) {
+            (33, Some(2)) | (33, Some(3)) => Some(true),
+            (65, Some(4)) => Some(false),
+            _ => None,
+        }
+    }
+
+    fn negate_serialized(bytes: &[u8]) -> Result<Vec<u8>, ErrorKey> {
+        Self::deserialize_canonical(bytes)?;
+        // (x, y) and (x, -y) only differ by the parity of y in the prefix
+        let mut negated = bytes.to_vec();
+        negated[0] ^= 1;
+        Ok(negated)
+    }
+
     fn pk_to_key_slice(&self) -> Vec<u8> {
         let mut v = vec![4_u8];
         let x_vec = BigInt::to_bytes(&self.x_coor().unwrap());
@@ -398,6 +599,10 @@ impl ECPoint for Secp256k1Point {
         v
     }
 
+    fn ct_eq(&self, other: &Secp256k1Point) -> Choice {
+        self.ge.serialize_uncompressed()[..].ct_eq(&other.ge.serialize_uncompressed()[..])
+    }
+
     fn scalar_mul(&self, fe: &SK) -> Secp256k1Point {
         let mut new_point = *self;
         new_point
@@ -407,6 +612,10 @@ impl ECPoint for Secp256k1Point {
         new_point
     }
 
+    fn generator_mul(k: &Secp256k1Scalar) -> Secp256k1Point {
+        Self::scalar_mul_base_ct(k)
+    }
+
     fn add_point(&self, other: &PK) -> Secp256k1Point {
         Secp256k1Point {
             purpose: "combine",
@@ -414,6 +623,64 @@ impl ECPoint for Secp256k1Point {
         }
     }
 
+    fn double_scalar_mul_base(
+        k: &Secp256k1Scalar,
+        point: &Secp256k1Point,
+        e: &Secp256k1Scalar,
+    ) -> Option<Secp256k1Point> {
+        // libsecp256k1 refuses to multiply by zero, and k*G = -e*point sums to the point at
+        // infinity
+        let is_zero = |s: &Secp256k1Scalar| s.fe[..].iter().all(|b| *b == 0);
+        match (is_zero(k), is_zero(e)) {
+            (true, true) => None,
+            (true, false) => Some(point.scalar_mul(&e.get_element())),
+            (false, true) => Some(Self::scalar_mul_base_ct(k)),
+            (false, false) => {
+                // the bindings don't expose secp256k1_ecmult, which would do both
+                // multiplications at once, but ecmult_gen's precomputed table still saves time
+                // on k*G
+                let kg = Self::scalar_mul_base_ct(k);
+                let ep = point.scalar_mul(&e.get_element());
+                let ge = kg.ge.combine(&ep.ge).ok()?;
+                Some(Secp256k1Point {
+                    purpose: "double_scalar_mul_base",
+                    ge,
+                })
+            }
+        }
+    }
+
+    fn multi_scalar_mul(
+        scalars: &[Secp256k1Scalar],
+        points: &[Secp256k1Point],
+    ) -> Option<Secp256k1Point> {
+        assert_eq!(
+            scalars.len(),
+            points.len(),
+            "multi-scalar multiplication of vectors of different lengths"
+        );
+        // the sum of no terms is the point at infinity, which a PublicKey can't be
+        if points.is_empty() {
+            return None;
+        }
+        let ge = if points.len() < PIPPENGER_MIN_TERMS {
+            // libsecp256k1 refuses to multiply by zero, zero terms are left out instead
+            let products: Vec<PK> = points
+                .iter()
+                .zip(scalars)
+                .filter(|(_, s)| s.to_big_int() != BigInt::zero())
+                .map(|(p, s)| p.scalar_mul(&s.get_element()).ge)
+                .collect();
+            sum_points(&products.iter().collect::<Vec<_>>())
+        } else {
+            pippenger(points, scalars)
+        };
+        Some(Secp256k1Point {
+            purpose: "multi_scalar_mul",
+            ge: ge?,
+        })
+    }
+
     fn sub_point(&self, other: &PK) -> Secp256k1Point {
         let point = Secp256k1Point {
             purpose: "sub_point",
@@ -477,6 +744,260 @@ impl ECPoint for Secp256k1Point {
             ge: PK::from_slice(&v).unwrap(),
         }
     }
+
+    fn from_coor_checked(x: &BigInt, y: &BigInt) -> Result<Secp256k1Point, ErrorCoordinates> {
+        let p = field_prime();
+        let in_field = |c: &BigInt| *c >= BigInt::zero() && *c < p;
+        if !in_field(x) || !in_field(y) {
+            return Err(ErrorCoordinates::OutOfFieldRange);
+        }
+        if Self::curve_equation_residual(x, y) != BigInt::zero() {
+            return Err(ErrorCoordinates::NotOnCurve);
+        }
+        Ok(Self::from_coor(x, y))
+    }
+
+    fn from_coor_reduce(x: &BigInt, y: &BigInt) -> Result<Secp256k1Point, ErrorCoordinates> {
+        let p = field_prime();
+        Self::from_coor_checked(&x.modulus(&p), &y.modulus(&p))
+    }
+
+    fn curve_equation_residual(x: &BigInt, y: &BigInt) -> BigInt {
+        // y^2 = x^3 + 7
+        let p = BigInt::from_bytes(&FIELD_SIZE);
+        let x_cube = BigInt::mod_pow(x, &BigInt::from(3), &p);
+        let rhs = BigInt::mod_add(&x_cube, &BigInt::from(7), &p);
+        BigInt::mod_sub(&BigInt::mod_mul(y, y, &p), &rhs, &p)
+    }
+
+    fn hash_to_curve(message: &[u8]) -> Secp256k1Point {
+        // try-and-increment: the first counter for which H(counter || message) is the x
+        // coordinate of a curve point gives the result
+        let ge = (0u32..)
+            .find_map(|counter| {
+                let hash = Sha256::new()
+                    .chain(counter.to_be_bytes())
+                    .chain(message)
+                    .result();
+                let mut bytes = vec![2u8];
+                bytes.extend_from_slice(&hash);
+                PK::from_slice(&bytes).ok()
+            })
+            .unwrap();
+        Secp256k1Point {
+            purpose: "hash_to_curve",
+            ge,
+        }
+    }
+}
+
+impl ConstantTimeBaseMul for Secp256k1Point {
+    fn scalar_mul_base_ct(k: &Secp256k1Scalar) -> Secp256k1Point {
+        // from_secret_key goes through secp256k1_ecmult_gen. scalar_mul's tweak_mul is measurably
+        // faster for small scalars
+        Secp256k1Point {
+            purpose: "scalar_mul_base_ct",
+            ge: PK::from_secret_key(&SIGNING_CONTEXT, &k.get_element()),
+        }
+    }
+}
+
+// length of the hash to field outputs of secp256k1_XMD:SHA-256_SSWU_RO_
+const SSWU_L: usize = 48;
+
+// constants of secp256k1_XMD:SHA-256_SSWU_RO_ (RFC 9380, sections 8.7 and E.1): the curve
+// E': y^2 = x^3 + A'x + B' 3-isogenous to secp256k1, Z, and the coefficients of the isogeny map
+const SSWU_A: &str = "3f8731abdd661adca08a5558f0f5d272e953d363cb6f0e5d405447c01a444533";
+const SSWU_B: u32 = 1771;
+const SSWU_Z: i32 = -11;
+const ISO_X_NUM: [&str; 4] = [
+    "8e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38daaaaa8c7",
+    "7d3d4c80bc321d5b9f315cea7fd44c5d595d2fc0bf63b92dfff1044f17c6581",
+    "534c328d23f234e6e2a413deca25caece4506144037c40314ecbd0b53d9dd262",
+    "8e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38e38daaaaa88c",
+];
+const ISO_X_DEN: [&str; 3] = [
+    "d35771193d94918a9ca34ccbb7b640dd86cd409542f8487d9fe6b745781eb49b",
+    "edadc6f64383dc1df7c4b2d51b54225406d36b641f5e41bbc52a56612a8c6d14",
+    "1",
+];
+const ISO_Y_NUM: [&str; 4] = [
+    "4bda12f684bda12f684bda12f684bda12f684bda12f684bda12f684b8e38e23c",
+    "c75e0c32d5cb7c0fa9d0a54b12a0a6d5647ab046d686da6fdffc90fc201d71a3",
+    "29a6194691f91a73715209ef6512e576722830a201be2018a765e85a9ecee931",
+    "2f684bda12f684bda12f684bda12f684bda12f684bda12f684bda12f38e38d84",
+];
+const ISO_Y_DEN: [&str; 4] = [
+    "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffff93b",
+    "7a06534bb8bdb49fd5e9e6632722c2989467c1bfc8e8d978dfb425d2685c2573",
+    "6484aa716545ca2cf3a70c3fa8fe337e0a3d21162f0d6299a7bf8192bfd2a76f",
+    "1",
+];
+
+fn field_prime() -> BigInt {
+    BigInt::from_bytes(&FIELD_SIZE)
+}
+
+// simplified SWU map to E' (RFC 9380, section 6.6.2), followed by the 3-isogeny to secp256k1
+fn map_to_curve_sswu(u: &BigInt) -> Secp256k1Point {
+    let p = field_prime();
+    let a = BigInt::from_hex(SSWU_A).unwrap();
+    let b = BigInt::from(SSWU_B);
+    let z = BigInt::mod_sub(&BigInt::zero(), &BigInt::from(-SSWU_Z), &p);
+    let g = |x: &BigInt| {
+        let x3 = BigInt::mod_pow(x, &BigInt::from(3), &p);
+        BigInt::mod_add(
+            &BigInt::mod_add(&x3, &BigInt::mod_mul(&a, x, &p), &p),
+            &b,
+            &p,
+        )
+    };
+    // x^((p-1)/2) is 0 or 1 exactly for squares, and x^((p+1)/4) is a square root since
+    // p = 3 mod 4
+    let is_square =
+        |x: &BigInt| BigInt::mod_pow(x, &((&p - BigInt::one()) >> 1), &p) <= BigInt::one();
+    let sqrt = |x: &BigInt| BigInt::mod_pow(x, &((&p + BigInt::one()) >> 2), &p);
+    let inv0 = |x: &BigInt| BigInt::mod_pow(x, &(&p - BigInt::from(2)), &p);
+
+    let zu2 = BigInt::mod_mul(&z, &BigInt::mod_mul(u, u, &p), &p);
+    let tv1 = inv0(&BigInt::mod_add(&BigInt::mod_mul(&zu2, &zu2, &p), &zu2, &p));
+    let x1 = if tv1 == BigInt::zero() {
+        BigInt::mod_mul(&b, &inv0(&BigInt::mod_mul(&z, &a, &p)), &p)
+    } else {
+        let minus_b_over_a =
+            BigInt::mod_sub(&BigInt::zero(), &BigInt::mod_mul(&b, &inv0(&a), &p), &p);
+        BigInt::mod_mul(
+            &minus_b_over_a,
+            &BigInt::mod_add(&BigInt::one(), &tv1, &p),
+            &p,
+        )
+    };
+    let gx1 = g(&x1);
+    let (x, mut y) = if is_square(&gx1) {
+        let y1 = sqrt(&gx1);
+        (x1, y1)
+    } else {
+        let x2 = BigInt::mod_mul(&zu2, &x1, &p);
+        let y2 = sqrt(&g(&x2));
+        (x2, y2)
+    };
+    if u.test_bit(0) != y.test_bit(0) {
+        y = BigInt::mod_sub(&BigInt::zero(), &y, &p);
+    }
+
+    let eval = |coefficients: &[&str]| {
+        coefficients.iter().rev().fold(BigInt::zero(), |acc, k| {
+            BigInt::mod_add(
+                &BigInt::mod_mul(&acc, &x, &p),
+                &BigInt::from_hex(k).unwrap(),
+                &p,
+            )
+        })
+    };
+    let iso_x = BigInt::mod_mul(&eval(&ISO_X_NUM), &inv0(&eval(&ISO_X_DEN)), &p);
+    let iso_y = BigInt::mod_mul(
+        &y,
+        &BigInt::mod_mul(&eval(&ISO_Y_NUM), &inv0(&eval(&ISO_Y_DEN)), &p),
+        &p,
+    );
+    Secp256k1Point::try_from_coor(&iso_x, &iso_y).expect("the isogeny maps E' to secp256k1")
+}
+
+// below this many terms, multiplying every point with libsecp256k1's ecmult is faster
+const PIPPENGER_MIN_TERMS: usize = 128;
+
+// Adds up points, `None` standing for the point at infinity. libsecp256k1 accumulates the sum in
+// jacobian coordinates and converts it back to affine once, so a single call is much cheaper than
+// adding the points one by one.
+fn sum_points(points: &[&PK]) -> Option<PK> {
+    if points.is_empty() {
+        return None;
+    }
+    PK::combine_keys(points).ok()
+}
+
+// Pippenger's bucket method (see "Faster batch forgery identification", D. J. Bernstein et al.,
+// section 4). Scalars are split into signed digits of `c` bits, from the most significant
+// window down. In every window each point goes into the bucket of its digit, the point being
+// negated for a negative digit, then sum_j j*B_j is added to 2^c times the sum so far.
+//
+// libsecp256k1 only exposes additions through `combine_keys`, each call paying a field
+// inversion, so the multiples j*B_j are summed by repeating B_j rather than with the usual
+// running sums. This keeps a call per bucket and one per window.
+fn pippenger(points: &[Secp256k1Point], scalars: &[Secp256k1Scalar]) -> Option<PK> {
+    let c = match points.len() {
+        0..=2047 => 4,
+        _ => 6,
+    };
+    let bucket_count = 1 << (c - 1);
+    let digits: Vec<Vec<i32>> = scalars.iter().map(|s| signed_digits(s, c)).collect();
+    let negated: Vec<PK> = points
+        .iter()
+        .map(|p| {
+            let mut ge = p.ge;
+            ge.negate_assign(get_context());
+            ge
+        })
+        .collect();
+
+    let mut acc: Option<PK> = None;
+    for window in (0..digits[0].len()).rev() {
+        let mut buckets: Vec<Vec<&PK>> = vec![Vec::new(); bucket_count];
+        for (k, point_digits) in digits.iter().enumerate() {
+            let digit = point_digits[window];
+            if digit > 0 {
+                buckets[digit as usize - 1].push(&points[k].ge);
+            } else if digit < 0 {
+                buckets[(-digit) as usize - 1].push(&negated[k]);
+            }
+        }
+        let bucket_sums: Vec<Option<PK>> = buckets.iter().map(|b| sum_points(b)).collect();
+
+        let mut terms: Vec<&PK> = Vec::new();
+        if let Some(acc) = &acc {
+            terms.extend(std::iter::repeat_n(acc, 1 << c));
+        }
+        for (j, bucket_sum) in bucket_sums.iter().enumerate() {
+            if let Some(bucket_sum) = bucket_sum {
+                terms.extend(std::iter::repeat_n(bucket_sum, j + 1));
+            }
+        }
+        acc = sum_points(&terms);
+    }
+    acc
+}
+
+// Digits of `s` in base 2^c in [-2^(c-1), 2^(c-1)], least significant first, with
+// s = sum_i d_i * 2^(c*i)
+fn signed_digits(s: &Secp256k1Scalar, c: usize) -> Vec<i32> {
+    let bytes = &s.fe[..];
+    let bit = |i: usize| -> i32 {
+        if i >= 256 {
+            0
+        } else {
+            ((bytes[31 - i / 8] >> (i % 8)) & 1) as i32
+        }
+    };
+    let windows = 256 / c + 1;
+    let mut digits = Vec::with_capacity(windows);
+    let mut carry = 0;
+    for window in 0..windows {
+        let mut digit = carry;
+        for i in 0..c {
+            digit += bit(window * c + i) << i;
+        }
+        carry = 0;
+        if digit > 1 << (c - 1) {
+            digit -= 1 << c;
+            carry = 1;
+        }
+        digits.push(digit);
+    }
+    digits
+}
+
+lazy_static::lazy_static! {
+    static ref SIGNING_CONTEXT: Secp256k1<SignOnly> = Secp256k1::signing_only();
 }
 
 static mut CONTEXT: Option<Secp256k1<VerifyOnly>> = None;
@@ -583,7 +1104,8 @@ impl<'de> Visitor<'de> for Secp256k1PointVisitor {
         let bx = BigInt::from_hex(x).map_err(V::Error::custom)?;
         let by = BigInt::from_hex(y).map_err(V::Error::custom)?;
 
-        Ok(Secp256k1Point::from_coor(&bx, &by))
+        Secp256k1Point::try_from_coor(&bx, &by)
+            .map_err(|_| V::Error::custom("invalid secp256k1 point"))
     }
 
     fn visit_map<E: MapAccess<'de>>(self, mut map: E) -> Result<Secp256k1Point, E::Error> {
@@ -604,7 +1126,8 @@ impl<'de> Visitor<'de> for Secp256k1PointVisitor {
         let bx = BigInt::from_hex(&x).map_err(E::Error::custom)?;
         let by = BigInt::from_hex(&y).map_err(E::Error::custom)?;
 
-        Ok(Secp256k1Point::from_coor(&bx, &by))
+        Secp256k1Point::try_from_coor(&bx, &by)
+            .map_err(|_| E::Error::custom("invalid secp256k1 point"))
     }
 }
 
@@ -613,6 +1136,7 @@ mod tests {
     use super::BigInt;
     use super::Secp256k1Point;
     use super::Secp256k1Scalar;
+    use super::{field_prime, FIELD_SIZE};
     use crate::arithmetic::traits::*;
     use crate::cryptographic_primitives::hashing::hash_sha256::HSha256;
     use crate::cryptographic_primitives::hashing::traits::Hash;
@@ -683,6 +1207,133 @@ mod tests {
         assert_eq!(des_pk.ge, pk.ge);
     }
 
+    #[test]
+    fn deserialize_invalid_pk_fails() {
+        let off_curve = "{\"x\":\"5\",\"y\":\"5\"}";
+        assert!(serde_json::from_str::<Secp256k1Point>(off_curve).is_err());
+        let too_big = format!("{{\"x\":\"{}\",\"y\":\"5\"}}", "1".repeat(65));
+        assert!(serde_json::from_str::<Secp256k1Point>(&too_big).is_err());
+    }
+
+    #[test]
+    fn test_tweak_add() {
+        let sk: Secp256k1Scalar = ECScalar::new_random();
+        let tweak: Secp256k1Scalar = ECScalar::new_random();
+        let pk = Secp256k1Point::generator() * sk;
+
+        let tweaked_pk = pk.tweak_add(&tweak).unwrap();
+        assert_eq!(tweaked_pk, Secp256k1Point::generator() * (sk + tweak));
+        let tweaked_sk = sk.tweak_add(&tweak).unwrap();
+        assert_eq!(tweaked_sk, sk + tweak);
+        assert_eq!(tweaked_pk, Secp256k1Point::generator() * tweaked_sk);
+
+        // tweaking by -sk gives the point at infinity and the zero key
+        let minus_sk: Secp256k1Scalar = ECScalar::from(&(Secp256k1Scalar::q() - sk.to_big_int()));
+        assert!(pk.tweak_add(&minus_sk).is_err());
+        assert!(sk.tweak_add(&minus_sk).is_err());
+    }
+
+    #[test]
+    fn test_multi_scalar_mul_edge_cases() {
+        let g = Secp256k1Point::generator();
+        let points: Vec<Secp256k1Point> = (0..300)
+            .map(|_| g * Secp256k1Scalar::new_random())
+            .collect();
+        let q = Secp256k1Scalar::q();
+        // scalars with every digit at the bounds of the signed windows, and pairs of terms
+        // cancelling out so that buckets and windows sum to the point at infinity
+        let mut scalars: Vec<Secp256k1Scalar> = vec![
+            ECScalar::from(&(q.clone() - BigInt::one())),
+            ECScalar::from(&BigInt::one()),
+            ECScalar::from(&((BigInt::one() << 256) - &q - BigInt::one())),
+            ECScalar::from(
+                &BigInt::from_hex(
+                    "8888888888888888888888888888888888888888888888888888888888888888",
+                )
+                .unwrap(),
+            ),
+        ];
+        scalars.extend((4..150).map(|_| Secp256k1Scalar::new_random()));
+        let mut points_cancelling = points[..150].to_vec();
+        points_cancelling.extend_from_slice(&points[..150]);
+        let mut scalars_cancelling = scalars.clone();
+        scalars_cancelling.extend(
+            scalars
+                .iter()
+                .map(|s| -> Secp256k1Scalar { ECScalar::from(&(q.clone() - s.to_big_int())) }),
+        );
+        let seven: Secp256k1Scalar = ECScalar::from(&BigInt::from(7));
+        points_cancelling.push(g);
+        scalars_cancelling.push(seven);
+        assert_eq!(
+            Secp256k1Point::multi_scalar_mul(&scalars_cancelling, &points_cancelling),
+            Some(g * seven)
+        );
+        // without the last term, the sum is the point at infinity
+        assert_eq!(
+            Secp256k1Point::multi_scalar_mul(&scalars_cancelling[..300], &points_cancelling[..300]),
+            None
+        );
+
+        let expected = points[..150]
+            .iter()
+            .zip(&scalars)
+            .fold(None, |acc: Option<Secp256k1Point>, (p, s)| {
+                let product = *p * *s;
+                Some(acc.map_or(product, |acc| acc + product))
+            })
+            .unwrap();
+        assert_eq!(
+            Secp256k1Point::multi_scalar_mul(&scalars, &points[..150]),
+            Some(expected)
+        );
+    }
+
+    // RFC 9380, appendix J.8.1
+    #[test]
+    fn test_hash_to_curve_rfc9380_vectors() {
+        let dst = b"QUUX-V01-CS02-with-secp256k1_XMD:SHA-256_SSWU_RO_";
+        let q128 = format!("q128_{}", "q".repeat(128));
+        let a512 = format!("a512_{}", "a".repeat(512));
+        let vectors: [(&[u8], &str, &str); 5] = [
+            (
+                b"",
+                "c1cae290e291aee617ebaef1be6d73861479c48b841eaba9b7b5852ddfeb1346",
+                "64fa678e07ae116126f08b022a94af6de15985c996c3a91b64c406a960e51067",
+            ),
+            (
+                b"abc",
+                "3377e01eab42db296b512293120c6cee72b6ecf9f9205760bd9ff11fb3cb2c4b",
+                "7f95890f33efebd1044d382a01b1bee0900fb6116f94688d487c6c7b9c8371f6",
+            ),
+            (
+                b"abcdef0123456789",
+                "bac54083f293f1fe08e4a70137260aa90783a5cb84d3f35848b324d0674b0e3a",
+                "4436476085d4c3c4508b60fcf4389c40176adce756b398bdee27bca19758d828",
+            ),
+            (
+                q128.as_bytes(),
+                "e2167bc785333a37aa562f021f1e881defb853839babf52a7f72b102e41890e9",
+                "f2401dd95cc35867ffed4f367cd564763719fbc6a53e969fb8496a1e6685d873",
+            ),
+            (
+                a512.as_bytes(),
+                "e3c8d35aaaf0b9b647e88a0a0a7ee5d5bed5ad38238152e4e6fd8c1f8cb7c998",
+                "8446eeb6181bf12f56a9d24e262221cc2f0c4725c7e3803024b5888ee5823aa6",
+            ),
+        ];
+        for (msg, x, y) in vectors.iter() {
+            let point = Secp256k1Point::hash_to_curve_rfc9380(msg, dst);
+            assert_eq!(point.x_coor().unwrap(), BigInt::from_hex(x).unwrap());
+            assert_eq!(point.y_coor().unwrap(), BigInt::from_hex(y).unwrap());
+        }
+        // the tag separates domains
+        assert_ne!(
+            Secp256k1Point::hash_to_curve_rfc9380(b"abc", b"another tag"),
+            Secp256k1Point::hash_to_curve_rfc9380(b"abc", dst)
+        );
+    }
+
     #[test]
     fn bincode_pk() {
         let pk = Secp256k1Point::generator();
@@ -692,7 +1343,7 @@ mod tests {
     }
 
     use crate::elliptic::curves::secp256_k1::{FE, GE};
-    use crate::ErrorKey;
+    use crate::{ErrorCoordinates, ErrorKey};
 
     #[test]
     fn test_serdes_pk() {
@@ -758,6 +1409,49 @@ mod tests {
         assert!(result.is_ok() | result.is_err())
     }
 
+    #[test]
+    fn test_double_scalar_mul_base_at_infinity() {
+        let x: FE = ECScalar::new_random();
+        let e: FE = ECScalar::new_random();
+        let point = GE::generator() * x;
+        // k*G = -e*point
+        let k: FE = ECScalar::from(&(FE::q() - (e * x).to_big_int()));
+        assert!(GE::double_scalar_mul_base(&k, &point, &e).is_none());
+
+        // libsecp256k1 doesn't multiply by zero, which is skipped
+        let zero = FE::zero();
+        assert!(GE::double_scalar_mul_base(&zero, &point, &zero).is_none());
+        assert_eq!(
+            GE::double_scalar_mul_base(&zero, &point, &e).unwrap(),
+            point * e
+        );
+        assert_eq!(
+            GE::double_scalar_mul_base(&k, &point, &zero).unwrap(),
+            GE::generator() * k
+        );
+    }
+
+    #[test]
+    fn test_from_bytes_hybrid() {
+        for _ in 0..10 {
+            let point = GE::generator() * FE::new_random();
+            let mut hybrid = point.get_element().serialize_uncompressed();
+            let odd = point.y_coor().unwrap().test_bit(0);
+            hybrid[0] = if odd { 7 } else { 6 };
+            assert_eq!(GE::from_bytes(&hybrid).unwrap(), point);
+
+            // the parity bit of the other point with this x
+            hybrid[0] ^= 1;
+            assert_eq!(GE::from_bytes(&hybrid), Err(ErrorKey::InvalidPublicKey));
+        }
+
+        // x = 5 isn't the x-coordinate of a point of the curve
+        let mut hybrid = [0u8; 65];
+        hybrid[0] = 6;
+        hybrid[32] = 5;
+        assert_eq!(GE::from_bytes(&hybrid), Err(ErrorKey::InvalidPublicKey));
+    }
+
     #[test]
     fn test_minus_point() {
         let a: FE = ECScalar::new_random();
@@ -834,4 +1528,118 @@ mod tests {
             base_point2
         );
     }
+    #[test]
+    fn test_libsecp_bytes() {
+        let a: FE = ECScalar::new_random();
+        let bytes = a.to_libsecp_bytes();
+        // same bytes as the ones the C library serializes
+        assert_eq!(hex::encode(bytes), a.get_element().to_string());
+        assert_eq!(bytes[..], a.get_element()[..]);
+        assert_eq!(FE::from_libsecp_bytes(&bytes), Some(a));
+
+        assert_eq!(FE::zero().to_libsecp_bytes(), [0u8; 32]);
+        assert_eq!(FE::from_libsecp_bytes(&[0u8; 32]), Some(FE::zero()));
+        assert_eq!(FE::from_libsecp_bytes(&[0xff; 32]), None);
+    }
+
+    #[test]
+    fn test_from_bytes_exact() {
+        let a: FE = ECScalar::new_random();
+        let a_bn_bytes = BigInt::to_bytes(&a.to_big_int());
+        let mut a_bytes = vec![0u8; 32 - a_bn_bytes.len()];
+        a_bytes.extend_from_slice(&a_bn_bytes);
+        assert_eq!(FE::from_bytes_exact(&a_bytes), Some(a));
+        assert_eq!(FE::from_bytes_exact(&[0u8; 32]), Some(FE::zero()));
+        assert!(FE::from_bytes_exact(&a_bytes[1..]).is_none());
+
+        let q_bytes = BigInt::to_bytes(&FE::q());
+        assert!(FE::from_bytes_exact(&q_bytes).is_none());
+        assert!(FE::from_bytes_exact(&[0xff; 32]).is_none());
+    }
+
+    #[test]
+    fn test_from_bytes_mod_order() {
+        // 0xfffefd...e0 as a big-endian number, reduced modulo q
+        let bytes: Vec<u8> = (0..32).map(|i| 0xff - i).collect();
+        let mut arr = [0u8; 32];
+        arr.copy_from_slice(&bytes);
+        let expected =
+            BigInt::from_hex("fffefdfcfbfaf9f8f7f6f5f4f3f2f1f0efeeedecebeae9e8e7e6e5e4e3e2e1e0")
+                .unwrap();
+        assert_eq!(FE::from_bytes_mod_order(&arr).to_big_int(), expected);
+        assert_eq!(FE::from_bytes_mod_order(&[0u8; 32]), FE::zero());
+    }
+
+    #[test]
+    fn test_curve_equation_residual() {
+        let g: GE = ECPoint::generator();
+        let (x, y) = (g.x_coor().unwrap(), g.y_coor().unwrap());
+        assert_eq!(GE::curve_equation_residual(&x, &y), BigInt::zero());
+        let y_plus_one = y + BigInt::one();
+        assert_ne!(GE::curve_equation_residual(&x, &y_plus_one), BigInt::zero());
+    }
+
+    #[test]
+    fn test_from_coor_checked() {
+        let g: GE = ECPoint::generator();
+        let (x, y) = (g.x_coor().unwrap(), g.y_coor().unwrap());
+        assert_eq!(GE::from_coor_checked(&x, &y).unwrap(), g);
+        assert_eq!(
+            GE::from_coor_checked(&field_prime(), &y).err(),
+            Some(ErrorCoordinates::OutOfFieldRange)
+        );
+        assert_eq!(
+            GE::from_coor_checked(&x, &BigInt::from(-1)).err(),
+            Some(ErrorCoordinates::OutOfFieldRange)
+        );
+        assert_eq!(
+            GE::from_coor_checked(&x, &(y + BigInt::one())).err(),
+            Some(ErrorCoordinates::NotOnCurve)
+        );
+    }
+
+    #[test]
+    fn test_from_coor_reduce() {
+        let g: GE = ECPoint::generator();
+        let (x, y) = (g.x_coor().unwrap(), g.y_coor().unwrap());
+        let p = field_prime();
+        let point = GE::from_coor_reduce(&(&x + &p), &y).unwrap();
+        assert_eq!(point, g);
+        let point = GE::from_coor_reduce(&x, &(&y - &p)).unwrap();
+        assert_eq!(point, g);
+        assert_eq!(
+            GE::from_coor_checked(&(&x + &p), &y).err(),
+            Some(ErrorCoordinates::OutOfFieldRange)
+        );
+        assert_eq!(
+            GE::from_coor_reduce(&(&x + &p), &(y + BigInt::one())).err(),
+            Some(ErrorCoordinates::NotOnCurve)
+        );
+    }
+
+    #[test]
+    fn test_deserialize_canonical() {
+        let g: GE = ECPoint::generator();
+        let compressed = g.get_element().serialize();
+        assert!(GE::is_canonical_encoding(&compressed));
+        assert_eq!(GE::deserialize_canonical(&compressed).unwrap(), g);
+
+        // from_bytes accepts all of these
+        assert!(!GE::is_canonical_encoding(&g.pk_to_key_slice()));
+        assert!(!GE::is_canonical_encoding(&g.pk_to_key_slice()[1..]));
+        assert!(!GE::is_canonical_encoding(&compressed[1..]));
+        let mut padded = vec![0u8];
+        padded.extend_from_slice(&compressed);
+        assert!(!GE::is_canonical_encoding(&padded));
+        let mut trailing = compressed.to_vec();
+        trailing.push(0);
+        assert!(!GE::is_canonical_encoding(&trailing));
+
+        let mut wrong_tag = compressed;
+        wrong_tag[0] = 4;
+        assert!(!GE::is_canonical_encoding(&wrong_tag));
+        let mut unreduced = vec![2u8];
+        unreduced.extend_from_slice(&FIELD_SIZE);
+        assert!(!GE::is_canonical_encoding(&unreduced));
+    }
 }
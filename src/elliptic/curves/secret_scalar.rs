@@ -0,0 +1,149 @@
+/*
+    This file is part of Curv library
+    Copyright 2018 by Kzen Networks
+    (https://github.com/KZen-networks/curv)
+    License MIT: <https://github.com/KZen-networks/curv/blob/master/LICENSE>
+*/
+
+//! An opt-in wrapper zeroizing a secret scalar on drop.
+//!
+//! Scalars are `Copy`, so they can't zeroize themselves when dropped. [SecretScalar] isn't: it
+//! owns the one copy of a secret key or nonce kept around, and zeroizes it when it goes out of
+//! scope. The scalar is reached through `Deref`, and copies taken out of the wrapper, such as
+//! `*secret`, are plain scalars again.
+//!
+//! ```
+//! # use curv::elliptic::curves::secp256_k1::{FE, GE};
+//! # use curv::elliptic::curves::secret_scalar::SecretScalar;
+//! # use curv::elliptic::curves::traits::ConstantTimeBaseMul;
+//! let sk: SecretScalar<FE> = SecretScalar::new_random();
+//! let pk = GE::scalar_mul_base_ct(&sk);
+//! // sk is zeroized here
+//! ```
+
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+use zeroize::Zeroize;
+
+use super::traits::ECScalar;
+
+pub struct SecretScalar<S: Zeroize>(S);
+
+impl<S: Zeroize> SecretScalar<S> {
+    pub fn new(scalar: S) -> SecretScalar<S> {
+        SecretScalar(scalar)
+    }
+}
+
+impl<S: ECScalar + Zeroize> SecretScalar<S> {
+    pub fn new_random() -> SecretScalar<S> {
+        SecretScalar(S::new_random())
+    }
+}
+
+impl<S: Zeroize> From<S> for SecretScalar<S> {
+    fn from(scalar: S) -> SecretScalar<S> {
+        SecretScalar(scalar)
+    }
+}
+
+impl<S: Zeroize + Clone> Clone for SecretScalar<S> {
+    fn clone(&self) -> SecretScalar<S> {
+        SecretScalar(self.0.clone())
+    }
+}
+
+impl<S: Zeroize> Deref for SecretScalar<S> {
+    type Target = S;
+
+    fn deref(&self) -> &S {
+        &self.0
+    }
+}
+
+impl<S: Zeroize> DerefMut for SecretScalar<S> {
+    fn deref_mut(&mut self) -> &mut S {
+        &mut self.0
+    }
+}
+
+impl<S: Zeroize> Zeroize for SecretScalar<S> {
+    fn zeroize(&mut self) {
+        self.0.zeroize()
+    }
+}
+
+impl<S: Zeroize> Drop for SecretScalar<S> {
+    fn drop(&mut self) {
+        self.0.zeroize()
+    }
+}
+
+/// Doesn't print the scalar
+impl<S: Zeroize> fmt::Debug for SecretScalar<S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("SecretScalar(..)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::mem::MaybeUninit;
+    use std::ptr;
+
+    use zeroize::Zeroize;
+
+    use super::SecretScalar;
+    use crate::elliptic::curves::traits::{ECPoint, ECScalar};
+    use crate::test_for_all_curves;
+
+    // stands for a backend scalar, all of whose bytes are secret
+    #[derive(Clone)]
+    struct MockScalar([u8; 32]);
+
+    impl Zeroize for MockScalar {
+        fn zeroize(&mut self) {
+            self.0.zeroize();
+        }
+    }
+
+    #[test]
+    fn test_secret_scalar_is_zeroized_on_drop() {
+        let mut slot = MaybeUninit::<SecretScalar<MockScalar>>::uninit();
+        unsafe {
+            slot.as_mut_ptr()
+                .write(SecretScalar::new(MockScalar([0xab; 32])));
+            assert_eq!((*slot.as_ptr()).0 .0, [0xab; 32]);
+            ptr::drop_in_place(slot.as_mut_ptr());
+            // the memory of the dropped scalar is still owned by `slot`
+            assert_eq!((*slot.as_ptr()).0 .0, [0u8; 32]);
+        }
+    }
+
+    #[test]
+    fn test_secret_scalar_doesnt_print_the_scalar() {
+        let secret = SecretScalar::new(MockScalar([0xab; 32]));
+        assert_eq!(format!("{:?}", secret), "SecretScalar(..)");
+    }
+
+    test_for_all_curves!(test_secret_scalar_derefs_to_the_scalar);
+    fn test_secret_scalar_derefs_to_the_scalar<P>()
+    where
+        P: ECPoint,
+        P::Scalar: Zeroize + Clone,
+    {
+        let secret: SecretScalar<P::Scalar> = SecretScalar::new_random();
+        let copy = secret.clone();
+        assert_eq!(copy.to_big_int(), secret.to_big_int());
+        let pk = P::generator_mul(&secret);
+        assert_eq!(
+            pk.pk_to_key_slice(),
+            P::generator_mul(&copy).pk_to_key_slice()
+        );
+
+        let mut zeroized = copy;
+        zeroized.zeroize();
+        assert_eq!(zeroized.to_big_int(), P::Scalar::zero().to_big_int());
+    }
+}
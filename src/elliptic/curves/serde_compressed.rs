@@ -0,0 +1,159 @@
+/*
+    This file is part of Curv library
+    Copyright 2018 by Kzen Networks
+    (https://github.com/KZen-networks/curv)
+    License MIT: <https://github.com/KZen-networks/curv/blob/master/LICENSE>
+*/
+
+//! Serde support for points in their compressed encoding, to be used with
+//! `#[serde(with = "curv::elliptic::curves::serde_compressed")]`.
+//!
+//! The default serde implementations of the points encode their coordinates. This module
+//! encodes the [serialize_compressed](ECPoint::serialize_compressed) bytes instead, as a hex
+//! string for human readable formats such as JSON and as a byte string for binary formats.
+//! Points are decoded with [deserialize_canonical](ECPoint::deserialize_canonical), so an
+//! encoding of a point that isn't on the curve (or outside of the prime order subgroup) is
+//! reported as an error.
+//!
+//! ```
+//! # use serde::{Deserialize, Serialize};
+//! # use curv::elliptic::curves::secp256_k1::GE;
+//! #[derive(Serialize, Deserialize)]
+//! struct Message {
+//!     #[serde(with = "curv::elliptic::curves::serde_compressed")]
+//!     pk: GE,
+//! }
+//! ```
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use serde::de::{self, Visitor};
+use serde::{Deserializer, Serializer};
+
+use super::traits::ECPoint;
+
+pub fn serialize<P, S>(point: &P, serializer: S) -> Result<S::Ok, S::Error>
+where
+    P: ECPoint,
+    S: Serializer,
+{
+    let bytes = point.serialize_compressed();
+    if serializer.is_human_readable() {
+        serializer.serialize_str(&hex::encode(bytes))
+    } else {
+        serializer.serialize_bytes(&bytes)
+    }
+}
+
+pub fn deserialize<'de, P, D>(deserializer: D) -> Result<P, D::Error>
+where
+    P: ECPoint,
+    D: Deserializer<'de>,
+{
+    let visitor = CompressedPointVisitor(PhantomData);
+    if deserializer.is_human_readable() {
+        deserializer.deserialize_str(visitor)
+    } else {
+        deserializer.deserialize_bytes(visitor)
+    }
+}
+
+struct CompressedPointVisitor<P>(PhantomData<P>);
+
+impl<'de, P: ECPoint> Visitor<'de> for CompressedPointVisitor<P> {
+    type Value = P;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "a compressed {} point of {} bytes",
+            P::CURVE_NAME,
+            P::COMPRESSED_SIZE
+        )
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<P, E> {
+        let bytes = hex::decode(s).map_err(E::custom)?;
+        self.visit_bytes(&bytes)
+    }
+
+    fn visit_bytes<E: de::Error>(self, bytes: &[u8]) -> Result<P, E> {
+        P::deserialize_canonical(bytes)
+            .map_err(|_| E::custom(format!("invalid {} point", P::CURVE_NAME)))
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<P, A::Error> {
+        let mut bytes = Vec::with_capacity(P::COMPRESSED_SIZE);
+        while let Some(byte) = seq.next_element::<u8>()? {
+            bytes.push(byte);
+        }
+        self.visit_bytes(&bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::elliptic::curves::secp256_k1;
+    use crate::elliptic::curves::traits::ECScalar;
+    use crate::test_for_all_curves;
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(bound = "")]
+    struct Message<P: ECPoint> {
+        #[serde(with = "crate::elliptic::curves::serde_compressed")]
+        pk: P,
+    }
+
+    test_for_all_curves!(test_compressed_serde_round_trips);
+    fn test_compressed_serde_round_trips<P>()
+    where
+        P: ECPoint + Clone,
+    {
+        let pk = P::generator() * P::Scalar::new_random();
+        let message = Message { pk: pk.clone() };
+
+        let json = serde_json::to_string(&message).unwrap();
+        let expected = format!(r#"{{"pk":"{}"}}"#, hex::encode(pk.serialize_compressed()));
+        assert_eq!(json, expected);
+        let decoded: Message<P> = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.pk.pk_to_key_slice(), pk.pk_to_key_slice());
+
+        let encoded = bincode::serialize(&message).unwrap();
+        // a length prefix followed by the encoding
+        assert_eq!(encoded.len(), 8 + P::COMPRESSED_SIZE);
+        let decoded: Message<P> = bincode::deserialize(&encoded).unwrap();
+        assert_eq!(decoded.pk.pk_to_key_slice(), pk.pk_to_key_slice());
+    }
+
+    test_for_all_curves!(test_compressed_serde_rejects_invalid_encodings);
+    fn test_compressed_serde_rejects_invalid_encodings<P>()
+    where
+        P: ECPoint,
+    {
+        fn decode<P: ECPoint>(bytes: &[u8]) -> Result<Message<P>, serde_json::Error> {
+            serde_json::from_str(&format!(r#"{{"pk":"{}"}}"#, hex::encode(bytes)))
+        }
+        let g = P::generator().serialize_compressed();
+        assert!(decode::<P>(&g).is_ok());
+        assert!(decode::<P>(&g[1..]).is_err());
+        assert!(decode::<P>(&vec![0xff; P::COMPRESSED_SIZE]).is_err());
+        assert!(serde_json::from_str::<Message<P>>(r#"{"pk":"not hex"}"#).is_err());
+    }
+
+    #[test]
+    fn test_compressed_serde_rejects_points_off_the_curve() {
+        // x = 5 isn't the x-coordinate of a point of secp256k1
+        let mut bytes = [0u8; 33];
+        bytes[0] = 2;
+        bytes[32] = 5;
+        let json = format!(r#"{{"pk":"{}"}}"#, hex::encode(bytes));
+        let err = serde_json::from_str::<Message<secp256_k1::GE>>(&json)
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("invalid secp256k1 point"));
+    }
+}
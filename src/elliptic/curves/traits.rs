@@ -7,23 +7,356 @@
 
 use std::ops::{Add, Mul};
 
+use digest::Digest;
+use sha2::Sha256;
+use subtle::{Choice, ConstantTimeEq};
+
+use crate::arithmetic::traits::*;
+use crate::cryptographic_primitives::hashing::expand_message::expand_message_xmd_sha256;
 use crate::BigInt;
-use crate::ErrorKey;
+use crate::{ErrorCoordinates, ErrorKey};
 
+/// Scalars of the supported curves are `Copy` and don't zeroize on drop: every copy is a new
+/// value the compiler may leave in memory. Secret scalars are zeroized explicitly with
+/// [Zeroize](zeroize::Zeroize), or kept in a
+/// [SecretScalar](super::secret_scalar::SecretScalar), which zeroizes its scalar when dropped.
 pub trait ECScalar: Mul<Output = Self> + Add<Output = Self> + Sized {
     type SecretKey;
 
     fn new_random() -> Self;
+    /// Samples a random scalar other than zero, as needed for secret keys. Zero, sampled with
+    /// negligible probability, is rejected and another scalar is sampled.
+    fn new_random_nonzero() -> Self {
+        loop {
+            let s = Self::new_random();
+            if s.to_big_int() != BigInt::zero() {
+                return s;
+            }
+        }
+    }
     fn zero() -> Self;
+    /// The multiplicative identity. Backends can override it to avoid going through BigInt.
+    fn one() -> Self {
+        Self::from(&BigInt::one())
+    }
+    fn is_one(&self) -> bool {
+        self.to_big_int() == BigInt::one()
+    }
     fn get_element(&self) -> Self::SecretKey;
     fn set_element(&mut self, element: Self::SecretKey);
     fn from(n: &BigInt) -> Self;
+    /// Parses the canonical encoding of the scalar used by the backend library, without going
+    /// through BigInt.
+    ///
+    /// The encoding is 32 bytes long for every supported curve. It is big-endian for secp256k1,
+    /// P-256 and BLS12-381, and little-endian for ed25519 and ristretto. Returns `None` if the
+    /// slice is of a wrong length or encodes a number that is not smaller than the group order.
+    fn from_bytes_exact(bytes: &[u8]) -> Option<Self>;
+    /// Reduces a 32 bytes long number modulo the group order. The bytes are read with the same
+    /// endianness as [from_bytes_exact](Self::from_bytes_exact).
+    ///
+    /// The result is **biased**: numbers below `2^256 mod q` are hit more often than the others,
+    /// and the bias is significant for ed25519, ristretto and BLS12-381, whose orders are much
+    /// smaller than `2^256`. Use it only where a protocol specifies this exact reduction, for instance to stay compatible
+    /// with another implementation. To derive a uniform scalar, reduce at least 64 bytes with
+    /// [from](Self::from) instead.
+    fn from_bytes_mod_order(bytes: &[u8; 32]) -> Self;
+    /// Reads `bytes` as a big-endian number of any length, reduced modulo the group order, for
+    /// parsing wire formats. Unlike [from_bytes_mod_order](Self::from_bytes_mod_order), the
+    /// encoding is big-endian for every curve. An empty slice is zero.
+    ///
+    /// Reducing a number of the size of the order is biased in the same way, so uniform scalars
+    /// should be derived from at least 64 bytes.
+    fn from_be_bytes_mod_order(bytes: &[u8]) -> Self {
+        let n = BigInt::from_bytes(bytes).modulus(&Self::q());
+        // not every backend accepts zero in `from`
+        if n == BigInt::zero() {
+            Self::zero()
+        } else {
+            Self::from(&n)
+        }
+    }
+    /// Big-endian encoding of the scalar, left-padded with zeros to the byte length of the group
+    /// order (32 bytes for every supported curve). Read back by
+    /// [from_be_bytes_mod_order](Self::from_be_bytes_mod_order).
+    fn to_bytes(&self) -> Vec<u8> {
+        let width = Self::q().bit_length().div_ceil(8);
+        let bytes = BigInt::to_bytes(&self.to_big_int());
+        let mut padded = vec![0u8; width - bytes.len()];
+        padded.extend_from_slice(&bytes);
+        padded
+    }
+    /// Compares two scalars without an early exit, unlike `PartialEq`, for comparing secret
+    /// values such as a tag derived from a shared secret.
+    ///
+    /// The default compares the [to_bytes](Self::to_bytes) encodings, which go through BigInt,
+    /// whose conversion isn't constant-time. secp256k1, p256 and ristretto compare the encodings
+    /// of their backend libraries instead.
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.to_bytes().ct_eq(&other.to_bytes())
+    }
     fn to_big_int(&self) -> BigInt;
     fn q() -> BigInt;
     fn add(&self, other: &Self::SecretKey) -> Self;
     fn mul(&self, other: &Self::SecretKey) -> Self;
     fn sub(&self, other: &Self::SecretKey) -> Self;
     fn invert(&self) -> Self;
+    /// Inverse of the scalar, computed as `s^(q-2)`. Returns `None` for zero, and the same
+    /// scalar as [invert](Self::invert) otherwise.
+    ///
+    /// ## Timing
+    /// The running time doesn't depend on the value of the scalar for secp256k1 (libsecp256k1's
+    /// scalar multiplication), p256 (the p256 crate's exponentiation by the fixed `q-2`) and
+    /// ristretto (dalek's inversion), which is checked by the tests of the `ct-test` feature.
+    /// The default implementation, used by ed25519 and the BLS12-381 groups, goes through
+    /// [mul](Self::mul), which is a variable-time big integer multiplication for those curves,
+    /// so this guarantee doesn't hold for them.
+    fn invert_ct(&self) -> Option<Self> {
+        let exponent = Self::q() - BigInt::from(2);
+        let mut inverse = Self::one();
+        for i in (0..exponent.bit_length()).rev() {
+            inverse = ECScalar::mul(&inverse, &inverse.get_element());
+            if exponent.test_bit(i) {
+                inverse = ECScalar::mul(&inverse, &self.get_element());
+            }
+        }
+        // s^(q-2) is zero for s = 0, and the inverse of s otherwise
+        if ECScalar::mul(&inverse, &self.get_element()).to_big_int() == BigInt::one() {
+            Some(inverse)
+        } else {
+            None
+        }
+    }
+
+    /// Computes `a1*b1 + a2*b2 + ... + an*bn`. Returns zero for empty slices.
+    ///
+    /// ## Panics
+    /// Panics if `a` and `b` differ in length.
+    fn inner_product(a: &[Self], b: &[Self]) -> Self
+    where
+        Self: Clone,
+    {
+        assert_eq!(
+            a.len(),
+            b.len(),
+            "inner product of vectors of different lengths"
+        );
+        a.iter().zip(b).fold(Self::zero(), |acc, (a_i, b_i)| {
+            acc + a_i.clone() * b_i.clone()
+        })
+    }
+
+    /// Computes the Lagrange coefficients `l_i(0)` of all the parties in `indices`, in the same
+    /// order, so that `sum l_i(0)*f(x_i) = f(0)` for any polynomial f of degree smaller than the
+    /// number of parties.
+    ///
+    /// Indices are counted from zero, the party of index `i` holding `f(i+1)`, as in
+    /// [VerifiableSS::reconstruct](crate::cryptographic_primitives::secret_sharing::feldman_vss::VerifiableSS::reconstruct).
+    /// The products shared between the coefficients are computed once, and all denominators are
+    /// inverted at once with a single inversion.
+    ///
+    /// ## Panics
+    /// Panics if an index appears more than once.
+    fn lagrange_coefficients(indices: &[usize]) -> Vec<Self>
+    where
+        Self: Clone,
+    {
+        // l_i(0) = prod_{j != i} x_j / (x_j - x_i) = (prod_j x_j) / (x_i * prod_{j != i} (x_j - x_i))
+        let points: Vec<Self> = indices
+            .iter()
+            .map(|i| Self::from(&BigInt::from(*i as u64 + 1)))
+            .collect();
+        let one = Self::one();
+        let numerator = points.iter().fold(one, |acc, x| acc * x.clone());
+        let denominators: Vec<Self> = points
+            .iter()
+            .enumerate()
+            .map(|(i, x_i)| {
+                points
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| *j != i)
+                    .fold(x_i.clone(), |acc, (_, x_j)| {
+                        acc * x_j.sub(&x_i.get_element())
+                    })
+            })
+            .collect();
+        let mut inverses = denominators;
+        let zeros = batch_invert(&mut inverses);
+        assert!(zeros.is_empty(), "an index appears more than once");
+        inverses
+            .into_iter()
+            .map(|inverse| numerator.clone() * inverse)
+            .collect()
+    }
+
+    /// Computes `self^exp` modulo the group order by square-and-multiply. `x^0` is one for any
+    /// x, zero included.
+    fn pow(&self, exp: u64) -> Self {
+        let n = BigInt::mod_pow(&self.to_big_int(), &BigInt::from(exp), &Self::q());
+        // zero can't be built from a BigInt on every curve
+        if n == BigInt::zero() {
+            Self::zero()
+        } else {
+            Self::from(&n)
+        }
+    }
+
+    /// Returns whether the scalar is in the lower half of the group, i.e. `s <= q/2`
+    fn is_low(&self) -> bool {
+        self.to_big_int() <= Self::q() / BigInt::from(2)
+    }
+
+    /// Returns whichever of `s` and `q - s` is in the lower half of the group, as required to
+    /// reject malleable signatures
+    fn normalize_low(&self) -> Self
+    where
+        Self: Clone,
+    {
+        if self.is_low() {
+            self.clone()
+        } else {
+            Self::from(&(Self::q() - self.to_big_int()))
+        }
+    }
+
+    /// Square root of the scalar modulo the group order, computed with Tonelli-Shanks in variable
+    /// time. Returns `None` if the scalar is not a quadratic residue. Either of the two roots can
+    /// be returned.
+    fn sqrt(&self) -> Option<Self> {
+        let q = Self::q();
+        let n = self.to_big_int();
+        if n == BigInt::zero() {
+            return Some(Self::zero());
+        }
+        let one = BigInt::one();
+        let q_minus_one = &q - &one;
+        // Euler's criterion
+        let is_residue = |x: &BigInt| BigInt::mod_pow(x, &(&q_minus_one >> 1), &q) == one;
+        if !is_residue(&n) {
+            return None;
+        }
+
+        // q - 1 = odd * 2^s
+        let mut s = 0;
+        while !q_minus_one.test_bit(s) {
+            s += 1;
+        }
+        let odd = &q_minus_one >> s;
+        let mut z = BigInt::from(2);
+        while is_residue(&z) {
+            z += &one;
+        }
+
+        let mut m = s;
+        let mut c = BigInt::mod_pow(&z, &odd, &q);
+        let mut t = BigInt::mod_pow(&n, &odd, &q);
+        let mut r = BigInt::mod_pow(&n, &((&odd + &one) >> 1), &q);
+        while t != one {
+            // least i such that t^(2^i) = 1
+            let mut i = 0;
+            let mut t_pow = t.clone();
+            while t_pow != one {
+                t_pow = BigInt::mod_mul(&t_pow, &t_pow, &q);
+                i += 1;
+            }
+            let mut b = c;
+            for _ in 0..m - i - 1 {
+                b = BigInt::mod_mul(&b, &b, &q);
+            }
+            m = i;
+            c = BigInt::mod_mul(&b, &b, &q);
+            t = BigInt::mod_mul(&t, &c, &q);
+            r = BigInt::mod_mul(&r, &b, &q);
+        }
+        Some(Self::from(&r))
+    }
+
+    /// Samples a random scalar of exactly `bits` significant bits, i.e. in
+    /// `[2^(bits-1); 2^bits)`, for testing arithmetic on worst-case inputs. Not a uniform
+    /// scalar, so it must not be used as a secret.
+    ///
+    /// ## Panics
+    /// Panics if `bits` is zero or greater than the bit length of the group order.
+    fn random_with_bit_length(bits: usize) -> Self {
+        let q = Self::q();
+        assert!(
+            bits > 0 && bits <= q.bit_length(),
+            "no scalar has {} significant bits",
+            bits
+        );
+        // the order may be barely above 2^(bits-1), as for ed25519, so the upper bound is
+        // clamped to it rather than rejecting samples
+        let lower = BigInt::one() << (bits - 1);
+        let upper = std::cmp::min(BigInt::one() << bits, q);
+        Self::from(&BigInt::sample_range(&lower, &upper))
+    }
+
+    /// Hashes `message` to a scalar bound to the domain separation tag `dst`, following
+    /// hash_to_field of RFC 9380 (section 5.2) with `expand_message_xmd` over SHA-256: the
+    /// message is expanded to `ceil((log2(q) + 128) / 8)` bytes, read big-endian and reduced
+    /// modulo the group order, so the bias of the result is below 2^-128.
+    fn hash_to_scalar(message: &[u8], dst: &[u8]) -> Self {
+        let q = Self::q();
+        let len = (q.bit_length() + 128).div_ceil(8);
+        let uniform_bytes = expand_message_xmd_sha256(message, dst, len);
+        Self::from(&BigInt::from_bytes(&uniform_bytes).modulus(&q))
+    }
+
+    /// Hashes every message to a scalar as [hash_to_scalar](Self::hash_to_scalar) does, in
+    /// parallel when the `rayon` feature is enabled. The scalars come in the order of
+    /// `messages`, and are the same with and without the feature.
+    fn hash_to_scalar_batch(messages: &[&[u8]], dst: &[u8]) -> Vec<Self>
+    where
+        Self: Send,
+    {
+        #[cfg(feature = "rayon")]
+        {
+            use rayon::prelude::*;
+            messages
+                .par_iter()
+                .map(|m| Self::hash_to_scalar(m, dst))
+                .collect()
+        }
+        #[cfg(not(feature = "rayon"))]
+        {
+            messages
+                .iter()
+                .map(|m| Self::hash_to_scalar(m, dst))
+                .collect()
+        }
+    }
+}
+
+/// Inverts in place all the non-zero scalars with a single inversion and 3(n-1)
+/// multiplications (Montgomery's trick). Zeros have no inverse: they are left as they are, and
+/// their indices are returned in increasing order.
+pub fn batch_invert<S>(scalars: &mut [S]) -> Vec<usize>
+where
+    S: ECScalar + Clone,
+{
+    let (zeros, nonzero): (Vec<usize>, Vec<usize>) =
+        (0..scalars.len()).partition(|i| scalars[*i].to_big_int() == BigInt::zero());
+    if nonzero.is_empty() {
+        return zeros;
+    }
+    // prefix[i] = s_0 * ... * s_i over the non-zero scalars
+    let mut prefix: Vec<S> = Vec::with_capacity(nonzero.len());
+    for i in &nonzero {
+        let product = match prefix.last() {
+            Some(last) => last.clone() * scalars[*i].clone(),
+            None => scalars[*i].clone(),
+        };
+        prefix.push(product);
+    }
+    let mut inverse = prefix[nonzero.len() - 1].invert();
+    for k in (1..nonzero.len()).rev() {
+        let s = scalars[nonzero[k]].clone();
+        scalars[nonzero[k]] = inverse.clone() * prefix[k - 1].clone();
+        inverse = inverse * s;
+    }
+    scalars[nonzero[0]] = inverse;
+    zeros
 }
 
 // TODO: add a fn is_point
@@ -37,16 +370,1072 @@ where
 
     type Scalar: ECScalar<SecretKey = Self::SecretKey>;
 
+    /// Name of the curve (or of the group for pairing friendly curves) the point belongs to
+    const CURVE_NAME: &'static str;
+    /// Length of the compressed encoding returned by
+    /// [serialize_compressed](Self::serialize_compressed)
+    const COMPRESSED_SIZE: usize;
+    /// Big-endian x coordinate of [generator](Self::generator), as returned by
+    /// [x_coor](Self::x_coor)
+    const GENERATOR_X: &'static [u8];
+    /// Big-endian y coordinate of [generator](Self::generator), as returned by
+    /// [y_coor](Self::y_coor)
+    const GENERATOR_Y: &'static [u8];
+    /// Where [generator](Self::generator) comes from, a standard for every supported curve
+    const GENERATOR_DERIVATION: &'static str;
+    /// How [base_point2](Self::base_point2) is derived from public data, so anyone can check
+    /// that nobody knows its discrete logarithm
+    const BASE_POINT2_DERIVATION: &'static str;
+
     fn base_point2() -> Self;
     fn generator() -> Self;
+    /// The identity of the group, `None` for secp256k1 and p256, whose points are public keys of
+    /// their backend libraries, which can't be the point at infinity
+    fn identity() -> Option<Self> {
+        None
+    }
     fn get_element(&self) -> Self::PublicKey;
     fn x_coor(&self) -> Option<BigInt>;
     fn y_coor(&self) -> Option<BigInt>;
     fn bytes_compressed_to_big_int(&self) -> BigInt;
     fn from_bytes(bytes: &[u8]) -> Result<Self, ErrorKey>;
+    /// Strict counterpart of [from_bytes](Self::from_bytes) that accepts exactly one encoding per
+    /// point, its compressed encoding of the curve's fixed length.
+    ///
+    /// Uncompressed or hybrid encodings, wrong lengths (including leading zeros or trailing
+    /// bytes), coordinates that are not reduced modulo the field prime, unset or unexpected flag
+    /// bits and points outside of the prime order subgroup are all rejected, so two accepted
+    /// byte strings never decode to the same point.
+    fn deserialize_canonical(bytes: &[u8]) -> Result<Self, ErrorKey>;
+    /// Returns whether `bytes` is accepted by [deserialize_canonical](Self::deserialize_canonical)
+    fn is_canonical_encoding(bytes: &[u8]) -> bool {
+        Self::deserialize_canonical(bytes).is_ok()
+    }
+    /// Length of the compressed encoding of a point, as returned by
+    /// [serialize_compressed](Self::serialize_compressed), or of its uncompressed encoding.
+    /// ed25519 and ristretto have a single encoding, of the same length either way.
+    fn serialized_len(compressed: bool) -> usize;
+    /// Tells from its length, and from the prefix or flag marking the form where the curve has
+    /// one, whether `bytes` is shaped as a compressed (`Some(true)`) or an uncompressed
+    /// (`Some(false)`) encoding, without checking that it encodes a point. Returns `None` for
+    /// anything else, such as a truncated uncompressed encoding, which
+    /// [from_bytes](Self::from_bytes) may still accept by padding it.
+    ///
+    /// The default only looks at the length, curves with a prefix or a flag override it.
+    fn is_compressed_encoding(bytes: &[u8]) -> Option<bool> {
+        if bytes.len() == Self::serialized_len(true) {
+            Some(true)
+        } else if bytes.len() == Self::serialized_len(false) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    /// Finds x in [1, `max`] such that `self` = x*`base`, by baby-step giant-step in
+    /// O(sqrt(`max`)) time and memory. Meant for tests that check discrete log relations
+    /// between points of known small logs, it isn't constant time.
+    #[cfg(any(test, feature = "test-utils"))]
+    fn known_discrete_log(&self, base: &Self, max: u64) -> Option<Self::Scalar>
+    where
+        Self: Clone,
+    {
+        if max == 0 {
+            return None;
+        }
+        let m = (max as f64).sqrt().ceil() as u64;
+        // baby steps j*base for j in [1, m]
+        let mut baby_steps = std::collections::HashMap::new();
+        let mut step = base.clone();
+        baby_steps.insert(step.pk_to_key_slice(), 1);
+        for j in 2..=m {
+            step = step.add_point(&base.get_element());
+            baby_steps.insert(step.pk_to_key_slice(), j);
+        }
+        // giant steps self - i*m*base, which is j*base for x = i*m + j. It is never the
+        // identity: i*m*base is found at the step before as j = m
+        let mut giant_step = self.clone();
+        for i in 0..=m {
+            if let Some(j) = baby_steps.get(&giant_step.pk_to_key_slice()) {
+                let x = i * m + j;
+                return if x <= max {
+                    Some(ECScalar::from(&BigInt::from(x)))
+                } else {
+                    None
+                };
+            }
+            giant_step = giant_step.sub_point(&step.get_element());
+        }
+        None
+    }
+    /// Compressed encoding of the point, of length [COMPRESSED_SIZE](Self::COMPRESSED_SIZE), the
+    /// one accepted by [deserialize_canonical](Self::deserialize_canonical)
+    fn serialize_compressed(&self) -> Vec<u8> {
+        let bytes = BigInt::to_bytes(&self.bytes_compressed_to_big_int());
+        let mut padded = vec![0u8; Self::COMPRESSED_SIZE - bytes.len()];
+        padded.extend_from_slice(&bytes);
+        padded
+    }
+    /// Returns the compressed encoding of `-P`, `P` being the point of the compressed encoding
+    /// `bytes`, which is validated as by [deserialize_canonical](Self::deserialize_canonical).
+    ///
+    /// The default negates the decoded point and serializes it back. Backends whose compressed
+    /// encoding carries the sign of a coordinate in a bit flip it instead: the prefix byte, 0x02
+    /// and 0x03, of secp256k1 and p256, the sign bit of x of ed25519 and the sign flag of the
+    /// BLS12-381 groups. The point is still decompressed to be validated.
+    fn negate_serialized(bytes: &[u8]) -> Result<Vec<u8>, ErrorKey> {
+        let point = Self::deserialize_canonical(bytes)?;
+        let minus_one: Self::Scalar = ECScalar::from(&(Self::Scalar::q() - BigInt::one()));
+        Ok((point * minus_one).serialize_compressed())
+    }
     fn pk_to_key_slice(&self) -> Vec<u8>;
+    /// Compares two points without an early exit, as [ct_eq](ECScalar::ct_eq) does for scalars.
+    /// Only the points are compared, not the metadata `PartialEq` looks at for some backends.
+    ///
+    /// The default compares the [pk_to_key_slice](Self::pk_to_key_slice) encodings, built from
+    /// BigInt coordinates for some backends. secp256k1 compares the encodings of libsecp256k1
+    /// instead.
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.pk_to_key_slice().ct_eq(&other.pk_to_key_slice())
+    }
     fn scalar_mul(&self, fe: &Self::SecretKey) -> Self;
+    /// Computes `k * G`, giving the same point as `G.scalar_mul(k)` faster: every backend
+    /// multiplies through a table of multiples of the generator, precomputed once by the
+    /// backend library (secp256k1, ed25519, ristretto) or by this crate the first time it is
+    /// needed (p256, BLS12-381).
+    ///
+    /// The curves implementing [ConstantTimeBaseMul] go through
+    /// [scalar_mul_base_ct](ConstantTimeBaseMul::scalar_mul_base_ct). The BLS12-381 groups
+    /// don't, and their table lookups depend on `k`: code multiplying secrets should require
+    /// `ConstantTimeBaseMul` and call `scalar_mul_base_ct` instead.
+    fn generator_mul(k: &Self::Scalar) -> Self {
+        Self::generator().scalar_mul(&k.get_element())
+    }
+    /// Computes `k * G` for every scalar of `scalars` with [generator_mul](Self::generator_mul),
+    /// all the multiplications sharing the same precomputed table, in parallel when the `rayon`
+    /// feature is enabled. The points come in the order of `scalars`.
+    fn generator_mul_batch(scalars: &[Self::Scalar]) -> Vec<Self>
+    where
+        Self: Send,
+        Self::Scalar: Sync,
+    {
+        #[cfg(feature = "rayon")]
+        {
+            use rayon::prelude::*;
+            scalars.par_iter().map(Self::generator_mul).collect()
+        }
+        #[cfg(not(feature = "rayon"))]
+        {
+            scalars.iter().map(Self::generator_mul).collect()
+        }
+    }
+    /// Computes `k * self` for a secret `k` as `k1 * self + k2 * self`, with `k1` sampled at
+    /// random and `k2 = k - k1`.
+    ///
+    /// The scalars going through the multiplications are fresh for every call, so the power
+    /// traces of two multiplications by the same secret don't correlate with it, which resists
+    /// differential power analysis. This is a countermeasure for devices an attacker can take
+    /// measurements of, at the cost of twice the multiplication time. It doesn't make the
+    /// multiplication constant time: a single trace of the backend's `scalar_mul` may still
+    /// reveal `k1` and `k2`, and then `k`.
+    fn scalar_mul_blinded(&self, k: &Self::Scalar) -> Self {
+        let k_bn = k.to_big_int();
+        // k1 != k, so that k2 is never zero, which some backends can't represent
+        let k1 = loop {
+            let k1: Self::Scalar = ECScalar::new_random();
+            if k1.to_big_int() != k_bn {
+                break k1;
+            }
+        };
+        let k2 = k.sub(&k1.get_element());
+        self.scalar_mul(&k1.get_element()) + self.scalar_mul(&k2.get_element())
+    }
+    /// Computes `k * self` for a public `k`, such as a verification challenge, in variable time.
+    ///
+    /// ## Timing
+    /// The running time depends on `k`, which therefore mustn't be secret. Backends override the
+    /// default, which falls back to `scalar_mul`, where they have a faster variable-time
+    /// multiplication: ristretto (dalek's NAF multiplication) and the BLS12-381 groups
+    /// (pairing-plus' wNAF). secp256k1 and p256 fall back, their backends only providing
+    /// constant-time multiplications. So does ed25519, whose `scalar_mul` is already ref10's
+    /// variable-time multiplication; neither it nor the double-and-add `scalar_mul` of the
+    /// BLS12-381 groups is a constant-time path for secret scalars.
+    fn scalar_mul_vartime(&self, k: &Self::Scalar) -> Self {
+        self.scalar_mul(&k.get_element())
+    }
     fn add_point(&self, other: &Self::PublicKey) -> Self;
     fn sub_point(&self, other: &Self::PublicKey) -> Self;
+    /// Computes `s1*P1 + s2*P2 + ... + sn*Pn`.
+    ///
+    /// The default implementation multiplies every point and adds up the products. Backends
+    /// override it with a faster multi-scalar multiplication when they can.
+    ///
+    /// Returns the [identity](Self::identity) for empty input, and `None` if the sum is the
+    /// identity on a curve that can't represent it, that is secp256k1 and p256.
+    ///
+    /// ## Panics
+    /// Panics if `scalars` and `points` differ in length.
+    fn multi_scalar_mul(scalars: &[Self::Scalar], points: &[Self]) -> Option<Self> {
+        assert_eq!(
+            scalars.len(),
+            points.len(),
+            "multi-scalar multiplication of vectors of different lengths"
+        );
+        points
+            .iter()
+            .zip(scalars)
+            .map(|(p, s)| p.scalar_mul(&s.get_element()))
+            .reduce(|acc, p| acc + p)
+            .or_else(Self::identity)
+    }
+    /// Computes `k*G + e*point`, the left-hand side of signature verification equations, in
+    /// variable time. Returns `None` if the sum is the identity on a curve that can't represent
+    /// it, that is secp256k1, which happens for inputs picked by whoever verifies them, so
+    /// verifiers should treat it as a failed check.
+    ///
+    /// The default implementation multiplies both points and adds up the products. Backends
+    /// override it with the precomputed tables of the generator: a single double-base
+    /// multiplication for ed25519 and ristretto, libsecp256k1's `ecmult_gen` for secp256k1.
+    fn double_scalar_mul_base(k: &Self::Scalar, point: &Self, e: &Self::Scalar) -> Option<Self> {
+        Some(Self::generator().scalar_mul(&k.get_element()) + point.scalar_mul(&e.get_element()))
+    }
     fn from_coor(x: &BigInt, y: &BigInt) -> Self;
+    /// Same as [from_coor](Self::from_coor), failing instead of panicking on coordinates that
+    /// aren't field elements or that aren't those of a point of the curve, so the two cases can
+    /// be reported differently.
+    ///
+    /// The BLS12-381 groups also reject curve points outside of their sub group with
+    /// [NotInSubgroup](ErrorCoordinates::NotInSubgroup). Ed25519 points are built as they are,
+    /// torsion included. Ristretto returns [Unsupported](ErrorCoordinates::Unsupported) for any
+    /// coordinates.
+    fn from_coor_checked(x: &BigInt, y: &BigInt) -> Result<Self, ErrorCoordinates>;
+    /// Lenient variant of [from_coor_checked](Self::from_coor_checked), for parsing points from
+    /// peers that don't reduce their coordinates: `x` and `y` are reduced modulo the field
+    /// prime before the curve equation is checked, so only
+    /// [NotOnCurve](ErrorCoordinates::NotOnCurve) can be returned, besides the errors specific
+    /// to a curve described there. Protocols that require a canonical encoding should stick to
+    /// `from_coor_checked`.
+    fn from_coor_reduce(x: &BigInt, y: &BigInt) -> Result<Self, ErrorCoordinates>;
+    /// Evaluates the curve equation at `(x, y)`, for debugging points that fail validation.
+    ///
+    /// Returns `y^2 - (x^3 + ax + b)` for short Weierstrass curves and `-x^2 + y^2 - 1 - dx^2y^2`
+    /// for twisted Edwards curves, reduced modulo the field prime. The result is zero if and only
+    /// if `(x, y)` is on the curve. Coordinates are encoded as by [x_coor](Self::x_coor) and
+    /// [y_coor](Self::y_coor).
+    fn curve_equation_residual(x: &BigInt, y: &BigInt) -> BigInt;
+    /// Deterministically maps a message to a point of unknown discrete logarithm.
+    ///
+    /// Curves without a standard hash-to-curve use try-and-increment, which is not constant
+    /// time, so the message must not be secret.
+    fn hash_to_curve(message: &[u8]) -> Self;
+
+    /// Returns `count` independent generators derived from `label`.
+    ///
+    /// Generators are obtained by hashing the label together with an index to the curve, so
+    /// nobody knows the discrete logarithm between any two of them. None of them is the identity
+    /// and they are pairwise distinct. The set for a given `(label, count)` is computed once and
+    /// cached.
+    fn generator_set(label: &[u8], count: usize) -> Vec<Self>
+    where
+        Self: Clone + Send + Sync + 'static,
+    {
+        super::generators::generator_set(label, count)
+    }
+
+    /// Checks that `point` is the generator at position `index` of
+    /// [generator_set](Self::generator_set)`(label, _)`, by deriving the set again, so parties
+    /// can audit the generators claimed by each other.
+    fn verify_hash_to_curve(point: &Self, label: &[u8], index: u32) -> bool
+    where
+        Self: Clone + Send + Sync + 'static,
+    {
+        super::generators::verify_hash_to_curve(point, label, index)
+    }
+
+    /// Short identifier of the point for displaying public keys, analogous to a PGP key id:
+    /// the first 8 bytes of [key_fingerprint](Self::key_fingerprint).
+    ///
+    /// Ids are short enough to collide, so they must not be used to authenticate a key.
+    fn key_id(&self) -> [u8; 8] {
+        let mut id = [0u8; 8];
+        id.copy_from_slice(&self.key_fingerprint()[..8]);
+        id
+    }
+
+    /// SHA-256 of the compressed encoding of the point, as returned by
+    /// [serialize_compressed](Self::serialize_compressed), truncated to 20 bytes
+    fn key_fingerprint(&self) -> [u8; 20] {
+        let digest = Sha256::digest(&self.serialize_compressed());
+        let mut fingerprint = [0u8; 20];
+        fingerprint.copy_from_slice(&digest[..20]);
+        fingerprint
+    }
+}
+
+/// Points of the curves multiplying the generator in constant time, for secret scalars.
+///
+/// Implemented by secp256k1 (libsecp256k1's `ecmult_gen`), p256 (a precomputed table of the
+/// generator with constant-time lookups), ed25519 (ref10 fixed-base multiplication with
+/// constant-time table lookups) and ristretto (dalek's basepoint table), which is checked by the
+/// tests of the `ct-test` feature. The BLS12-381 groups don't implement it: the field arithmetic
+/// of pairing-plus branches on its operands, so no table lookup on top of it runs in constant
+/// time.
+pub trait ConstantTimeBaseMul: ECPoint {
+    /// Computes `k * G` for a secret `k`, such as a signing nonce or a secret key, in a time
+    /// that doesn't depend on the value of `k`.
+    fn scalar_mul_base_ct(k: &Self::Scalar) -> Self;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_for_all_curves, test_for_ct_curves};
+
+    test_for_all_curves!(test_inner_product);
+    fn test_inner_product<P>()
+    where
+        P: ECPoint,
+        P::Scalar: Clone,
+    {
+        let a: Vec<P::Scalar> = (0..10).map(|_| ECScalar::new_random()).collect();
+        let b: Vec<P::Scalar> = (0..10).map(|_| ECScalar::new_random()).collect();
+        let expected = a.iter().zip(&b).fold(BigInt::from(0), |acc, (a_i, b_i)| {
+            BigInt::mod_add(
+                &acc,
+                &BigInt::mod_mul(&a_i.to_big_int(), &b_i.to_big_int(), &P::Scalar::q()),
+                &P::Scalar::q(),
+            )
+        });
+        assert_eq!(P::Scalar::inner_product(&a, &b).to_big_int(), expected);
+        assert_eq!(
+            P::Scalar::inner_product(&[], &[]).to_big_int(),
+            BigInt::from(0)
+        );
+    }
+
+    test_for_all_curves!(
+        #[should_panic]
+        test_inner_product_length_mismatch
+    );
+    fn test_inner_product_length_mismatch<P>()
+    where
+        P: ECPoint,
+        P::Scalar: Clone,
+    {
+        let a: Vec<P::Scalar> = (0..3).map(|_| ECScalar::new_random()).collect();
+        P::Scalar::inner_product(&a, &a[1..]);
+    }
+
+    test_for_all_curves!(test_normalize_low);
+    fn test_normalize_low<P>()
+    where
+        P: ECPoint,
+        P::Scalar: Clone,
+    {
+        let q = P::Scalar::q();
+        let half: P::Scalar = ECScalar::from(&(q.clone() / BigInt::from(2)));
+        let above_half: P::Scalar = ECScalar::from(&(q.clone() / BigInt::from(2) + BigInt::one()));
+        assert!(half.is_low());
+        assert!(!above_half.is_low());
+        assert_eq!(above_half.normalize_low().to_big_int(), half.to_big_int());
+        let minus_one: P::Scalar = ECScalar::from(&(q.clone() - BigInt::one()));
+        assert_eq!(minus_one.normalize_low().to_big_int(), BigInt::one());
+
+        for _ in 0..20 {
+            let s: P::Scalar = ECScalar::new_random();
+            let low = s.normalize_low();
+            assert!(low.is_low());
+            assert_eq!(low.normalize_low().to_big_int(), low.to_big_int());
+            assert!(low.to_big_int() == s.to_big_int() || low.to_big_int() == &q - s.to_big_int());
+        }
+    }
+
+    test_for_all_curves!(test_generator_constants);
+    fn test_generator_constants<P>()
+    where
+        P: ECPoint + Clone,
+    {
+        let x = BigInt::from_bytes(P::GENERATOR_X);
+        let y = BigInt::from_bytes(P::GENERATOR_Y);
+        assert_eq!(P::curve_equation_residual(&x, &y), BigInt::zero());
+        // ristretto points don't expose the coordinates of their edwards25519 representative
+        if P::CURVE_NAME != "ristretto" {
+            let g = P::generator();
+            assert_eq!(g.x_coor(), Some(x));
+            assert_eq!(g.y_coor(), Some(y));
+        }
+
+        assert!(!P::GENERATOR_DERIVATION.is_empty());
+        assert!(!P::BASE_POINT2_DERIVATION.is_empty());
+        let h = P::base_point2();
+        assert_ne!(h.pk_to_key_slice(), P::generator().pk_to_key_slice());
+        assert!(h.clone() + h.clone() != h);
+    }
+
+    test_for_all_curves!(test_lagrange_coefficients);
+    fn test_lagrange_coefficients<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Clone,
+    {
+        use crate::cryptographic_primitives::secret_sharing::feldman_vss::{
+            ShamirSecretSharing, VerifiableSS,
+        };
+
+        let params = ShamirSecretSharing {
+            threshold: 6,
+            share_count: 12,
+        };
+        let indices = [0, 2, 3, 5, 8, 9, 11];
+        let coefficients = P::Scalar::lagrange_coefficients(&indices);
+        assert_eq!(coefficients.len(), indices.len());
+        for (index, coefficient) in indices.iter().zip(&coefficients) {
+            let expected = VerifiableSS::<P>::map_share_to_new_params(&params, *index, &indices);
+            assert_eq!(coefficient.to_big_int(), expected.to_big_int());
+        }
+
+        // the coefficients reconstruct the secret
+        let secret: P::Scalar = ECScalar::new_random();
+        let (_, shares) = VerifiableSS::<P>::share(params.threshold, params.share_count, &secret);
+        let shares: Vec<P::Scalar> = indices.iter().map(|i| shares[*i].clone()).collect();
+        assert_eq!(
+            P::Scalar::inner_product(&coefficients, &shares).to_big_int(),
+            secret.to_big_int()
+        );
+
+        assert_eq!(
+            P::Scalar::lagrange_coefficients(&[4])[0].to_big_int(),
+            BigInt::one()
+        );
+        assert!(P::Scalar::lagrange_coefficients(&[]).is_empty());
+    }
+
+    test_for_all_curves!(test_pow);
+    fn test_pow<P>()
+    where
+        P: ECPoint,
+        P::Scalar: Clone,
+    {
+        let x: P::Scalar = ECScalar::new_random();
+        assert_eq!(x.pow(0).to_big_int(), BigInt::one());
+        assert_eq!(x.pow(1).to_big_int(), x.to_big_int());
+        let mut expected = x.clone();
+        for k in 2..10 {
+            expected = expected * x.clone();
+            assert_eq!(x.pow(k).to_big_int(), expected.to_big_int());
+        }
+        assert_eq!(P::Scalar::one().pow(1000).to_big_int(), BigInt::one());
+        assert!(P::Scalar::one().is_one());
+        assert!(x.pow(0).is_one());
+        assert!(!x.is_one());
+        assert!(!P::Scalar::zero().is_one());
+
+        let zero: P::Scalar = ECScalar::zero();
+        assert_eq!(zero.pow(0).to_big_int(), BigInt::one());
+        assert_eq!(zero.pow(3).to_big_int(), BigInt::zero());
+    }
+
+    test_for_all_curves!(test_batch_invert);
+    fn test_batch_invert<P>()
+    where
+        P: ECPoint,
+        P::Scalar: Clone,
+    {
+        let mut scalars: Vec<P::Scalar> = (0..6).map(|_| ECScalar::new_random()).collect();
+        scalars[0] = ECScalar::zero();
+        scalars[4] = ECScalar::zero();
+        let expected: Vec<BigInt> = scalars
+            .iter()
+            .map(|s| {
+                if s.to_big_int() == BigInt::zero() {
+                    BigInt::zero()
+                } else {
+                    s.invert().to_big_int()
+                }
+            })
+            .collect();
+        assert_eq!(batch_invert(&mut scalars), vec![0, 4]);
+        let inverted: Vec<BigInt> = scalars.iter().map(|s| s.to_big_int()).collect();
+        assert_eq!(inverted, expected);
+
+        let mut single = vec![P::Scalar::new_random()];
+        let inverse = single[0].invert();
+        assert!(batch_invert(&mut single).is_empty());
+        assert_eq!(single[0].to_big_int(), inverse.to_big_int());
+
+        let mut zeros: Vec<P::Scalar> = vec![ECScalar::zero(), ECScalar::zero()];
+        assert_eq!(batch_invert(&mut zeros), vec![0, 1]);
+        assert!(batch_invert::<P::Scalar>(&mut []).is_empty());
+    }
+
+    test_for_all_curves!(test_invert_ct);
+    fn test_invert_ct<P: ECPoint>() {
+        for _ in 0..20 {
+            let s: P::Scalar = ECScalar::new_random();
+            assert_eq!(s.invert_ct().unwrap().to_big_int(), s.invert().to_big_int());
+        }
+        let one: P::Scalar = ECScalar::from(&BigInt::one());
+        assert_eq!(one.invert_ct().unwrap().to_big_int(), BigInt::one());
+        assert!(P::Scalar::zero().invert_ct().is_none());
+    }
+
+    test_for_all_curves!(test_scalar_bytes);
+    fn test_scalar_bytes<P: ECPoint>() {
+        let q = P::Scalar::q();
+        for _ in 0..10 {
+            let s: P::Scalar = ECScalar::new_random();
+            let bytes = s.to_bytes();
+            assert_eq!(bytes.len(), 32);
+            assert_eq!(BigInt::from_bytes(&bytes), s.to_big_int());
+            let parsed = P::Scalar::from_be_bytes_mod_order(&bytes);
+            assert_eq!(parsed.to_big_int(), s.to_big_int());
+
+            // s + q, longer than the order
+            let longer = BigInt::to_bytes(&(s.to_big_int() + &q * BigInt::from(1 << 20)));
+            assert!(longer.len() > 32);
+            let parsed = P::Scalar::from_be_bytes_mod_order(&longer);
+            assert_eq!(parsed.to_big_int(), s.to_big_int());
+        }
+
+        // shorter inputs are padded
+        let small = P::Scalar::from_be_bytes_mod_order(&[1, 2]);
+        assert_eq!(small.to_big_int(), BigInt::from(0x0102));
+        let mut expected = vec![0u8; 30];
+        expected.extend_from_slice(&[1, 2]);
+        assert_eq!(small.to_bytes(), expected);
+
+        let zero = P::Scalar::from_be_bytes_mod_order(&[]);
+        assert_eq!(zero.to_big_int(), BigInt::zero());
+        assert_eq!(zero.to_bytes(), vec![0u8; 32]);
+        let q_bytes = BigInt::to_bytes(&q);
+        assert_eq!(
+            P::Scalar::from_be_bytes_mod_order(&q_bytes).to_big_int(),
+            BigInt::zero()
+        );
+    }
+
+    test_for_all_curves!(test_scalar_sqrt);
+    fn test_scalar_sqrt<P: ECPoint>() {
+        let q = P::Scalar::q();
+        for _ in 0..10 {
+            let s: P::Scalar = ECScalar::new_random();
+            let square = ECScalar::mul(&s, &s.get_element());
+            let root = square.sqrt().unwrap();
+            let root_squared = ECScalar::mul(&root, &root.get_element());
+            assert_eq!(root_squared.to_big_int(), square.to_big_int());
+            let r = root.to_big_int();
+            assert!(r == s.to_big_int() || r == &q - s.to_big_int());
+        }
+
+        // half of the nonzero scalars are non-residues
+        let non_residues = (0..40)
+            .map(|_| P::Scalar::new_random())
+            .filter(|s| {
+                let legendre = BigInt::mod_pow(&s.to_big_int(), &((&q - BigInt::one()) >> 1), &q);
+                legendre != BigInt::one()
+            })
+            .collect::<Vec<_>>();
+        assert!(!non_residues.is_empty());
+        assert!(non_residues.iter().all(|s| s.sqrt().is_none()));
+
+        assert_eq!(
+            P::Scalar::zero().sqrt().unwrap().to_big_int(),
+            BigInt::zero()
+        );
+        let one: P::Scalar = ECScalar::from(&BigInt::one());
+        let root_of_one = one.sqrt().unwrap().to_big_int();
+        assert!(root_of_one == BigInt::one() || root_of_one == &q - BigInt::one());
+    }
+
+    test_for_all_curves!(test_random_with_bit_length);
+    fn test_random_with_bit_length<P: ECPoint>() {
+        let q_bits = P::Scalar::q().bit_length();
+        for bits in [1, 2, 8, 64, 128, q_bits - 1, q_bits] {
+            for _ in 0..5 {
+                let s = P::Scalar::random_with_bit_length(bits);
+                assert_eq!(s.to_big_int().bit_length(), bits);
+                let product = ECScalar::mul(&s, &s.invert().get_element());
+                assert_eq!(product.to_big_int(), BigInt::one());
+                assert_eq!(s.invert_ct().unwrap().to_big_int(), s.invert().to_big_int());
+            }
+        }
+    }
+
+    test_for_all_curves!(
+        #[should_panic]
+        test_random_with_bit_length_too_long
+    );
+    fn test_random_with_bit_length_too_long<P: ECPoint>() {
+        P::Scalar::random_with_bit_length(P::Scalar::q().bit_length() + 1);
+    }
+
+    test_for_all_curves!(test_scalar_mul_blinded);
+    fn test_scalar_mul_blinded<P: ECPoint>() {
+        let point = P::base_point2();
+        for _ in 0..50 {
+            let k: P::Scalar = ECScalar::new_random();
+            assert_eq!(
+                point.scalar_mul_blinded(&k).pk_to_key_slice(),
+                point.scalar_mul(&k.get_element()).pk_to_key_slice()
+            );
+        }
+    }
+
+    test_for_all_curves!(test_hash_to_scalar_batch);
+    fn test_hash_to_scalar_batch<P>()
+    where
+        P: ECPoint,
+        P::Scalar: Send,
+    {
+        let messages: Vec<Vec<u8>> = (0..20u8).map(|i| vec![i; i as usize]).collect();
+        let messages: Vec<&[u8]> = messages.iter().map(|m| m.as_slice()).collect();
+        let batch = P::Scalar::hash_to_scalar_batch(&messages, b"curv test");
+        assert_eq!(batch.len(), messages.len());
+        for (m, s) in messages.iter().zip(&batch) {
+            let expected = P::Scalar::hash_to_scalar(m, b"curv test");
+            assert_eq!(s.to_big_int(), expected.to_big_int());
+        }
+        // bound to the tag
+        let other = P::Scalar::hash_to_scalar(messages[1], b"another tag");
+        assert_ne!(other.to_big_int(), batch[1].to_big_int());
+        assert!(P::Scalar::hash_to_scalar_batch(&[], b"curv test").is_empty());
+    }
+
+    test_for_all_curves!(test_is_compressed_encoding);
+    fn test_is_compressed_encoding<P: ECPoint>() {
+        let point = P::generator() * P::Scalar::new_random();
+        let compressed = point.serialize_compressed();
+        assert_eq!(compressed.len(), P::serialized_len(true));
+        assert_eq!(P::is_compressed_encoding(&compressed), Some(true));
+        assert_eq!(P::is_compressed_encoding(&compressed[1..]), None);
+        assert_eq!(P::is_compressed_encoding(&[]), None);
+        let uncompressed = point.pk_to_key_slice();
+        if uncompressed.len() != compressed.len() {
+            assert_eq!(uncompressed.len(), P::serialized_len(false));
+            assert_eq!(P::is_compressed_encoding(&uncompressed), Some(false));
+            // a truncated uncompressed encoding isn't a compressed one
+            let truncated = &uncompressed[..P::serialized_len(true)];
+            assert_eq!(P::is_compressed_encoding(truncated), None);
+        }
+    }
+
+    test_for_all_curves!(test_known_discrete_log);
+    fn test_known_discrete_log<P>()
+    where
+        P: ECPoint + Clone,
+        P::Scalar: Clone + PartialEq + std::fmt::Debug,
+    {
+        let base = P::generator() * P::Scalar::new_random();
+        for (x, max) in [
+            (1u64, 1u64),
+            (2, 2),
+            (7, 10),
+            (99, 100),
+            (1000, 1000),
+            (4321, 5000),
+        ] {
+            let scalar: P::Scalar = ECScalar::from(&BigInt::from(x));
+            let point = base.clone() * scalar.clone();
+            assert_eq!(point.known_discrete_log(&base, max), Some(scalar));
+        }
+        let point = base.clone() * <P::Scalar as ECScalar>::from(&BigInt::from(101));
+        assert_eq!(point.known_discrete_log(&base, 100), None);
+        assert_eq!(base.known_discrete_log(&base, 0), None);
+        let random = P::generator() * P::Scalar::new_random();
+        assert_eq!(random.known_discrete_log(&base, 1 << 12), None);
+    }
+
+    test_for_all_curves!(test_generator_mul_batch);
+    fn test_generator_mul_batch<P>()
+    where
+        P: ECPoint + Send,
+        P::Scalar: Sync,
+    {
+        let scalars: Vec<P::Scalar> = (0..20).map(|_| ECScalar::new_random()).collect();
+        let points = P::generator_mul_batch(&scalars);
+        assert_eq!(points.len(), scalars.len());
+        for (k, point) in scalars.iter().zip(&points) {
+            assert_eq!(
+                point.pk_to_key_slice(),
+                P::generator()
+                    .scalar_mul(&k.get_element())
+                    .pk_to_key_slice()
+            );
+        }
+        assert!(P::generator_mul_batch(&[]).is_empty());
+    }
+
+    test_for_all_curves!(test_ct_eq);
+    fn test_ct_eq<P: ECPoint>() {
+        let s: P::Scalar = ECScalar::new_random();
+        // the same scalar, computed another way
+        let same = P::Scalar::from_be_bytes_mod_order(&s.to_bytes());
+        assert!(bool::from(ECScalar::ct_eq(&s, &same)));
+        let other = ECScalar::add(&s, &P::Scalar::one().get_element());
+        assert!(!bool::from(ECScalar::ct_eq(&s, &other)));
+        assert!(bool::from(ECScalar::ct_eq(
+            &P::Scalar::zero(),
+            &P::Scalar::zero()
+        )));
+        assert!(!bool::from(ECScalar::ct_eq(&s, &P::Scalar::zero())));
+
+        let point = P::generator_mul(&s);
+        let same = P::generator().scalar_mul(&same.get_element());
+        assert!(bool::from(ECPoint::ct_eq(&point, &same)));
+        assert!(!bool::from(ECPoint::ct_eq(
+            &point,
+            &P::generator_mul(&other)
+        )));
+    }
+
+    test_for_all_curves!(test_negate_serialized);
+    fn test_negate_serialized<P: ECPoint>() {
+        let minus_one: P::Scalar = ECScalar::from(&(P::Scalar::q() - BigInt::one()));
+        for _ in 0..10 {
+            let point = P::generator() * P::Scalar::new_random();
+            let bytes = point.serialize_compressed();
+            let negated = P::deserialize_canonical(&bytes)
+                .unwrap()
+                .scalar_mul(&minus_one.get_element());
+            let negated_bytes = P::negate_serialized(&bytes).unwrap();
+            assert_eq!(negated_bytes, negated.serialize_compressed());
+            assert_eq!(P::negate_serialized(&negated_bytes).unwrap(), bytes);
+        }
+        let bytes = P::generator().serialize_compressed();
+        assert!(P::negate_serialized(&bytes[1..]).is_err());
+        assert!(P::negate_serialized(&vec![0xff; P::COMPRESSED_SIZE]).is_err());
+    }
+
+    test_for_all_curves!(test_generator_mul);
+    fn test_generator_mul<P: ECPoint>() {
+        let q = P::Scalar::q();
+        let edge_cases = [
+            BigInt::one(),
+            BigInt::from(2),
+            BigInt::from(15),
+            BigInt::from(16),
+            (BigInt::one() << 128) - BigInt::one(),
+            &q - BigInt::one(),
+        ];
+        let scalars = edge_cases
+            .iter()
+            .map(<P::Scalar as ECScalar>::from)
+            .chain((0..20).map(|_| P::Scalar::new_random()));
+        for k in scalars {
+            assert_eq!(
+                P::generator_mul(&k).pk_to_key_slice(),
+                P::generator()
+                    .scalar_mul(&k.get_element())
+                    .pk_to_key_slice()
+            );
+        }
+    }
+
+    test_for_all_curves!(test_scalar_mul_vartime);
+    fn test_scalar_mul_vartime<P: ECPoint>() {
+        for _ in 0..10 {
+            let point = P::generator().scalar_mul(&P::Scalar::new_random().get_element());
+            let k = P::Scalar::new_random();
+            assert_eq!(
+                point.scalar_mul_vartime(&k).pk_to_key_slice(),
+                point.scalar_mul(&k.get_element()).pk_to_key_slice()
+            );
+        }
+    }
+
+    test_for_all_curves!(test_double_scalar_mul_base);
+    fn test_double_scalar_mul_base<P>()
+    where
+        P: ECPoint + Clone,
+    {
+        for _ in 0..10 {
+            let k: P::Scalar = ECScalar::new_random();
+            let e: P::Scalar = ECScalar::new_random();
+            let point = P::generator() * P::Scalar::new_random();
+            let expected =
+                P::generator().scalar_mul(&k.get_element()) + point.scalar_mul(&e.get_element());
+            assert_eq!(
+                P::double_scalar_mul_base(&k, &point, &e)
+                    .unwrap()
+                    .pk_to_key_slice(),
+                expected.pk_to_key_slice()
+            );
+        }
+    }
+
+    test_for_all_curves!(test_multi_scalar_mul);
+    fn test_multi_scalar_mul<P>()
+    where
+        P: ECPoint + Clone,
+    {
+        for n in &[1, 2, 200] {
+            let points: Vec<P> = (0..*n)
+                .map(|_| P::generator().scalar_mul(&P::Scalar::new_random().get_element()))
+                .collect();
+            let scalars: Vec<P::Scalar> = (0..*n).map(|_| ECScalar::new_random()).collect();
+            let expected = points
+                .iter()
+                .zip(&scalars)
+                .map(|(p, s)| p.scalar_mul(&s.get_element()))
+                .reduce(|acc, p| acc + p)
+                .unwrap();
+            assert_eq!(
+                P::multi_scalar_mul(&scalars, &points)
+                    .unwrap()
+                    .pk_to_key_slice(),
+                expected.pk_to_key_slice()
+            );
+        }
+    }
+
+    #[test]
+    fn test_multi_scalar_mul_of_empty_vectors() {
+        fn assert_identity<P: ECPoint>() {
+            let identity = P::identity().unwrap();
+            assert_eq!(
+                P::multi_scalar_mul(&[], &[]).unwrap().pk_to_key_slice(),
+                identity.pk_to_key_slice()
+            );
+            // G + 0 = G
+            let g = P::generator();
+            let sum = g.add_point(&identity.get_element());
+            assert_eq!(sum.pk_to_key_slice(), g.pk_to_key_slice());
+        }
+        use crate::elliptic::curves::{bls12_381, curve_ristretto, ed25519};
+        assert_identity::<ed25519::GE>();
+        assert_identity::<curve_ristretto::GE>();
+        assert_identity::<bls12_381::g1::GE>();
+        assert_identity::<bls12_381::g2::GE>();
+    }
+
+    test_for_all_curves!(test_multi_scalar_mul_at_infinity);
+    fn test_multi_scalar_mul_at_infinity<P>()
+    where
+        P: ECPoint + Clone,
+    {
+        // x*G - x*G, the identity or None where it can't be represented
+        let x: P::Scalar = ECScalar::new_random();
+        let minus_x: P::Scalar = ECScalar::from(&(P::Scalar::q() - x.to_big_int()));
+        let g = P::generator();
+        let sum = P::multi_scalar_mul(&[x, minus_x], &[g.clone(), g.clone()]);
+        assert_eq!(
+            sum.map(|sum| sum.pk_to_key_slice()),
+            P::identity().map(|identity| identity.pk_to_key_slice())
+        );
+        // zero terms are left out
+        let zero = P::Scalar::zero();
+        let one: P::Scalar = ECScalar::from(&BigInt::one());
+        let sum = P::multi_scalar_mul(&[zero, one], &[P::base_point2(), g.clone()]);
+        assert_eq!(sum.unwrap().pk_to_key_slice(), g.pk_to_key_slice());
+    }
+
+    #[test]
+    fn test_multi_scalar_mul_of_empty_vectors_without_identity() {
+        use crate::elliptic::curves::{p256, secp256_k1};
+        assert!(secp256_k1::GE::multi_scalar_mul(&[], &[]).is_none());
+        assert!(p256::GE::multi_scalar_mul(&[], &[]).is_none());
+    }
+
+    test_for_all_curves!(
+        #[should_panic]
+        test_multi_scalar_mul_length_mismatch
+    );
+    fn test_multi_scalar_mul_length_mismatch<P>()
+    where
+        P: ECPoint + Clone,
+    {
+        let points = vec![P::generator(), P::base_point2()];
+        let scalars: Vec<P::Scalar> = vec![ECScalar::new_random()];
+        P::multi_scalar_mul(&scalars, &points);
+    }
+
+    test_for_all_curves!(test_key_id);
+    fn test_key_id<P>()
+    where
+        P: ECPoint + Clone + serde::Serialize + serde::de::DeserializeOwned,
+    {
+        let x: P::Scalar = ECScalar::new_random();
+        let pk = P::generator() * x;
+        let encoded = serde_json::to_string(&pk).unwrap();
+        let decoded: P = serde_json::from_str(&encoded).unwrap();
+        let expected = if P::CURVE_NAME == "ed25519" {
+            // deserializing an ed25519 point multiplies it by the cofactor, see
+            // ed25519::tests::test_serdes_pk
+            pk.clone() * ECScalar::from(&BigInt::from(8))
+        } else {
+            pk.clone()
+        };
+        assert_eq!(expected.key_id(), decoded.key_id());
+        assert_eq!(expected.key_fingerprint(), decoded.key_fingerprint());
+        assert_eq!(pk.key_id()[..], pk.key_fingerprint()[..8]);
+
+        let y: P::Scalar = ECScalar::new_random();
+        let other = P::generator() * y;
+        assert_ne!(pk.key_id(), other.key_id());
+        assert_ne!(pk.key_fingerprint(), other.key_fingerprint());
+    }
+
+    // the compressed encodings of ed25519 and ristretto start with a zero byte for about one point
+    // in 256, a byte BigInt would drop
+    fn assert_key_fingerprint_of_leading_zero<P: ECPoint>() {
+        let pk = (0..10_000)
+            .map(|_| P::generator() * P::Scalar::new_random())
+            .find(|pk| pk.serialize_compressed()[0] == 0)
+            .unwrap();
+        let digest = Sha256::digest(&pk.serialize_compressed());
+        assert_eq!(pk.key_fingerprint()[..], digest[..20]);
+        assert_eq!(pk.key_id()[..], digest[..8]);
+    }
+
+    #[test]
+    fn test_key_fingerprint_of_leading_zero_ed25519() {
+        assert_key_fingerprint_of_leading_zero::<crate::elliptic::curves::ed25519::GE>();
+    }
+
+    #[test]
+    fn test_key_fingerprint_of_leading_zero_ristretto() {
+        assert_key_fingerprint_of_leading_zero::<crate::elliptic::curves::curve_ristretto::GE>();
+    }
+
+    test_for_ct_curves!(test_scalar_mul_base_ct);
+    fn test_scalar_mul_base_ct<P>()
+    where
+        P: ConstantTimeBaseMul + Clone,
+        P::Scalar: Clone,
+    {
+        let scalars: Vec<P::Scalar> = vec![
+            ECScalar::new_random(),
+            ECScalar::from(&BigInt::one()),
+            ECScalar::from(&(P::Scalar::q() - BigInt::one())),
+        ];
+        for k in scalars {
+            assert_eq!(
+                P::scalar_mul_base_ct(&k).pk_to_key_slice(),
+                (P::generator() * k).pk_to_key_slice()
+            );
+        }
+    }
+}
+
+/// Statistical timing tests in the style of dudect, too slow and too sensitive to the machine's
+/// load to run by default. Run with `cargo test --release --features ct-test ct_tests`.
+#[cfg(all(test, feature = "ct-test"))]
+pub(crate) mod ct_tests {
+    use std::time::Instant;
+
+    use super::*;
+
+    pub(crate) const SAMPLES: usize = 20_000;
+    /// |t| above which timings are considered to depend on the input. dudect uses 4.5, the
+    /// margin avoids failures caused by noise
+    pub(crate) const THRESHOLD: f64 = 10.0;
+
+    /// Welch's t statistic between the running times of `f` over the inputs of the two classes,
+    /// given interleaved at random
+    pub(crate) fn two_classes_t<T, F>(inputs: &[(bool, T)], f: F) -> f64
+    where
+        F: Fn(&T),
+    {
+        let mut timings: Vec<(bool, f64)> = inputs
+            .iter()
+            .map(|(class, input)| {
+                let start = Instant::now();
+                f(input);
+                (*class, start.elapsed().as_nanos() as f64)
+            })
+            .collect();
+        // measurements interrupted by the scheduler are cropped
+        let mut sorted: Vec<f64> = timings.iter().map(|(_, t)| *t).collect();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let cutoff = sorted[sorted.len() * 9 / 10];
+        timings.retain(|(_, t)| *t <= cutoff);
+
+        let stats = |class: bool| {
+            let values: Vec<f64> = timings
+                .iter()
+                .filter(|(c, _)| *c == class)
+                .map(|(_, t)| *t)
+                .collect();
+            let n = values.len() as f64;
+            let mean = values.iter().sum::<f64>() / n;
+            let var = values.iter().map(|t| (t - mean).powi(2)).sum::<f64>() / (n - 1.0);
+            (n, mean, var)
+        };
+        let (n0, m0, v0) = stats(true);
+        let (n1, m1, v1) = stats(false);
+        (m0 - m1) / (v0 / n0 + v1 / n1).sqrt()
+    }
+
+    /// t statistic between the running times of `f` over a fixed scalar and over random ones
+    fn fixed_vs_random_t<P, R, F>(f: F) -> f64
+    where
+        P: ECPoint,
+        F: Fn(&P::Scalar) -> R,
+    {
+        let inputs: Vec<(bool, P::Scalar)> = (0..SAMPLES)
+            .map(|_| {
+                if rand::random() {
+                    (true, ECScalar::from(&BigInt::one()))
+                } else {
+                    (false, ECScalar::new_random())
+                }
+            })
+            .collect();
+        two_classes_t(&inputs, |k| {
+            let _ = f(k);
+        })
+    }
+
+    fn assert_scalar_mul_base_ct<P: ConstantTimeBaseMul>() {
+        let t = fixed_vs_random_t::<P, _, _>(P::scalar_mul_base_ct);
+        assert!(t.abs() < THRESHOLD, "t = {}", t);
+    }
+
+    #[test]
+    fn test_scalar_mul_base_ct_secp256k1() {
+        assert_scalar_mul_base_ct::<crate::elliptic::curves::secp256_k1::GE>();
+    }
+
+    #[test]
+    fn test_scalar_mul_base_ct_p256() {
+        assert_scalar_mul_base_ct::<crate::elliptic::curves::p256::GE>();
+    }
+
+    #[test]
+    fn test_scalar_mul_base_ct_ed25519() {
+        assert_scalar_mul_base_ct::<crate::elliptic::curves::ed25519::GE>();
+    }
+
+    #[test]
+    fn test_scalar_mul_base_ct_ristretto() {
+        assert_scalar_mul_base_ct::<crate::elliptic::curves::curve_ristretto::GE>();
+    }
+
+    fn assert_invert_ct<P: ECPoint>() {
+        let t = fixed_vs_random_t::<P, _, _>(|k| k.invert_ct());
+        assert!(t.abs() < THRESHOLD, "t = {}", t);
+    }
+
+    #[test]
+    fn test_invert_ct_secp256k1() {
+        assert_invert_ct::<crate::elliptic::curves::secp256_k1::GE>();
+    }
+
+    #[test]
+    fn test_invert_ct_p256() {
+        assert_invert_ct::<crate::elliptic::curves::p256::GE>();
+    }
+
+    #[test]
+    fn test_invert_ct_ristretto() {
+        assert_invert_ct::<crate::elliptic::curves::curve_ristretto::GE>();
+    }
+
+    /// The test must be able to tell a variable-time multiplication apart
+    #[test]
+    fn test_variable_time_is_detected() {
+        use crate::elliptic::curves::bls12_381::g1::GE;
+        let t = fixed_vs_random_t::<GE, _, _>(|k| GE::generator().scalar_mul(&k.get_element()));
+        assert!(t.abs() >= THRESHOLD, "t = {}", t);
+    }
 }
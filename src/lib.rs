@@ -14,11 +14,32 @@ pub use crate::arithmetic::BigInt;
 
 pub mod cryptographic_primitives;
 
+pub mod cbor;
+
+pub mod nums;
+pub use crate::nums::nums_report;
+
 #[derive(Copy, PartialEq, Eq, Clone, Debug)]
 pub enum ErrorKey {
     InvalidPublicKey,
 }
 
+/// Why [from_coor_checked](crate::elliptic::curves::traits::ECPoint::from_coor_checked) rejected a
+/// pair of coordinates
+#[derive(Copy, PartialEq, Eq, Clone, Debug)]
+pub enum ErrorCoordinates {
+    /// A coordinate is negative or not smaller than the field modulus
+    OutOfFieldRange,
+    /// The coordinates are field elements, but don't satisfy the curve equation
+    NotOnCurve,
+    /// The coordinates are those of a curve point outside of the prime order sub group, which
+    /// the points of the BLS12-381 groups must belong to
+    NotInSubgroup,
+    /// The curve doesn't build points from coordinates, which is the case of ristretto: its
+    /// points are classes of edwards25519 points, without coordinates of their own
+    Unsupported,
+}
+
 pub enum ErrorSS {
     VerifyShareError,
 }
@@ -62,3 +83,30 @@ macro_rules! test_for_all_curves {
         }
     };
 }
+
+/// Same as [test_for_all_curves], leaving out the BLS12-381 groups, which don't implement
+/// [ConstantTimeBaseMul](elliptic::curves::traits::ConstantTimeBaseMul)
+#[cfg(test)]
+#[macro_export]
+macro_rules! test_for_ct_curves {
+    ($fn: ident) => {
+        paste::paste! {
+            #[test]
+            fn [<$fn _secp256k1>]() {
+                $fn::<$crate::elliptic::curves::secp256_k1::GE>()
+            }
+            #[test]
+            fn [<$fn _ristretto>]() {
+                $fn::<$crate::elliptic::curves::curve_ristretto::GE>()
+            }
+            #[test]
+            fn [<$fn _ed25519>]() {
+                $fn::<$crate::elliptic::curves::ed25519::GE>()
+            }
+            #[test]
+            fn [<$fn _p256>]() {
+                $fn::<$crate::elliptic::curves::p256::GE>()
+            }
+        }
+    };
+}
@@ -0,0 +1,196 @@
+/*
+    This file is part of Curv library
+    Copyright 2018 by Kzen Networks
+    (https://github.com/KZen-networks/curv)
+    License MIT: <https://github.com/KZen-networks/curv/blob/master/LICENSE>
+*/
+
+//! Report of the fixed points of the library, for auditors checking that none of them hides a
+//! trapdoor ("nothing up my sleeve").
+//!
+//! Every supported group has two fixed points: its standard generator G, and
+//! [base_point2](crate::elliptic::curves::traits::ECPoint::base_point2), the second generator
+//! of Pedersen commitments and of the proofs built on them, derived by hashing G. Generators
+//! returned by [generator_set](crate::elliptic::curves::traits::ECPoint::generator_set) aren't
+//! fixed: they are hashed to the curve from the label given by the caller.
+
+use crate::elliptic::curves::traits::ECPoint;
+use crate::elliptic::curves::{bls12_381, curve_ristretto, ed25519, p256, secp256_k1};
+
+/// Lists every fixed point of the library with how it was obtained.
+///
+/// Entries are pairs `("<curve> <name> = <hex>", derivation)`, the point being hex encoded as
+/// returned by [pk_to_key_slice](ECPoint::pk_to_key_slice), so the report reads the same on
+/// every run and across versions as long as the constants don't change.
+pub fn nums_report() -> Vec<(String, String)> {
+    let mut report = Vec::new();
+    report_curve::<secp256_k1::GE>(&mut report);
+    report_curve::<p256::GE>(&mut report);
+    report_curve::<ed25519::GE>(&mut report);
+    report_curve::<curve_ristretto::GE>(&mut report);
+    report_curve::<bls12_381::g1::GE>(&mut report);
+    report_curve::<bls12_381::g2::GE>(&mut report);
+    report
+}
+
+fn report_curve<P: ECPoint>(report: &mut Vec<(String, String)>) {
+    let mut entry = |name: &str, point: P, derivation: &str| {
+        report.push((
+            format!(
+                "{} {} = {}",
+                P::CURVE_NAME,
+                name,
+                hex::encode(point.pk_to_key_slice())
+            ),
+            derivation.to_string(),
+        ))
+    };
+    entry("generator", P::generator(), P::GENERATOR_DERIVATION);
+    entry("base_point2", P::base_point2(), P::BASE_POINT2_DERIVATION);
+}
+
+#[cfg(test)]
+mod tests {
+    use pairing_plus::bls12_381::{G1, G2};
+    use pairing_plus::hash_to_curve::HashToCurve;
+    use pairing_plus::hash_to_field::ExpandMsgXmd;
+    use pairing_plus::serdes::SerDes;
+    use pairing_plus::CurveProjective;
+    use sha2::{Digest, Sha256};
+
+    use super::*;
+    use crate::arithmetic::traits::*;
+    use crate::BigInt;
+
+    // hex encoding of a point, as found in the report
+    fn reported(curve: &str, name: &str) -> String {
+        let prefix = format!("{} {} = ", curve, name);
+        let entries: Vec<String> = nums_report()
+            .into_iter()
+            .filter_map(|(constant, _)| constant.strip_prefix(&prefix).map(String::from))
+            .collect();
+        assert_eq!(entries.len(), 1, "{} {} is reported once", curve, name);
+        entries[0].clone()
+    }
+
+    // SHA-256 over the big-endian encoding of an integer, without leading zeros
+    fn sha256(n: &BigInt) -> BigInt {
+        BigInt::from_bytes(&Sha256::digest(&BigInt::to_bytes(n)))
+    }
+
+    fn bytes32(n: &BigInt) -> Vec<u8> {
+        let bytes = BigInt::to_bytes(n);
+        let mut padded = vec![0u8; 32 - bytes.len()];
+        padded.extend_from_slice(&bytes);
+        padded
+    }
+
+    #[test]
+    fn test_report_lists_every_constant() {
+        let report = nums_report();
+        assert_eq!(report.len(), 12);
+        assert!(report.iter().all(|(_, derivation)| !derivation.is_empty()));
+        assert_eq!(report, nums_report());
+    }
+
+    #[test]
+    fn test_generators_are_the_standard_ones() {
+        // SEC 2 v2, section 2.4.1
+        assert_eq!(
+            reported("secp256k1", "generator"),
+            "04\
+             79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798\
+             483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8"
+        );
+        // FIPS 186-4, appendix D.1.2.3
+        assert_eq!(
+            reported("p256", "generator"),
+            "04\
+             6b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296\
+             4fe342e2fe1a7f9b8ee7eb4a7c0f9e162bce33576b315ececbb6406837bf51f5"
+        );
+        // y = 4/5 in little-endian, with the sign bit of x unset
+        let p = (BigInt::one() << 255) - BigInt::from(19);
+        let y = BigInt::mod_mul(
+            &BigInt::from(4),
+            &BigInt::mod_inv(&BigInt::from(5), &p).unwrap(),
+            &p,
+        );
+        let mut y_le = bytes32(&y);
+        y_le.reverse();
+        assert_eq!(reported("ed25519", "generator"), hex::encode(y_le));
+        // RFC 9496, appendix A.1
+        assert_eq!(
+            reported("ristretto", "generator"),
+            "e2f2ae0a6abc4e71a884a961c500515f58e30b6aa582dd8db6a65945e08d2d76"
+        );
+        assert_eq!(
+            reported("bls12_381_1", "generator"),
+            "97f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb"
+        );
+        assert_eq!(
+            reported("bls12_381_2", "generator"),
+            "93e02b6052719f607dacd3a088274f65596bd0d09920b61ab5da61bbdc7f5049334cf11213945d57e5ac7d055d042b7e\
+             024aa2b2f08f0a91260805272dc51051c6e47ad4fa403b02b4510b647ae3d1770bac0326a805bbefd48056c8c121bdb8"
+        );
+    }
+
+    #[test]
+    fn test_base_points2_reproduce_from_their_derivation() {
+        fn compressed_g<P: ECPoint>() -> BigInt {
+            P::generator().bytes_compressed_to_big_int()
+        }
+
+        // x = SHA256(SHA256(SHA256(G))), y even
+        let x = sha256(&sha256(&sha256(&compressed_g::<secp256_k1::GE>())));
+        let mut encoding = vec![2u8];
+        encoding.extend_from_slice(&bytes32(&x));
+        let h = secp256_k1::GE::deserialize_canonical(&encoding).unwrap();
+        assert_eq!(
+            reported("secp256k1", "base_point2"),
+            hex::encode(h.pk_to_key_slice())
+        );
+
+        // x = SHA256(SHA256(G)), y even
+        let x = sha256(&sha256(&compressed_g::<p256::GE>()));
+        let mut encoding = vec![2u8];
+        encoding.extend_from_slice(&bytes32(&x));
+        let h = p256::GE::deserialize_canonical(&encoding).unwrap();
+        assert_eq!(
+            reported("p256", "base_point2"),
+            hex::encode(h.pk_to_key_slice())
+        );
+
+        // H = 8 * decode(SHA256(SHA256(G))), from_bytes clearing the cofactor
+        let hash = sha256(&sha256(&compressed_g::<ed25519::GE>()));
+        let h = ed25519::GE::from_bytes(&BigInt::to_bytes(&hash)).unwrap();
+        assert_eq!(
+            reported("ed25519", "base_point2"),
+            hex::encode(h.pk_to_key_slice())
+        );
+
+        // H = decode(SHA256(G))
+        let hash = sha256(&compressed_g::<curve_ristretto::GE>());
+        let h = curve_ristretto::GE::from_bytes(&BigInt::to_bytes(&hash)).unwrap();
+        assert_eq!(
+            reported("ristretto", "base_point2"),
+            hex::encode(h.pk_to_key_slice())
+        );
+
+        // H = hash_to_curve(msg = [1], dst = [1])
+        let h = <G1 as HashToCurve<ExpandMsgXmd<Sha256>>>::hash_to_curve([1u8], [1u8]);
+        let mut encoding = vec![];
+        h.into_affine().serialize(&mut encoding, true).unwrap();
+        assert_eq!(
+            reported("bls12_381_1", "base_point2"),
+            hex::encode(encoding)
+        );
+        let h = <G2 as HashToCurve<ExpandMsgXmd<Sha256>>>::hash_to_curve([1u8], [1u8]);
+        let mut encoding = vec![];
+        h.into_affine().serialize(&mut encoding, true).unwrap();
+        assert_eq!(
+            reported("bls12_381_2", "base_point2"),
+            hex::encode(encoding)
+        );
+    }
+}